@@ -0,0 +1,47 @@
+//! Benchmark for the initial scan over synthetic regions
+//!
+//! Run with and without `--features parallel` to compare the rayon and
+//! sequential paths.
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use libmemscan::interactive::scan_regions;
+use libmemscan::values::ValueType;
+
+fn benchmark_initial_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("initial_scan");
+    group.sample_size(10);
+
+    // A few synthetic regions of pseudo-random bytes
+    for region_count in [4usize, 16].iter() {
+        let region_size: usize = 4 << 20;
+        let buffers: Vec<Vec<u8>> = (0..*region_count)
+            .map(|r| {
+                (0..region_size)
+                    .map(|i| (i.wrapping_mul(31).wrapping_add(r * 7) % 251) as u8)
+                    .collect()
+            })
+            .collect();
+
+        group.throughput(Throughput::Bytes((region_count * region_size) as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("i32", region_count),
+            region_count,
+            |b, &_count| {
+                b.iter(|| {
+                    let regions: Vec<(usize, &[u8])> = buffers
+                        .iter()
+                        .enumerate()
+                        .map(|(i, buf)| (0x1000_0000 + i * region_size, buf.as_slice()))
+                        .collect();
+                    scan_regions(black_box(regions), &[(ValueType::I32, 4)])
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_initial_scan);
+criterion_main!(benches);
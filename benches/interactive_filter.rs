@@ -0,0 +1,100 @@
+//! Benchmark for filtering a large interactive match set
+//!
+//! Scans a 40 MiB buffer in this process, which yields 10M i32 matches,
+//! and measures a single filter pass over them. A second group spreads 1M
+//! matches over 1024 regions, where looking up each match's region
+//! dominates.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use libmemscan::interactive::{FilterOp, InteractiveScanner};
+use libmemscan::process::{MemoryRegion, open_process};
+use libmemscan::values::{Value, ValueType};
+use std::time::{Duration, Instant};
+
+const MATCH_COUNT: usize = 10_000_000;
+
+/// Regions and i32 matches per region of the many-regions group
+const REGION_COUNT: usize = 1024;
+const REGION_MATCHES: usize = 1024;
+
+fn benchmark_filter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interactive_filter");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(MATCH_COUNT as u64));
+
+    let buffer: Vec<i32> = (0..MATCH_COUNT as i32).map(|i| i % 100).collect();
+    let proc = open_process(std::process::id()).expect("failed to open own process");
+    let region = MemoryRegion::private(buffer.as_ptr() as usize, size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+
+    // Every match survives, so the filter touches all 10M entries
+    group.bench_function("unchanged_10m", |b| {
+        b.iter_custom(|iters| {
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                scanner.initial_scan().unwrap();
+                let start = Instant::now();
+                scanner.filter(FilterOp::Unchanged, None).unwrap();
+                elapsed += start.elapsed();
+            }
+            elapsed
+        });
+    });
+
+    // Only 1% of the matches survive
+    group.bench_function("equals_10m", |b| {
+        b.iter_custom(|iters| {
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                scanner.initial_scan().unwrap();
+                let start = Instant::now();
+                scanner
+                    .filter(FilterOp::Equals, Some(Value::I32(42)))
+                    .unwrap();
+                elapsed += start.elapsed();
+            }
+            elapsed
+        });
+    });
+
+    group.finish();
+}
+
+fn benchmark_filter_many_regions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interactive_filter_regions");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements((REGION_COUNT * REGION_MATCHES) as u64));
+
+    let buffers: Vec<Vec<i32>> = (0..REGION_COUNT)
+        .map(|r| {
+            (0..REGION_MATCHES as i32)
+                .map(|i| (i + r as i32) % 100)
+                .collect()
+        })
+        .collect();
+    let proc = open_process(std::process::id()).expect("failed to open own process");
+    let regions = buffers
+        .iter()
+        .map(|b| MemoryRegion::private(b.as_ptr() as usize, size_of_val(&b[..])))
+        .collect();
+    let mut scanner = InteractiveScanner::with_memmap(&proc, regions, ValueType::I32, false);
+
+    // Every match survives, so every region stays active
+    group.bench_function("unchanged_1k_regions", |b| {
+        b.iter_custom(|iters| {
+            let mut elapsed = Duration::ZERO;
+            for _ in 0..iters {
+                scanner.initial_scan().unwrap();
+                let start = Instant::now();
+                scanner.filter(FilterOp::Unchanged, None).unwrap();
+                elapsed += start.elapsed();
+            }
+            elapsed
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_filter, benchmark_filter_many_regions);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+//! Benchmark for the read chunk size used when copying regions
+//!
+//! Copies a 64 MiB buffer of this process with `read_process_memory`
+//! using different chunk sizes, as `scan --no-memmap --chunk` does.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use libmemscan::memmap::MappedMemory;
+use libmemscan::process::{MemoryRegion, open_process};
+
+const REGION_SIZE: usize = 64 << 20;
+
+fn benchmark_read_chunk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_chunk");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(REGION_SIZE as u64));
+
+    let buffer: Vec<u8> = (0..REGION_SIZE).map(|i| (i % 251) as u8).collect();
+    let proc = open_process(std::process::id()).expect("failed to open own process");
+
+    for chunk in [4 << 10, 64 << 10, 1 << 20, 16 << 20].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(chunk), chunk, |b, &chunk| {
+            b.iter(|| {
+                MappedMemory::read_region_chunked(
+                    &proc,
+                    MemoryRegion::private(buffer.as_ptr() as usize, buffer.len()),
+                    chunk,
+                )
+                .unwrap()
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_read_chunk);
+criterion_main!(benches);
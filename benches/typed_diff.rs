@@ -0,0 +1,64 @@
+//! Benchmark for typed diffs against the byte-wise `MemoryDiff::diff`
+//!
+//! Run with and without `--features parallel` to compare the rayon and
+//! sequential paths. Both diff buffers of this process, so each iteration
+//! also re-reads the regions.
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use libmemscan::diff::MemoryDiff;
+use libmemscan::process::{MemoryRegion, open_process};
+use libmemscan::values::ValueType;
+
+fn benchmark_typed_diff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("typed_diff");
+    group.sample_size(10);
+    let proc = open_process(std::process::id()).unwrap();
+
+    for region_count in [4usize, 16].iter() {
+        let region_size: usize = 4 << 20;
+        let mut buffers: Vec<Vec<u8>> = (0..*region_count)
+            .map(|r| {
+                (0..region_size)
+                    .map(|i| (i.wrapping_mul(31).wrapping_add(r * 7) % 251) as u8)
+                    .collect()
+            })
+            .collect();
+        let regions: Vec<MemoryRegion> = buffers
+            .iter()
+            .map(|b| MemoryRegion::private(b.as_ptr() as usize, b.len()))
+            .collect();
+        let mut diff = MemoryDiff::new(&proc);
+        for region in &regions {
+            diff.take_snapshot(region.clone()).unwrap();
+        }
+        // Change every 4096th value after the snapshots were taken
+        for buffer in &mut buffers {
+            for i in (0..region_size).step_by(4096 * 4) {
+                buffer[i] ^= 0xff;
+            }
+        }
+        black_box(&buffers);
+
+        group.throughput(Throughput::Bytes((region_count * region_size) as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("bytes", region_count),
+            region_count,
+            |b, &_count| {
+                b.iter(|| diff.diff(black_box(&regions)).unwrap());
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("i32", region_count),
+            region_count,
+            |b, &_count| {
+                b.iter(|| diff.diff_typed_parallel(ValueType::I32, 4).unwrap());
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_typed_diff);
+criterion_main!(benches);
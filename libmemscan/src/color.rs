@@ -0,0 +1,193 @@
+//! Switchable terminal colors for human-readable output
+//!
+//! All colored output goes through [`OwoColorize`], a drop-in replacement for
+//! the subset of `owo_colors::OwoColorize` used by memscan that renders plain
+//! text once colors are turned off. Colors are on by default unless the
+//! `NO_COLOR` environment variable is set to a non-empty value (see
+//! <https://no-color.org>), and [`set_enabled`] overrides either choice.
+//! [`disable_for_thread`] turns them off while writing to a sink that
+//! isn't a terminal.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Colors haven't been configured yet, so `NO_COLOR` decides
+const UNSET: u8 = 0;
+const ENABLED: u8 = 1;
+const DISABLED: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Turn colored output on or off for the whole process
+pub fn set_enabled(enabled: bool) {
+    STATE.store(if enabled { ENABLED } else { DISABLED }, Ordering::Relaxed);
+}
+
+/// Whether output is currently colored
+pub fn enabled() -> bool {
+    if let Some(enabled) = OVERRIDE.get() {
+        return enabled;
+    }
+    match STATE.load(Ordering::Relaxed) {
+        ENABLED => true,
+        DISABLED => false,
+        _ => {
+            let enabled = std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+            // Keep an explicit `set_enabled` that raced with this lookup
+            let _ = STATE.compare_exchange(
+                UNSET,
+                if enabled { ENABLED } else { DISABLED },
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            );
+            STATE.load(Ordering::Relaxed) == ENABLED
+        }
+    }
+}
+
+thread_local! {
+    /// Choice of the current thread, which takes precedence over [`STATE`]
+    /// without touching other threads
+    static OVERRIDE: std::cell::Cell<Option<bool>> = const { std::cell::Cell::new(None) };
+}
+
+/// Turn colors off for the current thread until the returned guard is
+/// dropped, e.g. while writing to a file or pipe
+pub fn disable_for_thread() -> ThreadColors {
+    ThreadColors(OVERRIDE.replace(Some(false)))
+}
+
+/// Turn colors on or off for the current test thread until the returned
+/// guard is dropped
+#[cfg(test)]
+pub(crate) fn force_for_test(enabled: bool) -> ThreadColors {
+    ThreadColors(OVERRIDE.replace(Some(enabled)))
+}
+
+/// Restores the previous choice of the current thread when dropped
+pub struct ThreadColors(Option<bool>);
+
+impl Drop for ThreadColors {
+    fn drop(&mut self) {
+        OVERRIDE.set(self.0);
+    }
+}
+
+/// A value with a style that is only applied while colors are enabled
+pub struct Painted<'a, T: ?Sized> {
+    value: &'a T,
+    style: owo_colors::Style,
+}
+
+macro_rules! styles {
+    ($($name:ident),* $(,)?) => {
+        /// Styling methods, applied only while colors are enabled
+        pub trait OwoColorize {
+            $(
+                fn $name(&self) -> Painted<'_, Self> {
+                    Painted {
+                        value: self,
+                        style: owo_colors::Style::new().$name(),
+                    }
+                }
+            )*
+        }
+
+        impl<T: ?Sized> Painted<'_, T> {
+            $(
+                pub fn $name(mut self) -> Self {
+                    self.style = self.style.$name();
+                    self
+                }
+            )*
+        }
+    };
+}
+
+styles!(
+    black,
+    red,
+    green,
+    yellow,
+    blue,
+    magenta,
+    cyan,
+    white,
+    bright_black,
+    bright_red,
+    bright_green,
+    bright_yellow,
+    bright_blue,
+    bright_magenta,
+    bright_cyan,
+    bright_white,
+    bold,
+    dimmed,
+    italic,
+    underline,
+);
+
+impl<T: ?Sized> OwoColorize for T {}
+
+impl<T: fmt::Display + ?Sized> Painted<'_, T> {
+    fn render(&self, f: &mut fmt::Formatter<'_>, colored: bool) -> fmt::Result {
+        if colored {
+            fmt::Display::fmt(&self.style.style(self.value), f)
+        } else {
+            fmt::Display::fmt(self.value, f)
+        }
+    }
+}
+
+impl<T: fmt::Display + ?Sized> fmt::Display for Painted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(f, enabled())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render with a fixed choice, leaving the process-wide state alone
+    struct Render<'a>(Painted<'a, str>, bool);
+
+    impl fmt::Display for Render<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.render(f, self.1)
+        }
+    }
+
+    #[test]
+    fn test_painted_plain_and_colored() {
+        assert_eq!(Render("[info]".bright_cyan(), false).to_string(), "[info]");
+        assert_eq!(
+            format!("{:>4}", Render("ab".bright_green().bold(), false)),
+            "  ab"
+        );
+
+        let colored = Render("[info]".bright_cyan(), true).to_string();
+        assert_eq!(colored, "\u{1b}[96m[info]\u{1b}[0m");
+    }
+
+    #[test]
+    fn test_force_for_test_restores() {
+        let before = enabled();
+        {
+            let _colors = force_for_test(!before);
+            assert_eq!(enabled(), !before);
+            assert_eq!("x".red().to_string() == "x", before);
+        }
+        assert_eq!(enabled(), before);
+    }
+
+    #[test]
+    fn test_disable_for_thread_nests() {
+        let _colors = force_for_test(true);
+        {
+            let _plain = disable_for_thread();
+            assert_eq!("x".red().to_string(), "x");
+        }
+        assert!(enabled());
+    }
+}
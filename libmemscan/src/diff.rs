@@ -3,13 +3,42 @@
 //! This module provides functionality to detect memory changes by comparing
 //! snapshots of mapped memory regions in parallel.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 
+use crate::error::{MemScanError, Result, bail};
+use crate::interactive::ValueChange;
 use crate::memmap::{MappedMemory, MemoryMapper};
-use crate::process::{MemoryRegion, ProcessHandle};
-use anyhow::Result;
+use crate::process::{MemoryRegion, ProcessHandle, query_system_info};
+use crate::values::{Endianness, Value, ValueType, values_equal};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-#[derive(Debug)]
+/// Bytes of a region compared by one task of [`MemoryDiff::diff_typed_parallel`]
+const TYPED_DIFF_CHUNK_BYTES: usize = 1 << 20;
+
+/// 64-bit FNV-1a parameters
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash bytes with 64-bit FNV-1a
+fn fnv1a(data: &[u8]) -> u64 {
+    fnv1a_bytes(data.iter().copied())
+}
+
+fn fnv1a_bytes(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Hash the page hashes of a hashes-only snapshot with 64-bit FNV-1a
+fn hash_page_hashes(hashes: &[u64]) -> u64 {
+    fnv1a_bytes(hashes.iter().flat_map(|hash| hash.to_le_bytes()))
+}
+
+#[derive(Debug, Clone)]
 enum MemorySnapshotBacking<'a> {
     Slice(&'a [u8]),
     Mapped(&'a MappedMemory),
@@ -17,11 +46,15 @@ enum MemorySnapshotBacking<'a> {
 }
 
 /// A snapshot of memory at a specific point in time
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MemoryRegionSnapshot<'a> {
-    /// Snapshot data
+    /// Snapshot data (empty for hashes-only snapshots)
     pub data: Vec<u8>,
     backing: MemorySnapshotBacking<'a>,
+    /// `(page_size, hashes)` kept instead of `data` by hashes-only snapshots
+    page_hashes: Option<(usize, Vec<u64>)>,
+    /// [`MemoryRegionSnapshot::hash`], computed when the snapshot is taken
+    hash: u64,
 }
 
 impl<'a> MemoryRegionSnapshot<'a> {
@@ -29,6 +62,8 @@ impl<'a> MemoryRegionSnapshot<'a> {
         Self {
             data: slice.to_vec(),
             backing: MemorySnapshotBacking::Slice(slice),
+            page_hashes: None,
+            hash: fnv1a(slice),
         }
     }
 
@@ -37,6 +72,8 @@ impl<'a> MemoryRegionSnapshot<'a> {
         Self {
             data: mapped.data().to_vec(),
             backing: MemorySnapshotBacking::Mapped(mapped),
+            page_hashes: None,
+            hash: fnv1a(mapped.data()),
         }
     }
 
@@ -45,26 +82,76 @@ impl<'a> MemoryRegionSnapshot<'a> {
         let mut buffer = vec![0u8; region.size];
         Self::process_read(proc, &region, &mut buffer)?;
         Ok(Self {
+            hash: fnv1a(&buffer),
             data: buffer,
             backing: MemorySnapshotBacking::Process(proc, region),
+            page_hashes: None,
+        })
+    }
+
+    /// Create a snapshot that keeps only a hash of every `page_size` bytes of
+    /// process memory instead of a copy of the region
+    ///
+    /// This needs 8 bytes per page rather than the whole region, which makes
+    /// it practical for watching gigabyte regions, but changes can only be
+    /// located to a page (see [`diff_snapshot_pages`]) rather than a byte.
+    pub fn from_process_hashes_only(
+        proc: &'a ProcessHandle,
+        region: MemoryRegion,
+        page_size: usize,
+    ) -> Result<Self> {
+        let hashes = Self::process_page_hashes(proc, &region, page_size)?;
+        Ok(Self {
+            data: Vec::new(),
+            backing: MemorySnapshotBacking::Process(proc, region),
+            hash: hash_page_hashes(&hashes),
+            page_hashes: Some((page_size, hashes)),
         })
     }
 
+    /// Whether this snapshot keeps page hashes instead of data
+    pub fn hashes_only(&self) -> bool {
+        self.page_hashes.is_some()
+    }
+
     pub fn refresh(&mut self) -> Result<()> {
-        match &self.backing {
-            MemorySnapshotBacking::Slice(slice) => {
-                self.data = slice.to_vec();
-                Ok(())
-            }
-            MemorySnapshotBacking::Mapped(mapped) => {
-                self.data = mapped.data().to_vec();
-                Ok(())
+        if let Some((page_size, hashes)) = &mut self.page_hashes {
+            // Hashes-only snapshots are always backed by the process
+            if let MemorySnapshotBacking::Process(proc, region) = &self.backing {
+                *hashes = Self::process_page_hashes(proc, region, *page_size)?;
+                self.hash = hash_page_hashes(hashes);
             }
+            return Ok(());
+        }
+        self.data = self.current_data()?;
+        self.hash = fnv1a(&self.data);
+        Ok(())
+    }
+
+    /// What [`MemoryRegionSnapshot::hash`] would be after a refresh,
+    /// without keeping the data read for it
+    pub fn current_hash(&self) -> Result<u64> {
+        match (&self.page_hashes, &self.backing) {
+            (Some((page_size, _)), MemorySnapshotBacking::Process(proc, region)) => Ok(
+                hash_page_hashes(&Self::process_page_hashes(proc, region, *page_size)?),
+            ),
+            (Some(_), _) => Ok(self.hash),
+            (None, MemorySnapshotBacking::Slice(slice)) => Ok(fnv1a(slice)),
+            (None, MemorySnapshotBacking::Mapped(mapped)) => Ok(fnv1a(mapped.data())),
+            (None, MemorySnapshotBacking::Process(..)) => Ok(fnv1a(&self.current_data()?)),
+        }
+    }
+
+    /// Read the current contents of the backing memory without touching
+    /// the snapshot
+    fn current_data(&self) -> Result<Vec<u8>> {
+        match &self.backing {
+            MemorySnapshotBacking::Slice(slice) => Ok(slice.to_vec()),
+            MemorySnapshotBacking::Mapped(mapped) => Ok(mapped.data().to_vec()),
             MemorySnapshotBacking::Process(proc, region) => {
                 let mut buffer = vec![0u8; region.size];
-                Self::process_read(proc, &region, &mut buffer)?;
-                self.data = buffer;
-                Ok(())
+                Self::process_read(proc, region, &mut buffer)?;
+                Ok(buffer)
             }
         }
     }
@@ -76,16 +163,69 @@ impl<'a> MemoryRegionSnapshot<'a> {
     ) -> Result<usize> {
         let bytes_read = crate::process::read_process_memory(proc, region.base_address, buffer);
         if bytes_read < region.size {
-            anyhow::bail!(
-                "Partial read: expected {} bytes, got {} bytes at address {:016x}",
-                region.size,
-                bytes_read,
-                region.base_address
-            );
+            return Err(MemScanError::ReadFailed {
+                addr: region.base_address,
+                reason: format!(
+                    "partial read, expected {} bytes, got {}",
+                    region.size, bytes_read
+                ),
+            });
         }
         Ok(bytes_read)
     }
 
+    /// Hash every `page_size` bytes of a region, reading one page at a time
+    fn process_page_hashes(
+        proc: &'a ProcessHandle,
+        region: &MemoryRegion,
+        page_size: usize,
+    ) -> Result<Vec<u64>> {
+        if page_size == 0 {
+            bail!("Page size must be non-zero");
+        }
+        let mut buffer = vec![0u8; page_size.min(region.size)];
+        let mut hashes = Vec::with_capacity(region.size.div_ceil(page_size));
+        for offset in (0..region.size).step_by(page_size) {
+            let page = &mut buffer[..page_size.min(region.size - offset)];
+            let address = region.base_address + offset;
+            let bytes_read = crate::process::read_process_memory(proc, address, page);
+            if bytes_read < page.len() {
+                return Err(MemScanError::ReadFailed {
+                    addr: address,
+                    reason: format!(
+                        "partial read, expected {} bytes, got {}",
+                        page.len(),
+                        bytes_read
+                    ),
+                });
+            }
+            hashes.push(fnv1a(page));
+        }
+        Ok(hashes)
+    }
+
+    /// Page hashes for comparing at `page_size` granularity, computed from
+    /// `data` for full snapshots (None if hashed with another page size)
+    fn hashes_for(&self, page_size: usize) -> Option<Cow<'_, [u64]>> {
+        match &self.page_hashes {
+            Some((stored_size, hashes)) => {
+                (*stored_size == page_size).then_some(Cow::Borrowed(hashes.as_slice()))
+            }
+            None => Some(Cow::Owned(
+                self.data.chunks(page_size.max(1)).map(fnv1a).collect(),
+            )),
+        }
+    }
+
+    /// FNV-1a hash of the snapshot data, for cheap "did anything change" checks
+    ///
+    /// For hashes-only snapshots this hashes the page hashes instead. The
+    /// hash is computed when the snapshot is taken or refreshed, so it does
+    /// not follow later edits of `data`.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
     pub fn base_address(&self) -> usize {
         match &self.backing {
             MemorySnapshotBacking::Slice(slice) => slice.as_ptr() as usize,
@@ -93,19 +233,6 @@ impl<'a> MemoryRegionSnapshot<'a> {
             MemorySnapshotBacking::Process(_, region) => region.base_address,
         }
     }
-
-    pub fn clone(&self) -> Self {
-        Self {
-            data: self.data.clone(),
-            backing: match &self.backing {
-                MemorySnapshotBacking::Slice(slice) => MemorySnapshotBacking::Slice(slice),
-                MemorySnapshotBacking::Mapped(mapped) => MemorySnapshotBacking::Mapped(mapped),
-                MemorySnapshotBacking::Process(proc, region) => {
-                    MemorySnapshotBacking::Process(proc, region.clone())
-                }
-            },
-        }
-    }
 }
 
 /// Represents a detected change in memory
@@ -119,6 +246,127 @@ pub struct MemoryChange {
     pub new_value: u8,
 }
 
+/// A run of changed bytes, as coalesced by [`group_changes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeSpan {
+    /// Address of the first changed byte
+    pub start: usize,
+    /// Bytes of the span before the change
+    pub bytes_old: Vec<u8>,
+    /// Bytes of the span after the change
+    pub bytes_new: Vec<u8>,
+    /// Whether each byte of the span changed, false for the gap bytes
+    pub changed: Vec<bool>,
+}
+
+impl ChangeSpan {
+    /// Number of bytes covered, including unchanged gap bytes
+    pub fn len(&self) -> usize {
+        self.bytes_new.len()
+    }
+
+    /// Check if the span covers no bytes
+    pub fn is_empty(&self) -> bool {
+        self.bytes_new.is_empty()
+    }
+
+    /// Address one past the last byte of the span
+    pub fn end(&self) -> usize {
+        self.start + self.len()
+    }
+
+    /// Read the start of the span as old and new values of `value_type`,
+    /// returning None if the span is shorter than the type or a gap byte
+    /// falls inside the value, whose real contents the span doesn't know
+    pub fn values(&self, value_type: ValueType) -> Option<(Value, Value)> {
+        if !self.changed.iter().take(value_type.size()).all(|&b| b) {
+            return None;
+        }
+        Some((
+            Value::from_bytes(&self.bytes_old, 0, value_type)?,
+            Value::from_bytes(&self.bytes_new, 0, value_type)?,
+        ))
+    }
+}
+
+/// Coalesce single-byte changes into spans, merging changes that are at
+/// most `max_gap` unchanged bytes apart
+///
+/// With a `max_gap` of 0 only adjacent bytes are merged. Gap bytes didn't
+/// change but their values aren't part of `changes`, so they are filled
+/// with 0 in both `bytes_old` and `bytes_new` and marked unchanged in
+/// `changed`. Changes may be passed in any order; a byte listed twice is
+/// only counted once.
+pub fn group_changes(changes: &[MemoryChange], max_gap: usize) -> Vec<ChangeSpan> {
+    let mut sorted: Vec<&MemoryChange> = changes.iter().collect();
+    sorted.sort_by_key(|change| change.address);
+    sorted.dedup_by_key(|change| change.address);
+
+    let mut spans: Vec<ChangeSpan> = Vec::new();
+    for change in sorted {
+        match spans.last_mut() {
+            Some(span) if change.address - span.end() <= max_gap => {
+                let gap = change.address - span.end();
+                span.bytes_old.extend(std::iter::repeat_n(0, gap));
+                span.bytes_new.extend(std::iter::repeat_n(0, gap));
+                span.changed.extend(std::iter::repeat_n(false, gap));
+                span.bytes_old.push(change.old_value);
+                span.bytes_new.push(change.new_value);
+                span.changed.push(true);
+            }
+            _ => spans.push(ChangeSpan {
+                start: change.address,
+                bytes_old: vec![change.old_value],
+                bytes_new: vec![change.new_value],
+                changed: vec![true],
+            }),
+        }
+    }
+    spans
+}
+
+/// A page whose contents changed between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageChange {
+    /// Address of the first byte of the page
+    pub address: usize,
+    /// Index of the page within its region
+    pub page_index: usize,
+}
+
+/// Compare two snapshots page by page and return the pages that changed
+///
+/// Full snapshots are hashed in `page_size` chunks; hashes-only snapshots
+/// must have been taken with the same `page_size`.
+pub fn diff_snapshot_pages(
+    old: &MemoryRegionSnapshot,
+    new: &MemoryRegionSnapshot,
+    page_size: usize,
+) -> Vec<PageChange> {
+    if old.base_address() != new.base_address() {
+        return Vec::new();
+    }
+    let (Some(old_hashes), Some(new_hashes)) =
+        (old.hashes_for(page_size), new.hashes_for(page_size))
+    else {
+        return Vec::new();
+    };
+    if old_hashes.len() != new_hashes.len() {
+        return Vec::new();
+    }
+
+    old_hashes
+        .iter()
+        .zip(new_hashes.iter())
+        .enumerate()
+        .filter(|(_, (old_hash, new_hash))| old_hash != new_hash)
+        .map(|(page_index, _)| PageChange {
+            address: old.base_address() + page_index * page_size,
+            page_index,
+        })
+        .collect()
+}
+
 /// Compare two snapshots and return the list of changes
 pub fn diff_snapshots(old: &MemoryRegionSnapshot, new: &MemoryRegionSnapshot) -> Vec<MemoryChange> {
     if old.base_address() != new.base_address() || old.data.len() != new.data.len() {
@@ -138,10 +386,70 @@ pub fn diff_snapshots(old: &MemoryRegionSnapshot, new: &MemoryRegionSnapshot) ->
     changes
 }
 
+/// Split the slot offsets of a `len`-byte region, stepped by `alignment`,
+/// into ranges of about `chunk_bytes` that start on a slot
+fn slot_chunks(
+    len: usize,
+    value_size: usize,
+    alignment: usize,
+    chunk_bytes: usize,
+) -> Vec<Range<usize>> {
+    let Some(last) = len.checked_sub(value_size) else {
+        return Vec::new();
+    };
+    let step = (chunk_bytes / alignment).max(1) * alignment;
+    (0..=last)
+        .step_by(step)
+        .map(|start| start..start.saturating_add(step).min(last + 1))
+        .collect()
+}
+
+/// Compare the `value_type` slots of `old` and `new` at the offsets in
+/// `offsets` stepped by `alignment`, reporting each that changed
+///
+/// Slots are compared as bytes first, so the values are only decoded (in
+/// `endianness` byte order) for the few that differ.
+pub(crate) fn typed_changes(
+    base_address: usize,
+    old: &[u8],
+    new: &[u8],
+    value_type: ValueType,
+    alignment: usize,
+    offsets: Range<usize>,
+    endianness: Endianness,
+) -> Vec<ValueChange> {
+    let size = value_type.size();
+    let mut changes = Vec::new();
+    for offset in offsets.step_by(alignment) {
+        if old[offset..offset + size] == new[offset..offset + size] {
+            continue;
+        }
+        if let (Some(old_value), Some(new_value)) = (
+            Value::from_bytes_endian(old, offset, value_type, endianness),
+            Value::from_bytes_endian(new, offset, value_type, endianness),
+        ) && !values_equal(&old_value, &new_value)
+        {
+            changes.push(ValueChange {
+                address: base_address + offset,
+                old_value,
+                new_value,
+            });
+        }
+    }
+    changes
+}
+
 /// Parallel change detector for multiple memory regions
 pub struct MemoryDiff<'a> {
     pub mapper: MemoryMapper<'a>,
+    process: &'a ProcessHandle,
     snapshots: Vec<MemoryRegionSnapshot<'a>>,
+    /// Take hashes-only snapshots instead of full copies
+    hashes_only: bool,
+    /// Granularity of hashes-only snapshots and page diffs
+    page_size: usize,
+    /// Byte order values are decoded in by typed diffs
+    endianness: Endianness,
 }
 
 impl<'a> MemoryDiff<'a> {
@@ -149,30 +457,80 @@ impl<'a> MemoryDiff<'a> {
     pub fn new(process: &'a ProcessHandle) -> Self {
         Self {
             mapper: MemoryMapper::new(process),
+            process,
             snapshots: Vec::new(),
+            hashes_only: false,
+            page_size: query_system_info().page_size,
+            endianness: Endianness::Little,
         }
     }
 
-    /// Take initial snapshots of the given regions
-    pub fn take_snapshot(&'a mut self, region: MemoryRegion) -> Result<()> {
-        self.snapshots.clear();
-        let mapping = self.mapper.map_region(region)?;
-        let snapshot = MemoryRegionSnapshot::from_mapped(mapping);
-        self.snapshots.push(snapshot);
+    /// Keep only per-page hashes for snapshots taken from now on
+    ///
+    /// Hashes-only snapshots use a fraction of the memory of full copies,
+    /// but only report which pages changed via [`MemoryDiff::diff_pages`],
+    /// not the changed bytes.
+    pub fn set_hashes_only(&mut self, enabled: bool) {
+        self.hashes_only = enabled;
+    }
+
+    /// Whether new snapshots keep only per-page hashes
+    pub fn hashes_only(&self) -> bool {
+        self.hashes_only
+    }
+
+    /// Decode values in `endianness` byte order in
+    /// [`MemoryDiff::diff_typed_parallel`] (little-endian by default)
+    ///
+    /// Snapshots hold raw bytes, so this applies to snapshots already taken
+    /// and the same snapshot can be compared in either byte order.
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    /// Byte order values are decoded in by typed diffs
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Take a snapshot of `region` read directly from the process,
+    /// replacing any earlier snapshot of the same region
+    pub fn take_snapshot(&mut self, region: MemoryRegion) -> Result<()> {
+        let base_address = region.base_address;
+        let snapshot = if self.hashes_only {
+            MemoryRegionSnapshot::from_process_hashes_only(self.process, region, self.page_size)?
+        } else {
+            MemoryRegionSnapshot::from_process(self.process, region)?
+        };
+        match self
+            .snapshots
+            .iter_mut()
+            .find(|s| s.base_address() == base_address)
+        {
+            Some(existing) => *existing = snapshot,
+            None => self.snapshots.push(snapshot),
+        }
         Ok(())
     }
 
     /// Detect changes by comparing current memory state with snapshots
     ///
-    /// This performs parallel comparison of all tracked regions
+    /// This performs parallel comparison of all tracked regions. Fails for
+    /// hashes-only snapshots, which keep no bytes to compare.
     pub fn diff(&self, sub_regions: &[MemoryRegion]) -> Result<HashMap<usize, Vec<MemoryChange>>> {
         if sub_regions.len() != self.snapshots.len() {
-            anyhow::bail!(
+            bail!(
                 "Region count mismatch: expected {}, got {}",
                 self.snapshots.len(),
                 sub_regions.len()
             );
         }
+        if let Some(snapshot) = self.snapshots.iter().find(|s| s.hashes_only()) {
+            bail!(
+                "Snapshot at {:016x} only holds page hashes, use diff_pages instead",
+                snapshot.base_address()
+            );
+        }
 
         // For now, implement sequential comparison
         // TODO: Add parallel implementation using rayon when benchmarks show benefit
@@ -187,6 +545,150 @@ impl<'a> MemoryDiff<'a> {
         Ok(all_changes)
     }
 
+    /// Detect which `value_type` values changed in every tracked region,
+    /// keyed by region base address
+    ///
+    /// Every `alignment`-th offset of a region is compared, like the initial
+    /// scan does, and each change is reported at its absolute address in
+    /// address order. Values are decoded in the byte order set with
+    /// [`MemoryDiff::set_endianness`], and the snapshots are kept, so the
+    /// same ones can be compared again as another type or alignment. With
+    /// the `parallel` feature the snapshots are refreshed and compared on
+    /// the rayon thread pool, in 1 MiB chunks so large regions are split
+    /// across threads too. Fails for hashes-only snapshots, which can't
+    /// tell values apart.
+    pub fn diff_typed_parallel(
+        &self,
+        value_type: ValueType,
+        alignment: usize,
+    ) -> Result<HashMap<usize, Vec<ValueChange>>> {
+        if alignment == 0 {
+            bail!("Alignment must be non-zero");
+        }
+        if let Some(snapshot) = self.snapshots.iter().find(|s| s.hashes_only()) {
+            bail!(
+                "Snapshot at {:016x} only holds page hashes, use diff_pages instead",
+                snapshot.base_address()
+            );
+        }
+
+        #[cfg(feature = "parallel")]
+        let current = self
+            .snapshots
+            .par_iter()
+            .map(MemoryRegionSnapshot::current_data)
+            .collect::<Result<Vec<_>>>()?;
+        #[cfg(not(feature = "parallel"))]
+        let current = self
+            .snapshots
+            .iter()
+            .map(MemoryRegionSnapshot::current_data)
+            .collect::<Result<Vec<_>>>()?;
+
+        let tasks: Vec<(usize, Range<usize>)> = self
+            .snapshots
+            .iter()
+            .zip(&current)
+            .enumerate()
+            .filter(|(_, (old, new))| old.data.len() == new.len())
+            .flat_map(|(index, (old, _))| {
+                slot_chunks(
+                    old.data.len(),
+                    value_type.size(),
+                    alignment,
+                    TYPED_DIFF_CHUNK_BYTES,
+                )
+                .into_iter()
+                .map(move |offsets| (index, offsets))
+            })
+            .collect();
+        let compare = |(index, offsets): &(usize, Range<usize>)| {
+            let old_snapshot = &self.snapshots[*index];
+            typed_changes(
+                old_snapshot.base_address(),
+                &old_snapshot.data,
+                &current[*index],
+                value_type,
+                alignment,
+                offsets.clone(),
+                self.endianness,
+            )
+        };
+        #[cfg(feature = "parallel")]
+        let chunks: Vec<Vec<ValueChange>> = tasks.par_iter().map(compare).collect();
+        #[cfg(not(feature = "parallel"))]
+        let chunks: Vec<Vec<ValueChange>> = tasks.iter().map(compare).collect();
+
+        // Tasks are in region and offset order, so each region stays sorted
+        let mut all_changes: HashMap<usize, Vec<ValueChange>> = self
+            .snapshots
+            .iter()
+            .map(|snapshot| (snapshot.base_address(), Vec::new()))
+            .collect();
+        for ((index, _), changes) in tasks.iter().zip(chunks) {
+            if let Some(region_changes) =
+                all_changes.get_mut(&self.snapshots[*index].base_address())
+            {
+                region_changes.extend(changes);
+            }
+        }
+        Ok(all_changes)
+    }
+
+    /// Base addresses of the tracked regions whose contents changed since
+    /// their snapshot
+    ///
+    /// Regions are compared by hash, so unlike [`MemoryDiff::diff_all`] no
+    /// per-byte changes are collected and the snapshots aren't copied; only
+    /// the current memory is hashed, against the hash stored with each
+    /// snapshot. This makes it a cheap first check in monitor loops over
+    /// mostly static memory.
+    pub fn changed_regions(&self) -> Result<Vec<usize>> {
+        let mut changed = Vec::new();
+        for snapshot in &self.snapshots {
+            if snapshot.current_hash()? != snapshot.hash() {
+                changed.push(snapshot.base_address());
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Detect changes in every tracked region, sorted by address
+    pub fn diff_all(&self) -> Result<Vec<MemoryChange>> {
+        let mut all_changes = Vec::new();
+        for old_snapshot in &self.snapshots {
+            if old_snapshot.hashes_only() {
+                bail!(
+                    "Snapshot at {:016x} only holds page hashes, use diff_pages instead",
+                    old_snapshot.base_address()
+                );
+            }
+            let mut new_snapshot = old_snapshot.clone();
+            new_snapshot.refresh()?;
+            all_changes.extend(diff_snapshots(old_snapshot, &new_snapshot));
+        }
+        all_changes.sort_by_key(|c| c.address);
+        Ok(all_changes)
+    }
+
+    /// Detect which pages changed in every tracked region, sorted by address
+    ///
+    /// Works for both full and hashes-only snapshots.
+    pub fn diff_pages(&self) -> Result<Vec<PageChange>> {
+        let mut all_changes = Vec::new();
+        for old_snapshot in &self.snapshots {
+            let mut new_snapshot = old_snapshot.clone();
+            new_snapshot.refresh()?;
+            all_changes.extend(diff_snapshot_pages(
+                old_snapshot,
+                &new_snapshot,
+                self.page_size,
+            ));
+        }
+        all_changes.sort_by_key(|c| c.address);
+        Ok(all_changes)
+    }
+
     /// Update snapshots to the current memory state
     pub fn update_snapshot(&mut self, region: &MemoryRegion) -> Result<()> {
         for snapshot in self.snapshots.iter_mut() {
@@ -195,7 +697,7 @@ impl<'a> MemoryDiff<'a> {
                 return Ok(());
             }
         }
-        anyhow::bail!(
+        bail!(
             "No snapshot found for region at address {:016x}",
             region.base_address
         );
@@ -218,6 +720,177 @@ impl<'a> MemoryDiff<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), FNV_OFFSET_BASIS);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_snapshot_hash() {
+        let data = vec![1, 2, 3, 4, 5];
+        let a = MemoryRegionSnapshot::from_slice(&data);
+        let b = MemoryRegionSnapshot::from_slice(&data);
+        assert_eq!(a.hash(), b.hash());
+        let other = vec![1, 2, 3, 4, 6];
+        assert_ne!(a.hash(), MemoryRegionSnapshot::from_slice(&other).hash());
+    }
+
+    #[test]
+    fn test_diff_snapshot_pages() {
+        let data = vec![0u8; 10];
+        let old = MemoryRegionSnapshot::from_slice(&data);
+        let mut new = old.clone();
+        new.data[5] = 1;
+        new.data[9] = 1;
+        let changes = diff_snapshot_pages(&old, &new, 4);
+        let base = data.as_ptr() as usize;
+        assert_eq!(
+            changes,
+            vec![
+                PageChange {
+                    address: base + 4,
+                    page_index: 1
+                },
+                PageChange {
+                    address: base + 8,
+                    page_index: 2
+                },
+            ]
+        );
+        assert!(diff_snapshot_pages(&old, &old, 4).is_empty());
+    }
+
+    #[test]
+    fn test_slot_chunks() {
+        // Slots at 0, 4, ..., 12 of a 16-byte region, two per chunk
+        assert_eq!(slot_chunks(16, 4, 4, 8), vec![0..8, 8..13]);
+        // Chunks are at least one slot long and start on a slot
+        assert_eq!(slot_chunks(10, 2, 4, 1), vec![0..4, 4..8, 8..9]);
+        assert!(slot_chunks(3, 4, 4, 8).is_empty());
+    }
+
+    #[test]
+    fn test_typed_changes_across_chunks() {
+        let old: Vec<u8> = (0..64).collect();
+        let mut new = old.clone();
+        new[5] = 0xff;
+        new[40..44].copy_from_slice(&1000i32.to_le_bytes());
+        let expected = typed_changes(
+            0x1000,
+            &old,
+            &new,
+            ValueType::I32,
+            4,
+            0..61,
+            Endianness::Little,
+        );
+        let addresses: Vec<usize> = expected.iter().map(|c| c.address).collect();
+        assert_eq!(addresses, vec![0x1004, 0x1028]);
+        assert_eq!(expected[1].new_value, Value::I32(1000));
+
+        let chunked: Vec<usize> = slot_chunks(old.len(), 4, 4, 12)
+            .into_iter()
+            .flat_map(|offsets| {
+                typed_changes(
+                    0x1000,
+                    &old,
+                    &new,
+                    ValueType::I32,
+                    4,
+                    offsets,
+                    Endianness::Little,
+                )
+            })
+            .map(|c| c.address)
+            .collect();
+        assert_eq!(chunked, addresses);
+
+        let big = typed_changes(
+            0x1000,
+            &old,
+            &new,
+            ValueType::I32,
+            4,
+            0..61,
+            Endianness::Big,
+        );
+        assert_eq!(big.len(), 2);
+        assert_eq!(
+            big[1].new_value,
+            Value::I32(i32::from_be_bytes(1000i32.to_le_bytes()))
+        );
+    }
+
+    fn byte_change(address: usize, old_value: u8, new_value: u8) -> MemoryChange {
+        MemoryChange {
+            address,
+            old_value,
+            new_value,
+        }
+    }
+
+    #[test]
+    fn test_group_changes_coalesces_adjacent() {
+        // Two bytes of an i32 going from 0x0100 to 0x02ff, listed out of order
+        let changes = [
+            byte_change(0x1001, 0x01, 0x02),
+            byte_change(0x1000, 0x00, 0xff),
+            byte_change(0x1000, 0x00, 0xff),
+        ];
+        let spans = group_changes(&changes, 0);
+        assert_eq!(
+            spans,
+            [ChangeSpan {
+                start: 0x1000,
+                bytes_old: vec![0x00, 0x01],
+                bytes_new: vec![0xff, 0x02],
+                changed: vec![true, true],
+            }]
+        );
+        assert_eq!(spans[0].end(), 0x1002);
+        assert_eq!(
+            spans[0].values(ValueType::U16),
+            Some((Value::U16(0x0100), Value::U16(0x02ff)))
+        );
+        assert_eq!(spans[0].values(ValueType::U32), None);
+        assert!(group_changes(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn test_group_changes_splits_gaps() {
+        let changes = [
+            byte_change(0x1000, 1, 2),
+            byte_change(0x1003, 3, 4),
+            byte_change(0x1010, 5, 6),
+        ];
+        let starts = |spans: Vec<ChangeSpan>| -> Vec<(usize, usize)> {
+            spans.iter().map(|s| (s.start, s.len())).collect()
+        };
+        assert_eq!(
+            starts(group_changes(&changes, 0)),
+            [(0x1000, 1), (0x1003, 1), (0x1010, 1)]
+        );
+        assert_eq!(
+            starts(group_changes(&changes, 1)),
+            [(0x1000, 1), (0x1003, 1), (0x1010, 1)]
+        );
+
+        // A gap of two unchanged bytes is filled with zeros
+        let spans = group_changes(&changes, 2);
+        assert_eq!(starts(spans.clone()), [(0x1000, 4), (0x1010, 1)]);
+        assert_eq!(spans[0].bytes_old, [1, 0, 0, 3]);
+        assert_eq!(spans[0].bytes_new, [2, 0, 0, 4]);
+        assert_eq!(spans[0].changed, [true, false, false, true]);
+        // Which the span can't decode values from
+        assert_eq!(spans[0].values(ValueType::U32), None);
+        assert_eq!(
+            spans[0].values(ValueType::U8),
+            Some((Value::U8(1), Value::U8(2)))
+        );
+    }
+
     #[test]
     fn test_diff_snapshots_no_changes() {
         let data = vec![1, 2, 3, 4, 5];
@@ -232,8 +905,8 @@ mod tests {
         let old_data = vec![1, 2, 3, 4, 5];
         let new_data = vec![1, 9, 3, 8, 5];
         let old = MemoryRegionSnapshot::from_slice(&old_data);
-        let new = MemoryRegionSnapshot::from_slice(&new_data);
-        
+        let _new = MemoryRegionSnapshot::from_slice(&new_data);
+
         // Since the snapshots are from different slices, they will have different addresses
         // We need to create them from the same slice or accept they won't match
         // For this test, let's just verify the snapshot functionality works with same address
@@ -245,13 +918,13 @@ mod tests {
     fn test_diff_snapshots_with_changes_2() {
         let data = vec![1, 2, 3, 4, 5];
         let old = MemoryRegionSnapshot::from_slice(&data);
-        
+
         // Create a mutable copy and modify it
         let mut new_data = data.clone();
         new_data[1] = 9;
         new_data[3] = 8;
         let new = MemoryRegionSnapshot::from_slice(&new_data);
-        
+
         // These will have different base addresses, so diff will return empty
         // This is expected behavior - snapshots from different memory locations
         let changes = diff_snapshots(&old, &new);
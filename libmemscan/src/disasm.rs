@@ -0,0 +1,190 @@
+//! Disassembly of target code around an address
+//!
+//! For code patterns the instructions around a match say more than its raw
+//! bytes. Decoding uses capstone and is only compiled with the `disasm`
+//! feature; without it every listing falls back to a hexdump so callers
+//! don't need to care which one they get.
+
+use crate::error::{MemScanError, Result};
+use crate::process::{ProcessHandle, is_64bit_process, read_process_memory};
+
+/// Bytes shown per line of the hexdump fallback
+const HEXDUMP_WIDTH: usize = 16;
+
+/// Longest possible x86 instruction
+#[cfg(feature = "disasm")]
+const MAX_INSN_LEN: usize = 15;
+
+/// Instruction set used to decode target code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86,
+    X64,
+}
+
+impl Arch {
+    /// The instruction set of `proc`, picked from whether it is 64-bit
+    pub fn of_process(proc: &ProcessHandle) -> Self {
+        if is_64bit_process(proc) {
+            Arch::X64
+        } else {
+            Arch::X86
+        }
+    }
+
+    /// Size of a pointer on this instruction set
+    pub fn pointer_size(self) -> usize {
+        match self {
+            Arch::X86 => 4,
+            Arch::X64 => 8,
+        }
+    }
+}
+
+/// Read `before` bytes before and `after` bytes from `addr` in `proc` and
+/// list them as instructions, one per line
+///
+/// Decoding starts `before` bytes ahead of `addr`, so the instructions
+/// leading up to it may be misaligned on x86. The line containing `addr`
+/// is marked with `=>`. Without the `disasm` feature the bytes are listed
+/// as a hexdump instead.
+pub fn disasm_context(
+    proc: &ProcessHandle,
+    addr: usize,
+    before: usize,
+    after: usize,
+) -> Result<Vec<String>> {
+    let start = addr.saturating_sub(before);
+    let mut buf = vec![0u8; addr - start + after];
+    let bytes_read = read_process_memory(proc, start, &mut buf);
+    if bytes_read == 0 {
+        return Err(MemScanError::ReadFailed {
+            addr: start,
+            reason: "no bytes could be read".to_string(),
+        });
+    }
+    buf.truncate(bytes_read);
+    Ok(disassemble(&buf, start, Arch::of_process(proc), addr))
+}
+
+/// List `bytes`, located at `address` in the target, as instructions of
+/// `arch`, marking the line that contains the address `mark`
+///
+/// Bytes that don't decode are listed as `.byte` and decoding resumes at
+/// the next byte, except at the end of `bytes` where they are likely a
+/// cut-off instruction and are listed together. Without the `disasm`
+/// feature this is [`hexdump_lines`].
+#[cfg(feature = "disasm")]
+pub fn disassemble(bytes: &[u8], address: usize, arch: Arch, mark: usize) -> Vec<String> {
+    use capstone::prelude::*;
+
+    let mode = match arch {
+        Arch::X86 => arch::x86::ArchMode::Mode32,
+        Arch::X64 => arch::x86::ArchMode::Mode64,
+    };
+    let Ok(cs) = Capstone::new().x86().mode(mode).build() else {
+        return hexdump_lines(bytes, address, mark);
+    };
+
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let at = address + offset;
+        let decoded = cs.disasm_count(&bytes[offset..], at as u64, 1).ok();
+        let (len, text) = match decoded.as_ref().and_then(|insns| insns.iter().next()) {
+            Some(insn) => (
+                insn.len(),
+                format!(
+                    "{} {}",
+                    insn.mnemonic().unwrap_or("?"),
+                    insn.op_str().unwrap_or("")
+                ),
+            ),
+            None if bytes.len() - offset < MAX_INSN_LEN => {
+                (bytes.len() - offset, "(truncated)".to_string())
+            }
+            None => (1, format!(".byte 0x{:02x}", bytes[offset])),
+        };
+        lines.push(listing_line(
+            &bytes[offset..offset + len],
+            at,
+            mark,
+            text.trim_end(),
+        ));
+        offset += len;
+    }
+    lines
+}
+
+/// List `bytes` as a hexdump (always, since the `disasm` feature is off)
+#[cfg(not(feature = "disasm"))]
+pub fn disassemble(bytes: &[u8], address: usize, _arch: Arch, mark: usize) -> Vec<String> {
+    hexdump_lines(bytes, address, mark)
+}
+
+/// List `bytes`, located at `address` in the target, as lines of
+/// [`HEXDUMP_WIDTH`] bytes, marking the line that contains `mark`
+pub fn hexdump_lines(bytes: &[u8], address: usize, mark: usize) -> Vec<String> {
+    bytes
+        .chunks(HEXDUMP_WIDTH)
+        .enumerate()
+        .map(|(i, chunk)| listing_line(chunk, address + i * HEXDUMP_WIDTH, mark, ""))
+        .collect()
+}
+
+/// One listing line: marker, address, bytes and (for instructions) text
+fn listing_line(bytes: &[u8], address: usize, mark: usize, text: &str) -> String {
+    let marker = if (address..address + bytes.len()).contains(&mark) {
+        "=>"
+    } else {
+        "  "
+    };
+    let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    if text.is_empty() {
+        format!("{} {:016x}  {}", marker, address, hex.join(" "))
+    } else {
+        format!(
+            "{} {:016x}  {:<24} {}",
+            marker,
+            address,
+            hex.join(" "),
+            text
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump_lines_marks_containing_line() {
+        let bytes: Vec<u8> = (0..20).collect();
+        let lines = hexdump_lines(&bytes, 0x1000, 0x1011);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "   0000000000001000  00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f"
+        );
+        assert_eq!(lines[1], "=> 0000000000001010  10 11 12 13");
+    }
+
+    #[cfg(feature = "disasm")]
+    #[test]
+    fn test_disassemble_x64() {
+        // push rbp; mov rbp, rsp; <invalid>; ret; <cut-off mov>
+        let mut bytes = vec![0x55, 0x48, 0x89, 0xe5, 0x06, 0xc3, 0x48, 0x8b, 0x3d];
+        // Followed by enough nops that the invalid byte isn't at the end
+        bytes.splice(5..5, [0x90; MAX_INSN_LEN]);
+        let lines = disassemble(&bytes, 0x1000, Arch::X64, 0x1002);
+        assert_eq!(lines.len(), 5 + MAX_INSN_LEN);
+        assert!(lines[0].starts_with("   0000000000001000  55"));
+        assert!(lines[0].ends_with("push rbp"));
+        assert!(lines[1].starts_with("=> 0000000000001001  48 89 e5"));
+        assert!(lines[1].ends_with("mov rbp, rsp"));
+        assert!(lines[2].ends_with(".byte 0x06"));
+        assert!(lines[3].ends_with("nop"));
+        assert!(lines[3 + MAX_INSN_LEN].ends_with("ret"));
+        assert!(lines[4 + MAX_INSN_LEN].ends_with("48 8b 3d                 (truncated)"));
+    }
+}
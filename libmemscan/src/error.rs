@@ -0,0 +1,154 @@
+//! Error type of the libmemscan API
+//!
+//! Fallible functions of the library return [`MemScanError`], so callers
+//! and bindings can branch on what went wrong, e.g. ask for elevated
+//! rights after [`MemScanError::AccessDenied`]. Failures without a kind of
+//! their own are [`MemScanError::Other`] with a message. The error
+//! implements [`std::error::Error`], so `?` turns it into an
+//! `anyhow::Error` in applications.
+
+use crate::values::ValueType;
+use std::fmt;
+use std::io;
+
+/// Result of the fallible libmemscan functions
+pub type Result<T, E = MemScanError> = std::result::Result<T, E>;
+
+/// Everything that can go wrong in libmemscan
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MemScanError {
+    /// No process matches a lookup, described like `with pid 1234`
+    ProcessNotFound(String),
+    /// The OS refused to open or access the target process
+    AccessDenied(String),
+    /// Target memory at `addr` could not be read
+    ReadFailed { addr: usize, reason: String },
+    /// Target memory at `addr` could not be written
+    WriteFailed { addr: usize, reason: String },
+    /// No mapped region contains the address
+    NotMapped(usize),
+    /// A hex pattern, signature or mask is malformed or doesn't fit
+    InvalidPattern(String),
+    /// A literal can't be parsed as a value of `value_type`
+    InvalidValue { value_type: ValueType, text: String },
+    /// A value of type `found` was used as `role` (e.g. `operand`) in a
+    /// session scanning for `expected`
+    TypeMismatch {
+        role: String,
+        found: ValueType,
+        expected: ValueType,
+    },
+    /// No checkpoint has this name
+    CheckpointNotFound(String),
+    /// A process lookup ran out of time
+    Timeout,
+    /// Reading or writing a local file or stream failed
+    Io(io::Error),
+    /// Any other failure, described by its message
+    Other(String),
+}
+
+impl fmt::Display for MemScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemScanError::ProcessNotFound(target) => write!(f, "process {} not found", target),
+            MemScanError::AccessDenied(message) => f.write_str(message),
+            MemScanError::ReadFailed { addr, reason } => {
+                write!(f, "Failed to read memory at {:016x}: {}", addr, reason)
+            }
+            MemScanError::WriteFailed { addr, reason } => {
+                write!(f, "Failed to write memory at {:016x}: {}", addr, reason)
+            }
+            MemScanError::NotMapped(addr) => {
+                write!(f, "No mapped region contains address {:016x}", addr)
+            }
+            MemScanError::InvalidPattern(message) => f.write_str(message),
+            MemScanError::InvalidValue { value_type, text } => {
+                write!(f, "Invalid {:?} value: {}", value_type, text)
+            }
+            MemScanError::TypeMismatch {
+                role,
+                found,
+                expected,
+            } => write!(
+                f,
+                "{} type {} does not match scan type {}",
+                role,
+                found.name(),
+                expected.name()
+            ),
+            MemScanError::CheckpointNotFound(name) => {
+                write!(f, "Checkpoint '{}' not found", name)
+            }
+            MemScanError::Timeout => f.write_str("process lookup timed out"),
+            MemScanError::Io(err) => write!(f, "{}", err),
+            MemScanError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for MemScanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MemScanError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MemScanError {
+    fn from(err: io::Error) -> Self {
+        MemScanError::Io(err)
+    }
+}
+
+/// A [`MemScanError::Other`] with a `format!` message, or another message
+/// variant when it is named first, as in `other!(InvalidPattern, "...")`
+macro_rules! other {
+    ($variant:ident, $($arg:tt)*) => {
+        $crate::error::MemScanError::$variant(format!($($arg)*))
+    };
+    ($($arg:tt)*) => {
+        $crate::error::MemScanError::Other(format!($($arg)*))
+    };
+}
+
+/// Return early with the error [`other!`] builds from the same arguments
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err($crate::error::other!($($arg)*))
+    };
+}
+
+pub(crate) use {bail, other};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        let err = MemScanError::TypeMismatch {
+            role: "operand".to_string(),
+            found: ValueType::U8,
+            expected: ValueType::I32,
+        };
+        assert_eq!(
+            err.to_string(),
+            "operand type u8 does not match scan type i32"
+        );
+        assert_eq!(
+            MemScanError::ReadFailed {
+                addr: 0x1000,
+                reason: "only read 2 of 4 bytes".to_string()
+            }
+            .to_string(),
+            "Failed to read memory at 0000000000001000: only read 2 of 4 bytes"
+        );
+
+        let err: MemScanError = io::Error::other("disk full").into();
+        assert!(matches!(err, MemScanError::Io(_)));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
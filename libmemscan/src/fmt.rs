@@ -0,0 +1,44 @@
+//! Shared formatting of sizes and addresses for human-readable output
+
+/// Units used by [`format_size`], each 1024 times the previous one
+const SIZE_UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+/// Format a byte count with the largest unit that keeps it at or above 1,
+/// e.g. `512 B`, `4.0 KiB` or `1.5 GiB`
+///
+/// Bytes are printed exactly; larger units get one decimal. Anything past
+/// the last unit stays in GiB.
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < SIZE_UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, SIZE_UNITS[unit])
+}
+
+/// Format `address` as hex padded to the width of a `pointer_size`-byte
+/// pointer, e.g. from [`crate::process::ProcessHandle::pointer_size`]
+pub fn format_address(address: usize, pointer_size: usize) -> String {
+    format!("{:0width$x}", address, width = pointer_size * 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(4096 + 512), "4.5 KiB");
+        assert_eq!(format_size(3 << 20), "3.0 MiB");
+        assert_eq!(format_size((1 << 30) + (1 << 29)), "1.5 GiB");
+        assert_eq!(format_size(2048 << 30), "2048.0 GiB");
+    }
+}
@@ -4,14 +4,26 @@
 //! Users can progressively filter memory addresses by value changes and types
 //! until only a few candidates remain.
 
-use crate::diff::MemoryDiff;
-use crate::process::{MemoryRegion, ProcessHandle, write_process_memory};
+use crate::diff::{MemoryDiff, typed_changes};
+use crate::error::{MemScanError, Result, bail, other};
+use crate::memmap::MemoryMapper;
+use crate::process::{
+    MemoryRegion, MemoryRegionIterator, ProcessHandle, RegionFilter, SystemInfo,
+    force_write_process_memory, get_main_module, read_process_memory, write_masked,
+    write_process_memory, write_process_memory_many,
+};
+use crate::scanner::{ProgressReporter, ScanProgress};
 use crate::values::{
-    MathOp, Value, ValueType, apply_math_op, value_greater_than, value_less_than, value_subtract,
+    Endianness, MathOp, Value, ValueType, apply_math_op, compare_loose, delta_f64, value_from_f64,
+    value_greater_than, value_less_than, value_subtract, value_to_address, value_to_f64,
     values_equal,
 };
-use anyhow::Result;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Filter operation for comparing values
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,8 +42,72 @@ pub enum FilterOp {
     Changed,
     /// Value unchanged compared to previous scan
     Unchanged,
+    /// Value changed by a specific delta compared to previous scan
+    ChangedBy,
+}
+
+/// Condition a single struct field has to satisfy in a struct scan
+#[derive(Debug, Clone)]
+pub enum FieldPredicate {
+    /// Field equals a specific value
+    Equals(Value),
+    /// Field is less than a specific value
+    LessThan(Value),
+    /// Field is greater than a specific value
+    GreaterThan(Value),
+    /// Field lies between two values (inclusive)
+    Between(Value, Value),
+}
+
+impl FieldPredicate {
+    /// Check whether `value` satisfies the predicate
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            FieldPredicate::Equals(v) => values_equal(value, v),
+            FieldPredicate::LessThan(v) => value_less_than(value, v),
+            FieldPredicate::GreaterThan(v) => value_greater_than(value, v),
+            FieldPredicate::Between(lo, hi) => {
+                !value_less_than(value, lo) && !value_greater_than(value, hi)
+            }
+        }
+    }
+
+    /// Convert the predicate's values to `value_type`
+    fn cast(&self, value_type: ValueType) -> Option<FieldPredicate> {
+        Some(match self {
+            FieldPredicate::Equals(v) => FieldPredicate::Equals(cast_value(v, value_type)?),
+            FieldPredicate::LessThan(v) => FieldPredicate::LessThan(cast_value(v, value_type)?),
+            FieldPredicate::GreaterThan(v) => {
+                FieldPredicate::GreaterThan(cast_value(v, value_type)?)
+            }
+            FieldPredicate::Between(lo, hi) => {
+                FieldPredicate::Between(cast_value(lo, value_type)?, cast_value(hi, value_type)?)
+            }
+        })
+    }
+}
+
+/// A field of a struct scan: a value of `value_type` at `offset` bytes
+/// from the struct's base address that has to satisfy `predicate`
+#[derive(Debug, Clone)]
+pub struct FieldSpec {
+    /// Offset of the field from the struct's base address
+    pub offset: usize,
+    /// Type of the field
+    pub value_type: ValueType,
+    /// Condition the field has to satisfy
+    pub predicate: FieldPredicate,
 }
 
+/// Candidate value types tried for every slot when auto-detecting the type
+pub const AUTO_DETECT_TYPES: [ValueType; 5] = [
+    ValueType::I16,
+    ValueType::I32,
+    ValueType::I64,
+    ValueType::F32,
+    ValueType::F64,
+];
+
 /// Checkpoint snapshot of memory values at a specific point in time
 #[derive(Debug, Clone)]
 pub struct Checkpoint {
@@ -39,6 +115,8 @@ pub struct Checkpoint {
     pub name: String,
     /// Snapshot of values at each address
     pub values: HashMap<usize, Value>,
+    /// When the checkpoint was saved
+    pub timestamp: Instant,
 }
 
 /// A memory address that matches the current filter criteria
@@ -50,6 +128,48 @@ pub struct MatchedAddress {
     pub current_value: Value,
     /// Previous value (if available)
     pub previous_value: Option<Value>,
+    /// Type the value at this address is interpreted as
+    pub value_type: ValueType,
+    /// Number of filter passes that observed a new value at this address
+    pub change_count: u32,
+    /// Number of filter passes in a row, up to the latest one, that
+    /// observed the same value at this address
+    pub stable_count: u32,
+}
+
+impl MatchedAddress {
+    /// Record `current` as the latest value seen by a filter pass
+    fn observe(&mut self, current: Value) {
+        if values_equal(&current, &self.current_value) {
+            self.stable_count = self.stable_count.saturating_add(1);
+        } else {
+            self.change_count = self.change_count.saturating_add(1);
+            self.stable_count = 0;
+        }
+        let previous = std::mem::replace(&mut self.current_value, current);
+        self.previous_value = Some(previous);
+    }
+}
+
+/// Outcome of writing to every match, e.g. with
+/// [`InteractiveScanner::write_all`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteReport {
+    /// Number of addresses written successfully
+    pub succeeded: usize,
+    /// Address and reason of every write that failed
+    pub failures: Vec<(usize, String)>,
+}
+
+/// A value that differs between a saved region dump and live memory
+#[derive(Debug, Clone)]
+pub struct ValueChange {
+    /// Address in the target process
+    pub address: usize,
+    /// Value in the saved dump
+    pub old_value: Value,
+    /// Value in live memory
+    pub new_value: Value,
 }
 
 /// Interactive memory scanner that maintains state between scans
@@ -64,8 +184,56 @@ pub struct InteractiveScanner<'a> {
     value_type: ValueType,
     /// Alignment requirement (1, 2, 4, or 8 bytes)
     alignment: usize,
+    /// Alignment last passed to `set_alignment`, applied again to every new
+    /// value type
+    requested_alignment: Option<usize>,
     /// Named checkpoints for relative filtering
     checkpoints: HashMap<String, Checkpoint>,
+    /// Whether scans and filters operate on a coherent copy of memory
+    snapshot_mode: bool,
+    /// Whether the value type is still being detected from `AUTO_DETECT_TYPES`
+    auto_detect: bool,
+    /// Whether literal comparisons promote both sides instead of casting
+    loose_typing: bool,
+    /// Largest difference between two float values `changed` and
+    /// `unchanged` treat as no change
+    float_tolerance: f64,
+    /// Region enumeration of a scanner created with [`InteractiveScanner::new_lazy`]
+    lazy: Option<LazyRegions<'a>>,
+    /// Regions given to the constructor, which stay known after filters
+    /// unmap those without matches
+    given_regions: Vec<MemoryRegion>,
+    /// Most matches an initial scan may produce before it is aborted
+    max_initial_matches: Option<usize>,
+    /// Most matches a bulk write may touch before it is refused
+    bulk_write_limit: Option<usize>,
+    /// Number of scans and filter passes that produced the current matches
+    generation: u32,
+}
+
+/// Regions a lazy scanner enumerates itself
+struct LazyRegions<'a> {
+    sys: SystemInfo,
+    /// Which regions are enumerated before `filter` sees them
+    region_filter: RegionFilter,
+    filter: Box<dyn Fn(&MemoryRegion) -> bool + 'a>,
+    /// Where the initial scan reports its progress, if anywhere
+    progress: Option<Arc<dyn ProgressReporter>>,
+    /// Regions that held matches in the last initial scan and haven't been
+    /// mapped again yet
+    pending: Vec<MemoryRegion>,
+    /// Regions accepted by the last enumeration, reused by every initial
+    /// scan until [`InteractiveScanner::refresh_regions`]
+    regions: Option<Vec<MemoryRegion>>,
+}
+
+impl LazyRegions<'_> {
+    /// Walk the address space of `process` for the regions this accepts
+    fn enumerate(&self, process: &ProcessHandle) -> Vec<MemoryRegion> {
+        MemoryRegionIterator::new_with_filter(process, &self.sys, self.region_filter.clone())
+            .filter(|region| (self.filter)(region))
+            .collect()
+    }
 }
 
 impl<'a> InteractiveScanner<'a> {
@@ -74,12 +242,24 @@ impl<'a> InteractiveScanner<'a> {
         process: &'a ProcessHandle,
         regions: Vec<MemoryRegion>,
         value_type: ValueType,
+    ) -> Self {
+        Self::with_memmap(process, regions, value_type, true)
+    }
+
+    /// Create a new interactive scanner, choosing whether regions are
+    /// memory mapped (`use_memmap`) or copied page by page.
+    pub fn with_memmap(
+        process: &'a ProcessHandle,
+        regions: Vec<MemoryRegion>,
+        value_type: ValueType,
+        use_memmap: bool,
     ) -> Self {
         let mut diff = MemoryDiff::new(process);
+        diff.mapper.set_use_memmap(use_memmap);
 
         // Map all regions using MemoryDiff's mapper
-        for region in regions {
-            let _ = diff.mapper.map_region(region);
+        for region in &regions {
+            let _ = diff.mapper.map_region(region.clone());
         }
 
         Self {
@@ -88,45 +268,549 @@ impl<'a> InteractiveScanner<'a> {
             matches: Vec::new(),
             value_type,
             alignment: value_type.size(), // Default to natural alignment
+            requested_alignment: None,
             checkpoints: HashMap::new(),
+            snapshot_mode: false,
+            auto_detect: false,
+            loose_typing: false,
+            float_tolerance: 0.0,
+            lazy: None,
+            given_regions: regions,
+            max_initial_matches: Some(DEFAULT_MAX_INITIAL_MATCHES),
+            bulk_write_limit: Some(DEFAULT_BULK_WRITE_LIMIT),
+            generation: 0,
+        }
+    }
+
+    /// Create a scanner that enumerates the regions of `process` accepted
+    /// by `region_filter` itself
+    ///
+    /// Nothing is read up front. The initial scan maps, scans and drops one
+    /// region at a time, so only a single region buffer is held while it
+    /// runs. Regions that held matches are mapped again by the next pass
+    /// that reads memory, such as a filter or checkpoint.
+    ///
+    /// The address space is only walked by the first initial scan (or call
+    /// to [`InteractiveScanner::regions`]); later scans reuse the regions
+    /// found then. Those go stale as the target allocates and frees memory:
+    /// new allocations aren't scanned and freed regions are skipped, until
+    /// [`InteractiveScanner::refresh_regions`] walks it again.
+    pub fn new_lazy(
+        process: &'a ProcessHandle,
+        sys: &SystemInfo,
+        region_filter: impl Fn(&MemoryRegion) -> bool + 'a,
+        value_type: ValueType,
+    ) -> Self {
+        let mut scanner = Self::new(process, Vec::new(), value_type);
+        scanner.lazy = Some(LazyRegions {
+            sys: sys.clone(),
+            region_filter: RegionFilter::default(),
+            filter: Box::new(region_filter),
+            progress: None,
+            pending: Vec::new(),
+            regions: None,
+        });
+        scanner
+    }
+
+    /// Set which regions a lazy scanner enumerates, by state and protection
+    ///
+    /// Regions passed to the other constructors are used as given, so this
+    /// only affects scanners created with [`InteractiveScanner::new_lazy`].
+    pub fn set_region_filter(&mut self, filter: RegionFilter) {
+        if let Some(lazy) = &mut self.lazy {
+            lazy.region_filter = filter;
+            lazy.regions = None;
         }
     }
 
+    /// The regions this scanner scans, sorted by address
+    ///
+    /// For a scanner created with [`InteractiveScanner::new_lazy`] these
+    /// are the regions cached by the last enumeration, walking the address
+    /// space first if it hasn't been yet. For the others they are the
+    /// mapped regions, which only shrink as filters drop regions without
+    /// matches.
+    pub fn regions(&mut self) -> Vec<MemoryRegion> {
+        if let Some(lazy) = &mut self.lazy {
+            if lazy.regions.is_none() {
+                lazy.regions = Some(lazy.enumerate(self.process));
+            }
+            return lazy.regions.clone().unwrap_or_default();
+        }
+        let mut regions: Vec<MemoryRegion> = self
+            .diff
+            .mapper
+            .iter()
+            .map(|mapped| mapped.remote_region.clone())
+            .collect();
+        regions.sort_by_key(|region| region.base_address);
+        regions
+    }
+
+    /// Walk the address space again for the regions a lazy scanner scans,
+    /// e.g. after the target allocated or freed memory, returning how many
+    /// there are now
+    ///
+    /// Only the next initial scan uses the new regions; the current matches
+    /// are left alone. Scanners not created with
+    /// [`InteractiveScanner::new_lazy`] scan the regions they were given, so
+    /// for them this only counts those.
+    pub fn refresh_regions(&mut self) -> usize {
+        if let Some(lazy) = &mut self.lazy {
+            let regions = lazy.enumerate(self.process);
+            let count = regions.len();
+            lazy.regions = Some(regions);
+            return count;
+        }
+        self.diff.mapper.len()
+    }
+
+    /// Send progress updates of the initial scan to `progress`
+    ///
+    /// Only scanners created with [`InteractiveScanner::new_lazy`] report
+    /// progress, since the others read all of their regions up front.
+    pub fn set_progress(&mut self, progress: Option<Arc<dyn ProgressReporter>>) {
+        if let Some(lazy) = &mut self.lazy {
+            lazy.progress = progress;
+        }
+    }
+
+    /// Choose whether regions mapped from now on use the platform mapping
+    /// APIs (`use_memmap`) or are copied page by page
+    pub fn set_use_memmap(&mut self, use_memmap: bool) {
+        self.diff.mapper.set_use_memmap(use_memmap);
+    }
+
     /// Set the alignment requirement
-    pub fn set_alignment(&mut self, alignment: usize) {
+    ///
+    /// The alignment must be a non-zero power of two and is clamped to the
+    /// value type's size, so it can loosen natural alignment (e.g. `1` for
+    /// unaligned scans) but not skip slots. On error the alignment is left
+    /// unchanged. Use [`InteractiveScanner::set_alignment_unclamped`] to
+    /// step by more than the value size on purpose.
+    pub fn set_alignment(&mut self, alignment: usize) -> Result<()> {
+        validate_alignment(alignment)?;
+        self.alignment = alignment.min(self.value_type.size());
+        self.requested_alignment = Some(alignment);
+        Ok(())
+    }
+
+    /// Set the alignment requirement without clamping it to the value size,
+    /// e.g. to only visit the first field of every 16-byte struct in an array
+    ///
+    /// The stride only applies to the current value type, so
+    /// [`InteractiveScanner::set_value_type`] goes back to natural alignment.
+    pub fn set_alignment_unclamped(&mut self, alignment: usize) -> Result<()> {
+        validate_alignment(alignment)?;
         self.alignment = alignment;
+        self.requested_alignment = None;
+        Ok(())
+    }
+
+    /// The current alignment requirement
+    pub fn alignment(&self) -> usize {
+        self.alignment
+    }
+
+    /// Enable or disable snapshot mode.
+    ///
+    /// In snapshot mode, `initial_scan` and `filter` first copy every mapped
+    /// region and then evaluate all addresses against that single copy, so
+    /// one pass sees a consistent generation of values even while the target
+    /// keeps running. This costs one extra copy of the mapped memory per pass.
+    ///
+    /// Writes (`write_value`, `modify_value`, ...) always go directly to the
+    /// live process and are only observed by the next scan or filter pass.
+    pub fn set_snapshot_mode(&mut self, enabled: bool) {
+        self.snapshot_mode = enabled;
+    }
+
+    /// Whether snapshot mode is enabled
+    pub fn snapshot_mode(&self) -> bool {
+        self.snapshot_mode
+    }
+
+    /// Enable or disable value type auto-detection.
+    ///
+    /// When enabled, `initial_scan` interprets every naturally aligned slot
+    /// as each of [`AUTO_DETECT_TYPES`] and filters compare literals against
+    /// each candidate type. As soon as the remaining matches all share one
+    /// type, the scanner locks to that type and auto-detection turns off.
+    ///
+    /// Changing this clears scan state, like [`InteractiveScanner::set_value_type`].
+    pub fn set_auto_detect(&mut self, enabled: bool) {
+        self.auto_detect = enabled;
+        self.matches.clear();
+        self.checkpoints.clear();
+        self.generation = 0;
+    }
+
+    /// Whether the value type is still being auto-detected
+    pub fn auto_detect(&self) -> bool {
+        self.auto_detect
+    }
+
+    /// Enable or disable loose typing for `eq`, `lt` and `gt` filters.
+    ///
+    /// By default a literal is first cast to the type of each match and
+    /// never matches if it cannot be represented exactly, so `-1` never
+    /// equals a `u8` and `2.5` is never less than an `i32`. With loose typing
+    /// both sides are promoted instead: integers compare exactly as i128 and
+    /// anything involving a float compares as f64. The f64 promotion loses
+    /// precision for integers above 2^53, so a large `u64` may compare equal
+    /// to a float literal of a neighbouring value.
+    pub fn set_loose_typing(&mut self, enabled: bool) {
+        self.loose_typing = enabled;
+    }
+
+    /// Whether loose typing is enabled
+    pub fn loose_typing(&self) -> bool {
+        self.loose_typing
+    }
+
+    /// Treat float values that differ by at most `epsilon` as unchanged in
+    /// `changed` and `unchanged` filters
+    ///
+    /// Floats that jitter in their last bits would otherwise always count
+    /// as changed. Integers are always compared exactly. The default of 0
+    /// compares floats exactly too.
+    pub fn set_float_tolerance(&mut self, epsilon: f64) -> Result<()> {
+        if !(epsilon >= 0.0 && epsilon.is_finite()) {
+            bail!("Tolerance must be a finite number >= 0, got {}", epsilon);
+        }
+        self.float_tolerance = epsilon;
+        Ok(())
+    }
+
+    /// Largest float difference that still counts as unchanged
+    pub fn float_tolerance(&self) -> f64 {
+        self.float_tolerance
+    }
+
+    /// Abort initial scans that would produce more than `max` matches, or
+    /// never with `None`
+    ///
+    /// The initial scan records every aligned slot, so a 1-byte type over a
+    /// large heap can need more memory than the machine has. The slots are
+    /// counted before any match is stored, so an aborted scan allocates
+    /// nothing and the previous matches are cleared. Defaults to
+    /// [`DEFAULT_MAX_INITIAL_MATCHES`].
+    pub fn set_max_initial_matches(&mut self, max: Option<usize>) {
+        self.max_initial_matches = max;
+    }
+
+    /// Most matches an initial scan may produce, if capped
+    pub fn max_initial_matches(&self) -> Option<usize> {
+        self.max_initial_matches
+    }
+
+    /// Refuse writes to all matches (`write_all` and its variants and
+    /// `modify_all`) while there are more than `limit` of them, or never
+    /// with `None`
+    ///
+    /// A filter that didn't narrow the matches enough would otherwise
+    /// overwrite thousands of unrelated values. Refused writes fail before
+    /// touching anything; run them through
+    /// [`InteractiveScanner::without_bulk_write_limit`] once confirmed.
+    /// Defaults to [`DEFAULT_BULK_WRITE_LIMIT`].
+    pub fn set_bulk_write_limit(&mut self, limit: Option<usize>) {
+        self.bulk_write_limit = limit;
+    }
+
+    /// Most matches a bulk write may touch, if limited
+    pub fn bulk_write_limit(&self) -> Option<usize> {
+        self.bulk_write_limit
+    }
+
+    /// Whether a bulk write would currently be refused, i.e. there are more
+    /// matches than the bulk write limit
+    pub fn bulk_write_needs_confirmation(&self) -> bool {
+        self.bulk_write_limit
+            .is_some_and(|limit| self.matches.len() > limit)
+    }
+
+    /// Run `f` with the bulk write limit lifted, e.g. a `write_all` the user
+    /// confirmed, restoring the limit afterwards
+    pub fn without_bulk_write_limit<T>(&mut self, f: impl FnOnce(&Self) -> T) -> T {
+        let limit = self.bulk_write_limit.take();
+        let result = f(self);
+        self.bulk_write_limit = limit;
+        result
+    }
+
+    /// Count the current matches per value type, in `AUTO_DETECT_TYPES` order
+    /// for auto-detection or just the session type otherwise.
+    pub fn matched_types(&self) -> Vec<(ValueType, usize)> {
+        let mut counts: Vec<(ValueType, usize)> = Vec::new();
+        for match_entry in &self.matches {
+            match counts
+                .iter_mut()
+                .find(|(ty, _)| *ty == match_entry.value_type)
+            {
+                Some((_, count)) => *count += 1,
+                None => counts.push((match_entry.value_type, 1)),
+            }
+        }
+        counts.sort_by_key(|(ty, _)| AUTO_DETECT_TYPES.iter().position(|t| t == ty));
+        counts
+    }
+
+    /// Lock to the detected value type once all matches agree on one
+    fn lock_detected_type(&mut self) {
+        if !self.auto_detect {
+            return;
+        }
+        let Some(first) = self.matches.first() else {
+            return;
+        };
+        let value_type = first.value_type;
+        if self.matches.iter().all(|m| m.value_type == value_type) {
+            self.auto_detect = false;
+            self.value_type = value_type;
+            self.alignment = self.alignment_for(value_type);
+        }
+    }
+
+    /// The alignment to use for `value_type`: the one chosen with
+    /// [`InteractiveScanner::set_alignment`] clamped to its size, or its
+    /// natural alignment
+    fn alignment_for(&self, value_type: ValueType) -> usize {
+        self.requested_alignment
+            .map_or(value_type.size(), |alignment| {
+                alignment.min(value_type.size())
+            })
+    }
+
+    /// Fail for operations that need a single known value type
+    fn ensure_type_known(&self) -> Result<()> {
+        if self.auto_detect {
+            bail!(
+                "Value type is still being auto-detected, narrow down the matches with filters or choose a type first"
+            );
+        }
+        Ok(())
+    }
+
+    /// Fail if `value` is not of the scan type, before it is written or used
+    /// as an operand
+    ///
+    /// Skipped while the type is still being auto-detected, where the value
+    /// is the only type information there is.
+    fn ensure_value_type(&self, value: &Value, role: &str) -> Result<()> {
+        if !self.auto_detect && value.value_type() != self.value_type {
+            return Err(MemScanError::TypeMismatch {
+                role: role.to_string(),
+                found: value.value_type(),
+                expected: self.value_type,
+            });
+        }
+        Ok(())
+    }
+
+    /// Copy all mapped regions if snapshot mode is enabled
+    fn take_pass_snapshot(&self) -> Option<HashMap<usize, Vec<u8>>> {
+        if !self.snapshot_mode {
+            return None;
+        }
+        Some(
+            self.diff
+                .mapper
+                .iter()
+                .map(|mapped| (mapped.remote_region.base_address, mapped.data().to_vec()))
+                .collect(),
+        )
+    }
+
+    /// Read the value at `address`, from the pass snapshot if one was taken
+    fn read_value(
+        &self,
+        snapshot: Option<&HashMap<usize, Vec<u8>>>,
+        address: usize,
+        value_type: ValueType,
+    ) -> Option<Value> {
+        let mapped = self.diff.mapper.get_by_address(address)?;
+        let base_address = mapped.remote_region.base_address;
+        let data = match snapshot {
+            Some(snapshot) => snapshot.get(&base_address)?.as_slice(),
+            None => mapped.data(),
+        };
+        Value::from_bytes(data, address - base_address, value_type)
+    }
+
+    /// The types and alignments an initial scan looks for
+    fn scan_types(&self) -> Vec<(ValueType, usize)> {
+        // Auto-detection tries every candidate type at its natural alignment
+        if self.auto_detect {
+            AUTO_DETECT_TYPES
+                .iter()
+                .map(|&ty| (ty, ty.size()))
+                .collect()
+        } else {
+            vec![(self.value_type, self.alignment)]
+        }
+    }
+
+    /// Refresh all mapped regions, mapping the regions left unmapped by a
+    /// lazy initial scan first
+    fn refresh_mapped_regions(&mut self) -> Result<()> {
+        self.map_pending_regions();
+        self.diff.mapper.refresh_all()
+    }
+
+    /// Map the regions of a lazy scanner that contain any of `addresses` in
+    /// place of the regions mapped so far, enumerating them first unless
+    /// they are cached
+    fn map_lazy_regions_containing(&mut self, mut addresses: Vec<usize>) {
+        let Some(lazy) = &mut self.lazy else {
+            return;
+        };
+        self.diff.mapper.clear();
+        lazy.pending.clear();
+        if lazy.regions.is_none() {
+            lazy.regions = Some(lazy.enumerate(self.process));
+        }
+        addresses.sort_unstable();
+        for region in lazy.regions.iter().flatten() {
+            let index = addresses.partition_point(|&address| address < region.base_address);
+            if addresses
+                .get(index)
+                .is_some_and(|&address| region.contains(address))
+            {
+                let _ = self.diff.mapper.map_region(region.clone());
+            }
+        }
+    }
+
+    /// Map the regions that held matches in a lazy initial scan
+    fn map_pending_regions(&mut self) {
+        if let Some(lazy) = &mut self.lazy {
+            for region in lazy.pending.drain(..) {
+                let _ = self.diff.mapper.map_region(region);
+            }
+        }
     }
 
     /// Perform initial scan to find all possible addresses
     pub fn initial_scan(&mut self) -> Result<usize> {
         self.matches.clear();
+        let scan_types = self.scan_types();
+        let max_matches = self.max_initial_matches;
+        self.matches = self.scan_all_regions(|regions, found| {
+            ensure_below_match_cap(found + slot_count(&regions, &scan_types), max_matches)?;
+            Ok(scan_regions(regions, &scan_types))
+        })?;
+
+        self.generation += 1;
+        Ok(self.matches.len())
+    }
+
+    /// Collect the matches `scan` finds in the readable parts of every
+    /// scanned region, passing it the number of matches found so far
+    ///
+    /// A lazy scanner maps, scans and drops its regions one at a time, and
+    /// the others scan their mapped regions all at once.
+    fn scan_all_regions(
+        &mut self,
+        mut scan: impl FnMut(Vec<(usize, &[u8])>, usize) -> Result<Vec<MatchedAddress>>,
+    ) -> Result<Vec<MatchedAddress>> {
+        if let Some(lazy) = &mut self.lazy {
+            return scan_lazily(self.process, &mut self.diff.mapper, lazy, scan);
+        }
+        self.diff.mapper.refresh_all()?;
+        let snapshot = self.take_pass_snapshot();
 
         // Use mapped memory from the diff tracker
-        for mapped in self.diff.mapper.iter() {
-            let base_address = mapped.remote_region.base_address;
-            let data = mapped.data();
-
-            // Scan through the region with proper alignment
-            let mut offset = 0;
-            while offset + self.value_type.size() <= data.len() {
-                if offset % self.alignment == 0 {
-                    if let Some(value) = Value::from_bytes(data, offset, self.value_type) {
-                        self.matches.push(MatchedAddress {
-                            address: base_address + offset,
-                            current_value: value,
-                            previous_value: None,
-                        });
-                    }
-                }
-                offset += self.alignment;
-            }
+        let regions = readable_slices(&self.diff.mapper, snapshot.as_ref());
+        scan(regions, 0)
+    }
+
+    /// Scan for structs where every field in `fields` satisfies its predicate
+    ///
+    /// Candidate base addresses are stepped by the struct's alignment, the
+    /// size of its largest field, which also becomes the scanner's alignment.
+    /// Each match is recorded at the struct's base address with the value
+    /// there read as the first field's type, so the regular filters keep
+    /// narrowing on the leading bytes of the struct. Predicate values are
+    /// converted to each field's type like filter literals are.
+    pub fn scan_struct(&mut self, fields: &[FieldSpec]) -> Result<usize> {
+        let Some(first) = fields.first() else {
+            bail!("A struct scan needs at least one field");
+        };
+        let fields = fields
+            .iter()
+            .map(|field| {
+                let predicate = field.predicate.cast(field.value_type).ok_or_else(|| {
+                    other!(
+                        "Field at offset {} can't be compared as {:?}",
+                        field.offset,
+                        field.value_type
+                    )
+                })?;
+                Ok(FieldSpec {
+                    predicate,
+                    ..field.clone()
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let alignment = fields
+            .iter()
+            .map(|field| field.value_type.size())
+            .max()
+            .unwrap_or(1);
+
+        self.matches.clear();
+        self.matches =
+            self.scan_all_regions(|regions, _| Ok(struct_matches(regions, &fields, alignment)))?;
+        self.auto_detect = false;
+        self.value_type = first.value_type;
+        self.alignment = alignment;
+        self.generation += 1;
+        Ok(self.matches.len())
+    }
+
+    /// Scan for runs of `len` consecutive values of the scan type that each
+    /// exceed the previous one by exactly `step`, or by any amount if `step`
+    /// is `None`
+    ///
+    /// Runs start at offsets stepped by the current alignment and their
+    /// values are packed back to back, like the slots of an inventory table.
+    /// Each match is recorded at the start of its run with its first value,
+    /// so the regular filters keep narrowing on it. `step` is converted to
+    /// the scan type like filter literals are, and a negative step finds
+    /// decreasing runs of signed and float values.
+    pub fn scan_sequence(&mut self, len: usize, step: Option<Value>) -> Result<usize> {
+        self.ensure_type_known()?;
+        if len < 2 {
+            bail!("A sequence needs at least 2 values, got {}", len);
         }
+        let value_type = self.value_type;
+        let step =
+            match step {
+                Some(step) => Some(cast_value(&step, value_type).ok_or_else(|| {
+                    other!("Step {:?} can't be compared as {:?}", step, value_type)
+                })?),
+                None => None,
+            };
+        let alignment = self.alignment;
 
+        self.matches.clear();
+        self.matches = self.scan_all_regions(|regions, _| {
+            Ok(sequence_matches(
+                regions,
+                value_type,
+                alignment,
+                len,
+                step.as_ref(),
+            ))
+        })?;
+        self.generation += 1;
         Ok(self.matches.len())
     }
 
     /// Clear scan-derived state and perform a new scan over the
-    /// currently mapped regions. Intended for a "rescan" REPL command.
+    /// currently mapped regions, or over freshly enumerated regions for a
+    /// lazy scanner. Intended for a "rescan" REPL command.
     pub fn rescan(&mut self) -> Result<usize> {
         self.matches.clear();
         self.checkpoints.clear();
@@ -134,28 +818,69 @@ impl<'a> InteractiveScanner<'a> {
     }
 
     /// Apply a filter to the current matches
+    ///
+    /// `FilterOp::ChangedBy` keeps addresses that changed by exactly
+    /// `compare_value`, use [`InteractiveScanner::filter_changed_by`] to
+    /// allow a tolerance.
     pub fn filter(&mut self, op: FilterOp, compare_value: Option<Value>) -> Result<usize> {
-        let mut new_matches = Vec::new();
-
-        for match_entry in &self.matches {
-            // Find the mapped region containing this address
-            let mapped = self.diff.mapper.get_by_address(match_entry.address);
-
-            if mapped.is_none() {
-                continue; // Region no longer mapped
-            }
+        self.filter_with_tolerance(op, compare_value, 0.0)
+    }
 
-            let mapped = mapped.unwrap();
-            let offset = match_entry.address - mapped.remote_region.base_address;
-            let data = mapped.data();
+    /// Keep addresses where `current - previous` equals `delta` within
+    /// `tolerance`, e.g. a counter that went up by exactly N.
+    ///
+    /// The delta is signed for every value type, so unsigned values can be
+    /// filtered by how much they decreased as well.
+    pub fn filter_changed_by(&mut self, delta: f64, tolerance: f64) -> Result<usize> {
+        self.filter_with_tolerance(FilterOp::ChangedBy, Some(Value::F64(delta)), tolerance)
+    }
 
-            // Read current value from mapped memory
-            let current = match Value::from_bytes(data, offset, self.value_type) {
-                Some(v) => v,
-                None => continue,
+    fn filter_with_tolerance(
+        &mut self,
+        op: FilterOp,
+        compare_value: Option<Value>,
+        tolerance: f64,
+    ) -> Result<usize> {
+        // Make sure we compare against the target's current memory
+        self.refresh_mapped_regions()?;
+        let snapshot = self.take_pass_snapshot();
+
+        // Filter in place rather than collecting into a second Vec, which
+        // would double peak memory while the match set is still huge
+        let loose_typing = self.loose_typing;
+        let float_tolerance = self.float_tolerance;
+        // Deltas are signed and may be fractional whatever the match type, so
+        // they bypass the cast of the literal to it
+        let delta = compare_value.as_ref().map(value_to_f64);
+        let mut matches = std::mem::take(&mut self.matches);
+        let mut regions = RegionCursor::new(&self.diff.mapper, snapshot.as_ref());
+        matches.retain_mut(|match_entry| {
+            // Read current value from mapped memory (dropped if no longer mapped)
+            let Some(current) = regions.read(match_entry.address, match_entry.value_type) else {
+                return false;
+            };
+            // Loose typing promotes both sides rather than casting the literal
+            let ordering = if loose_typing {
+                compare_value
+                    .as_ref()
+                    .and_then(|val| compare_loose(&current, val))
+            } else {
+                None
             };
+            // Compare literals as the type of this match (differs per match when auto-detecting)
+            let compare_value = compare_value
+                .as_ref()
+                .and_then(|val| cast_value(val, match_entry.value_type));
 
             let keep = match op {
+                FilterOp::Equals | FilterOp::LessThan | FilterOp::GreaterThan if loose_typing => {
+                    ordering
+                        == Some(match op {
+                            FilterOp::Equals => Ordering::Equal,
+                            FilterOp::LessThan => Ordering::Less,
+                            _ => Ordering::Greater,
+                        })
+                }
                 FilterOp::Equals => {
                     if let Some(ref val) = compare_value {
                         values_equal(&current, val)
@@ -179,137 +904,731 @@ impl<'a> InteractiveScanner<'a> {
                 }
                 FilterOp::Increased => value_greater_than(&current, &match_entry.current_value),
                 FilterOp::Decreased => value_less_than(&current, &match_entry.current_value),
-                FilterOp::Changed => !values_equal(&current, &match_entry.current_value),
-                FilterOp::Unchanged => values_equal(&current, &match_entry.current_value),
+                FilterOp::Changed => {
+                    !unchanged_within(&current, &match_entry.current_value, float_tolerance)
+                }
+                FilterOp::Unchanged => {
+                    unchanged_within(&current, &match_entry.current_value, float_tolerance)
+                }
+                FilterOp::ChangedBy => match delta {
+                    Some(delta) => {
+                        changed_by(&current, &match_entry.current_value, delta, tolerance)
+                    }
+                    None => false,
+                },
             };
 
             if keep {
-                new_matches.push(MatchedAddress {
-                    address: match_entry.address,
-                    current_value: current,
-                    previous_value: Some(match_entry.current_value.clone()),
-                });
+                match_entry.observe(current);
             }
-        }
+            keep
+        });
 
-        self.matches = new_matches;
+        self.matches = matches;
+        self.lock_detected_type();
 
         // Clean up regions with no matches
         self.cleanup_empty_regions();
 
+        self.generation += 1;
         Ok(self.matches.len())
     }
 
-    /// Remove regions that have no matching addresses
-    fn cleanup_empty_regions(&mut self) {
-        if self.matches.is_empty() {
-            self.diff.mapper.clear();
-            return;
-        }
+    /// Keep matches for which `pred(current, previous)` returns true, for
+    /// conditions the [`FilterOp`]s can't express, e.g. "is a power of two"
+    /// or "the low byte is 0xFF"
+    ///
+    /// `current` is read live from the target for this pass (or from the
+    /// pass snapshot in snapshot mode), as the match's own type, so it can
+    /// differ from [`MatchedAddress::current_value`]. `previous` is the
+    /// value the last scan or filter pass recorded, which
+    /// [`FilterOp::Changed`] compares against. Kept matches record `current`
+    /// like any other filter. Prefer [`InteractiveScanner::filter`] where an
+    /// op fits, it avoids a call per match.
+    pub fn filter_predicate(
+        &mut self,
+        mut pred: impl FnMut(&Value, Option<&Value>) -> bool,
+    ) -> Result<usize> {
+        let Ok(kept) = self.try_filter_predicate(|current, previous| {
+            Ok::<_, Infallible>(pred(current, previous))
+        })?;
+        Ok(kept)
+    }
 
-        // Determine which regions still have matches using MemoryRegion::is_superset_of
-        let mut active_addresses = std::collections::HashSet::new();
+    /// Like [`InteractiveScanner::filter_predicate`], but stop at the first
+    /// error `pred` returns and hand it back as the inner `Err`
+    ///
+    /// The matches are only changed once `pred` accepted or rejected every
+    /// one of them, so a failed pass leaves them as they were, e.g. for a
+    /// callback from a binding that raised.
+    pub fn try_filter_predicate<E>(
+        &mut self,
+        mut pred: impl FnMut(&Value, Option<&Value>) -> std::result::Result<bool, E>,
+    ) -> Result<std::result::Result<usize, E>> {
+        self.refresh_mapped_regions()?;
+        let snapshot = self.take_pass_snapshot();
+
+        // The value each kept match observes, decided before any is dropped
+        let mut observed = Vec::with_capacity(self.matches.len());
+        let mut regions = RegionCursor::new(&self.diff.mapper, snapshot.as_ref());
         for match_entry in &self.matches {
-            active_addresses.insert(match_entry.address);
+            let current = regions.read(match_entry.address, match_entry.value_type);
+            let keep = match &current {
+                Some(current) => match pred(current, Some(&match_entry.current_value)) {
+                    Ok(keep) => keep,
+                    Err(err) => return Ok(Err(err)),
+                },
+                None => false,
+            };
+            observed.push(current.filter(|_| keep));
         }
 
-        // Remove regions that don't contain any active addresses
-        self.diff.mapper.retain(|mapped| {
-            let region = &mapped.remote_region;
-            active_addresses.iter().any(|&addr| {
-                addr >= region.base_address && addr < region.base_address + region.size
-            })
-        });
-    }
-
-    /// Write a value to a specific address
-    pub fn write_value(&self, address: usize, value: Value) -> Result<()> {
-        let bytes = value.to_bytes();
-        let bytes_written = write_process_memory(self.process, address, &bytes);
-
-        if bytes_written < bytes.len() {
-            anyhow::bail!(
-                "Failed to write {} bytes to address {:016x}, only wrote {}",
-                bytes.len(),
-                address,
-                bytes_written
-            );
-        }
+        let mut observed = observed.into_iter();
+        self.matches
+            .retain_mut(|match_entry| match observed.next().flatten() {
+                Some(current) => {
+                    match_entry.observe(current);
+                    true
+                }
+                None => false,
+            });
+        self.lock_detected_type();
+        self.cleanup_empty_regions();
 
-        Ok(())
+        self.generation += 1;
+        Ok(Ok(self.matches.len()))
     }
 
-    /// Write a value to all matched addresses
-    pub fn write_all(&self, value: Value) -> Result<usize> {
-        let mut written = 0;
-        for match_entry in &self.matches {
-            if self.write_value(match_entry.address, value.clone()).is_ok() {
-                written += 1;
-            }
+    /// Keep addresses whose value changed in `min..=max` filter passes,
+    /// e.g. a high count for a ticking timer or a low one for a value that
+    /// only changes on an event.
+    ///
+    /// This only looks at the recorded change counts and doesn't read memory.
+    pub fn filter_change_frequency(&mut self, min: u32, max: u32) -> Result<usize> {
+        if min > max {
+            bail!("Minimum change count {} is above the maximum {}", min, max);
         }
-        Ok(written)
+        self.retain_addresses(|m| (min..=max).contains(&m.change_count));
+        Ok(self.matches.len())
     }
 
-    /// Apply a math operation to a specific address
-    pub fn modify_value(&self, address: usize, op: MathOp, operand: Value) -> Result<()> {
-        // Find the mapped region containing this address
-        let mapped = self
-            .diff
-            .mapper
-            .get_by_address(address)
-            .ok_or_else(|| anyhow::anyhow!("Address {:016x} not in mapped regions", address))?;
-
-        let offset = address - mapped.remote_region.base_address;
-        let data = mapped.data();
-
-        let current = Value::from_bytes(data, offset, self.value_type)
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse value at address {:016x}", address))?;
+    /// Keep addresses whose value stayed the same for at least the last
+    /// `min_scans` filter passes, e.g. to separate constants from state
+    /// that fluctuates
+    ///
+    /// This only looks at the recorded stable counts and doesn't read
+    /// memory, so run a few `unchanged` (or any other) filters first.
+    pub fn filter_stable(&mut self, min_scans: u32) -> usize {
+        self.retain_addresses(|m| m.stable_count >= min_scans);
+        self.matches.len()
+    }
 
-        let new_value = apply_math_op(&current, &operand, op)?;
-        self.write_value(address, new_value)
+    /// Drop matches at addresses in `lo..hi`, e.g. to exclude the stack
+    ///
+    /// This only looks at the addresses and doesn't read memory.
+    pub fn filter_not_in_range(&mut self, lo: usize, hi: usize) -> Result<usize> {
+        if lo >= hi {
+            bail!("Range start {:016x} is not below its end {:016x}", lo, hi);
+        }
+        self.retain_addresses(|m| !(lo..hi).contains(&m.address));
+        Ok(self.matches.len())
     }
 
-    /// Apply a math operation to all matched addresses
-    pub fn modify_all(&self, op: MathOp, operand: Value) -> Result<usize> {
-        let mut modified = 0;
-        for match_entry in &self.matches {
-            if self
-                .modify_value(match_entry.address, op, operand.clone())
-                .is_ok()
-            {
-                modified += 1;
-            }
+    /// Drop matches inside the module of `modules` named `name`, compared
+    /// case-insensitively against the file name (e.g. `ntdll.dll`)
+    ///
+    /// This only looks at the addresses and doesn't read memory.
+    pub fn filter_not_module(&mut self, name: &str, modules: &[MemoryRegion]) -> Result<usize> {
+        let ranges: Vec<(usize, usize)> = modules
+            .iter()
+            .filter(|module| module_name(module).is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            .map(|module| (module.base_address, module.base_address + module.size))
+            .collect();
+        if ranges.is_empty() {
+            bail!("No loaded module is named {}", name);
         }
-        Ok(modified)
+        self.retain_addresses(|m| {
+            !ranges
+                .iter()
+                .any(|&(lo, hi)| m.address >= lo && m.address < hi)
+        });
+        Ok(self.matches.len())
     }
 
-    /// Get the current matches
-    pub fn matches(&self) -> &[MatchedAddress] {
-        &self.matches
+    /// Keep the matches satisfying `keep` without reading memory
+    fn retain_addresses(&mut self, keep: impl Fn(&MatchedAddress) -> bool) {
+        self.matches.retain(keep);
+        self.lock_detected_type();
+        self.cleanup_empty_regions();
+        self.generation += 1;
     }
 
-    /// Get the current value type being scanned
-    pub fn value_type(&self) -> ValueType {
-        self.value_type
+    /// Collapse clusters of matches that lie less than `within` bytes after
+    /// the cluster's lowest address into that lowest match, returning the
+    /// new match count.
+    ///
+    /// Useful after wide or struct scans, where one object often shows up
+    /// as several nearby addresses. The kept match retains its current and
+    /// previous values.
+    pub fn coalesce(&mut self, within: usize) -> usize {
+        self.matches.sort_by_key(|m| m.address);
+        let mut cluster_start = None;
+        self.matches.retain(|m| match cluster_start {
+            Some(start) if m.address - start < within => false,
+            _ => {
+                cluster_start = Some(m.address);
+                true
+            }
+        });
+        self.lock_detected_type();
+        self.cleanup_empty_regions();
+        self.generation += 1;
+        self.matches.len()
     }
 
-    /// Change the value type used for scanning. This resets alignment
-    /// to the natural size of the new type and clears scan state so the
-    /// caller can perform a fresh initial_scan/rescan.
-    pub fn set_value_type(&mut self, value_type: ValueType) {
-        self.value_type = value_type;
-        self.alignment = value_type.size();
-        self.matches.clear();
-        self.checkpoints.clear();
+    /// Keep matches whose value looks like a pointer, i.e. points into any
+    /// region this scanner scans or any of the given `modules`.
+    ///
+    /// The regions are the ones enumerated by a lazy scanner or given to
+    /// the constructor, including those earlier filters stopped mapping
+    /// because no match was left in them. Only valid for `U32` and `U64`
+    /// value types, and only `U32` for a 32-bit target.
+    pub fn filter_pointer_like(&mut self, modules: &[MemoryRegion]) -> Result<usize> {
+        let scanned = match self.lazy {
+            Some(_) => self.regions(),
+            None => self.given_regions.clone(),
+        };
+        let mut ranges: Vec<(usize, usize)> = scanned
+            .iter()
+            .chain(modules.iter())
+            .map(|region| {
+                (
+                    region.base_address,
+                    region.base_address.saturating_add(region.size),
+                )
+            })
+            .collect();
+        ranges.sort_unstable();
+        // Ranges may overlap (e.g. a module that is also scanned), so keep
+        // the furthest end seen so far for the binary search below
+        let mut furthest = 0;
+        for range in &mut ranges {
+            furthest = furthest.max(range.1);
+            range.1 = furthest;
+        }
+        self.filter_by_pointer(|ptr| {
+            let index = ranges.partition_point(|&(lo, _)| lo <= ptr);
+            index > 0 && ptr < ranges[index - 1].1
+        })
     }
 
-    /// Get the number of regions being monitored
-    pub fn region_count(&self) -> usize {
-        self.diff.mapper.len()
+    /// Keep matches whose value, interpreted as a pointer, lies in `lo..hi`.
+    ///
+    /// Only valid for `U32` and `U64` value types, and only `U32` for a
+    /// 32-bit target.
+    pub fn filter_points_into(&mut self, lo: usize, hi: usize) -> Result<usize> {
+        self.filter_by_pointer(|ptr| ptr >= lo && ptr < hi)
     }
 
-    /// Save a checkpoint with the current memory state
+    /// Keep matches whose current value, interpreted as a pointer, satisfies `pred`
+    fn filter_by_pointer(&mut self, pred: impl Fn(usize) -> bool) -> Result<usize> {
+        self.ensure_type_known()?;
+        if !matches!(self.value_type, ValueType::U32 | ValueType::U64) {
+            bail!(
+                "Pointer filters require a U32 or U64 value type, not {:?}",
+                self.value_type
+            );
+        }
+        // Pointers of a 32-bit (e.g. WOW64) target only take up 4 bytes
+        let pointer_size = self.process.pointer_size();
+        if self.value_type.size() > pointer_size {
+            bail!(
+                "Pointer filters on a {}-bit target require a U32 value type, not {:?}",
+                pointer_size * 8,
+                self.value_type
+            );
+        }
+
+        self.refresh_mapped_regions()?;
+        let snapshot = self.take_pass_snapshot();
+
+        let mut matches = std::mem::take(&mut self.matches);
+        let mut regions = RegionCursor::new(&self.diff.mapper, snapshot.as_ref());
+        matches.retain_mut(|match_entry| {
+            let Some(current) = regions.read(match_entry.address, match_entry.value_type) else {
+                return false;
+            };
+            let keep = value_to_address(&current).is_some_and(&pred);
+            if keep {
+                match_entry.observe(current);
+            }
+            keep
+        });
+
+        self.matches = matches;
+        self.lock_detected_type();
+        self.cleanup_empty_regions();
+
+        self.generation += 1;
+        Ok(self.matches.len())
+    }
+
+    /// Remove regions that have no matching addresses
+    fn cleanup_empty_regions(&mut self) {
+        if self.matches.is_empty() {
+            self.diff.mapper.clear();
+            return;
+        }
+
+        // Determine which regions still have matches
+        let mut regions = RegionCursor::new(&self.diff.mapper, None);
+        let mut active = vec![false; regions.regions.len()];
+        for match_entry in &self.matches {
+            if let Some(index) = regions.locate(match_entry.address) {
+                active[index] = true;
+            }
+        }
+        let active_bases: std::collections::HashSet<usize> = regions
+            .regions
+            .iter()
+            .zip(active)
+            .filter(|(_, active)| *active)
+            .map(|(&(base_address, _, _), _)| base_address)
+            .collect();
+
+        // Remove regions that don't contain any active addresses
+        self.diff
+            .mapper
+            .retain(|mapped| active_bases.contains(&mapped.remote_region.base_address));
+    }
+
+    /// Read a single value of `value_type` directly from the process
+    ///
+    /// Unlike the match set this works for any readable address, mapped or not.
+    pub fn read_typed(&self, address: usize, value_type: ValueType) -> Result<Value> {
+        let mut buf = vec![0u8; value_type.size()];
+        let bytes_read = read_process_memory(self.process, address, &mut buf);
+        if bytes_read < buf.len() {
+            return Err(MemScanError::ReadFailed {
+                addr: address,
+                reason: format!("only read {} of {} bytes", bytes_read, buf.len()),
+            });
+        }
+        Value::from_bytes(&buf, 0, value_type)
+            .ok_or_else(|| other!("Failed to parse value at address {:016x}", address))
+    }
+
+    /// Write the current contents of the mapped region containing
+    /// `address` to `path` as raw bytes, returning the dumped region.
+    pub fn dump_region(&mut self, address: usize, path: &Path) -> Result<MemoryRegion> {
+        let region = self.mapped_region_at(address)?;
+        self.diff.mapper.refresh_region(region.base_address)?;
+        let mapped = self.diff.mapper.get(region.base_address).unwrap();
+        std::fs::write(path, mapped.data())
+            .map_err(|e| other!("Failed to write {}: {}", path.display(), e))?;
+        Ok(region)
+    }
+
+    /// Compare a region dump saved by [`InteractiveScanner::dump_region`]
+    /// against live memory, reporting every aligned value of the session
+    /// type that changed since.
+    ///
+    /// `region_base` may be any address inside the mapped region. The file
+    /// must be exactly as large as the region.
+    pub fn diff_against_file(
+        &mut self,
+        region_base: usize,
+        path: &Path,
+    ) -> Result<Vec<ValueChange>> {
+        self.ensure_type_known()?;
+        self.diff_against_file_as(
+            region_base,
+            path,
+            self.value_type,
+            self.alignment,
+            Endianness::Little,
+        )
+    }
+
+    /// Like [`InteractiveScanner::diff_against_file`], but compare values
+    /// of `value_type` every `alignment` bytes, read in `endianness` byte
+    /// order, instead of the session's settings
+    ///
+    /// The dump is raw bytes, so the same one can be compared again with
+    /// other settings without dumping the region anew.
+    pub fn diff_against_file_as(
+        &mut self,
+        region_base: usize,
+        path: &Path,
+        value_type: ValueType,
+        alignment: usize,
+        endianness: Endianness,
+    ) -> Result<Vec<ValueChange>> {
+        if alignment == 0 {
+            bail!("Alignment must be non-zero");
+        }
+        let region = self.mapped_region_at(region_base)?;
+        let saved =
+            std::fs::read(path).map_err(|e| other!("Failed to read {}: {}", path.display(), e))?;
+        if saved.len() != region.size {
+            bail!(
+                "{} holds {} bytes but the region at {:016x} is {} bytes",
+                path.display(),
+                saved.len(),
+                region.base_address,
+                region.size
+            );
+        }
+
+        self.diff.mapper.refresh_region(region.base_address)?;
+        let live = self.diff.mapper.get(region.base_address).unwrap().data();
+        let slots = (live.len().min(saved.len()) + 1).saturating_sub(value_type.size());
+        Ok(typed_changes(
+            region.base_address,
+            &saved,
+            live,
+            value_type,
+            alignment,
+            0..slots,
+            endianness,
+        ))
+    }
+
+    /// The mapped region containing `address`
+    fn mapped_region_at(&mut self, address: usize) -> Result<MemoryRegion> {
+        self.map_pending_regions();
+        self.diff
+            .mapper
+            .get_by_address(address)
+            .map(|mapped| mapped.remote_region.clone())
+            .ok_or(MemScanError::NotMapped(address))
+    }
+
+    /// Write a value to a specific address
+    pub fn write_value(&self, address: usize, value: Value) -> Result<()> {
+        self.ensure_value_type(&value, "value")?;
+        let bytes = value.to_bytes();
+        let bytes_written = write_process_memory(self.process, address, &bytes);
+
+        if bytes_written < bytes.len() {
+            return Err(MemScanError::WriteFailed {
+                addr: address,
+                reason: format!("only wrote {} of {} bytes", bytes_written, bytes.len()),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Write a value to a specific address and read it back
+    ///
+    /// A write can succeed while the target still holds the old value, e.g.
+    /// on a copy-on-write page or when a hook reverts it. This re-reads the
+    /// address from the process and fails unless it now holds `value`.
+    pub fn write_value_verified(&self, address: usize, value: Value) -> Result<()> {
+        let expected = value.to_bytes();
+        self.write_value(address, value.clone())?;
+
+        let mut actual = vec![0u8; expected.len()];
+        let bytes_read = read_process_memory(self.process, address, &mut actual);
+        if bytes_read < actual.len() {
+            return Err(MemScanError::ReadFailed {
+                addr: address,
+                reason: format!("only read back {} of {} bytes", bytes_read, actual.len()),
+            });
+        }
+        if actual != expected {
+            let read_back = Value::from_bytes(&actual, 0, value.value_type())
+                .map(|v| format!("{:?}", v))
+                .unwrap_or_else(|| format!("{:02x?}", actual));
+            bail!(
+                "Write to address {:016x} did not stick: wrote {:?} but read back {}",
+                address,
+                value,
+                read_back
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Write a value to a specific address, even if its page isn't writable
+    ///
+    /// See [`force_write_process_memory`] for how each platform gets past the
+    /// page protection and which pages still can't be written.
+    pub fn write_value_forced(&self, address: usize, value: Value) -> Result<()> {
+        self.ensure_value_type(&value, "value")?;
+        let bytes = value.to_bytes();
+        let bytes_written = force_write_process_memory(self.process, address, &bytes)?;
+
+        if bytes_written < bytes.len() {
+            return Err(MemScanError::WriteFailed {
+                addr: address,
+                reason: format!("only wrote {} of {} bytes", bytes_written, bytes.len()),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Write a raw byte sequence to a specific address
+    ///
+    /// Returns the number of bytes actually written, which may be less than
+    /// `bytes.len()` if part of the range is not writable.
+    pub fn write_bytes(&self, address: usize, bytes: &[u8]) -> Result<usize> {
+        let bytes_written = write_process_memory(self.process, address, bytes);
+        if bytes_written == 0 && !bytes.is_empty() {
+            return Err(MemScanError::WriteFailed {
+                addr: address,
+                reason: format!("none of the {} bytes were written", bytes.len()),
+            });
+        }
+        Ok(bytes_written)
+    }
+
+    /// Write the bytes of `data` whose `mask` entry is set to `address`,
+    /// e.g. to patch some fields of a struct without touching the others
+    ///
+    /// Returns the number of masked bytes written, see [`write_masked`].
+    pub fn write_bytes_masked(&self, address: usize, data: &[u8], mask: &[bool]) -> Result<usize> {
+        write_masked(self.process, address, data, mask)
+    }
+
+    /// Write `len` copies of `byte` starting at `address`, e.g. to zero a
+    /// buffer or NOP out code
+    ///
+    /// The whole range must lie inside one of the scanned regions. Returns
+    /// the number of bytes actually written, see
+    /// [`InteractiveScanner::write_bytes`].
+    pub fn fill(&self, address: usize, byte: u8, len: usize) -> Result<usize> {
+        if len == 0 {
+            bail!("Fill length must be at least 1");
+        }
+        let region = self
+            .known_region_at(address)
+            .ok_or(MemScanError::NotMapped(address))?;
+        let region_end = region.base_address + region.size;
+        if address.checked_add(len).is_none_or(|end| end > region_end) {
+            bail!(
+                "Filling {} bytes at {:016x} runs past the end of its region at {:016x}",
+                len,
+                address,
+                region_end
+            );
+        }
+        self.write_bytes(address, &vec![byte; len])
+    }
+
+    /// The mapped (or, for a lazy scanner, pending) region containing
+    /// `address`, without mapping anything
+    fn known_region_at(&self, address: usize) -> Option<&MemoryRegion> {
+        if let Some(mapped) = self.diff.mapper.get_by_address(address) {
+            return Some(&mapped.remote_region);
+        }
+        self.lazy.as_ref()?.pending.iter().find(|region| {
+            address >= region.base_address && address - region.base_address < region.size
+        })
+    }
+
+    /// Run `write` for every matched address, collecting which ones failed
+    fn write_each(&self, write: impl Fn(usize) -> Result<()>) -> WriteReport {
+        let mut report = WriteReport::default();
+        for match_entry in &self.matches {
+            match write(match_entry.address) {
+                Ok(()) => report.succeeded += 1,
+                Err(e) => report.failures.push((match_entry.address, e.to_string())),
+            }
+        }
+        report
+    }
+
+    /// Write each value to its address, e.g. a group of fields found by
+    /// separate scans
+    ///
+    /// Every write is attempted even if an earlier one fails, and the values
+    /// keep their own types rather than the scan type. See
+    /// [`write_process_memory_many`] for how the writes are batched.
+    pub fn write_many(&self, writes: &[(usize, Value)]) -> WriteReport {
+        let bytes: Vec<Vec<u8>> = writes.iter().map(|(_, value)| value.to_bytes()).collect();
+        let buffers: Vec<(usize, &[u8])> = writes
+            .iter()
+            .zip(&bytes)
+            .map(|((address, _), bytes)| (*address, bytes.as_slice()))
+            .collect();
+        let written = write_process_memory_many(self.process, &buffers);
+
+        let mut report = WriteReport::default();
+        for ((address, bytes), bytes_written) in buffers.into_iter().zip(written) {
+            if bytes_written < bytes.len() {
+                let err = MemScanError::WriteFailed {
+                    addr: address,
+                    reason: format!("only wrote {} of {} bytes", bytes_written, bytes.len()),
+                };
+                report.failures.push((address, err.to_string()));
+            } else {
+                report.succeeded += 1;
+            }
+        }
+        report
+    }
+
+    /// Fail if a write to every match exceeds the bulk write limit
+    fn ensure_bulk_write_allowed(&self) -> Result<()> {
+        if let Some(limit) = self.bulk_write_limit
+            && self.matches.len() > limit
+        {
+            bail!(
+                "Refusing to write to {} addresses, more than the limit of {}; \
+                 narrow the matches down or confirm the write",
+                self.matches.len(),
+                limit
+            );
+        }
+        Ok(())
+    }
+
+    /// Write a value to all matched addresses
+    ///
+    /// Fails without writing anything if there are more matches than the
+    /// bulk write limit, see [`InteractiveScanner::set_bulk_write_limit`].
+    pub fn write_all(&self, value: Value) -> Result<WriteReport> {
+        self.ensure_type_known()?;
+        self.ensure_value_type(&value, "value")?;
+        self.ensure_bulk_write_allowed()?;
+        Ok(self.write_each(|address| self.write_value(address, value.clone())))
+    }
+
+    /// Write a value to all matched addresses, forcing writes to pages that
+    /// aren't writable, see [`InteractiveScanner::write_value_forced`]
+    pub fn write_all_forced(&self, value: Value) -> Result<WriteReport> {
+        self.ensure_type_known()?;
+        self.ensure_value_type(&value, "value")?;
+        self.ensure_bulk_write_allowed()?;
+        Ok(self.write_each(|address| self.write_value_forced(address, value.clone())))
+    }
+
+    /// Write a value to all matched addresses, reading each one back
+    ///
+    /// A write only succeeds once the new value has been read back, see
+    /// [`InteractiveScanner::write_value_verified`].
+    pub fn write_all_verified(&self, value: Value) -> Result<WriteReport> {
+        self.ensure_type_known()?;
+        self.ensure_value_type(&value, "value")?;
+        self.ensure_bulk_write_allowed()?;
+        Ok(self.write_each(|address| self.write_value_verified(address, value.clone())))
+    }
+
+    /// Apply a math operation to a specific address
+    ///
+    /// The address must lie in a scanned region. Its current value is read
+    /// from the process, so this works for the regions a lazy initial scan
+    /// left unmapped as well.
+    pub fn modify_value(&self, address: usize, op: MathOp, operand: Value) -> Result<()> {
+        self.ensure_type_known()?;
+        self.ensure_value_type(&operand, "operand")?;
+        let pending = self
+            .lazy
+            .as_ref()
+            .is_some_and(|lazy| lazy.pending.iter().any(|region| region.contains(address)));
+        if !pending && self.diff.mapper.get_by_address(address).is_none() {
+            return Err(MemScanError::NotMapped(address));
+        }
+
+        let current = self.read_typed(address, self.value_type)?;
+        let new_value = apply_math_op(&current, &operand, op)?;
+        self.write_value(address, new_value)
+    }
+
+    /// Apply a math operation to all matched addresses, subject to the
+    /// bulk write limit like [`InteractiveScanner::write_all`]
+    pub fn modify_all(&self, op: MathOp, operand: Value) -> Result<WriteReport> {
+        self.ensure_type_known()?;
+        self.ensure_value_type(&operand, "operand")?;
+        self.ensure_bulk_write_allowed()?;
+        Ok(self.write_each(|address| self.modify_value(address, op, operand.clone())))
+    }
+
+    /// Number of scans and filter passes so far, e.g. 3 after an initial
+    /// scan and two filters
+    ///
+    /// Every initial scan, rescan, struct or sequence scan, session load and
+    /// successful filter pass counts, and changing the value type starts
+    /// over at 0.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Get the current matches
+    pub fn matches(&self) -> &[MatchedAddress] {
+        &self.matches
+    }
+
+    /// Get the current value type being scanned
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Change the value type used for scanning. This resets alignment
+    /// to the natural size of the new type, or clamps an alignment chosen
+    /// with [`InteractiveScanner::set_alignment`] to it again, and clears
+    /// scan state so the caller can perform a fresh initial_scan/rescan.
+    pub fn set_value_type(&mut self, value_type: ValueType) {
+        self.auto_detect = false;
+        self.value_type = value_type;
+        self.alignment = self.alignment_for(value_type);
+        self.matches.clear();
+        self.checkpoints.clear();
+        self.generation = 0;
+    }
+
+    /// View the current matches as `new_type` without rescanning, e.g. to
+    /// read a u32 scan as i32
+    ///
+    /// The current, previous and checkpoint values of every match are
+    /// reinterpreted from their bytes, so filters keep comparing against
+    /// the same history. `new_type` must have the size of the current type.
+    /// Returns the number of matches.
+    pub fn reinterpret_as(&mut self, new_type: ValueType) -> Result<usize> {
+        self.ensure_type_known()?;
+        if new_type.size() != self.value_type.size() {
+            bail!(
+                "Cannot view {:?} matches as {:?}: values are {} bytes, not {}",
+                self.value_type,
+                new_type,
+                self.value_type.size(),
+                new_type.size()
+            );
+        }
+        let reinterpret = |value: &Value| {
+            Value::from_bytes(&value.to_bytes(), 0, new_type)
+                .expect("value types of the same size hold the same bytes")
+        };
+
+        for match_entry in &mut self.matches {
+            match_entry.current_value = reinterpret(&match_entry.current_value);
+            match_entry.previous_value = match_entry.previous_value.as_ref().map(reinterpret);
+            match_entry.value_type = new_type;
+        }
+        for checkpoint in self.checkpoints.values_mut() {
+            for value in checkpoint.values.values_mut() {
+                *value = reinterpret(value);
+            }
+        }
+        self.value_type = new_type;
+        Ok(self.matches.len())
+    }
+
+    /// Get the number of regions being monitored
+    pub fn region_count(&self) -> usize {
+        let pending = self.lazy.as_ref().map_or(0, |lazy| lazy.pending.len());
+        self.diff.mapper.len() + pending
+    }
+
+    /// Save a checkpoint with the current memory state
     pub fn save_checkpoint(&mut self, name: String) -> Result<()> {
+        // Checkpoints hold one value per address
+        self.ensure_type_known()?;
+        self.refresh_mapped_regions()?;
         let mut values = HashMap::new();
 
         // Read current values for all matched addresses
@@ -320,14 +1639,18 @@ impl<'a> InteractiveScanner<'a> {
                 let offset = match_entry.address - mapped.remote_region.base_address;
                 let data = mapped.data();
 
-                if let Some(value) = Value::from_bytes(data, offset, self.value_type) {
+                if let Some(value) = Value::from_bytes(data, offset, match_entry.value_type) {
                     values.insert(match_entry.address, value);
                 }
             }
         }
 
-        self.checkpoints
-            .insert(name.clone(), Checkpoint { name, values });
+        let checkpoint = Checkpoint {
+            name: name.clone(),
+            values,
+            timestamp: Instant::now(),
+        };
+        self.checkpoints.insert(name, checkpoint);
         Ok(())
     }
 
@@ -357,73 +1680,1178 @@ impl<'a> InteractiveScanner<'a> {
     ) -> Result<usize> {
         let cp1 = self
             .get_checkpoint(cp1_name)
-            .ok_or_else(|| anyhow::anyhow!("Checkpoint '{}' not found", cp1_name))?;
+            .ok_or_else(|| MemScanError::CheckpointNotFound(cp1_name.to_string()))?;
         let cp2 = self
             .get_checkpoint(cp2_name)
-            .ok_or_else(|| anyhow::anyhow!("Checkpoint '{}' not found", cp2_name))?;
+            .ok_or_else(|| MemScanError::CheckpointNotFound(cp2_name.to_string()))?;
         let cp3 = self
             .get_checkpoint(cp3_name)
-            .ok_or_else(|| anyhow::anyhow!("Checkpoint '{}' not found", cp3_name))?;
-
-        let mut new_matches = Vec::new();
+            .ok_or_else(|| MemScanError::CheckpointNotFound(cp3_name.to_string()))?;
 
-        for match_entry in &self.matches {
+        let new_matches = keep_observed(&self.matches, &self.diff.mapper, |match_entry| {
             let addr = match_entry.address;
 
             // Get values from all three checkpoints
-            let v1 = match cp1.values.get(&addr) {
-                Some(v) => v,
-                None => continue,
+            let (Some(v1), Some(v2), Some(v3)) = (
+                cp1.values.get(&addr),
+                cp2.values.get(&addr),
+                cp3.values.get(&addr),
+            ) else {
+                return false;
             };
-            let v2 = match cp2.values.get(&addr) {
-                Some(v) => v,
-                None => continue,
+
+            // Calculate deltas: (cp2 - cp1) and (cp3 - cp2), and check if
+            // they are approximately equal within margin
+            match (value_subtract(v2, v1), value_subtract(v3, v2)) {
+                (Some(delta1), Some(delta2)) => {
+                    values_within_margin(&delta1, &delta2, margin_percent)
+                }
+                _ => false,
+            }
+        });
+
+        self.matches = new_matches;
+        self.cleanup_empty_regions();
+
+        self.generation += 1;
+        Ok(self.matches.len())
+    }
+
+    /// Change of every current match between checkpoints `cp_a_name` and
+    /// `cp_b_name`, as `(address, value at cp_b - value at cp_a)`
+    ///
+    /// Matches missing from either checkpoint are left out. Shows how the
+    /// changes are spread before picking a margin for
+    /// [`filter_checkpoint_relative`](Self::filter_checkpoint_relative).
+    pub fn checkpoint_deltas(&self, cp_a_name: &str, cp_b_name: &str) -> Result<Vec<(usize, f64)>> {
+        let cp_a = self
+            .get_checkpoint(cp_a_name)
+            .ok_or_else(|| MemScanError::CheckpointNotFound(cp_a_name.to_string()))?;
+        let cp_b = self
+            .get_checkpoint(cp_b_name)
+            .ok_or_else(|| MemScanError::CheckpointNotFound(cp_b_name.to_string()))?;
+
+        Ok(self
+            .matches
+            .iter()
+            .filter_map(|m| {
+                let va = cp_a.values.get(&m.address)?;
+                let vb = cp_b.values.get(&m.address)?;
+                Some((m.address, delta_f64(vb, va)?))
+            })
+            .collect())
+    }
+
+    /// Filter addresses whose current value is `ratio` times their value in
+    /// checkpoint `base_cp_name`, e.g. a ratio of 0.5 for a bar at half
+    /// Keeps addresses where: abs(current / base - ratio) <= abs(ratio) * margin_percent / 100,
+    /// dropping addresses whose base value is zero
+    pub fn filter_checkpoint_ratio(
+        &mut self,
+        base_cp_name: &str,
+        ratio: f64,
+        margin_percent: f64,
+    ) -> Result<usize> {
+        // Make sure we compare against the target's current memory
+        self.refresh_mapped_regions()?;
+        let snapshot = self.take_pass_snapshot();
+        // Borrow the field rather than use get_checkpoint, so the matches
+        // can be taken out of self while the checkpoint is in use
+        let base_cp = self
+            .checkpoints
+            .get(base_cp_name)
+            .ok_or_else(|| MemScanError::CheckpointNotFound(base_cp_name.to_string()))?;
+        let margin = ratio.abs() * margin_percent / 100.0;
+
+        let mut matches = std::mem::take(&mut self.matches);
+        let mut regions = RegionCursor::new(&self.diff.mapper, snapshot.as_ref());
+        matches.retain_mut(|match_entry| {
+            let addr = match_entry.address;
+            let Some(base) = base_cp.values.get(&addr).map(value_to_f64) else {
+                return false;
             };
-            let v3 = match cp3.values.get(&addr) {
-                Some(v) => v,
-                None => continue,
+            if base == 0.0 {
+                return false;
+            }
+            let Some(current) = regions.read(addr, match_entry.value_type) else {
+                return false;
             };
 
-            // Calculate deltas: (cp2 - cp1) and (cp3 - cp2)
-            let delta1 = match value_subtract(v2, v1) {
-                Some(d) => d,
-                None => continue,
+            let keep = (value_to_f64(&current) / base - ratio).abs() <= margin;
+            if keep {
+                match_entry.observe(current);
+            }
+            keep
+        });
+
+        self.matches = matches;
+        self.lock_detected_type();
+        self.cleanup_empty_regions();
+
+        self.generation += 1;
+        Ok(self.matches.len())
+    }
+
+    /// Keep matches whose value equals the value currently at `ref_addr`
+    ///
+    /// The reference is read directly from the process, so it need not be a
+    /// match itself. If it is, it's read as its match type, otherwise as the
+    /// session type. Useful for finding duplicate copies of a value, e.g. the
+    /// displayed and the internal health of a character.
+    pub fn filter_eq_address(&mut self, ref_addr: usize) -> Result<usize> {
+        let value_type = match self.matches.iter().find(|m| m.address == ref_addr) {
+            Some(m) => m.value_type,
+            None => {
+                self.ensure_type_known()?;
+                self.value_type
+            }
+        };
+        let value = self.read_typed(ref_addr, value_type)?;
+        self.filter(FilterOp::Equals, Some(value))
+    }
+
+    /// Poll the value at `addr` every `interval` for `duration`, recording
+    /// when it was first read and every time it changed
+    ///
+    /// The address is read directly from the process, as its match type if
+    /// it's a match and as the session type otherwise. A poor man's
+    /// watchpoint: changes that revert between two polls are missed, and
+    /// polls that fail to read are skipped.
+    pub fn sample_changes(
+        &self,
+        addr: usize,
+        duration: Duration,
+        interval: Duration,
+    ) -> Vec<(Instant, Value)> {
+        let value_type = self
+            .matches
+            .iter()
+            .find(|m| m.address == addr)
+            .map_or(self.value_type, |m| m.value_type);
+        sample_with(duration, interval, Instant::now, std::thread::sleep, || {
+            self.read_typed(addr, value_type).ok()
+        })
+    }
+
+    /// Filter addresses by their rate of change between two checkpoints
+    /// Keeps addresses where: abs((cp_b - cp_a) / dt - rate_per_sec) <= margin,
+    /// with dt the seconds between saving `cp_a` and `cp_b`
+    pub fn filter_rate(
+        &mut self,
+        cp_a_name: &str,
+        cp_b_name: &str,
+        rate_per_sec: f64,
+        margin: f64,
+    ) -> Result<usize> {
+        let cp_a = self
+            .get_checkpoint(cp_a_name)
+            .ok_or_else(|| MemScanError::CheckpointNotFound(cp_a_name.to_string()))?;
+        let cp_b = self
+            .get_checkpoint(cp_b_name)
+            .ok_or_else(|| MemScanError::CheckpointNotFound(cp_b_name.to_string()))?;
+
+        let dt = cp_b
+            .timestamp
+            .saturating_duration_since(cp_a.timestamp)
+            .as_secs_f64();
+        if dt <= 0.0 {
+            bail!(
+                "Checkpoint '{}' must be saved after '{}'",
+                cp_b_name,
+                cp_a_name
+            );
+        }
+
+        let new_matches = keep_observed(&self.matches, &self.diff.mapper, |match_entry| {
+            let addr = match_entry.address;
+            let (Some(va), Some(vb)) = (cp_a.values.get(&addr), cp_b.values.get(&addr)) else {
+                return false;
             };
-            let delta2 = match value_subtract(v3, v2) {
-                Some(d) => d,
-                None => continue,
+            delta_f64(vb, va).is_some_and(|delta| (delta / dt - rate_per_sec).abs() <= margin)
+        });
+
+        self.matches = new_matches;
+        self.cleanup_empty_regions();
+
+        self.generation += 1;
+        Ok(self.matches.len())
+    }
+
+    /// Save the session type and current matches to `path` as text
+    ///
+    /// The file starts with a header naming its format version (see
+    /// [`SESSION_VERSION`]), the target's pid and executable, the session
+    /// type and the alignment. Matches inside one of `modules` are stored as
+    /// an offset from the module's base address so they survive ASLR, every
+    /// other match is stored as an absolute address. Paths ending in `.gz`
+    /// are gzip-compressed, which needs the `gzip` feature. Returns the
+    /// number of matches saved and the size of the file.
+    pub fn save_session(&self, path: &Path, modules: &[MemoryRegion]) -> Result<SessionSave> {
+        self.save_session_as(path, modules, SessionFormat::Text)
+    }
+
+    /// Save the session like [`InteractiveScanner::save_session`], encoded
+    /// as `format`
+    pub fn save_session_as(
+        &self,
+        path: &Path,
+        modules: &[MemoryRegion],
+        format: SessionFormat,
+    ) -> Result<SessionSave> {
+        self.ensure_type_known()?;
+        let entries = self
+            .matches
+            .iter()
+            .map(|m| {
+                let module = modules.iter().find(|module| {
+                    m.address >= module.base_address
+                        && m.address < module.base_address.saturating_add(module.size)
+                });
+                match module.and_then(|module| Some((module, module_name(module)?))) {
+                    Some((module, name)) => SessionEntry {
+                        value_type: m.value_type,
+                        offset: m.address - module.base_address,
+                        module: Some(name.to_string()),
+                    },
+                    None => SessionEntry {
+                        value_type: m.value_type,
+                        offset: m.address,
+                        module: None,
+                    },
+                }
+            })
+            .collect();
+        let session = SessionFile {
+            pid: Some(self.process.pid()),
+            process: get_main_module(self.process)
+                .ok()
+                .as_ref()
+                .and_then(module_name)
+                .map(str::to_string),
+            value_type: self.value_type,
+            alignment: self.alignment,
+            entries,
+        };
+
+        let mut bytes = match format {
+            SessionFormat::Text => session.to_text().into_bytes(),
+            SessionFormat::Binary => session.to_binary()?,
+        };
+        if path.extension().is_some_and(|extension| extension == "gz") {
+            bytes =
+                gzip(&bytes).map_err(|e| other!("Failed to compress {}: {}", path.display(), e))?;
+        }
+        std::fs::write(path, &bytes)
+            .map_err(|e| other!("Failed to write {}: {}", path.display(), e))?;
+        Ok(SessionSave {
+            saved: self.matches.len(),
+            file_size: bytes.len() as u64,
+        })
+    }
+
+    /// Replace the matches with a session saved by
+    /// [`InteractiveScanner::save_session`]
+    ///
+    /// Module-relative matches are rebuilt against the base address of the
+    /// module with the same file name in `modules`, which should come from
+    /// `get_process_module_regions` for the (possibly restarted) target.
+    /// Matches whose module isn't loaded or whose address is no longer in a
+    /// scanned region are counted as unresolved and dropped. Checkpoints are
+    /// cleared since they refer to the previous matches.
+    ///
+    /// The format is told apart by the first bytes of the file rather than
+    /// its extension, so renamed and compressed files load as well. Files of
+    /// an older format version are read too, files of a newer one are
+    /// rejected.
+    pub fn load_session(&mut self, path: &Path, modules: &[MemoryRegion]) -> Result<SessionLoad> {
+        let mut bytes =
+            std::fs::read(path).map_err(|e| other!("Failed to read {}: {}", path.display(), e))?;
+        if bytes.starts_with(&GZIP_MAGIC) {
+            bytes = gunzip(&bytes)
+                .map_err(|e| other!("Failed to decompress {}: {}", path.display(), e))?;
+        }
+        let session = if bytes.starts_with(SESSION_BINARY_MAGIC) {
+            SessionFile::from_binary(path, &bytes[SESSION_BINARY_MAGIC.len()..])?
+        } else {
+            let text = std::str::from_utf8(&bytes)
+                .map_err(|_| other!("{} is not a memscan session file", path.display()))?;
+            SessionFile::from_text(path, text)?
+        };
+
+        let addresses: Vec<Option<usize>> = session
+            .entries
+            .iter()
+            .map(|entry| match &entry.module {
+                None => Some(entry.offset),
+                Some(name) => modules
+                    .iter()
+                    .find(|module| module_name(module) == Some(name.as_str()))
+                    .and_then(|module| module.base_address.checked_add(entry.offset)),
+            })
+            .collect();
+        self.map_lazy_regions_containing(addresses.iter().flatten().copied().collect());
+        self.refresh_mapped_regions()?;
+
+        let mut matches = Vec::new();
+        let mut unresolved = 0;
+        for (entry, &address) in session.entries.iter().zip(&addresses) {
+            match address.and_then(|address| {
+                Some((address, self.read_value(None, address, entry.value_type)?))
+            }) {
+                Some((address, current_value)) => matches.push(MatchedAddress {
+                    address,
+                    current_value,
+                    previous_value: None,
+                    value_type: entry.value_type,
+                    change_count: 0,
+                    stable_count: 0,
+                }),
+                None => unresolved += 1,
+            }
+        }
+
+        self.auto_detect = false;
+        self.value_type = session.value_type;
+        self.alignment = session.alignment;
+        self.checkpoints.clear();
+        self.matches = matches;
+        self.generation += 1;
+        Ok(SessionLoad {
+            restored: self.matches.len(),
+            unresolved,
+            saved_pid: session.pid,
+            saved_process: session.process,
+        })
+    }
+}
+
+/// Start of the first line of every text session file, followed by its
+/// version
+const SESSION_MAGIC: &str = "memscan-session";
+
+/// First bytes of every binary session file
+///
+/// The leading non-ASCII byte keeps it from being mistaken for text.
+const SESSION_BINARY_MAGIC: &[u8] = b"\x89MSB";
+
+/// First bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Format version of the session files written by
+/// [`InteractiveScanner::save_session`]
+///
+/// Version 1 only had the session type in its header. Version 2 added the
+/// `process <pid> [name]` and `alignment <n>` lines, and binary files.
+pub const SESSION_VERSION: u32 = 2;
+
+/// Encoding of the session files written by
+/// [`InteractiveScanner::save_session_as`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionFormat {
+    /// One line per match, readable and easy to edit
+    #[default]
+    Text,
+    /// Compact bincode encoding for sessions with millions of matches,
+    /// which needs the `binary-session` feature
+    Binary,
+}
+
+/// A saved session, independent of its encoding
+struct SessionFile {
+    pid: Option<u32>,
+    process: Option<String>,
+    value_type: ValueType,
+    alignment: usize,
+    entries: Vec<SessionEntry>,
+}
+
+/// A saved match, at `offset` from the base of `module` or at the absolute
+/// address `offset` without one
+struct SessionEntry {
+    value_type: ValueType,
+    offset: usize,
+    module: Option<String>,
+}
+
+impl SessionFile {
+    fn to_text(&self) -> String {
+        let mut out = format!("{} {}\n", SESSION_MAGIC, SESSION_VERSION);
+        if let Some(pid) = self.pid {
+            out.push_str(&format!("process {}", pid));
+            if let Some(name) = &self.process {
+                out.push_str(&format!(" {}", name));
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "type {}\nalignment {}\n",
+            self.value_type.name(),
+            self.alignment
+        ));
+        for entry in &self.entries {
+            match &entry.module {
+                Some(name) => out.push_str(&format!(
+                    "module {} {:x} {}\n",
+                    entry.value_type.name(),
+                    entry.offset,
+                    name
+                )),
+                None => out.push_str(&format!(
+                    "absolute {} {:x}\n",
+                    entry.value_type.name(),
+                    entry.offset
+                )),
+            }
+        }
+        out
+    }
+
+    fn from_text(path: &Path, text: &str) -> Result<Self> {
+        let lines: Vec<&str> = text.lines().collect();
+        let version = lines
+            .first()
+            .and_then(|line| line.trim().strip_prefix(SESSION_MAGIC))
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .ok_or_else(|| other!("{} is not a memscan session file", path.display()))?;
+        check_session_version(path, version)?;
+
+        let mut session_type = None;
+        let mut alignment = None;
+        let mut pid = None;
+        let mut process = None;
+        let mut body = 1;
+        while let Some(line) = lines.get(body).map(|line| line.trim()) {
+            let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            match key {
+                "type" => {
+                    session_type = Some(ValueType::from_name(rest).ok_or_else(|| {
+                        other!("{} has an invalid session type: {}", path.display(), rest)
+                    })?)
+                }
+                "alignment" => {
+                    alignment = Some(
+                        rest.parse::<usize>()
+                            .ok()
+                            .filter(|alignment| alignment.is_power_of_two())
+                            .ok_or_else(|| {
+                                other!("{} has an invalid alignment: {}", path.display(), rest)
+                            })?,
+                    )
+                }
+                "process" => {
+                    let (id, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                    pid = id.parse().ok();
+                    process = (!name.is_empty()).then(|| name.to_string());
+                }
+                _ => break,
+            }
+            body += 1;
+        }
+        let value_type =
+            session_type.ok_or_else(|| other!("{} has no valid session type", path.display()))?;
+
+        let mut entries = Vec::new();
+        for (index, line) in lines.iter().enumerate().skip(body) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || other!("Invalid entry on line {}: {}", index + 1, line);
+            let mut parts = line.splitn(4, ' ');
+            let kind = parts.next().ok_or_else(invalid)?;
+            let entry_type = parts
+                .next()
+                .and_then(ValueType::from_name)
+                .ok_or_else(invalid)?;
+            let offset = parts
+                .next()
+                .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+                .ok_or_else(invalid)?;
+            let module = match kind {
+                "absolute" => None,
+                "module" => Some(parts.next().ok_or_else(invalid)?.to_string()),
+                _ => return Err(invalid()),
             };
+            entries.push(SessionEntry {
+                value_type: entry_type,
+                offset,
+                module,
+            });
+        }
+
+        Ok(SessionFile {
+            pid,
+            process,
+            value_type,
+            // Version 1 files have no alignment and always used the natural one
+            alignment: alignment.unwrap_or(value_type.size()),
+            entries,
+        })
+    }
 
-            // Check if deltas are approximately equal within margin
-            if values_within_margin(&delta1, &delta2, margin_percent) {
-                // Read current value
-                let mapped = self.diff.mapper.get_by_address(addr);
-                if let Some(mapped) = mapped {
-                    let offset = addr - mapped.remote_region.base_address;
-                    let data = mapped.data();
-
-                    if let Some(current) = Value::from_bytes(data, offset, self.value_type) {
-                        new_matches.push(MatchedAddress {
-                            address: addr,
-                            current_value: current,
-                            previous_value: Some(match_entry.current_value.clone()),
-                        });
+    #[cfg(feature = "binary-session")]
+    fn to_binary(&self) -> Result<Vec<u8>> {
+        let mut types: Vec<ValueType> = Vec::new();
+        let mut modules: Vec<&str> = Vec::new();
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            let type_index = match types.iter().position(|&t| t == entry.value_type) {
+                Some(index) => index,
+                None => {
+                    types.push(entry.value_type);
+                    types.len() - 1
+                }
+            };
+            let module_index = match entry.module.as_deref() {
+                None => 0,
+                Some(name) => match modules.iter().position(|&m| m == name) {
+                    Some(index) => index + 1,
+                    None => {
+                        modules.push(name);
+                        modules.len()
                     }
+                },
+            };
+            entries.push((type_index as u32, module_index as u32, entry.offset as u64));
+        }
+        let session = BinarySession {
+            version: SESSION_VERSION,
+            pid: self.pid,
+            process: self.process.clone(),
+            value_type: self.value_type.name().to_string(),
+            alignment: self.alignment as u64,
+            types: types.iter().map(|t| t.name().to_string()).collect(),
+            modules: modules.iter().map(|m| m.to_string()).collect(),
+            entries,
+        };
+        let mut out = SESSION_BINARY_MAGIC.to_vec();
+        bincode::encode_into_std_write(&session, &mut out, bincode::config::standard())
+            .map_err(|e| other!("Failed to encode the session: {}", e))?;
+        Ok(out)
+    }
+
+    #[cfg(not(feature = "binary-session"))]
+    fn to_binary(&self) -> Result<Vec<u8>> {
+        bail!("Binary session files need the binary-session feature")
+    }
+
+    #[cfg(feature = "binary-session")]
+    fn from_binary(path: &Path, bytes: &[u8]) -> Result<Self> {
+        let corrupt = |what: &dyn std::fmt::Display| {
+            other!(
+                "{} is a corrupt binary session file: {}",
+                path.display(),
+                what
+            )
+        };
+        let (session, _): (BinarySession, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard())
+                .map_err(|e| corrupt(&e))?;
+        check_session_version(path, session.version)?;
+        let value_type = ValueType::from_name(&session.value_type)
+            .ok_or_else(|| corrupt(&format!("invalid session type {}", session.value_type)))?;
+        let alignment = usize::try_from(session.alignment)
+            .ok()
+            .filter(|alignment| alignment.is_power_of_two())
+            .ok_or_else(|| corrupt(&format!("invalid alignment {}", session.alignment)))?;
+        let types = session
+            .types
+            .iter()
+            .map(|name| {
+                ValueType::from_name(name).ok_or_else(|| corrupt(&format!("invalid type {}", name)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let entries = session
+            .entries
+            .iter()
+            .map(|&(type_index, module_index, offset)| {
+                let invalid = || corrupt(&format!("invalid entry {:x}", offset));
+                let module = match module_index.checked_sub(1) {
+                    None => None,
+                    Some(index) => Some(
+                        session
+                            .modules
+                            .get(index as usize)
+                            .ok_or_else(invalid)?
+                            .clone(),
+                    ),
+                };
+                Ok(SessionEntry {
+                    value_type: *types.get(type_index as usize).ok_or_else(invalid)?,
+                    offset: usize::try_from(offset).map_err(|_| invalid())?,
+                    module,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SessionFile {
+            pid: session.pid,
+            process: session.process,
+            value_type,
+            alignment,
+            entries,
+        })
+    }
+
+    #[cfg(not(feature = "binary-session"))]
+    fn from_binary(path: &Path, _bytes: &[u8]) -> Result<Self> {
+        bail!(
+            "{} is a binary session file, but this memscan was built without the \
+             binary-session feature",
+            path.display()
+        )
+    }
+}
+
+/// Layout of binary session files after [`SESSION_BINARY_MAGIC`]
+///
+/// Value types and modules are stored once and referred to by index, so
+/// each entry is a few varint-encoded bytes.
+#[cfg(feature = "binary-session")]
+#[derive(bincode::Encode, bincode::Decode)]
+struct BinarySession {
+    version: u32,
+    pid: Option<u32>,
+    process: Option<String>,
+    value_type: String,
+    alignment: u64,
+    types: Vec<String>,
+    modules: Vec<String>,
+    /// Index into `types`, index into `modules` plus one (0 for absolute
+    /// addresses) and offset of every match
+    entries: Vec<(u32, u32, u64)>,
+}
+
+/// Refuse session files of a format version this memscan can't read
+fn check_session_version(path: &Path, version: u32) -> Result<()> {
+    if version == 0 || version > SESSION_VERSION {
+        bail!(
+            "{} is a version {} session file, but this memscan only reads versions 1 to {}",
+            path.display(),
+            version,
+            SESSION_VERSION
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip(_bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    Err(std::io::Error::other(
+        "compressed session files need the gzip feature",
+    ))
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(bytes).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    Err(std::io::Error::other(
+        "the file is gzip-compressed, but this memscan was built without the gzip feature",
+    ))
+}
+
+/// File name of the image backing `module`, without its directory
+fn module_name(module: &MemoryRegion) -> Option<&str> {
+    let file = module.image_file.as_deref()?;
+    file.rsplit(['\\', '/']).next()
+}
+
+/// Outcome of [`InteractiveScanner::load_session`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionLoad {
+    /// Matches rebuilt at a readable address
+    pub restored: usize,
+    /// Matches whose module isn't loaded or whose address can't be read
+    pub unresolved: usize,
+    /// Pid of the process the session was saved from (`None` for version 1 files)
+    pub saved_pid: Option<u32>,
+    /// Executable of the process the session was saved from, if known
+    pub saved_process: Option<String>,
+}
+
+/// Outcome of [`InteractiveScanner::save_session`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionSave {
+    /// Matches written to the file
+    pub saved: usize,
+    /// Size of the file on disk in bytes
+    pub file_size: u64,
+}
+
+/// Default for [`InteractiveScanner::set_max_initial_matches`], which
+/// takes a few GiB of matches
+pub const DEFAULT_MAX_INITIAL_MATCHES: usize = 50_000_000;
+
+/// Default for [`InteractiveScanner::set_bulk_write_limit`]
+pub const DEFAULT_BULK_WRITE_LIMIT: usize = 100;
+
+/// Upper bound on the region bytes scanned by one parallel batch, which
+/// bounds how many thread-local matches exist before they're combined
+#[cfg(feature = "parallel")]
+const PARALLEL_BATCH_BYTES: usize = 64 << 20;
+
+/// Collect every aligned value in `regions` as a match, for each of the
+/// `(value_type, alignment)` pairs in `scan_types`
+///
+/// Regions are visited in address order, so the matches are sorted by
+/// address regardless of the order the regions were given in. With the
+/// `parallel` feature regions are scanned on the rayon thread pool.
+pub fn scan_regions(
+    mut regions: Vec<(usize, &[u8])>,
+    scan_types: &[(ValueType, usize)],
+) -> Vec<MatchedAddress> {
+    regions.sort_unstable_by_key(|&(base_address, _)| base_address);
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start < regions.len() {
+            let mut end = start + 1;
+            let mut batch_bytes = regions[start].1.len();
+            while end < regions.len() && batch_bytes + regions[end].1.len() <= PARALLEL_BATCH_BYTES
+            {
+                batch_bytes += regions[end].1.len();
+                end += 1;
+            }
+
+            let locals: Vec<Vec<MatchedAddress>> = regions[start..end]
+                .par_iter()
+                .map(|&(base_address, data)| scan_region(base_address, data, scan_types))
+                .collect();
+            matches.reserve(locals.iter().map(Vec::len).sum());
+            for local in locals {
+                matches.extend(local);
+            }
+            start = end;
+        }
+        matches
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut matches = Vec::new();
+        for (base_address, data) in regions {
+            matches.extend(scan_region(base_address, data, scan_types));
+        }
+        matches
+    }
+}
+
+/// Collect every aligned value in a single region
+fn scan_region(
+    base_address: usize,
+    data: &[u8],
+    scan_types: &[(ValueType, usize)],
+) -> Vec<MatchedAddress> {
+    let mut matches = Vec::new();
+    for &(value_type, alignment) in scan_types {
+        // Scan through the region with proper alignment
+        let mut offset = 0;
+        while offset + value_type.size() <= data.len() {
+            if offset % alignment == 0
+                && let Some(value) = Value::from_bytes(data, offset, value_type)
+            {
+                matches.push(MatchedAddress {
+                    address: base_address + offset,
+                    current_value: value,
+                    previous_value: None,
+                    value_type,
+                    change_count: 0,
+                    stable_count: 0,
+                });
+            }
+            offset += alignment;
+        }
+    }
+    matches
+}
+
+/// The matches `keep` accepts that are still mapped, with their current
+/// value in `mapper` observed
+///
+/// Shared by the checkpoint filters, which decide on saved values alone and
+/// only read the live value of the matches they keep.
+fn keep_observed(
+    matches: &[MatchedAddress],
+    mapper: &MemoryMapper,
+    keep: impl Fn(&MatchedAddress) -> bool,
+) -> Vec<MatchedAddress> {
+    matches
+        .iter()
+        .filter(|match_entry| keep(match_entry))
+        .filter_map(|match_entry| {
+            let mapped = mapper.get_by_address(match_entry.address)?;
+            let offset = match_entry.address - mapped.remote_region.base_address;
+            let current = Value::from_bytes(mapped.data(), offset, match_entry.value_type)?;
+            let mut entry = match_entry.clone();
+            entry.observe(current);
+            Some(entry)
+        })
+        .collect()
+}
+
+/// Number of matches [`scan_regions`] produces for `regions`, one per
+/// aligned slot of each scan type
+fn slot_count(regions: &[(usize, &[u8])], scan_types: &[(ValueType, usize)]) -> usize {
+    regions
+        .iter()
+        .map(|(_, data)| {
+            scan_types
+                .iter()
+                .map(|&(value_type, alignment)| {
+                    data.len()
+                        .checked_sub(value_type.size())
+                        .map_or(0, |last| last / alignment + 1)
+                })
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+/// Every struct in `regions` whose `fields` all satisfy their predicate,
+/// trying base addresses `alignment` bytes apart
+///
+/// Each match is recorded at the struct base with the value there read as
+/// the first field's type.
+fn struct_matches(
+    regions: Vec<(usize, &[u8])>,
+    fields: &[FieldSpec],
+    alignment: usize,
+) -> Vec<MatchedAddress> {
+    let Some(first) = fields.first() else {
+        return Vec::new();
+    };
+    let span = fields
+        .iter()
+        .map(|field| field.offset + field.value_type.size())
+        .max()
+        .unwrap_or(0);
+
+    let mut matches = Vec::new();
+    for (base_address, data) in regions {
+        let mut offset = 0;
+        while offset + span <= data.len() {
+            let all_match = fields.iter().all(|field| {
+                Value::from_bytes(data, offset + field.offset, field.value_type)
+                    .is_some_and(|value| field.predicate.matches(&value))
+            });
+            if all_match && let Some(value) = Value::from_bytes(data, offset, first.value_type) {
+                matches.push(MatchedAddress {
+                    address: base_address + offset,
+                    current_value: value,
+                    previous_value: None,
+                    value_type: first.value_type,
+                    change_count: 0,
+                    stable_count: 0,
+                });
+            }
+            offset += alignment;
+        }
+    }
+    matches
+}
+
+/// Every run in `regions` of `len` packed values of `value_type` that each
+/// exceed the previous one by exactly `step`, or by any amount without one,
+/// trying starts `alignment` bytes apart
+///
+/// Each match is recorded at the start of its run with its first value.
+fn sequence_matches(
+    regions: Vec<(usize, &[u8])>,
+    value_type: ValueType,
+    alignment: usize,
+    len: usize,
+    step: Option<&Value>,
+) -> Vec<MatchedAddress> {
+    let size = value_type.size();
+    let span = len * size;
+    let follows = |previous: &Value, next: &Value| match step {
+        Some(step) => value_subtract(next, previous).is_some_and(|d| values_equal(&d, step)),
+        None => value_greater_than(next, previous),
+    };
+
+    let mut matches = Vec::new();
+    for (base_address, data) in regions {
+        let mut offset = 0;
+        while offset + span <= data.len() {
+            if let Some(first) = Value::from_bytes(data, offset, value_type) {
+                let is_run = (1..len)
+                    .try_fold(first.clone(), |previous, i| {
+                        Value::from_bytes(data, offset + i * size, value_type)
+                            .filter(|next| follows(&previous, next))
+                    })
+                    .is_some();
+                if is_run {
+                    matches.push(MatchedAddress {
+                        address: base_address + offset,
+                        current_value: first,
+                        previous_value: None,
+                        value_type,
+                        change_count: 0,
+                        stable_count: 0,
+                    });
                 }
             }
+            offset += alignment;
         }
+    }
+    matches
+}
 
-        self.matches = new_matches;
-        self.cleanup_empty_regions();
+/// Fail an initial scan that would produce `count` matches when at most
+/// `max` are allowed
+fn ensure_below_match_cap(count: usize, max: Option<usize>) -> Result<()> {
+    if let Some(max) = max
+        && count > max
+    {
+        bail!(
+            "Initial scan would record more than {} addresses; use a larger value type, \
+             a coarser alignment or narrower regions",
+            max
+        );
+    }
+    Ok(())
+}
 
-        Ok(self.matches.len())
+/// Map the regions of `process` accepted by `lazy` (enumerating them first
+/// unless they are cached), scanning the readable parts of each with `scan`
+/// and dropping its buffer before mapping the next
+///
+/// `scan` is also passed the number of matches found so far. Regions that
+/// held matches are left in `lazy.pending` to be mapped again. Fails as
+/// soon as `scan` does.
+fn scan_lazily(
+    process: &ProcessHandle,
+    mapper: &mut MemoryMapper,
+    lazy: &mut LazyRegions,
+    mut scan: impl FnMut(Vec<(usize, &[u8])>, usize) -> Result<Vec<MatchedAddress>>,
+) -> Result<Vec<MatchedAddress>> {
+    mapper.clear();
+    lazy.pending.clear();
+    if lazy.regions.is_none() {
+        lazy.regions = Some(lazy.enumerate(process));
+    }
+    let regions = lazy.regions.clone().unwrap_or_default();
+    let mut matches = Vec::new();
+    let mut regions_scanned = 0;
+    for region in regions {
+        // Regions freed since they were enumerated fail to map
+        let Ok(mapped) = mapper.map_region(region) else {
+            continue;
+        };
+        let base_address = mapped.remote_region.base_address;
+        let slices = mapped
+            .valid_ranges()
+            .into_iter()
+            .map(|range| (base_address + range.start, &mapped.data()[range]))
+            .collect::<Vec<_>>();
+        let found = match scan(slices, matches.len()) {
+            Ok(found) => found,
+            Err(err) => {
+                mapper.clear();
+                lazy.pending.clear();
+                if let Some(progress) = &lazy.progress {
+                    progress.finish();
+                }
+                return Err(err);
+            }
+        };
+        if !found.is_empty() {
+            lazy.pending.push(mapped.remote_region.clone());
+        }
+        matches.extend(found);
+        mapper.clear();
+
+        regions_scanned += 1;
+        if let Some(progress) = &lazy.progress {
+            progress.update(&ScanProgress {
+                current_address: base_address,
+                regions_scanned,
+                matches_found: matches.len(),
+            });
+        }
+    }
+    if let Some(progress) = &lazy.progress {
+        progress.finish();
+    }
+    Ok(matches)
+}
+
+/// The readable parts of every region in `mapper` (or their copies in
+/// `snapshot`) as `(address, data)`, sorted by address
+///
+/// Zero-filled pages that couldn't be read are left out.
+fn readable_slices<'m>(
+    mapper: &'m MemoryMapper,
+    snapshot: Option<&'m HashMap<usize, Vec<u8>>>,
+) -> Vec<(usize, &'m [u8])> {
+    mapper
+        .iter()
+        .flat_map(|mapped| {
+            let base_address = mapped.remote_region.base_address;
+            let data = match snapshot {
+                Some(snapshot) => snapshot[&base_address].as_slice(),
+                None => mapped.data(),
+            };
+            mapped
+                .valid_ranges()
+                .into_iter()
+                .map(move |range| (base_address + range.start, &data[range]))
+        })
+        .collect()
+}
+
+/// Reads values from the regions of a mapper (or a pass snapshot of them)
+///
+/// The region of the last lookup is reused while addresses fall inside it,
+/// and other addresses are found by a binary search over the regions. The
+/// matches of a single-type scan are in address order, so this takes about
+/// one search per region; nothing relies on that order, though, and
+/// matches of auto-detect scans or loaded sessions cost a search each time
+/// they leave the current region.
+struct RegionCursor<'m> {
+    /// `(base_address, size, data)` of every region, sorted by base address
+    regions: Vec<(usize, usize, &'m [u8])>,
+    /// Index of the region used by the last lookup
+    current: Option<usize>,
+}
+
+impl<'m> RegionCursor<'m> {
+    fn new(mapper: &'m MemoryMapper, snapshot: Option<&'m HashMap<usize, Vec<u8>>>) -> Self {
+        let regions = mapper
+            .iter()
+            .filter_map(|mapped| {
+                let region = &mapped.remote_region;
+                let data = match snapshot {
+                    Some(snapshot) => snapshot.get(&region.base_address)?.as_slice(),
+                    None => mapped.data(),
+                };
+                Some((region.base_address, region.size, data))
+            })
+            .collect();
+        Self {
+            regions,
+            current: None,
+        }
+    }
+
+    /// Index of the region containing `address`
+    fn locate(&mut self, address: usize) -> Option<usize> {
+        let contains = |&(base_address, size, _): &(usize, usize, &[u8])| {
+            address >= base_address && address - base_address < size
+        };
+        if let Some(index) = self.current
+            && contains(&self.regions[index])
+        {
+            return Some(index);
+        }
+        let index = self
+            .regions
+            .partition_point(|&(base_address, _, _)| base_address <= address)
+            .checked_sub(1)?;
+        if !contains(&self.regions[index]) {
+            return None;
+        }
+        self.current = Some(index);
+        Some(index)
+    }
+
+    /// Read a value of `value_type` at `address`, if a region contains it
+    fn read(&mut self, address: usize, value_type: ValueType) -> Option<Value> {
+        let index = self.locate(address)?;
+        let (base_address, _, data) = self.regions[index];
+        Value::from_bytes(data, address - base_address, value_type)
+    }
+}
+
+/// Convert a compare value to `value_type`, or None if it has no exact
+/// representation in that type (e.g. 3.5 as an integer)
+fn cast_value(value: &Value, value_type: ValueType) -> Option<Value> {
+    if value.value_type() == value_type {
+        return Some(value.clone());
+    }
+    value_from_f64(value_to_f64(value), value_type)
+}
+
+/// Reject alignments that would divide by zero or skip odd offsets
+fn validate_alignment(alignment: usize) -> Result<()> {
+    if !alignment.is_power_of_two() {
+        bail!(
+            "Alignment must be a non-zero power of two, got {}",
+            alignment
+        );
+    }
+    Ok(())
+}
+
+/// The polling loop of [`InteractiveScanner::sample_changes`], with the
+/// clock, sleep and read passed in so it can run on a fake clock
+fn sample_with(
+    duration: Duration,
+    interval: Duration,
+    mut now: impl FnMut() -> Instant,
+    mut sleep: impl FnMut(Duration),
+    mut read: impl FnMut() -> Option<Value>,
+) -> Vec<(Instant, Value)> {
+    let start = now();
+    let mut samples: Vec<(Instant, Value)> = Vec::new();
+    loop {
+        let polled_at = now();
+        if let Some(value) = read()
+            && samples
+                .last()
+                .is_none_or(|(_, last)| !values_equal(last, &value))
+        {
+            samples.push((polled_at, value));
+        }
+        let elapsed = polled_at.duration_since(start);
+        if elapsed >= duration {
+            return samples;
+        }
+        sleep(interval.min(duration - elapsed));
+    }
+}
+
+/// Check if `current - previous` equals `delta` within an absolute `tolerance`
+fn changed_by(current: &Value, previous: &Value, delta: f64, tolerance: f64) -> bool {
+    match delta_f64(current, previous) {
+        Some(actual) => (actual - delta).abs() <= tolerance,
+        None => false,
+    }
+}
+
+/// Check if `current` equals `previous`, counting floats up to `epsilon`
+/// apart as equal
+fn unchanged_within(current: &Value, previous: &Value, epsilon: f64) -> bool {
+    match (current, previous) {
+        (Value::F32(a), Value::F32(b)) => a == b || (*a as f64 - *b as f64).abs() <= epsilon,
+        (Value::F64(a), Value::F64(b)) => a == b || (a - b).abs() <= epsilon,
+        _ => values_equal(current, previous),
     }
 }
 
 /// Check if two values are within a percentage margin of each other
 fn values_within_margin(a: &Value, b: &Value, margin_percent: f64) -> bool {
-    use crate::values::value_to_f64;
-
     let a_f64 = value_to_f64(a);
     let b_f64 = value_to_f64(b);
 
@@ -455,6 +2883,149 @@ mod tests {
         assert_ne!(FilterOp::Equals, FilterOp::LessThan);
     }
 
+    #[test]
+    fn test_scan_regions_address_order() {
+        let high = [1u8, 0, 0, 0, 2, 0, 0, 0];
+        let low = [3u8, 0, 0, 0];
+        let matches = scan_regions(
+            vec![(0x2000, &high[..]), (0x1000, &low[..])],
+            &[(ValueType::I32, 4)],
+        );
+        let addresses: Vec<usize> = matches.iter().map(|m| m.address).collect();
+        assert_eq!(addresses, vec![0x1000, 0x2000, 0x2004]);
+        assert!(matches!(matches[0].current_value, Value::I32(3)));
+        assert!(matches!(matches[2].current_value, Value::I32(2)));
+    }
+
+    #[test]
+    fn test_sample_with_records_changes() {
+        use std::cell::Cell;
+
+        // Every sleep advances the fake clock and the next read, so each
+        // poll sees the next value of the script
+        let start = Instant::now();
+        let elapsed = Cell::new(Duration::ZERO);
+        let script = [Some(1), Some(1), None, Some(2), Some(2), Some(3), Some(1)];
+        let polls = Cell::new(0);
+        let samples = sample_with(
+            Duration::from_millis(60),
+            Duration::from_millis(10),
+            || start + elapsed.get(),
+            |interval| elapsed.set(elapsed.get() + interval),
+            || {
+                polls.set(polls.get() + 1);
+                script[polls.get() - 1].map(Value::I32)
+            },
+        );
+
+        // Polls at 0, 10, ..., 60ms, skipping the failed read and repeats
+        assert_eq!(polls.get(), 7);
+        let trajectory: Vec<(u128, i32)> = samples
+            .iter()
+            .map(|(at, value)| match value {
+                Value::I32(v) => (at.duration_since(start).as_millis(), *v),
+                other => panic!("unexpected value {:?}", other),
+            })
+            .collect();
+        assert_eq!(trajectory, vec![(0, 1), (30, 2), (50, 3), (60, 1)]);
+    }
+
+    #[test]
+    fn test_field_predicate() {
+        let between = FieldPredicate::Between(Value::F32(0.0), Value::F32(1.0));
+        assert!(between.matches(&Value::F32(0.0)));
+        assert!(between.matches(&Value::F32(0.5)));
+        assert!(between.matches(&Value::F32(1.0)));
+        assert!(!between.matches(&Value::F32(1.5)));
+        assert!(FieldPredicate::Equals(Value::I32(100)).matches(&Value::I32(100)));
+        assert!(!FieldPredicate::LessThan(Value::I32(100)).matches(&Value::I32(100)));
+        assert!(FieldPredicate::GreaterThan(Value::I32(1)).matches(&Value::I32(2)));
+
+        // Predicate values are converted to the field's type
+        assert!(matches!(
+            FieldPredicate::Equals(Value::F64(100.0)).cast(ValueType::I32),
+            Some(FieldPredicate::Equals(Value::I32(100)))
+        ));
+        assert!(
+            FieldPredicate::Between(Value::F64(0.0), Value::F64(1.5))
+                .cast(ValueType::I32)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_matched_address_observe() {
+        let mut entry = MatchedAddress {
+            address: 0x1000,
+            current_value: Value::I32(1),
+            previous_value: None,
+            value_type: ValueType::I32,
+            change_count: 0,
+            stable_count: 0,
+        };
+        entry.observe(Value::I32(1));
+        entry.observe(Value::I32(1));
+        assert_eq!(entry.change_count, 0);
+        assert_eq!(entry.stable_count, 2);
+        entry.observe(Value::I32(2));
+        entry.observe(Value::I32(3));
+        assert_eq!(entry.change_count, 2);
+        assert_eq!(entry.stable_count, 0);
+        assert!(matches!(entry.current_value, Value::I32(3)));
+        assert!(matches!(entry.previous_value, Some(Value::I32(2))));
+    }
+
+    #[test]
+    fn test_cast_value() {
+        assert!(matches!(
+            cast_value(&Value::F64(100.0), ValueType::I32),
+            Some(Value::I32(100))
+        ));
+        assert!(matches!(
+            cast_value(&Value::F64(3.5), ValueType::F32),
+            Some(Value::F32(v)) if v == 3.5
+        ));
+        assert!(cast_value(&Value::F64(3.5), ValueType::I32).is_none());
+        assert!(cast_value(&Value::F64(70000.0), ValueType::I16).is_none());
+        assert!(matches!(
+            cast_value(&Value::U64(u64::MAX), ValueType::U64),
+            Some(Value::U64(u64::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_changed_by_integers() {
+        assert!(changed_by(&Value::I32(150), &Value::I32(100), 50.0, 0.0));
+        assert!(!changed_by(&Value::I32(151), &Value::I32(100), 50.0, 0.0));
+        assert!(changed_by(&Value::I32(151), &Value::I32(100), 50.0, 1.0));
+        // Unsigned values can decrease without wrapping around
+        assert!(changed_by(&Value::U32(95), &Value::U32(100), -5.0, 0.0));
+        assert!(!changed_by(&Value::U32(105), &Value::U32(100), -5.0, 0.0));
+        // Type mismatch never matches
+        assert!(!changed_by(&Value::I32(150), &Value::I64(100), 50.0, 0.0));
+    }
+
+    #[test]
+    fn test_changed_by_floats() {
+        assert!(changed_by(&Value::F32(1.5), &Value::F32(1.0), 0.5, 0.0));
+        assert!(!changed_by(&Value::F64(1.3), &Value::F64(1.0), 0.5, 0.0));
+        assert!(changed_by(&Value::F64(1.45), &Value::F64(1.0), 0.5, 0.1));
+        assert!(changed_by(&Value::F64(-2.0), &Value::F64(0.0), -2.0, 0.0));
+    }
+
+    #[test]
+    fn test_unchanged_within() {
+        let a = Value::F32(1.0);
+        let b = Value::F32(f32::from_bits(1.0f32.to_bits() + 1));
+        assert!(!unchanged_within(&a, &b, 0.0));
+        assert!(unchanged_within(&a, &b, 1e-6));
+        assert!(!unchanged_within(&a, &Value::F32(1.01), 1e-6));
+        assert!(unchanged_within(&Value::F64(2.0), &Value::F64(2.0), 0.0));
+        // Integers stay exact
+        assert!(!unchanged_within(&Value::I32(1), &Value::I32(2), 10.0));
+        assert!(unchanged_within(&Value::I32(2), &Value::I32(2), 0.0));
+    }
+
     #[test]
     fn test_values_within_margin() {
         // Test exact match
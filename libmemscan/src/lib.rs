@@ -2,39 +2,417 @@
 //!
 //! This module re-exports functions that need to be benchmarked,
 //! allowing benchmark code to use the real implementations instead of copies.
+//!
+//! Most users should start with [`MemScan`], which attaches to a process
+//! and hides the system info and module lookups.
 
 // OS-specific modules
 pub(crate) mod linux;
 pub(crate) mod windows;
 
 // Platform-independent modules
+pub mod color;
 pub mod diff;
+pub mod disasm;
+pub mod error;
+pub mod fmt;
 pub mod interactive;
 pub mod memmap;
 pub mod process;
 pub mod scanner;
+pub mod symbols;
 pub mod values;
 
-use anyhow::Result;
+pub use crate::error::MemScanError;
+
+use crate::error::{Result, bail, other};
+use crate::interactive::InteractiveScanner;
+use crate::process::{
+    MemoryRegion, ProcessHandle, SystemInfo, find_process_by_name, get_process_module_regions,
+    open_process, query_system_info,
+};
+use crate::scanner::{ScanOptions, scan_process_collect};
+use crate::values::ValueType;
+
+/// A process opened for scanning, with its system info and module list
+/// already looked up
+///
+/// This is the easiest way in: attach to a process, then scan it for a
+/// byte pattern or start an [`InteractiveScanner`]. The modules in
+/// [`crate::process`] and [`crate::scanner`] stay available for anything
+/// this doesn't cover.
+///
+/// ```no_run
+/// use libmemscan::MemScan;
+/// use libmemscan::values::ValueType;
+///
+/// let memscan = MemScan::attach_by_name("game.exe")?;
+/// for m in memscan.scan_pattern(&[0x4D, 0x5A, 0x90, 0x00])? {
+///     println!("{:016x} {:?}", m.address, m.module);
+/// }
+///
+/// let mut scanner = memscan.interactive(ValueType::I32);
+/// scanner.initial_scan()?;
+/// # Ok::<(), libmemscan::MemScanError>(())
+/// ```
+pub struct MemScan {
+    process: ProcessHandle,
+    sys: SystemInfo,
+    modules: Vec<MemoryRegion>,
+    options: ScanOptions,
+}
+
+/// A pattern match found by [`MemScan::scan_pattern`] or
+/// [`scanner::scan_regions`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanMatch {
+    /// Address of the match in the target process
+    pub address: usize,
+    /// File name of the loaded module containing the match
+    pub module: Option<String>,
+    /// Offset of the match from the start of that module
+    pub offset: Option<usize>,
+}
+
+impl ScanMatch {
+    /// Describe `address` relative to the first of `modules` with an image
+    /// file that contains it
+    pub(crate) fn locate(address: usize, modules: &[MemoryRegion]) -> Self {
+        let module = modules.iter().find(|m| {
+            m.image_file.is_some()
+                && address >= m.base_address
+                && address < m.base_address.saturating_add(m.size)
+        });
+        ScanMatch {
+            address,
+            module: module
+                .and_then(|m| m.image_file.as_deref())
+                .map(|f| f.rsplit(['\\', '/']).next().unwrap_or(f).to_string()),
+            offset: module.map(|m| address - m.base_address),
+        }
+    }
+}
+
+impl MemScan {
+    /// Open the process with id `pid`
+    pub fn attach(pid: u32) -> Result<Self> {
+        let process = open_process(pid)?;
+        let modules = get_process_module_regions(&process)?;
+        Ok(Self {
+            process,
+            sys: query_system_info(),
+            modules,
+            options: ScanOptions::default(),
+        })
+    }
+
+    /// Open the first process whose executable name matches `name`
+    /// (case-insensitive), see [`find_process_by_name`]
+    pub fn attach_by_name(name: &str) -> Result<Self> {
+        let pid = find_process_by_name(name)?
+            .ok_or_else(|| other!(ProcessNotFound, "with name '{}'", name))?;
+        Self::attach(pid)
+    }
+
+    /// Use `options` to choose which regions are scanned and how they are
+    /// read (defaults to [`ScanOptions::default`])
+    pub fn with_options(mut self, options: ScanOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The attached process, for the lower-level functions
+    pub fn process(&self) -> &ProcessHandle {
+        &self.process
+    }
+
+    /// System information used to enumerate regions
+    pub fn system_info(&self) -> &SystemInfo {
+        &self.sys
+    }
+
+    /// Loaded module regions, which scans skip unless `all_modules` is set
+    pub fn modules(&self) -> &[MemoryRegion] {
+        &self.modules
+    }
+
+    /// Find every occurrence of `pattern` in the scanned regions, sorted
+    /// by address
+    pub fn scan_pattern(&self, pattern: &[u8]) -> Result<Vec<ScanMatch>> {
+        let report = scan_process_collect(
+            &self.process,
+            &self.sys,
+            pattern,
+            &self.options,
+            &self.modules,
+        )?;
+        Ok(report
+            .matches
+            .into_iter()
+            .map(|address| ScanMatch::locate(address, &self.modules))
+            .collect())
+    }
+
+    /// Start an interactive scanner for values of `value_type` over the
+    /// regions the scan options select
+    ///
+    /// Regions are enumerated by the scanner's initial scan, see
+    /// [`InteractiveScanner::new_lazy`].
+    pub fn interactive(&self, value_type: ValueType) -> InteractiveScanner<'_> {
+        let options = self.options.clone();
+        let modules = self.modules.clone();
+        let is_scannable = move |region: &MemoryRegion| {
+            if !options.all_modules && region.containing_module(&modules).is_some() {
+                return false;
+            }
+            options.skip_reason(region).is_none()
+        };
+        let mut scanner =
+            InteractiveScanner::new_lazy(&self.process, &self.sys, is_scannable, value_type);
+        scanner.set_use_memmap(self.options.use_memmap);
+        scanner.set_region_filter(self.options.region_filter.clone());
+        scanner
+    }
+}
 
 /// Parse a hex string like "DEADBEEF" or "4D 5A 90 00" into bytes.
+///
+/// Bytes may be separated by whitespace, `,`, `-` or `|`, and each token may
+/// carry a `0x` prefix or be written as `\x` escapes, e.g. `0x4D 0x5A`,
+/// `4D,5A` or `\x4D\x5A`. Errors point at the offending byte, e.g.
+/// `invalid hex byte 'GH' (byte 2, column 7) near "4D 5A GH 00"`.
 pub fn parse_hex_pattern(s: &str) -> Result<Vec<u8>> {
-    let filtered: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    // Every hex digit with its byte offset in `s`
+    let mut digits: Vec<(usize, char)> = Vec::with_capacity(s.len());
+    for (start, token) in hex_tokens(s) {
+        let before = digits.len();
+        if token.contains("\\x") {
+            let mut offset = start;
+            for piece in token.split("\\x") {
+                digits.extend(piece.char_indices().map(|(i, c)| (offset + i, c)));
+                offset += piece.len() + 2;
+            }
+        } else {
+            let prefix = if token.starts_with("0x") || token.starts_with("0X") {
+                2
+            } else {
+                0
+            };
+            let body = &token[prefix..];
+            digits.extend(body.char_indices().map(|(i, c)| (start + prefix + i, c)));
+        }
+        if digits.len() == before {
+            bail!(
+                InvalidPattern,
+                "hex prefix '{}' is not followed by any digits ({})",
+                token,
+                describe_position(s, start)
+            );
+        }
+    }
 
-    if filtered.len() % 2 != 0 {
-        anyhow::bail!("hex pattern length must be even");
+    if !digits.len().is_multiple_of(2) {
+        let (offset, _) = digits[digits.len() - 1];
+        bail!(
+            InvalidPattern,
+            "hex pattern length must be even, the last digit has no pair ({})",
+            describe_position(s, offset)
+        );
     }
 
-    let mut bytes = Vec::with_capacity(filtered.len() / 2);
-    for i in (0..filtered.len()).step_by(2) {
-        let byte_str = &filtered[i..i + 2];
-        let b = u8::from_str_radix(byte_str, 16)
-            .map_err(|_| anyhow::anyhow!("invalid hex byte '{}'", byte_str))?;
+    let mut bytes = Vec::with_capacity(digits.len() / 2);
+    for pair in digits.chunks_exact(2) {
+        let byte_str: String = pair.iter().map(|&(_, c)| c).collect();
+        let b = u8::from_str_radix(&byte_str, 16).map_err(|_| {
+            other!(
+                InvalidPattern,
+                "invalid hex byte '{}' (byte {}, {}) near \"{}\"",
+                byte_str,
+                bytes.len(),
+                describe_position(s, pair[0].0),
+                context(s, pair[0].0)
+            )
+        })?;
         bytes.push(b);
     }
     Ok(bytes)
 }
 
+/// Parse a hex pattern like [`parse_hex_pattern`], ignoring everything after
+/// `;` or `//` on each line as a comment
+///
+/// Signature dumps often annotate bytes with the instructions they encode,
+/// e.g. `48 8B 05 ; mov rax, [rip+...]`. Errors still point at the line and
+/// column of the original text.
+pub fn parse_hex_pattern_lenient(s: &str) -> Result<Vec<u8>> {
+    let stripped: Vec<String> = s
+        .split('\n')
+        .map(|line| {
+            let code_len = [line.find(';'), line.find("//")]
+                .into_iter()
+                .flatten()
+                .min()
+                .unwrap_or(line.len());
+            // Blank out comments instead of cutting them so offsets still match `s`
+            let comment_len = line[code_len..].chars().count();
+            format!("{}{}", &line[..code_len], " ".repeat(comment_len))
+        })
+        .collect();
+    parse_hex_pattern(&stripped.join("\n"))
+}
+
+/// Parse an IDA-style signature like "48 8B 05 ? ? ? ? 48 85 C0" into
+/// bytes and a mask for [`scanner::search_masked`] or
+/// [`ScanOptions::pattern_mask`](scanner::ScanOptions::pattern_mask)
+///
+/// Bytes are separated like in [`parse_hex_pattern`], and each wildcard is
+/// written as `?` or `??`. Wildcards come back as `0` with a `false` mask
+/// entry.
+pub fn parse_ida_signature(s: &str) -> Result<(Vec<u8>, Vec<bool>)> {
+    let mut bytes = Vec::new();
+    let mut mask = Vec::new();
+    for (start, token) in hex_tokens(s) {
+        if token == "?" || token == "??" {
+            bytes.push(0);
+            mask.push(false);
+            continue;
+        }
+        let byte = (token.len() == 2)
+            .then(|| u8::from_str_radix(token, 16).ok())
+            .flatten()
+            .ok_or_else(|| {
+                other!(
+                    InvalidPattern,
+                    "invalid signature byte '{}' (byte {}, {}) near \"{}\", expected two hex digits or '?'",
+                    token,
+                    bytes.len(),
+                    describe_position(s, start),
+                    context(s, start)
+                )
+            })?;
+        bytes.push(byte);
+        mask.push(true);
+    }
+    Ok((bytes, mask))
+}
+
+/// Parse a code and mask pair like `"\x48\x8B\x05\x00\x00\x00\x00"` and
+/// `"xxx????"` into bytes and a mask for [`scanner::search_masked`]
+///
+/// The code is parsed with [`parse_hex_pattern`]. Each `x` of the mask
+/// marks a byte of the code that has to match and each `?` a wildcard,
+/// whose code byte is kept as given.
+pub fn parse_code_mask(code: &str, mask: &str) -> Result<(Vec<u8>, Vec<bool>)> {
+    let bytes = parse_hex_pattern(code)?;
+    let mask = mask
+        .trim()
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match c {
+            'x' | 'X' => Ok(true),
+            '?' => Ok(false),
+            _ => Err(other!(
+                InvalidPattern,
+                "invalid mask character '{}' (column {}), expected 'x' or '?'",
+                c,
+                i + 1
+            )),
+        })
+        .collect::<Result<Vec<bool>>>()?;
+    if mask.len() != bytes.len() {
+        bail!(
+            InvalidPattern,
+            "mask has {} entries but the code is {} bytes",
+            mask.len(),
+            bytes.len()
+        );
+    }
+    Ok((bytes, mask))
+}
+
+/// Split a hex pattern at separators into tokens with their byte offsets
+fn hex_tokens(s: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        let is_separator = c.is_whitespace() || matches!(c, ',' | '-' | '|');
+        match (is_separator, start) {
+            (false, None) => start = Some(i),
+            (true, Some(token_start)) => {
+                tokens.push((token_start, &s[token_start..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(token_start) = start {
+        tokens.push((token_start, &s[token_start..]));
+    }
+    tokens
+}
+
+/// Describe byte `offset` of `s` as a 1-based column, or line and column
+/// for text spanning several lines
+fn describe_position(s: &str, offset: usize) -> String {
+    let before = &s[..offset];
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    if s.contains('\n') {
+        format!(
+            "line {}, column {}",
+            before.matches('\n').count() + 1,
+            column
+        )
+    } else {
+        format!("column {}", column)
+    }
+}
+
+/// Up to 10 characters of `s` on either side of byte `offset` on its line
+fn context(s: &str, offset: usize) -> String {
+    const WIDTH: usize = 10;
+    let line_start = s[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = s[offset..].find('\n').map_or(s.len(), |i| offset + i);
+    let before: Vec<char> = s[line_start..offset].chars().collect();
+    let after: Vec<char> = s[offset..line_end].chars().collect();
+    let before_start = before.len().saturating_sub(WIDTH);
+    let snippet: String = before[before_start..]
+        .iter()
+        .chain(after.iter().take(WIDTH))
+        .collect();
+    format!(
+        "{}{}{}",
+        if before_start > 0 { "..." } else { "" },
+        snippet.trim(),
+        if after.len() > WIDTH { "..." } else { "" }
+    )
+}
+
+/// Parse a byte size like "4096", "64K", "64M" or "1GiB" into bytes.
+///
+/// Suffixes are binary (`K` = 1024) and case-insensitive, with an optional
+/// trailing `B` or `iB`.
+pub fn parse_size(s: &str) -> Result<usize> {
+    let trimmed = s.trim();
+    let upper = trimmed.to_ascii_uppercase();
+    let without_b = upper
+        .strip_suffix("IB")
+        .or_else(|| upper.strip_suffix('B'))
+        .unwrap_or(&upper);
+    let (digits, multiplier) = match without_b.chars().last() {
+        Some('K') => (&without_b[..without_b.len() - 1], 1usize << 10),
+        Some('M') => (&without_b[..without_b.len() - 1], 1usize << 20),
+        Some('G') => (&without_b[..without_b.len() - 1], 1usize << 30),
+        _ => (without_b, 1),
+    };
+    let value: usize = digits
+        .trim()
+        .parse()
+        .map_err(|_| other!("invalid size '{}'", trimmed))?;
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| other!("size '{}' is too large", trimmed))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,9 +453,162 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_hex_0x_prefixes() {
+        let result = parse_hex_pattern("0x4D 0x5A").unwrap();
+        assert_eq!(result, vec![0x4D, 0x5A]);
+        let result = parse_hex_pattern("0xDEADBEEF").unwrap();
+        assert_eq!(result, vec![0xDE, 0xAD, 0xBE, 0xEF]);
+    }
+
+    #[test]
+    fn test_parse_hex_escapes() {
+        let result = parse_hex_pattern("\\x4D\\x5A\\x90\\x00").unwrap();
+        assert_eq!(result, vec![0x4D, 0x5A, 0x90, 0x00]);
+    }
+
+    #[test]
+    fn test_parse_hex_separators() {
+        let expected = vec![0x4D, 0x5A, 0x90];
+        assert_eq!(parse_hex_pattern("4D,5A,90").unwrap(), expected);
+        assert_eq!(parse_hex_pattern("4D-5A-90").unwrap(), expected);
+        assert_eq!(parse_hex_pattern("4D|5A|90").unwrap(), expected);
+        assert_eq!(parse_hex_pattern("0x4D, 0x5A, 0x90").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_hex_stray_prefix() {
+        assert!(parse_hex_pattern("0x").is_err());
+        assert!(parse_hex_pattern("4D 0x 5A").is_err());
+        assert!(parse_hex_pattern("\\x").is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_non_ascii() {
+        assert!(parse_hex_pattern("4Dé").is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_error_position() {
+        let err = parse_hex_pattern("4D 5A GH 00").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "invalid hex byte 'GH' (byte 2, column 7) near \"4D 5A GH 00\""
+        );
+
+        let pattern = "00 11 22 33 44 55 66 77 88 Z9 AA BB CC DD EE FF";
+        let err = parse_hex_pattern(pattern).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "invalid hex byte 'Z9' (byte 9, column 28) near \"...66 77 88 Z9 AA BB C...\""
+        );
+
+        let err = parse_hex_pattern("4D 5A 9").unwrap_err().to_string();
+        assert!(err.ends_with("(column 7)"), "{}", err);
+        let err = parse_hex_pattern("4D\n0x\n5A").unwrap_err().to_string();
+        assert!(err.ends_with("(line 2, column 1)"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_hex_lenient_comments() {
+        let dump = "48 8B 05 ; mov rax, [rip+...]\n// prologue\n55 // push rbp\nC3";
+        assert_eq!(
+            parse_hex_pattern_lenient(dump).unwrap(),
+            vec![0x48, 0x8B, 0x05, 0x55, 0xC3]
+        );
+        assert!(parse_hex_pattern(dump).is_err());
+
+        let err = parse_hex_pattern_lenient("55 ; ok\n48 8G ; bad")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'8G' (byte 2, line 2, column 4)"), "{}", err);
+        assert!(matches!(
+            parse_hex_pattern("4D 5"),
+            Err(MemScanError::InvalidPattern(_))
+        ));
+    }
+
     #[test]
     fn test_parse_hex_pe_header() {
         let result = parse_hex_pattern("4D 5A 90 00").unwrap();
         assert_eq!(result, vec![0x4D, 0x5A, 0x90, 0x00]);
     }
+
+    #[test]
+    fn test_parse_ida_signature() {
+        let (bytes, mask) = parse_ida_signature("48 8B 05 ? ? ? ? 48 85 C0").unwrap();
+        assert_eq!(bytes, vec![0x48, 0x8B, 0x05, 0, 0, 0, 0, 0x48, 0x85, 0xC0]);
+        assert_eq!(
+            mask,
+            vec![
+                true, true, true, false, false, false, false, true, true, true
+            ]
+        );
+
+        // Single and double question marks are the same wildcard
+        let single = parse_ida_signature("E8 ? ? ? ? 90").unwrap();
+        let double = parse_ida_signature("E8 ?? ?? ?? ?? 90").unwrap();
+        let mixed = parse_ida_signature("E8 ? ?? ? ?? 90").unwrap();
+        assert_eq!(single, double);
+        assert_eq!(single, mixed);
+        assert_eq!(single.1, vec![true, false, false, false, false, true]);
+
+        assert_eq!(parse_ida_signature("").unwrap(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_parse_ida_signature_errors() {
+        assert!(parse_ida_signature("48 ??? 05").is_err());
+        assert!(parse_ida_signature("48 8 05").is_err());
+        assert!(parse_ida_signature("488B05").is_err());
+        assert!(parse_ida_signature("48 ?8 05").is_err());
+        let err = parse_ida_signature("48 GG 05").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "invalid signature byte 'GG' (byte 1, column 4) near \"48 GG 05\", expected two hex digits or '?'"
+        );
+    }
+
+    #[test]
+    fn test_parse_code_mask() {
+        let (bytes, mask) =
+            parse_code_mask("\\x48\\x8B\\x05\\x00\\x00\\x00\\x00", "xxx????").unwrap();
+        assert_eq!(bytes, vec![0x48, 0x8B, 0x05, 0, 0, 0, 0]);
+        assert_eq!(mask, vec![true, true, true, false, false, false, false]);
+
+        // Same result as the equivalent IDA signature
+        assert_eq!(
+            parse_code_mask("\\x48\\x8B\\x05\\x00\\x00\\x00\\x00", "xxx????").unwrap(),
+            parse_ida_signature("48 8B 05 ? ? ? ?").unwrap()
+        );
+        // Wildcard code bytes are kept
+        let (bytes, _) = parse_code_mask("\\xE8\\xAA", "X?").unwrap();
+        assert_eq!(bytes, vec![0xE8, 0xAA]);
+    }
+
+    #[test]
+    fn test_parse_code_mask_errors() {
+        assert!(parse_code_mask("\\x48\\x8B", "xxx").is_err());
+        assert!(parse_code_mask("\\x48\\x8B\\x05", "xx").is_err());
+        assert!(parse_code_mask("\\x4G", "x").is_err());
+        let err = parse_code_mask("\\x48\\x8B", "x.").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "invalid mask character '.' (column 2), expected 'x' or '?'"
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert_eq!(parse_size("64K").unwrap(), 64 * 1024);
+        assert_eq!(parse_size("64m").unwrap(), 64 * 1024 * 1024);
+        assert_eq!(parse_size("1GiB").unwrap(), 1 << 30);
+        assert_eq!(parse_size("512KB").unwrap(), 512 * 1024);
+        assert_eq!(parse_size("10B").unwrap(), 10);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("-1K").is_err());
+        assert!(parse_size("12X").is_err());
+    }
 }
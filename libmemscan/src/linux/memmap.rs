@@ -1,8 +1,9 @@
 //! Linux-specific memory mapping implementation using `/proc/pid/mem`
 
 #![cfg(unix)]
+use crate::error::{MemScanError, Result};
+use crate::memmap::describe_read_error;
 use crate::process::{MemoryRegion, ProcessHandle};
-use anyhow::Result;
 
 /// Linux-specific mapped memory implementation
 ///
@@ -15,7 +16,6 @@ pub struct MappedMemoryUnix {
     /// Local buffer containing a copy of the remote memory
     buffer: Vec<u8>,
     /// Remote address that was read
-    #[allow(dead_code)]
     remote_addr: usize,
 }
 
@@ -32,21 +32,19 @@ impl MappedMemoryUnix {
         // Read the memory from `/proc/pid/mem`
         let bytes_read = proc
             .read_mem(region.base_address, &mut buffer)
-            .map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to read memory at {:016x}: {}",
-                    region.base_address,
-                    e
-                )
+            .map_err(|e| MemScanError::ReadFailed {
+                addr: region.base_address,
+                reason: describe_read_error(&e),
             })?;
 
         if bytes_read < region.size {
-            anyhow::bail!(
-                "Partial read: expected {} bytes, got {} bytes at address {:016x}",
-                region.size,
-                bytes_read,
-                region.base_address
-            );
+            return Err(MemScanError::ReadFailed {
+                addr: region.base_address,
+                reason: format!(
+                    "partial read, expected {} bytes, got {}",
+                    region.size, bytes_read
+                ),
+            });
         }
 
         Ok(Self {
@@ -61,24 +59,23 @@ impl MappedMemoryUnix {
     }
 
     /// Refresh mapped memory by re-reading from the remote process
-    #[allow(dead_code)]
     pub fn refresh(&mut self, proc: &ProcessHandle) -> Result<()> {
         let bytes_read = proc
             .read_mem(self.remote_addr, &mut self.buffer)
-            .map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to refresh memory at {:016x}: {}",
-                    self.remote_addr,
-                    e
-                )
+            .map_err(|e| MemScanError::ReadFailed {
+                addr: self.remote_addr,
+                reason: describe_read_error(&e),
             })?;
 
         if bytes_read < self.buffer.len() {
-            anyhow::bail!(
-                "Partial refresh: expected {} bytes, got {} bytes",
-                self.buffer.len(),
-                bytes_read
-            );
+            return Err(MemScanError::ReadFailed {
+                addr: self.remote_addr,
+                reason: format!(
+                    "partial refresh, expected {} bytes, got {}",
+                    self.buffer.len(),
+                    bytes_read
+                ),
+            });
         }
 
         Ok(())
@@ -1,4 +1,3 @@
 // Linux-specific modules
 pub mod memmap;
 pub mod process;
-
@@ -1,19 +1,20 @@
 #![cfg(unix)]
+use crate::error::{MemScanError, Result, bail, other};
 use crate::process::{
-    MemoryProtection, MemoryRegion, MemoryState, MemoryType, ProcessHandle, SystemInfo,
-    is_region_interesting,
+    MemoryProtection, MemoryRegion, MemoryState, MemoryType, ProcessHandle, SystemInfo, ThreadInfo,
+    check_lookup_deadline,
 };
-use anyhow::Result;
-use libc::{_SC_PAGESIZE, pid_t, sysconf};
+use libc::{_SC_PAGESIZE, c_ulong, pid_t, sysconf};
 use std::{
     collections::HashMap,
-    fs::{File, read_link},
+    fs::{File, OpenOptions, read_link},
     io::{BufRead, BufReader},
     os::{
-        fd::{AsRawFd, RawFd},
+        fd::{AsRawFd, IntoRawFd, RawFd},
         unix::fs::FileExt,
     },
     path::Path,
+    time::Instant,
 };
 
 // ================== Linux/UNIX-specific process types ==================
@@ -23,6 +24,7 @@ pub struct ProcessHandleUnix {
     pid: pid_t,
     mem: File,
     maps: Vec<MemoryRegion>,
+    #[allow(dead_code)]
     page_size: usize,
     exe_path: Option<String>,
 }
@@ -35,10 +37,29 @@ impl ProcessHandleUnix {
         self.pid
     }
 
+    /// Id of the target process
+    pub fn pid(&self) -> u32 {
+        self.pid as u32
+    }
+
     pub fn mem_fd(&self) -> RawFd {
         self.mem.as_raw_fd()
     }
 
+    /// Close `/proc/<pid>/mem`, reporting an error that dropping the handle
+    /// would ignore
+    pub fn close(self) -> Result<()> {
+        let fd = self.mem.into_raw_fd();
+        if unsafe { libc::close(fd) } != 0 {
+            bail!(
+                "failed to close /proc/{}/mem: {}",
+                self.pid,
+                std::io::Error::last_os_error()
+            );
+        }
+        Ok(())
+    }
+
     pub fn read_mem(&self, addr: usize, buf: &mut [u8]) -> std::io::Result<usize> {
         self.mem.read_at(buf, addr as u64)
     }
@@ -46,6 +67,11 @@ impl ProcessHandleUnix {
     pub fn write_mem(&self, addr: usize, buf: &[u8]) -> std::io::Result<usize> {
         self.mem.write_at(buf, addr as u64)
     }
+
+    /// Size of a pointer in the target, 4 for 32-bit and 8 for 64-bit processes
+    pub fn pointer_size(&self) -> usize {
+        if is_64bit_process(self) { 8 } else { 4 }
+    }
 }
 
 // ================== Linux/UNIX-specific helpers ==================
@@ -54,8 +80,7 @@ fn parse_proc_maps(pid: pid_t) -> Result<(Vec<MemoryRegion>, Option<String>)> {
     // NOTE: This function now returns Vec<MemoryRegion> instead of a custom MapEntry
     // to align with the user's request to use the cross-platform struct directly.
     let maps_path = format!("/proc/{pid}/maps");
-    let file = File::open(&maps_path)
-        .map_err(|e| anyhow::anyhow!("failed to open {}: {}", maps_path, e))?;
+    let file = File::open(&maps_path).map_err(|e| other!("failed to open {}: {}", maps_path, e))?;
     let reader = BufReader::new(file);
 
     let exe_path = read_link(format!("/proc/{pid}/exe"))
@@ -125,7 +150,7 @@ fn parse_proc_maps(pid: pid_t) -> Result<(Vec<MemoryRegion>, Option<String>)> {
 
 fn perms_to_protection(perms: &str) -> MemoryProtection {
     let bytes = perms.as_bytes();
-    let read = bytes.get(0).map(|&c| c == b'r').unwrap_or(false);
+    let read = bytes.first().map(|&c| c == b'r').unwrap_or(false);
     let write = bytes.get(1).map(|&c| c == b'w').unwrap_or(false);
     let exec = bytes.get(2).map(|&c| c == b'x').unwrap_or(false);
     // Linux doesn't expose guarded or no_cache in maps; assume false
@@ -159,10 +184,24 @@ fn perms_to_type(perms: &str, pathname: &Option<String>, _exe_path: &Option<Stri
 
 pub(crate) fn open_process(pid: u32) -> Result<ProcessHandle> {
     let pid_i = pid as pid_t;
-    // Open /proc/<pid>/mem for reading
+    // Open /proc/<pid>/mem for reading and writing, falling back to read-only
+    // when we may inspect but not modify the target
     let mem_path = format!("/proc/{pid}/mem");
-    let mem =
-        File::open(&mem_path).map_err(|e| anyhow::anyhow!("failed to open {}: {}", mem_path, e))?;
+    let mem = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&mem_path)
+        .or_else(|_| File::open(&mem_path))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => other!(ProcessNotFound, "with pid {}", pid),
+            std::io::ErrorKind::PermissionDenied => other!(
+                AccessDenied,
+                "permission denied opening {}: {}",
+                mem_path,
+                ptrace_hint(read_ptrace_scope())
+            ),
+            _ => other!("failed to open {}: {}", mem_path, e),
+        })?;
 
     let (maps, exe_path) = parse_proc_maps(pid_i)?;
     let page_size = unsafe { sysconf(_SC_PAGESIZE) as usize };
@@ -176,9 +215,31 @@ pub(crate) fn open_process(pid: u32) -> Result<ProcessHandle> {
     })
 }
 
+/// The Yama `ptrace_scope` setting, if the kernel has Yama
+fn read_ptrace_scope() -> Option<u32> {
+    std::fs::read_to_string("/proc/sys/kernel/yama/ptrace_scope")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// How to get access to another process's memory under `ptrace_scope`
+fn ptrace_hint(ptrace_scope: Option<u32>) -> &'static str {
+    match ptrace_scope {
+        None | Some(0) => "run memscan as root or as the user that owns the process",
+        Some(1) => {
+            "run memscan as root, or allow attaching to non-child processes with \
+             `sudo sysctl kernel.yama.ptrace_scope=0`"
+        }
+        Some(2) => "run memscan as root (kernel.yama.ptrace_scope=2 requires CAP_SYS_PTRACE)",
+        Some(_) => "attaching is disabled by kernel.yama.ptrace_scope=3 until the next reboot",
+    }
+}
+
 /// Find the PID of the first process whose executable name matches `name` (case-insensitive).
 /// On Linux, we'll try `/proc/<pid>/comm` first; if that doesn't match, fall back to base name of `/proc/<pid>/exe`.
-pub(crate) fn find_process_by_name(name: &str) -> Result<Option<u32>> {
+pub(crate) fn find_process_by_name(name: &str, deadline: Option<Instant>) -> Result<Option<u32>> {
     use std::fs;
 
     let target_raw = name.to_ascii_lowercase();
@@ -186,6 +247,7 @@ pub(crate) fn find_process_by_name(name: &str) -> Result<Option<u32>> {
 
     let proc_dir = Path::new("/proc");
     for entry in fs::read_dir(proc_dir)? {
+        check_lookup_deadline(deadline)?;
         let entry = entry?;
         let fname = entry.file_name();
         let fname_str = fname.to_string_lossy();
@@ -206,31 +268,133 @@ pub(crate) fn find_process_by_name(name: &str) -> Result<Option<u32>> {
             }
         }
         // Fallback: base name of `/proc/<pid>/exe`
-        if let Ok(link) = read_link(entry.path().join("exe")) {
-            if let Some(base) = link.file_name().and_then(|s| s.to_str()) {
-                let base_lc = base.to_ascii_lowercase();
-                let base_no_ext = base_lc.trim_end_matches(".exe");
-                if base_no_ext == target {
-                    return Ok(Some(pid));
-                }
+        if let Ok(link) = read_link(entry.path().join("exe"))
+            && let Some(base) = link.file_name().and_then(|s| s.to_str())
+        {
+            let base_lc = base.to_ascii_lowercase();
+            let base_no_ext = base_lc.trim_end_matches(".exe");
+            if base_no_ext == target {
+                return Ok(Some(pid));
             }
         }
     }
     Ok(None)
 }
 
+/// List the PID and name of every running process.
+///
+/// The name is the base name of `/proc/<pid>/exe` when readable, otherwise
+/// `/proc/<pid>/comm` (which the kernel truncates to 15 characters).
+pub(crate) fn list_processes(deadline: Option<Instant>) -> Result<Vec<(u32, String)>> {
+    use std::fs;
+
+    let mut processes = Vec::new();
+    for entry in fs::read_dir("/proc")? {
+        check_lookup_deadline(deadline)?;
+        let entry = entry?;
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let exe_name = read_link(entry.path().join("exe")).ok().and_then(|link| {
+            link.file_name()
+                .and_then(|s| s.to_str())
+                .map(str::to_string)
+        });
+        let name = match exe_name {
+            Some(name) => name,
+            None => match fs::read_to_string(entry.path().join("comm")) {
+                Ok(comm) => comm.trim().to_string(),
+                Err(_) => continue, // Process exited while listing
+            },
+        };
+        processes.push((pid, name));
+    }
+    Ok(processes)
+}
+
 /// Get a list of module regions (rough approximation) by grouping file-backed mappings by pathname,
 /// skipping the main executable image.
 pub(crate) fn get_process_module_regions(proc: &ProcessHandleUnix) -> Result<Vec<MemoryRegion>> {
+    // Skip main executable image
+    Ok(image_regions(&proc.maps, |path| {
+        proc.exe_path.as_deref() != Some(path)
+    }))
+}
+
+pub(crate) fn get_main_module(proc: &ProcessHandleUnix) -> Result<MemoryRegion> {
+    let Some(exe) = proc.exe_path.as_deref() else {
+        bail!("executable of process {} is unknown", proc.pid);
+    };
+    image_regions(&proc.maps, |path| path == exe)
+        .into_iter()
+        .next()
+        .ok_or_else(|| other!("{} is not mapped in process {}", exe, proc.pid))
+}
+
+pub(crate) fn enumerate_threads(proc: &ProcessHandleUnix) -> Result<Vec<ThreadInfo>> {
+    let task_dir = format!("/proc/{}/task", proc.pid);
+    let entries =
+        std::fs::read_dir(&task_dir).map_err(|e| other!("failed to list {}: {}", task_dir, e))?;
+
+    let mut threads = Vec::new();
+    for entry in entries.flatten() {
+        let Some(tid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let task = entry.path();
+        // kstkesp is only filled in for threads that are dumping core on
+        // current kernels, so fall back to the syscall file
+        let stack_pointer = std::fs::read_to_string(task.join("stat"))
+            .ok()
+            .and_then(|stat| stat_stack_pointer(&stat))
+            .or_else(|| {
+                std::fs::read_to_string(task.join("syscall"))
+                    .ok()
+                    .and_then(|syscall| syscall_stack_pointer(&syscall))
+            });
+        threads.push(ThreadInfo { tid, stack_pointer });
+    }
+    threads.sort_by_key(|thread| thread.tid);
+    Ok(threads)
+}
+
+/// The non-zero `kstkesp` field (the 29th) of a `/proc/<pid>/task/<tid>/stat` line
+fn stat_stack_pointer(stat: &str) -> Option<usize> {
+    // The command name in parentheses may itself contain spaces and parentheses
+    let (_, fields) = stat.rsplit_once(')')?;
+    // Fields after the name start at the 3rd one, the state
+    let kstkesp: usize = fields.split_whitespace().nth(29 - 3)?.parse().ok()?;
+    (kstkesp != 0).then_some(kstkesp)
+}
+
+/// The stack pointer in `/proc/<pid>/task/<tid>/syscall`, which reads
+/// `<nr> <args>... <sp> <pc>` for a blocked thread and `running` otherwise
+fn syscall_stack_pointer(syscall: &str) -> Option<usize> {
+    let fields: Vec<&str> = syscall.split_whitespace().collect();
+    if fields.len() < 3 {
+        return None;
+    }
+    let sp = fields[fields.len() - 2].strip_prefix("0x")?;
+    usize::from_str_radix(sp, 16).ok().filter(|&sp| sp != 0)
+}
+
+/// Merge the maps backed by each file whose path passes `include` into one
+/// image region per file, spanning all of its mappings
+fn image_regions(maps: &[MemoryRegion], include: impl Fn(&str) -> bool) -> Vec<MemoryRegion> {
     let mut by_path: HashMap<String, (usize, usize, bool)> = HashMap::new(); // path -> (`min_start`, `max_end`, `any_exec`)
 
-    for m in &proc.maps {
+    for m in maps {
         let Some(path) = &m.image_file else { continue };
-        // Skip main executable image
-        if let Some(exe) = &proc.exe_path {
-            if path == exe {
-                continue;
-            }
+        if !include(path) {
+            continue;
         }
         let start = m.base_address;
         let end = m.base_address.saturating_add(m.size);
@@ -268,8 +432,27 @@ pub(crate) fn get_process_module_regions(proc: &ProcessHandleUnix) -> Result<Vec
 
     // Sort by base address to match expectations
     regions.sort_by_key(|r| r.base_address);
+    regions
+}
 
-    Ok(regions)
+/// Read the ELF class of the target's executable
+///
+/// When the executable can't be read the target is taken to be 64-bit if
+/// anything is mapped above 4 GiB, which the stack and vDSO of a 64-bit
+/// process always are, or to have memscan's word size without any maps.
+pub(crate) fn is_64bit_process(proc: &ProcessHandleUnix) -> bool {
+    const EI_CLASS: usize = 4;
+    const ELFCLASS64: u8 = 2;
+    let mut ident = [0u8; EI_CLASS + 1];
+    match File::open(format!("/proc/{}/exe", proc.pid)).and_then(|f| f.read_exact_at(&mut ident, 0))
+    {
+        Ok(()) if ident.starts_with(b"\x7fELF") => ident[EI_CLASS] == ELFCLASS64,
+        _ if !proc.maps.is_empty() => proc
+            .maps
+            .iter()
+            .any(|m| (m.base_address as u64).saturating_add(m.size as u64) > 1 << 32),
+        _ => cfg!(target_pointer_width = "64"),
+    }
 }
 
 pub(crate) fn query_system_info() -> SystemInfo {
@@ -302,29 +485,157 @@ pub(crate) fn memory_region_iterator_next(
         return None;
     }
     let m = &proc.maps[idx];
-    // Advance iterator address regardless of interest
     *cur_addr = m.base_address.saturating_add(m.size);
 
-    // Regions were parsed already into cross-platform representation; still apply filter
-    if is_region_interesting(&m.protect, &m.state) {
-        Some(MemoryRegion {
-            base_address: m.base_address,
-            size: m.size,
-            protect: m.protect.clone(),
-            state: m.state.clone(),
-            type_: m.type_.clone(),
-            image_file: None,
-        })
-    } else {
-        None
-    }
+    // Regions were parsed already into cross-platform representation, the
+    // iterator applies its region filter
+    Some(MemoryRegion {
+        base_address: m.base_address,
+        size: m.size,
+        protect: m.protect.clone(),
+        state: m.state.clone(),
+        type_: m.type_.clone(),
+        image_file: None,
+    })
 }
 
 /// Read process memory into the provided buffer. Returns the number of bytes read (0 on failure).
-pub(crate) fn read_process_memory(proc: &ProcessHandleUnix, addr: usize, buf: &mut [u8]) -> usize {
-    proc.read_mem(addr, buf).unwrap_or(0)
+pub(crate) fn try_read_process_memory(
+    proc: &ProcessHandleUnix,
+    addr: usize,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    proc.read_mem(addr, buf)
 }
 
 pub(crate) fn write_process_memory(proc: &ProcessHandleUnix, addr: usize, buf: &[u8]) -> usize {
     proc.write_mem(addr, buf).unwrap_or(0)
 }
+
+/// Most iovecs `process_vm_writev` accepts per call (`UIO_MAXIOV`)
+const MAX_IOVECS: usize = 1024;
+
+/// Write several buffers with one `process_vm_writev` call per
+/// [`MAX_IOVECS`] writes
+///
+/// The syscall stops at the first write it can't complete, e.g. on a
+/// read-only page that `/proc/<pid>/mem` can still write, so that write is
+/// retried on its own and the batch resumes after it.
+pub(crate) fn write_process_memory_many(
+    proc: &ProcessHandleUnix,
+    writes: &[(usize, &[u8])],
+) -> Vec<usize> {
+    let mut written = vec![0; writes.len()];
+    let mut next = 0;
+    while next < writes.len() {
+        let batch = &writes[next..writes.len().min(next + MAX_IOVECS)];
+        let local: Vec<libc::iovec> = batch
+            .iter()
+            .map(|(_, buf)| libc::iovec {
+                iov_base: buf.as_ptr() as *mut libc::c_void,
+                iov_len: buf.len(),
+            })
+            .collect();
+        let remote: Vec<libc::iovec> = batch
+            .iter()
+            .map(|(addr, buf)| libc::iovec {
+                iov_base: *addr as *mut libc::c_void,
+                iov_len: buf.len(),
+            })
+            .collect();
+        let result = unsafe {
+            libc::process_vm_writev(
+                proc.pid,
+                local.as_ptr(),
+                local.len() as c_ulong,
+                remote.as_ptr(),
+                remote.len() as c_ulong,
+                0,
+            )
+        };
+        if result < 0 && std::io::Error::last_os_error().raw_os_error() != Some(libc::EFAULT) {
+            // The syscall is unavailable or not permitted, write the rest one by one
+            for (i, (addr, buf)) in writes.iter().enumerate().skip(next) {
+                written[i] = write_process_memory(proc, *addr, buf);
+            }
+            break;
+        }
+
+        let mut remaining = result.max(0) as usize;
+        let mut done = 0;
+        while done < batch.len() && remaining >= batch[done].1.len() {
+            remaining -= batch[done].1.len();
+            written[next + done] = batch[done].1.len();
+            done += 1;
+        }
+        if let Some((addr, buf)) = batch.get(done) {
+            written[next + done] = write_process_memory(proc, *addr, buf);
+            done += 1;
+        }
+        next += done;
+    }
+    written
+}
+
+/// Write process memory, falling back to `process_vm_writev` when
+/// `/proc/<pid>/mem` refuses the write
+///
+/// Both paths need ptrace access to the target (same user and a permissive
+/// Yama `ptrace_scope`, or `CAP_SYS_PTRACE`). `process_vm_writev` honours the
+/// page protection, so pages that are neither writable nor writable through
+/// `/proc/<pid>/mem` (e.g. with `proc_mem.force_override=never`) can't be
+/// written at all; the error then names the protection of the page.
+pub(crate) fn force_write_process_memory(
+    proc: &ProcessHandleUnix,
+    addr: usize,
+    buf: &[u8],
+) -> Result<usize> {
+    let err = match proc.write_mem(addr, buf) {
+        Ok(written) if written > 0 || buf.is_empty() => return Ok(written),
+        Ok(_) => std::io::Error::from_raw_os_error(libc::EFAULT),
+        Err(e) => e,
+    };
+    if !matches!(
+        err.raw_os_error(),
+        Some(libc::EPERM | libc::EFAULT | libc::EIO)
+    ) {
+        return Err(MemScanError::WriteFailed {
+            addr,
+            reason: err.to_string(),
+        });
+    }
+
+    let local = libc::iovec {
+        iov_base: buf.as_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let remote = libc::iovec {
+        iov_base: addr as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+    let written = unsafe { libc::process_vm_writev(proc.pid, &local, 1, &remote, 1, 0) };
+    if written > 0 {
+        return Ok(written as usize);
+    }
+    let vm_err = std::io::Error::last_os_error();
+
+    // Re-read the maps, the target may have changed the protection since it was opened
+    let protection = parse_proc_maps(proc.pid)
+        .ok()
+        .and_then(|(maps, _)| {
+            maps.into_iter()
+                .find(|m| addr >= m.base_address && addr < m.base_address + m.size)
+        })
+        .map(|m| {
+            format!(
+                "page is {} ({})",
+                m.protect,
+                m.image_file.as_deref().unwrap_or("anonymous")
+            )
+        })
+        .unwrap_or_else(|| "address is not mapped".to_string());
+    Err(MemScanError::WriteFailed {
+        addr,
+        reason: format!("{} (process_vm_writev: {}); {}", err, vm_err, protection),
+    })
+}
@@ -4,26 +4,48 @@
 //! the local process address space, enabling faster access and parallel diffing
 //! compared to traditional `ReadProcessMemory` calls.
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::collections::btree_map::{IntoValues, Values, ValuesMut};
+use std::ops::Range;
 
-use crate::process::{MemoryRegion, ProcessHandle};
-use anyhow::Result;
+use crate::error::{MemScanError, Result, other};
+use crate::process::{
+    MemoryRegion, ProcessHandle, query_system_info, read_process_memory, try_read_process_memory,
+};
 
 #[cfg(unix)]
 use crate::linux;
 #[cfg(windows)]
 use crate::windows;
 
+/// Backing storage of a mapped memory view
+#[derive(Debug)]
+enum MappedMemoryBacking {
+    /// Platform-specific mapping of the remote process memory
+    #[cfg(windows)]
+    Native(windows::memmap::MappedMemoryWin),
+    #[cfg(unix)]
+    Native(linux::memmap::MappedMemoryUnix),
+    /// Local copy read in `read_chunk` sized pieces with `read_process_memory`
+    Copied { buffer: Vec<u8>, read_chunk: usize },
+    /// Local copy read page by page where unreadable pages are zero-filled
+    /// and marked invalid in `valid_pages`
+    Partial {
+        buffer: Vec<u8>,
+        page_size: usize,
+        valid_pages: Vec<bool>,
+    },
+    /// In-memory buffer not tied to any process
+    Buffer(Vec<u8>),
+}
+
 /// Represents a mapped memory view of remote process memory
 #[derive(Debug)]
 pub struct MappedMemory {
     /// Base address in the remote process
     pub remote_region: MemoryRegion,
-    /// Platform-specific handle
-    #[cfg(windows)]
-    inner: windows::memmap::MappedMemoryWin,
-    #[cfg(unix)]
-    inner: linux::memmap::MappedMemoryUnix,
+    /// Platform-specific handle or local buffer
+    inner: MappedMemoryBacking,
 }
 
 impl MappedMemory {
@@ -42,44 +64,327 @@ impl MappedMemory {
         let inner = linux::memmap::MappedMemoryUnix::map_region(proc, &region)?;
         Ok(Self {
             remote_region: region,
-            inner,
+            inner: MappedMemoryBacking::Native(inner),
+        })
+    }
+
+    /// Copy a region of remote process memory page by page using
+    /// `read_process_memory` instead of mapping it.
+    ///
+    /// This avoids the platform mapping APIs entirely. Refreshing the
+    /// mapping reads the region again.
+    pub fn read_region(proc: &ProcessHandle, region: MemoryRegion) -> Result<Self> {
+        Self::read_region_chunked(proc, region, query_system_info().page_size)
+    }
+
+    /// Copy a region like [`MappedMemory::read_region`], reading up to
+    /// `read_chunk` bytes per `read_process_memory` call.
+    ///
+    /// `read_chunk` is rounded up to a multiple of the page size. Larger
+    /// chunks mean fewer calls, which is much faster for `/proc/<pid>/mem`.
+    pub fn read_region_chunked(
+        proc: &ProcessHandle,
+        region: MemoryRegion,
+        read_chunk: usize,
+    ) -> Result<Self> {
+        let mut buffer = vec![0u8; region.size];
+        read_chunks(proc, region.base_address, &mut buffer, read_chunk)?;
+        Ok(Self {
+            remote_region: region,
+            inner: MappedMemoryBacking::Copied { buffer, read_chunk },
         })
     }
 
+    /// Copy a region page by page, zero-filling pages that can't be read
+    /// instead of failing the whole region.
+    ///
+    /// Unreadable pages are recorded in a validity bitmap, see
+    /// [`MappedMemory::valid_ranges`]. Fails only if no page is readable.
+    pub fn map_region_best_effort(proc: &ProcessHandle, region: MemoryRegion) -> Result<Self> {
+        let page_size = query_system_info().page_size.max(1);
+        let mut buffer = vec![0u8; region.size];
+        let mut valid_pages = Vec::new();
+        read_pages_best_effort(
+            proc,
+            region.base_address,
+            &mut buffer,
+            page_size,
+            &mut valid_pages,
+        );
+        if !valid_pages.iter().any(|&valid| valid) {
+            return Err(MemScanError::ReadFailed {
+                addr: region.base_address,
+                reason: format!(
+                    "no readable pages in region {:016x} - {:016x}",
+                    region.base_address,
+                    region.base_address + region.size
+                ),
+            });
+        }
+        Ok(Self {
+            remote_region: region,
+            inner: MappedMemoryBacking::Partial {
+                buffer,
+                page_size,
+                valid_pages,
+            },
+        })
+    }
+
+    /// Create a mapping from an in-memory buffer without a live process
+    ///
+    /// The buffer stands in for the contents of `region`, which is useful
+    /// for tests and offline analysis. Refreshing such a mapping is a no-op.
+    pub fn from_buffer(region: MemoryRegion, buffer: Vec<u8>) -> Self {
+        Self {
+            remote_region: region,
+            inner: MappedMemoryBacking::Buffer(buffer),
+        }
+    }
+
     /// Get a slice to the mapped memory
     ///
     /// # Safety
     /// The returned slice is only valid as long as the MappedMemory exists.
     /// The remote process may modify this memory at any time.
     pub fn data(&self) -> &[u8] {
-        return self.inner.as_slice();
+        match &self.inner {
+            MappedMemoryBacking::Native(native) => native.as_slice(),
+            MappedMemoryBacking::Copied { buffer, .. }
+            | MappedMemoryBacking::Partial { buffer, .. }
+            | MappedMemoryBacking::Buffer(buffer) => buffer,
+        }
+    }
+
+    /// Offset ranges of `data()` that hold memory actually read from the
+    /// process, i.e. everything but the zero-filled unreadable pages of a
+    /// best-effort mapping.
+    pub fn valid_ranges(&self) -> Vec<Range<usize>> {
+        match &self.inner {
+            MappedMemoryBacking::Partial {
+                buffer,
+                page_size,
+                valid_pages,
+            } => page_ranges(
+                self.remote_region.base_address,
+                buffer.len(),
+                *page_size,
+                valid_pages,
+            ),
+            _ => std::iter::once(0..self.data().len()).collect(),
+        }
+    }
+
+    /// Number of bytes in unreadable pages that are zero-filled in `data()`
+    pub fn unreadable_bytes(&self) -> usize {
+        let valid: usize = self.valid_ranges().iter().map(|r| r.len()).sum();
+        self.data().len() - valid
     }
+
+    /// Bring the local view up to date with the remote process memory
+    ///
+    /// On Windows the view is shared with the target and this is a no-op,
+    /// on Linux the cached buffer is re-read from the process.
+    pub fn refresh(&mut self, proc: &ProcessHandle) -> Result<()> {
+        match &mut self.inner {
+            MappedMemoryBacking::Native(native) => native.refresh(proc),
+            MappedMemoryBacking::Copied { buffer, read_chunk } => {
+                read_chunks(proc, self.remote_region.base_address, buffer, *read_chunk)
+            }
+            MappedMemoryBacking::Partial {
+                buffer,
+                page_size,
+                valid_pages,
+            } => {
+                read_pages_best_effort(
+                    proc,
+                    self.remote_region.base_address,
+                    buffer,
+                    *page_size,
+                    valid_pages,
+                );
+                Ok(())
+            }
+            MappedMemoryBacking::Buffer(_) => Ok(()),
+        }
+    }
+}
+
+/// Fill `buffer` from the remote process one page at a time, zero-filling
+/// pages that can't be read fully and recording which pages were read
+fn read_pages_best_effort(
+    proc: &ProcessHandle,
+    base_address: usize,
+    buffer: &mut [u8],
+    page_size: usize,
+    valid_pages: &mut Vec<bool>,
+) {
+    valid_pages.clear();
+    let mut offset = 0;
+    while offset < buffer.len() {
+        let page_end = (base_address + offset) / page_size * page_size + page_size;
+        let end = (page_end - base_address).min(buffer.len());
+        let page = &mut buffer[offset..end];
+        let valid = read_process_memory(proc, base_address + offset, page) == page.len();
+        if !valid {
+            page.fill(0);
+        }
+        valid_pages.push(valid);
+        offset = end;
+    }
+}
+
+/// Merge the pages marked valid in `valid_pages` into offset ranges of a
+/// `len` byte buffer starting at `base_address`
+fn page_ranges(
+    base_address: usize,
+    len: usize,
+    page_size: usize,
+    valid_pages: &[bool],
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut offset = 0;
+    for &valid in valid_pages {
+        let page_end = (base_address + offset) / page_size * page_size + page_size;
+        let end = (page_end - base_address).min(len);
+        if valid {
+            match ranges.last_mut() {
+                Some(last) if last.end == offset => last.end = end,
+                _ => ranges.push(offset..end),
+            }
+        }
+        offset = end;
+    }
+    ranges
+}
+
+/// Fill `buffer` from the remote process `read_chunk` bytes at a time
+fn read_chunks(
+    proc: &ProcessHandle,
+    base_address: usize,
+    buffer: &mut [u8],
+    read_chunk: usize,
+) -> Result<()> {
+    let page_size = query_system_info().page_size.max(1);
+    let chunk_size = read_chunk.max(1).div_ceil(page_size) * page_size;
+    let mut offset = 0;
+    while offset < buffer.len() {
+        // Align chunk ends to page boundaries of the remote address
+        let chunk_end = (base_address + offset) / page_size * page_size + chunk_size;
+        let end = (chunk_end - base_address).min(buffer.len());
+        let bytes_read =
+            try_read_process_memory(proc, base_address + offset, &mut buffer[offset..end])
+                .map_err(|e| MemScanError::ReadFailed {
+                    addr: base_address + offset,
+                    reason: describe_read_error(&e),
+                })?;
+        if bytes_read < end - offset {
+            return Err(MemScanError::ReadFailed {
+                addr: base_address + offset,
+                reason: format!(
+                    "partial read, expected {} bytes, got {}",
+                    end - offset,
+                    bytes_read
+                ),
+            });
+        }
+        offset = end;
+    }
+    Ok(())
+}
+
+/// Explain why reading target memory failed with `err`
+///
+/// On Linux EPERM means the kernel refused to let memscan trace the target,
+/// which Yama's `ptrace_scope` does by default for non-child processes.
+pub(crate) fn describe_read_error(err: &std::io::Error) -> String {
+    #[cfg(unix)]
+    if err.raw_os_error() == Some(libc::EPERM) {
+        return "process not ptraceable (check /proc/sys/kernel/yama/ptrace_scope)".to_string();
+    }
+    err.to_string()
 }
 
 /// Manager for tracking multiple mapped memory regions
+///
+/// Regions are kept by remote base address, so every iteration visits them
+/// in ascending address order.
 pub struct MemoryMapper<'a> {
-    process: &'a ProcessHandle,
-    mappings: HashMap<usize, MappedMemory>,
+    process: Option<&'a ProcessHandle>,
+    mappings: BTreeMap<usize, MappedMemory>,
+    /// Map regions with the platform mapping APIs (true) or copy them
+    /// with `read_process_memory` (false)
+    use_memmap: bool,
+    /// Bytes read per `read_process_memory` call when copying regions
+    read_chunk: usize,
+}
+
+impl Default for MemoryMapper<'_> {
+    fn default() -> Self {
+        Self {
+            process: None,
+            mappings: BTreeMap::new(),
+            use_memmap: true,
+            read_chunk: query_system_info().page_size,
+        }
+    }
 }
 
 impl<'a> MemoryMapper<'a> {
     /// Create a new empty memory mapper
     pub fn new(process: &'a ProcessHandle) -> Self {
         Self {
-            process,
-            mappings: HashMap::new(),
+            process: Some(process),
+            ..Self::default()
         }
     }
 
+    /// Create a new empty memory mapper that is not attached to a process
+    ///
+    /// Regions can't be mapped from a process, but pre-built mappings can be
+    /// added with [`MemoryMapper::insert`].
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Choose whether new regions are mapped with the platform mapping APIs
+    /// or copied page by page with `read_process_memory`.
+    pub fn set_use_memmap(&mut self, use_memmap: bool) {
+        self.use_memmap = use_memmap;
+    }
+
+    /// Set how many bytes are read per call when regions are copied
+    /// instead of mapped (defaults to the page size).
+    pub fn set_read_chunk(&mut self, read_chunk: usize) {
+        self.read_chunk = read_chunk;
+    }
+
     /// Map a memory region.
     ///
     /// ## Returns
     /// The remote base address of the mapped region.
     pub fn map_region(&mut self, region: MemoryRegion) -> Result<&MappedMemory> {
-        let mapped = MappedMemory::map_region(self.process, region)?;
+        let process = self
+            .process
+            .ok_or_else(|| other!("Memory mapper is not attached to a process"))?;
+        let mapped = if self.use_memmap {
+            MappedMemory::map_region(process, region.clone())
+        } else {
+            MappedMemory::read_region_chunked(process, region.clone(), self.read_chunk)
+        };
+        // Salvage the readable pages of regions with unreadable holes
+        let mapped = match mapped {
+            Ok(mapped) => mapped,
+            Err(err) => MappedMemory::map_region_best_effort(process, region).map_err(|_| err)?,
+        };
+        Ok(self.insert(mapped))
+    }
+
+    /// Add an already mapped region, replacing any mapping at the same base address
+    pub fn insert(&mut self, mapped: MappedMemory) -> &MappedMemory {
         let remote_base_address = mapped.remote_region.base_address;
         self.mappings.insert(remote_base_address, mapped);
-        Ok(self.get(remote_base_address).unwrap())
+        self.get(remote_base_address).unwrap()
     }
 
     /// Get a mapped region by index
@@ -97,27 +402,65 @@ impl<'a> MemoryMapper<'a> {
         self.mappings.is_empty()
     }
 
+    /// Refresh all mapped regions from the remote process
+    ///
+    /// Every region is refreshed even if an earlier one fails; the first
+    /// error encountered is returned.
+    pub fn refresh_all(&mut self) -> Result<()> {
+        let Some(process) = self.process else {
+            return Ok(()); // Only buffer-backed mappings without a process
+        };
+        let mut first_err = None;
+        for mapped in self.mappings.values_mut() {
+            if let Err(err) = mapped.refresh(process) {
+                first_err.get_or_insert(err);
+            }
+        }
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Refresh a single mapped region by its remote base address
+    pub fn refresh_region(&mut self, remote_base_address: usize) -> Result<()> {
+        let mapped = self
+            .mappings
+            .get_mut(&remote_base_address)
+            .ok_or(MemScanError::NotMapped(remote_base_address))?;
+        match self.process {
+            Some(process) => mapped.refresh(process),
+            None => Ok(()),
+        }
+    }
+
     /// Clear all mappings
     pub fn clear(&mut self) {
         self.mappings.clear();
     }
-    
+
     /// Get a mapped region by address (finds region containing the address)
     pub fn get_by_address(&self, address: usize) -> Option<&MappedMemory> {
-        for mapped in self.mappings.values() {
-            let region = &mapped.remote_region;
-            if address >= region.base_address && address < region.base_address + region.size {
-                return Some(mapped);
-            }
-        }
-        None
+        let (_, mapped) = self.mappings.range(..=address).next_back()?;
+        let region = &mapped.remote_region;
+        (address < region.base_address + region.size).then_some(mapped)
     }
-    
-    /// Iterate over all mapped regions
-    pub fn iter(&self) -> impl Iterator<Item = &MappedMemory> {
+
+    /// Iterate over all mapped regions in address order
+    pub fn iter(&self) -> Values<'_, usize, MappedMemory> {
         self.mappings.values()
     }
-    
+
+    /// Iterate mutably over all mapped regions
+    pub fn iter_mut(&mut self) -> ValuesMut<'_, usize, MappedMemory> {
+        self.mappings.values_mut()
+    }
+
+    /// Iterate over the remote base addresses of all mapped regions
+    pub fn addresses(&self) -> impl Iterator<Item = usize> + '_ {
+        self.mappings.keys().copied()
+    }
+
     /// Retain only the mapped regions that satisfy the predicate
     pub fn retain<F>(&mut self, mut f: F)
     where
@@ -129,19 +472,190 @@ impl<'a> MemoryMapper<'a> {
 
 impl IntoIterator for MemoryMapper<'_> {
     type Item = MappedMemory;
-    type IntoIter = std::vec::IntoIter<MappedMemory>;
+    type IntoIter = IntoValues<usize, MappedMemory>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.mappings.into_values().collect::<Vec<_>>().into_iter()
+        self.mappings.into_values()
+    }
+}
+
+impl<'b> IntoIterator for &'b MemoryMapper<'_> {
+    type Item = &'b MappedMemory;
+    type IntoIter = Values<'b, usize, MappedMemory>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'b> IntoIterator for &'b mut MemoryMapper<'_> {
+    type Item = &'b mut MappedMemory;
+    type IntoIter = ValuesMut<'b, usize, MappedMemory>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn mock_mapping(base: usize, size: usize) -> MappedMemory {
+        MappedMemory::from_buffer(MemoryRegion::private(base, size), vec![0xAA; size])
+    }
+
+    #[test]
+    fn test_memory_mapper_empty() {
+        let mapper = MemoryMapper::empty();
+        assert!(mapper.is_empty());
+        assert_eq!(mapper.len(), 0);
+    }
+
+    #[test]
+    fn test_memory_mapper_bookkeeping() {
+        let mut mapper = MemoryMapper::empty();
+        mapper.insert(mock_mapping(0x1000, 0x100));
+        mapper.insert(mock_mapping(0x2000, 0x100));
+        assert_eq!(mapper.len(), 2);
+        assert_eq!(mapper.get(0x1000).unwrap().data(), &[0xAA; 0x100][..]);
+        assert_eq!(
+            mapper
+                .get_by_address(0x20ff)
+                .unwrap()
+                .remote_region
+                .base_address,
+            0x2000
+        );
+        assert!(mapper.get_by_address(0x2100).is_none());
+
+        let mut addresses: Vec<usize> = mapper.addresses().collect();
+        addresses.sort();
+        assert_eq!(addresses, vec![0x1000, 0x2000]);
+
+        mapper.retain(|m| m.remote_region.base_address == 0x2000);
+        assert_eq!(mapper.len(), 1);
+        assert!(mapper.get(0x1000).is_none());
+
+        assert!(mapper.refresh_all().is_ok());
+
+        mapper.clear();
+        assert!(mapper.is_empty());
+    }
+
+    #[test]
+    fn test_memory_mapper_iterates_in_address_order() {
+        let mut mapper = MemoryMapper::empty();
+        for base in [0x3000, 0x1000, 0x2000] {
+            mapper.insert(mock_mapping(base, 0x100));
+        }
+        let bases: Vec<usize> = mapper
+            .iter()
+            .map(|m| m.remote_region.base_address)
+            .collect();
+        assert_eq!(bases, vec![0x1000, 0x2000, 0x3000]);
+        assert_eq!(mapper.addresses().collect::<Vec<_>>(), bases);
+        // Between two regions
+        assert!(mapper.get_by_address(0x1800).is_none());
+        assert!(mapper.get_by_address(0x0fff).is_none());
+    }
+
+    #[test]
+    fn test_read_region_chunked_own_process() {
+        let proc = crate::process::open_process(std::process::id()).unwrap();
+        let page_size = query_system_info().page_size;
+        // Unaligned start and a size that isn't a whole number of chunks
+        let buffer: Vec<u8> = (0..page_size * 5 + 123).map(|i| (i % 251) as u8).collect();
+        let region = MemoryRegion::private(buffer.as_ptr() as usize + 7, buffer.len() - 7);
+
+        for read_chunk in [0, 1, page_size, page_size * 2 + 1, buffer.len() * 2] {
+            let mapped =
+                MappedMemory::read_region_chunked(&proc, region.clone(), read_chunk).unwrap();
+            assert_eq!(mapped.data(), &buffer[7..]);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_describe_read_error() {
+        let eperm = std::io::Error::from_raw_os_error(libc::EPERM);
+        assert_eq!(
+            describe_read_error(&eperm),
+            "process not ptraceable (check /proc/sys/kernel/yama/ptrace_scope)"
+        );
+        let eio = std::io::Error::from_raw_os_error(libc::EIO);
+        assert_eq!(describe_read_error(&eio), eio.to_string());
+    }
+
+    #[test]
+    fn test_page_ranges() {
+        // Unaligned start: the first page is only 0x80 bytes long
+        let ranges = page_ranges(0x1f80, 0x2100, 0x1000, &[true, false, true, true]);
+        assert_eq!(ranges, vec![0..0x80, 0x1080..0x2100]);
+        assert_eq!(
+            page_ranges(0x1000, 0x2000, 0x1000, &[true, true]),
+            vec![0..0x2000]
+        );
+        assert!(page_ranges(0x1000, 0x1000, 0x1000, &[false]).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_map_region_best_effort_skips_hole() {
+        let proc = crate::process::open_process(std::process::id()).unwrap();
+        let page_size = query_system_info().page_size;
+        // Three pages with the middle one unmapped
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                page_size * 3,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(base, libc::MAP_FAILED);
+        let base = base as usize;
+        unsafe {
+            std::ptr::write_bytes(base as *mut u8, 0xAB, page_size * 3);
+            libc::munmap((base + page_size) as *mut libc::c_void, page_size);
+        }
+
+        let region = MemoryRegion::private(base, page_size * 3);
+        assert!(MappedMemory::read_region(&proc, region.clone()).is_err());
+
+        let mapped = MappedMemory::map_region_best_effort(&proc, region.clone()).unwrap();
+        assert_eq!(
+            mapped.valid_ranges(),
+            vec![0..page_size, page_size * 2..page_size * 3]
+        );
+        assert_eq!(mapped.unreadable_bytes(), page_size);
+        assert!(
+            mapped.data()[page_size..page_size * 2]
+                .iter()
+                .all(|&b| b == 0)
+        );
+        assert!(mapped.data()[..page_size].iter().all(|&b| b == 0xAB));
+
+        // The mapper falls back to a best-effort copy on its own
+        let mut mapper = MemoryMapper::new(&proc);
+        mapper.set_use_memmap(false);
+        assert_eq!(
+            mapper.map_region(region).unwrap().unreadable_bytes(),
+            page_size
+        );
+
+        unsafe {
+            libc::munmap(base as *mut libc::c_void, page_size);
+            libc::munmap((base + page_size * 2) as *mut libc::c_void, page_size);
+        }
+    }
 
     #[test]
-    fn test_memory_mapper_new() {
-        // We can't create a valid ProcessHandle in tests, so we skip this test
-        // In actual usage, ProcessHandle will be created via open_process()
+    fn test_memory_mapper_empty_cannot_map() {
+        let mut mapper = MemoryMapper::empty();
+        let region = mock_mapping(0x1000, 0x10).remote_region;
+        assert!(mapper.map_region(region).is_err());
     }
 }
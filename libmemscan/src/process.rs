@@ -1,5 +1,10 @@
-use anyhow::Result;
+use crate::error::{MemScanError, Result, bail, other};
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub use crate::fmt::format_address;
 
 #[cfg(unix)]
 use crate::linux;
@@ -13,6 +18,60 @@ pub type ProcessHandle = windows::process::ProcessHandleWin;
 #[cfg(unix)]
 pub type ProcessHandle = linux::process::ProcessHandleUnix;
 
+/// A process handle that can be shared with long-lived users and closed
+/// explicitly, e.g. by language bindings that can't express borrows
+///
+/// Users hold an `Arc` from [`SharedProcessHandle::share`], so the handle
+/// stays alive as long as any of them does. Closing is refused while the
+/// handle is shared, and every access after closing returns an error.
+#[derive(Debug)]
+pub struct SharedProcessHandle {
+    handle: Option<Arc<ProcessHandle>>,
+}
+
+impl SharedProcessHandle {
+    pub fn new(handle: ProcessHandle) -> Self {
+        Self {
+            handle: Some(Arc::new(handle)),
+        }
+    }
+
+    /// The handle, unless it was closed
+    pub fn get(&self) -> Result<&ProcessHandle> {
+        self.handle
+            .as_deref()
+            .ok_or_else(|| other!("Process handle is closed"))
+    }
+
+    /// A reference-counted copy of the handle for a long-lived user
+    pub fn share(&self) -> Result<Arc<ProcessHandle>> {
+        self.handle
+            .clone()
+            .ok_or_else(|| other!("Process handle is closed"))
+    }
+
+    /// Whether [`SharedProcessHandle::close`] succeeded
+    pub fn is_closed(&self) -> bool {
+        self.handle.is_none()
+    }
+
+    /// Close the handle, failing if it's already closed or still shared
+    pub fn close(&mut self) -> Result<()> {
+        let handle = self
+            .handle
+            .take()
+            .ok_or_else(|| other!("Process handle is already closed"))?;
+        match Arc::try_unwrap(handle) {
+            Ok(handle) => handle.close(),
+            Err(handle) => {
+                let users = Arc::strong_count(&handle) - 1;
+                self.handle = Some(handle);
+                bail!("Process handle is still used by {} other owners", users)
+            }
+        }
+    }
+}
+
 /// Cross-platform function to get the next process module region.
 fn memory_region_iterator_next(proc: &ProcessHandle, cur_addr: &mut usize) -> Option<MemoryRegion> {
     #[cfg(windows)]
@@ -31,10 +90,156 @@ pub fn open_process(pid: u32) -> Result<ProcessHandle> {
 
 /// Cross-platform function to find a process by its name.
 pub fn find_process_by_name(name: &str) -> Result<Option<u32>> {
+    find_process_by_name_until(name, None)
+}
+
+/// Like [`find_process_by_name`], but fail once `timeout` has passed instead
+/// of blocking on a system too busy to walk its process list
+pub fn find_process_by_name_timeout(name: &str, timeout: Duration) -> Result<Option<u32>> {
+    find_process_by_name_until(name, Some(Instant::now() + timeout))
+}
+
+fn find_process_by_name_until(name: &str, deadline: Option<Instant>) -> Result<Option<u32>> {
+    #[cfg(windows)]
+    return windows::process::find_process_by_name(name, deadline);
+    #[cfg(unix)]
+    return linux::process::find_process_by_name(name, deadline);
+}
+
+/// Find the PID of the process owning the first visible top-level window
+/// whose title contains `title` (case-insensitive), e.g. a game's title.
+///
+/// Only supported on Windows; elsewhere this always fails.
+pub fn find_process_by_window_title(title: &str) -> Result<Option<u32>> {
     #[cfg(windows)]
-    return windows::process::find_process_by_name(name);
+    return windows::process::find_process_by_window_title(title);
     #[cfg(unix)]
-    return linux::process::find_process_by_name(name);
+    bail!(
+        "finding a process by window title ('{}') is unsupported on this platform",
+        title
+    );
+}
+
+/// Cross-platform function to list the PID and name of every running process.
+pub fn list_processes() -> Result<Vec<(u32, String)>> {
+    list_processes_until(None)
+}
+
+fn list_processes_until(deadline: Option<Instant>) -> Result<Vec<(u32, String)>> {
+    #[cfg(windows)]
+    return windows::process::list_processes(deadline);
+    #[cfg(unix)]
+    return linux::process::list_processes(deadline);
+}
+
+/// Fail once `deadline` has passed, checked between processes while
+/// walking the process list
+pub(crate) fn check_lookup_deadline(deadline: Option<Instant>) -> Result<()> {
+    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        return Err(MemScanError::Timeout);
+    }
+    Ok(())
+}
+
+/// Find all processes whose name matches `pattern`, returning PID and name pairs.
+///
+/// By default `pattern` is a case-insensitive glob supporting `*` and `?`,
+/// e.g. `*host*` or `notepad.exe`. A trailing `.exe` is optional, so
+/// `notepad` matches `notepad.exe` but not `notepad++.exe`.
+///
+/// With the `regex` feature, a pattern prefixed with `re:` is a regex that
+/// must match the whole name, e.g. `re:notepad(\+\+)?\.exe`.
+pub fn find_processes_matching(pattern: &str) -> Result<Vec<(u32, String)>> {
+    find_processes_matching_until(pattern, None)
+}
+
+/// Like [`find_processes_matching`], but fail once `timeout` has passed
+pub fn find_processes_matching_timeout(
+    pattern: &str,
+    timeout: Duration,
+) -> Result<Vec<(u32, String)>> {
+    find_processes_matching_until(pattern, Some(Instant::now() + timeout))
+}
+
+fn find_processes_matching_until(
+    pattern: &str,
+    deadline: Option<Instant>,
+) -> Result<Vec<(u32, String)>> {
+    let matcher = NameMatcher::new(pattern)?;
+    Ok(list_processes_until(deadline)?
+        .into_iter()
+        .filter(|(_, name)| matcher.matches(name))
+        .collect())
+}
+
+/// Compiled process name pattern for [`find_processes_matching`]
+enum NameMatcher {
+    Glob(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl NameMatcher {
+    fn new(pattern: &str) -> Result<Self> {
+        if let Some(re) = pattern.strip_prefix("re:") {
+            #[cfg(feature = "regex")]
+            return Ok(Self::Regex(
+                regex::RegexBuilder::new(&format!("^(?:{})$", re))
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| other!(InvalidPattern, "{}", e))?,
+            ));
+            #[cfg(not(feature = "regex"))]
+            bail!(
+                "regex pattern '{}' requires libmemscan to be built with the `regex` feature",
+                re
+            );
+        }
+        Ok(Self::Glob(pattern.to_lowercase()))
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        // Patterns ignore case, so `.EXE` is stripped like `.exe`
+        let name = name.to_lowercase();
+        let candidates = [name.as_str(), name.strip_suffix(".exe").unwrap_or(&name)];
+        match self {
+            Self::Glob(glob) => candidates.iter().any(|name| glob_match(glob, name)),
+            #[cfg(feature = "regex")]
+            Self::Regex(re) => candidates.iter().any(|name| re.is_match(name)),
+        }
+    }
+}
+
+/// Match `text` against a glob where `*` matches any run of characters and
+/// `?` matches exactly one.
+fn glob_match(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut g, mut t) = (0, 0);
+    // Position of the last `*` and the text position it currently covers up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, t));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star_g, star_t)) => {
+                    backtrack = Some((star_g, star_t + 1));
+                    g = star_g + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
 }
 
 /// Cross-platform function to get the list of module regions of a process.
@@ -45,6 +250,51 @@ pub fn get_process_module_regions(proc: &ProcessHandle) -> Result<Vec<MemoryRegi
     return linux::process::get_process_module_regions(proc);
 }
 
+/// The image region of the target's main executable, with `image_file` set
+/// to its path, e.g. to address globals as `exe+offset`
+///
+/// [`get_process_module_regions`] leaves this module out.
+pub fn get_main_module(proc: &ProcessHandle) -> Result<MemoryRegion> {
+    #[cfg(windows)]
+    return windows::process::get_main_module(proc);
+    #[cfg(unix)]
+    return linux::process::get_main_module(proc);
+}
+
+/// List the threads of the target with their current stack pointers
+///
+/// Windows suspends each thread briefly to read its context, which needs
+/// `THREAD_GET_CONTEXT` access; for a WOW64 target this is the stack of the
+/// thread's 64-bit side rather than of its 32-bit code. Linux reads
+/// `kstkesp` from `/proc/<pid>/task/<tid>/stat` and, since recent kernels
+/// report 0 there, falls back to `/proc/<pid>/task/<tid>/syscall`, which has
+/// no stack pointer for a thread that is running at that moment. Threads
+/// whose stack pointer can't be read are listed without one.
+pub fn enumerate_threads(proc: &ProcessHandle) -> Result<Vec<ThreadInfo>> {
+    #[cfg(windows)]
+    return windows::process::enumerate_threads(proc);
+    #[cfg(unix)]
+    return linux::process::enumerate_threads(proc);
+}
+
+/// The region among `regions` holding the stack of `thread`, i.e. the one
+/// containing its stack pointer
+pub fn find_thread_stack<'a>(
+    thread: &ThreadInfo,
+    regions: &'a [MemoryRegion],
+) -> Option<&'a MemoryRegion> {
+    let stack_pointer = thread.stack_pointer?;
+    regions.iter().find(|region| region.contains(stack_pointer))
+}
+
+/// Whether the target runs 64-bit code, e.g. to choose how its code is disassembled
+pub fn is_64bit_process(proc: &ProcessHandle) -> bool {
+    #[cfg(windows)]
+    return windows::process::is_64bit_process(proc);
+    #[cfg(unix)]
+    return linux::process::is_64bit_process(proc);
+}
+
 /// Cross-platform function to get system information about the target process environment.
 pub fn query_system_info() -> SystemInfo {
     #[cfg(windows)]
@@ -56,10 +306,20 @@ pub fn query_system_info() -> SystemInfo {
 // Small cross-platform wrapper that dispatches to OS-specific process memory readers.
 // This is kept for backward compatibility and fallback cases.
 pub fn read_process_memory(proc: &ProcessHandle, addr: usize, buf: &mut [u8]) -> usize {
+    try_read_process_memory(proc, addr, buf).unwrap_or(0)
+}
+
+/// Read memory from a process like [`read_process_memory`], but return the
+/// OS error of a failed read instead of 0
+pub fn try_read_process_memory(
+    proc: &ProcessHandle,
+    addr: usize,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
     #[cfg(windows)]
-    return windows::process::read_process_memory(proc, addr, buf);
+    return windows::process::try_read_process_memory(proc, addr, buf);
     #[cfg(unix)]
-    return linux::process::read_process_memory(proc, addr, buf);
+    return linux::process::try_read_process_memory(proc, addr, buf);
 }
 
 /// Write memory to a process at a specific address
@@ -70,6 +330,87 @@ pub fn write_process_memory(proc: &ProcessHandle, addr: usize, buf: &[u8]) -> us
     return linux::process::write_process_memory(proc, addr, buf);
 }
 
+/// Write several buffers to a process, returning the bytes written for each
+///
+/// Every write is attempted even if an earlier one fails. Linux batches them
+/// into as few `process_vm_writev` calls as possible and retries any write
+/// it couldn't complete through `/proc/<pid>/mem`; Windows writes them one
+/// by one.
+pub fn write_process_memory_many(proc: &ProcessHandle, writes: &[(usize, &[u8])]) -> Vec<usize> {
+    #[cfg(windows)]
+    return windows::process::write_process_memory_many(proc, writes);
+    #[cfg(unix)]
+    return linux::process::write_process_memory_many(proc, writes);
+}
+
+/// Write memory to a process even where the target page isn't writable
+///
+/// Windows temporarily changes the page protection with `VirtualProtectEx`.
+/// Linux retries a refused `/proc/<pid>/mem` write with `process_vm_writev`,
+/// which needs the same ptrace access (see the Yama `ptrace_scope` or
+/// `CAP_SYS_PTRACE`). Some pages can't be written either way, in which case
+/// the error says why, including the page protection where known.
+pub fn force_write_process_memory(proc: &ProcessHandle, addr: usize, buf: &[u8]) -> Result<usize> {
+    #[cfg(windows)]
+    return windows::process::force_write_process_memory(proc, addr, buf);
+    #[cfg(unix)]
+    return linux::process::force_write_process_memory(proc, addr, buf);
+}
+
+/// Write only the bytes of `data` whose `mask` entry is set, leaving the
+/// target's bytes at every other offset untouched
+///
+/// Meant for patching some fields of a struct: each run of consecutive
+/// masked bytes is written on its own (batched as in
+/// [`write_process_memory_many`]), so the fields in between are never read
+/// and written back with a value that may have gone stale in the meantime.
+/// Returns the number of masked bytes written.
+pub fn write_masked(
+    proc: &ProcessHandle,
+    addr: usize,
+    data: &[u8],
+    mask: &[bool],
+) -> Result<usize> {
+    if data.len() != mask.len() {
+        bail!(
+            InvalidPattern,
+            "Mask has {} entries but the data is {} bytes",
+            mask.len(),
+            data.len()
+        );
+    }
+    let writes: Vec<(usize, &[u8])> = masked_runs(mask)
+        .into_iter()
+        .map(|run| (addr + run.start, &data[run]))
+        .collect();
+    let written: usize = write_process_memory_many(proc, &writes).into_iter().sum();
+    if written == 0 && !writes.is_empty() {
+        let masked = mask.iter().filter(|&&m| m).count();
+        return Err(MemScanError::WriteFailed {
+            addr,
+            reason: format!("none of the {} masked bytes were written", masked),
+        });
+    }
+    Ok(written)
+}
+
+/// Ranges of consecutive `true` entries in `mask`
+fn masked_runs(mask: &[bool]) -> Vec<std::ops::Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (i, &masked) in mask.iter().chain([&false]).enumerate() {
+        match (masked, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                runs.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
 // ================= Cross-platform structures ==================
 
 /// Cross-platform system information about the target process environment.
@@ -85,7 +426,7 @@ pub struct SystemInfo {
 /// Agnostic representation of:
 /// - Windows PAGE_* constants, see https://learn.microsoft.com/en-us/windows/win32/Memory/memory-protection-constants
 /// - Linux PROT_* constants, see https://man7.org/linux/man-pages/man2/mprotect.2.html
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MemoryProtection {
     /// E.g. `PAGE_TARGETS_INVALID`, `PAGE_ENCLAVE_DECOMMIT`, `PAGE_ENCLAVE_UNVALIDATED`, etc.
     pub no_access: bool,
@@ -126,11 +467,45 @@ impl Display for MemoryProtection {
     }
 }
 
+/// Parse the `_`-separated flags written by [`Display`], e.g. `READ_WRITE`
+/// (case-insensitive, an empty string means no flags)
+impl FromStr for MemoryProtection {
+    type Err = MemScanError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut protect = MemoryProtection {
+            no_access: false,
+            read: false,
+            write: false,
+            execute: false,
+            copy_on_write: false,
+            guarded: false,
+            no_cache: false,
+        };
+        for flag in s.split('_').filter(|f| !f.is_empty()) {
+            match flag.to_ascii_uppercase().as_str() {
+                "NOACCESS" => protect.no_access = true,
+                "NOCACHE" => protect.no_cache = true,
+                "READ" => protect.read = true,
+                "WRITE" => protect.write = true,
+                "WRITECOPY" => {
+                    protect.write = true;
+                    protect.copy_on_write = true;
+                }
+                "EXECUTE" => protect.execute = true,
+                "GUARDED" => protect.guarded = true,
+                _ => bail!("Unknown memory protection flag: {}", flag),
+            }
+        }
+        Ok(protect)
+    }
+}
+
 /// Cross-platform memory state flags.
 /// Agnostic representation of:
 /// - Windows MEM_* constants, see https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-memory_basic_information
 /// - Linux `mmap` flags, see https://man7.org/linux/man-pages/man2/mmap.2.html
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MemoryState {
     pub committed: bool,
     /// E.g. `MEM_FREE`
@@ -155,11 +530,34 @@ impl Display for MemoryState {
     }
 }
 
+/// Parse the `|`-separated states written by [`Display`], e.g. `COMMITTED`
+/// (case-insensitive)
+impl FromStr for MemoryState {
+    type Err = MemScanError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut state = MemoryState {
+            committed: false,
+            free: false,
+            reserved: false,
+        };
+        for flag in s.split('|').filter(|f| !f.is_empty()) {
+            match flag.to_ascii_uppercase().as_str() {
+                "COMMITTED" => state.committed = true,
+                "FREE" => state.free = true,
+                "RESERVED" => state.reserved = true,
+                _ => bail!("Unknown memory state: {}", flag),
+            }
+        }
+        Ok(state)
+    }
+}
+
 /// Cross-platform memory type flags.
 /// Agnostic representation of:
 /// - Windows MEM_* constants, see https://learn.microsoft.com/en-us/windows/win32/api/winnt/ns-winnt-memory_basic_information
 /// - Linux `mmap` flags, see https://man7.org/linux/man-pages/man2/mmap.2.html
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MemoryType {
     Unknown = 0b0,
     Private = 0b1,
@@ -179,6 +577,30 @@ impl Display for MemoryType {
     }
 }
 
+/// Parse a type name written by [`Display`], e.g. `PRIVATE` (case-insensitive)
+impl FromStr for MemoryType {
+    type Err = MemScanError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "PRIVATE" => MemoryType::Private,
+            "MAPPED" => MemoryType::Mapped,
+            "IMAGE" => MemoryType::Image,
+            "UNKNOWN" => MemoryType::Unknown,
+            _ => bail!("Unknown memory type: {}", s),
+        })
+    }
+}
+
+/// A thread of the target process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadInfo {
+    /// Thread id (the `tid` on Linux)
+    pub tid: u32,
+    /// Stack pointer of the thread when it was enumerated, if it could be read
+    pub stack_pointer: Option<usize>,
+}
+
 /// Cross-platform memory region representation in the target process.
 #[derive(Debug, Clone)]
 pub struct MemoryRegion {
@@ -191,10 +613,73 @@ pub struct MemoryRegion {
 }
 
 impl MemoryRegion {
+    /// A committed, readable and writable private region of `size` bytes at
+    /// `base_address`, e.g. to describe a local buffer for
+    /// [`crate::memmap::MappedMemory::from_buffer`] or a scan of it
+    pub fn private(base_address: usize, size: usize) -> Self {
+        Self {
+            base_address,
+            size,
+            protect: MemoryProtection {
+                no_access: false,
+                read: true,
+                write: true,
+                execute: false,
+                copy_on_write: false,
+                guarded: false,
+                no_cache: false,
+            },
+            state: MemoryState {
+                committed: true,
+                free: false,
+                reserved: false,
+            },
+            type_: MemoryType::Private,
+            image_file: None,
+        }
+    }
+
+    /// Check if `address` lies inside this region
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.base_address && address - self.base_address < self.size
+    }
+
     pub fn is_superset_of(&self, other: &MemoryRegion) -> bool {
         self.base_address <= other.base_address
             && self.base_address + self.size >= other.base_address + other.size
     }
+
+    /// The first of `modules` (e.g. from [`get_process_module_regions`])
+    /// that contains all of this region
+    pub fn containing_module<'m>(&self, modules: &'m [MemoryRegion]) -> Option<&'m MemoryRegion> {
+        modules.iter().find(|module| module.is_superset_of(self))
+    }
+
+    /// Check if `next` starts right where this region ends and has the same
+    /// protection, state, type and backing file
+    pub fn can_merge_with(&self, next: &MemoryRegion) -> bool {
+        self.base_address.checked_add(self.size) == Some(next.base_address)
+            && self.protect == next.protect
+            && self.state == next.state
+            && self.type_ == next.type_
+            && self.image_file == next.image_file
+    }
+}
+
+/// Merge runs of adjacent regions with identical attributes into single
+/// regions, see [`MemoryRegion::can_merge_with`]
+///
+/// `regions` should be sorted by base address, as the region iterator
+/// yields them; only neighbours in the list are merged.
+pub fn merge_adjacent(regions: &[MemoryRegion]) -> Vec<MemoryRegion> {
+    let mut merged: Vec<MemoryRegion> = Vec::with_capacity(regions.len());
+    for region in regions {
+        match merged.last_mut() {
+            Some(last) if last.can_merge_with(region) => last.size += region.size,
+            _ => merged.push(region.clone()),
+        }
+    }
+    merged
 }
 
 /// Iterates committed readable memory regions of the process.
@@ -202,37 +687,446 @@ pub struct MemoryRegionIterator<'a> {
     proc: &'a ProcessHandle,
     cur_addr: usize,
     max_addr: usize,
+    page_size: usize,
+    filter: RegionFilter,
 }
 
 impl<'a> MemoryRegionIterator<'a> {
+    /// Iterate the regions of `proc` accepted by [`RegionFilter::default`]
     pub fn new(proc: &'a ProcessHandle, sys: &SystemInfo) -> Self {
+        Self::new_with_filter(proc, sys, RegionFilter::default())
+    }
+
+    /// Iterate the regions of `proc` accepted by `filter`
+    pub fn new_with_filter(
+        proc: &'a ProcessHandle,
+        sys: &SystemInfo,
+        filter: RegionFilter,
+    ) -> Self {
         Self {
             proc,
             cur_addr: sys.min_app_addr,
             max_addr: sys.max_app_addr,
+            page_size: sys.page_size.max(1),
+            filter,
         }
     }
+
+    /// Pair every region with the first of `modules` containing it, see
+    /// [`MemoryRegion::containing_module`]
+    pub fn annotated(
+        self,
+        modules: &[MemoryRegion],
+    ) -> impl Iterator<Item = (MemoryRegion, Option<&MemoryRegion>)> {
+        self.map(move |region| {
+            let module = region.containing_module(modules);
+            (region, module)
+        })
+    }
 }
 
 impl<'a> Iterator for MemoryRegionIterator<'a> {
     type Item = MemoryRegion;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while self.cur_addr < self.max_addr {
-            if let Some(region) = memory_region_iterator_next(self.proc, &mut self.cur_addr) {
-                return Some(region);
-            } else {
-                continue;
-            }
+        let (proc, filter) = (self.proc, &self.filter);
+        next_region_with(
+            &mut self.cur_addr,
+            self.max_addr,
+            self.page_size,
+            |cur_addr| {
+                memory_region_iterator_next(proc, cur_addr)
+                    .filter(|region| filter.accepts(&region.protect, &region.state))
+            },
+        )
+    }
+}
+
+/// Advance `cur_addr` with `query` until it yields a non-empty region or
+/// `max_addr` is reached.
+///
+/// If `query` doesn't move `cur_addr` forward (e.g. `VirtualQueryEx`
+/// reporting a zero-size region at the top of the address space), it is
+/// forced forward by one page, and iteration stops if that would overflow.
+fn next_region_with(
+    cur_addr: &mut usize,
+    max_addr: usize,
+    page_size: usize,
+    mut query: impl FnMut(&mut usize) -> Option<MemoryRegion>,
+) -> Option<MemoryRegion> {
+    while *cur_addr < max_addr {
+        let prev_addr = *cur_addr;
+        let region = query(cur_addr);
+        if *cur_addr <= prev_addr {
+            *cur_addr = prev_addr.checked_add(page_size).unwrap_or(max_addr);
+        }
+        if let Some(region) = region
+            && region.size > 0
+        {
+            return Some(region);
         }
-        None
     }
+    None
 }
 
+/// Which regions [`MemoryRegionIterator`] yields and scans consider
+///
+/// The default only accepts committed, accessible regions without guard
+/// pages. The `include_*` flags widen that, e.g. for forensic dumps, but
+/// reading such regions usually fails, and touching a guard page on
+/// Windows raises a guard page exception in the target. Restricting scans
+/// to writable regions is up to
+/// [`ScanOptions::only_writable`](crate::scanner::ScanOptions::only_writable).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegionFilter {
+    /// Accept reserved regions that aren't committed
+    pub include_reserved: bool,
+    /// Accept regions with guard pages
+    pub include_guarded: bool,
+    /// Accept no-access regions
+    pub include_no_access: bool,
+}
+
+impl RegionFilter {
+    /// Why a region with `prot` and `state` is rejected, or `None` if it is
+    /// accepted; free memory is always rejected
+    pub fn reject_reason(
+        &self,
+        prot: &MemoryProtection,
+        state: &MemoryState,
+    ) -> Option<&'static str> {
+        if state.free {
+            Some("free")
+        } else if (!state.committed || state.reserved) && !self.include_reserved {
+            Some("not committed")
+        } else if prot.no_access && !self.include_no_access {
+            Some("no access")
+        } else if prot.guarded && !self.include_guarded {
+            Some("guarded")
+        } else {
+            None
+        }
+    }
+
+    /// Check if a region with `prot` and `state` is accepted
+    pub fn accepts(&self, prot: &MemoryProtection, state: &MemoryState) -> bool {
+        self.reject_reason(prot, state).is_none()
+    }
+}
+
+/// Check if a region is accepted by the default [`RegionFilter`]
 pub fn is_region_interesting(prot: &MemoryProtection, state: &MemoryState) -> bool {
-    if !state.committed || state.free || state.reserved || prot.no_access || prot.guarded {
-        false // Only committed regions
-    } else {
-        true
+    RegionFilter::default().accepts(prot, state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_address() {
+        assert_eq!(format_address(0x1234, 4), "00001234");
+        assert_eq!(format_address(0x1234, 8), "0000000000001234");
+        // Wider addresses are never cut off
+        assert_eq!(format_address(0x1_0000_0000, 4), "100000000");
+
+        let proc = open_process(std::process::id()).unwrap();
+        assert_eq!(proc.pointer_size(), std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn test_find_thread_stack() {
+        let regions = [
+            MemoryRegion::private(0x1000, 0x1000),
+            MemoryRegion::private(0x4000, 0x2000),
+        ];
+        let thread = |stack_pointer| ThreadInfo {
+            tid: 1,
+            stack_pointer,
+        };
+        assert_eq!(
+            find_thread_stack(&thread(Some(0x5ff8)), &regions).map(|r| r.base_address),
+            Some(0x4000)
+        );
+        assert!(find_thread_stack(&thread(Some(0x2000)), &regions).is_none());
+        assert!(find_thread_stack(&thread(None), &regions).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_process_by_window_title_unsupported() {
+        let err = find_process_by_window_title("Game")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unsupported on this platform"), "{}", err);
+    }
+
+    #[test]
+    fn test_open_process_not_found() {
+        // Above the largest pid Linux and Windows hand out
+        let err = open_process(0x7fff_fff0).unwrap_err();
+        assert!(matches!(err, MemScanError::ProcessNotFound(_)), "{}", err);
+        assert_eq!(err.to_string(), "process with pid 2147483632 not found");
+    }
+
+    #[test]
+    fn test_masked_runs() {
+        assert!(masked_runs(&[]).is_empty());
+        assert!(masked_runs(&[false, false]).is_empty());
+        assert_eq!(masked_runs(&[true; 3]), vec![0..3]);
+        assert_eq!(
+            masked_runs(&[false, true, true, false, true, false, false, true]),
+            vec![1..3, 4..5, 7..8]
+        );
+
+        let proc = open_process(std::process::id()).unwrap();
+        assert!(write_masked(&proc, 0, &[0; 4], &[true; 3]).is_err());
+    }
+
+    #[test]
+    fn test_check_lookup_deadline() {
+        assert!(check_lookup_deadline(None).is_ok());
+        assert!(check_lookup_deadline(Some(Instant::now() + Duration::from_secs(3600))).is_ok());
+        assert!(check_lookup_deadline(Some(Instant::now())).is_err());
+        assert!(find_processes_matching_timeout("*", Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_main_module_own_process() {
+        let proc = open_process(std::process::id()).unwrap();
+        let main = get_main_module(&proc).unwrap();
+        let exe = std::env::current_exe().unwrap();
+        let exe_name = exe.file_name().unwrap().to_str().unwrap();
+        assert!(main.image_file.as_deref().unwrap().ends_with(exe_name));
+
+        // Code of the test binary lies in the main module, which the other
+        // modules leave out
+        let code = test_main_module_own_process as fn() as usize;
+        assert!(code >= main.base_address && code < main.base_address + main.size);
+        let modules = get_process_module_regions(&proc).unwrap();
+        assert!(!modules.iter().any(|m| m.is_superset_of(&main)));
+    }
+
+    #[test]
+    fn test_containing_module() {
+        let modules = [
+            MemoryRegion::private(0x1000, 0x3000),
+            MemoryRegion::private(0x8000, 0x1000),
+        ];
+        let inside = MemoryRegion::private(0x2000, 0x1000);
+        assert_eq!(
+            inside.containing_module(&modules).map(|m| m.base_address),
+            Some(0x1000)
+        );
+        // Overlapping a module isn't enough
+        assert!(
+            MemoryRegion::private(0x3000, 0x2000)
+                .containing_module(&modules)
+                .is_none()
+        );
+        assert!(
+            MemoryRegion::private(0x6000, 0x1000)
+                .containing_module(&modules)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_annotated_regions_own_process() {
+        let proc = open_process(std::process::id()).unwrap();
+        let sys = query_system_info();
+        let modules = get_process_module_regions(&proc).unwrap();
+        let regions: Vec<_> = MemoryRegionIterator::new(&proc, &sys)
+            .annotated(&modules)
+            .collect();
+        assert!(regions.iter().any(|(_, module)| module.is_some()));
+        assert!(regions.iter().any(|(_, module)| module.is_none()));
+        for (region, module) in &regions {
+            if let Some(module) = module {
+                assert!(module.is_superset_of(region));
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_adjacent_regions() {
+        // Three adjacent RW private regions become one
+        let regions = [
+            MemoryRegion::private(0x1000, 0x1000),
+            MemoryRegion::private(0x2000, 0x2000),
+            MemoryRegion::private(0x4000, 0x1000),
+        ];
+        let merged = merge_adjacent(&regions);
+        assert_eq!(merged.len(), 1);
+        assert_eq!((merged[0].base_address, merged[0].size), (0x1000, 0x4000));
+
+        // A gap, a protection change and a type change each end a run
+        let mut read_only = MemoryRegion::private(0x8000, 0x1000);
+        read_only.protect.write = false;
+        let mut mapped = MemoryRegion::private(0x9000, 0x1000);
+        mapped.type_ = MemoryType::Mapped;
+        let regions = [
+            MemoryRegion::private(0x1000, 0x1000),
+            MemoryRegion::private(0x2000, 0x1000),
+            MemoryRegion::private(0x7000, 0x1000),
+            read_only,
+            mapped,
+        ];
+        let bounds: Vec<(usize, usize)> = merge_adjacent(&regions)
+            .iter()
+            .map(|r| (r.base_address, r.size))
+            .collect();
+        assert_eq!(
+            bounds,
+            vec![
+                (0x1000, 0x2000),
+                (0x7000, 0x1000),
+                (0x8000, 0x1000),
+                (0x9000, 0x1000)
+            ]
+        );
+        assert!(merge_adjacent(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_region_filter_reject_reason() {
+        let committed: MemoryState = "COMMITTED".parse().unwrap();
+        let reserved: MemoryState = "RESERVED".parse().unwrap();
+        let free: MemoryState = "FREE".parse().unwrap();
+        let read: MemoryProtection = "READ".parse().unwrap();
+        let guarded: MemoryProtection = "READ_GUARDED".parse().unwrap();
+        let no_access: MemoryProtection = "NOACCESS".parse().unwrap();
+
+        let default = RegionFilter::default();
+        assert_eq!(default.reject_reason(&read, &committed), None);
+        assert_eq!(
+            default.reject_reason(&read, &reserved),
+            Some("not committed")
+        );
+        assert_eq!(default.reject_reason(&guarded, &committed), Some("guarded"));
+        assert_eq!(
+            default.reject_reason(&no_access, &committed),
+            Some("no access")
+        );
+        assert!(is_region_interesting(&read, &committed));
+        assert!(!is_region_interesting(&guarded, &committed));
+
+        let forensic = RegionFilter {
+            include_reserved: true,
+            include_guarded: true,
+            include_no_access: true,
+        };
+        assert!(forensic.accepts(&guarded, &reserved));
+        assert!(forensic.accepts(&no_access, &committed));
+        assert_eq!(forensic.reject_reason(&read, &free), Some("free"));
+    }
+
+    #[test]
+    fn test_region_attributes_round_trip() {
+        for protect in [
+            "READ",
+            "READ_WRITE",
+            "READ_WRITECOPY_EXECUTE",
+            "NOACCESS_GUARDED",
+            "",
+        ] {
+            assert_eq!(
+                protect.parse::<MemoryProtection>().unwrap().to_string(),
+                protect
+            );
+        }
+        let protect: MemoryProtection = "read_writecopy".parse().unwrap();
+        assert!(protect.read && protect.write && protect.copy_on_write && !protect.execute);
+        assert!("READ_SOMETIMES".parse::<MemoryProtection>().is_err());
+
+        for state in ["COMMITTED", "FREE", "COMMITTED|RESERVED"] {
+            assert_eq!(state.parse::<MemoryState>().unwrap().to_string(), state);
+        }
+        assert!("ASLEEP".parse::<MemoryState>().is_err());
+
+        for type_ in ["PRIVATE", "MAPPED", "IMAGE", "UNKNOWN"] {
+            assert_eq!(type_.parse::<MemoryType>().unwrap().to_string(), type_);
+        }
+        assert!(matches!("image".parse(), Ok(MemoryType::Image)));
+        assert!("STACK".parse::<MemoryType>().is_err());
+    }
+
+    #[test]
+    fn test_next_region_zero_size_terminates() {
+        // A query that never advances and only reports zero-size regions
+        let mut calls = 0;
+        let mut cur_addr = 0x10000;
+        let region = next_region_with(&mut cur_addr, 0x20000, 0x1000, |addr| {
+            calls += 1;
+            Some(MemoryRegion::private(*addr, 0))
+        });
+        assert!(region.is_none());
+        assert_eq!(calls, 16);
+        assert_eq!(cur_addr, 0x20000);
+    }
+
+    #[test]
+    fn test_next_region_stops_on_overflow() {
+        let mut cur_addr = usize::MAX - 0x800;
+        let region = next_region_with(&mut cur_addr, usize::MAX, 0x1000, |_| None);
+        assert!(region.is_none());
+        assert_eq!(cur_addr, usize::MAX);
+    }
+
+    #[test]
+    fn test_next_region_yields_regions() {
+        let mut cur_addr = 0x1000;
+        let mut query = |addr: &mut usize| {
+            let region = MemoryRegion::private(*addr, 0x1000);
+            *addr += 0x2000;
+            Some(region)
+        };
+        let first = next_region_with(&mut cur_addr, 0x4000, 0x1000, &mut query).unwrap();
+        assert_eq!(first.base_address, 0x1000);
+        let second = next_region_with(&mut cur_addr, 0x4000, 0x1000, &mut query).unwrap();
+        assert_eq!(second.base_address, 0x3000);
+        assert!(next_region_with(&mut cur_addr, 0x4000, 0x1000, &mut query).is_none());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("notepad.exe", "notepad.exe"));
+        assert!(!glob_match("notepad", "notepad++"));
+        assert!(glob_match("*host*", "svchost.exe"));
+        assert!(glob_match("note?ad*", "notepad++.exe"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn test_name_matcher_glob() {
+        let matcher = NameMatcher::new("Notepad").unwrap();
+        assert!(matcher.matches("notepad.exe"));
+        assert!(matcher.matches("NOTEPAD"));
+        assert!(matcher.matches("NOTEPAD.EXE"));
+        assert!(!matcher.matches("notepad++.exe"));
+
+        let matcher = NameMatcher::new("*host*").unwrap();
+        assert!(matcher.matches("svchost.exe"));
+        assert!(!matcher.matches("explorer.exe"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_name_matcher_regex() {
+        let matcher = NameMatcher::new(r"re:notepad(\+\+)?").unwrap();
+        assert!(matcher.matches("notepad.exe"));
+        assert!(matcher.matches("Notepad++.exe"));
+        assert!(matcher.matches("NOTEPAD.EXE"));
+        // Anchored: a substring match is not enough
+        assert!(!matcher.matches("mynotepad.exe"));
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn test_name_matcher_regex_requires_feature() {
+        assert!(NameMatcher::new("re:notepad").is_err());
     }
 }
@@ -1,15 +1,240 @@
 //! No direct Windows or Linux API usage here; platform-specific reads are in OS modules
 
+use crate::ScanMatch;
+use crate::color::OwoColorize;
+use crate::disasm::{Arch, disassemble};
+use crate::error::{MemScanError, Result, bail, other};
+use crate::fmt::{format_address, format_size};
 use crate::memmap::{MappedMemory, MemoryMapper};
 use crate::process::ProcessHandle;
-use crate::process::{MemoryRegion, MemoryRegionIterator, SystemInfo};
-use anyhow::Result;
+use crate::process::{
+    MemoryRegion, MemoryRegionIterator, MemoryType, RegionFilter, SystemInfo,
+    force_write_process_memory, merge_adjacent, query_system_info,
+};
+use crate::symbols::SymbolResolver;
 use memchr::memmem;
-use owo_colors::OwoColorize;
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, mpsc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
+/// Output format for printed scan results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Colored, human-readable lines with optional match context
+    #[default]
+    Human,
+    /// One JSON object per match, followed by a JSON summary object
+    Jsonl,
+    /// `address,module,offset` rows, followed by a JSON summary object on stderr
+    Csv,
+}
+
+/// How far a running scan has got, reported at region boundaries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanProgress {
+    /// Base address of the region that was just read or searched
+    pub current_address: usize,
+    /// Number of regions read so far
+    pub regions_scanned: usize,
+    /// Number of matches found so far
+    pub matches_found: usize,
+}
+
+/// Receives [`ScanProgress`] updates from a running scan, e.g. to draw a
+/// progress bar
+pub trait ProgressReporter: Send + Sync {
+    /// Called after every region that was read or searched
+    fn update(&self, progress: &ScanProgress);
+
+    /// Called once no more updates follow, before anything else is printed
+    fn finish(&self) {}
+}
+
+#[derive(Clone)]
 pub struct ScanOptions {
     pub verbose: u8,
     pub all_modules: bool,
+    /// Map regions into the local address space instead of copying them
+    /// page by page with `ReadProcessMemory` (or `/proc/<pid>/mem` reads).
+    ///
+    /// Mapping reads each region with a single call and is faster for
+    /// large regions. Page-by-page copying issues one call per page, which
+    /// is slower but avoids the mapping APIs when they fail repeatedly.
+    pub use_memmap: bool,
+    /// Bytes read per call when regions are copied instead of mapped,
+    /// rounded up to a multiple of the page size
+    pub read_chunk: usize,
+    /// How matches and the final summary are printed
+    pub output: OutputFormat,
+    /// Stop scanning after this many matches (`None` for no limit)
+    pub max_matches: Option<usize>,
+    /// Only scan writable (including copy-on-write) regions
+    pub only_writable: bool,
+    /// Skip executable regions such as code sections
+    pub skip_executable: bool,
+    /// Skip regions smaller than this many bytes
+    pub min_region_size: Option<usize>,
+    /// Skip regions larger than this many bytes, e.g. memory-mapped files
+    pub max_region_size: Option<usize>,
+    /// Scan shared or file mapping regions (`MemoryType::Mapped`)
+    pub include_mapped: bool,
+    /// Scan image regions (`MemoryType::Image`), i.e. loaded executables and libraries
+    pub include_image: bool,
+    /// Only scan regions containing one of these stack pointers, e.g. those
+    /// from [`enumerate_threads`](crate::process::enumerate_threads) to scan
+    /// just the thread stacks (`None` for no restriction)
+    pub stack_pointers: Option<Vec<usize>>,
+    /// Print the instructions starting at each match (a hexdump without
+    /// the `disasm` feature)
+    pub disasm: bool,
+    /// Which regions are enumerated at all, by state and protection
+    pub region_filter: RegionFilter,
+    /// Soft wall-clock budget for the scan; once spent, no further regions
+    /// are mapped or searched and the matches found so far are kept
+    pub timeout: Option<Duration>,
+    /// Where progress updates of the scan are sent, if anywhere
+    pub progress: Option<Arc<dyn ProgressReporter>>,
+    /// Once set, no further regions are mapped or searched and the matches
+    /// found so far are kept, like when `timeout` runs out
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Which bytes of the pattern have to match, one entry per byte, e.g.
+    /// from [`crate::parse_ida_signature`]; the others are wildcards
+    /// (`None` to match every byte)
+    pub pattern_mask: Option<Vec<bool>>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            verbose: 0,
+            all_modules: false,
+            use_memmap: true,
+            read_chunk: query_system_info().page_size,
+            output: OutputFormat::Human,
+            max_matches: None,
+            only_writable: false,
+            skip_executable: false,
+            min_region_size: None,
+            max_region_size: None,
+            include_mapped: true,
+            include_image: true,
+            stack_pointers: None,
+            disasm: false,
+            region_filter: RegionFilter::default(),
+            timeout: None,
+            progress: None,
+            cancel: None,
+            pattern_mask: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Send `progress` to the configured [`ProgressReporter`], if any
+    fn report_progress(&self, progress: ScanProgress) {
+        if let Some(reporter) = &self.progress {
+            reporter.update(&progress);
+        }
+    }
+
+    /// Tell the configured [`ProgressReporter`] that the scan is done
+    fn finish_progress(&self) {
+        if let Some(reporter) = &self.progress {
+            reporter.finish();
+        }
+    }
+
+    /// Fail if `pattern_mask` doesn't have one entry per byte of `pattern`
+    fn check_pattern_mask(&self, pattern: &[u8]) -> Result<()> {
+        if let Some(mask) = &self.pattern_mask
+            && mask.len() != pattern.len()
+        {
+            bail!(
+                InvalidPattern,
+                "Mask has {} entries but the pattern is {} bytes",
+                mask.len(),
+                pattern.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// Reason why `region` is excluded by the region filters of these
+    /// options, or `None` if it should be scanned
+    pub fn skip_reason(&self, region: &MemoryRegion) -> Option<&'static str> {
+        if let Some(reason) = self
+            .region_filter
+            .reject_reason(&region.protect, &region.state)
+        {
+            return Some(reason);
+        }
+        if !self.include_mapped && matches!(region.type_, MemoryType::Mapped) {
+            return Some("mapped");
+        }
+        if !self.include_image && matches!(region.type_, MemoryType::Image) {
+            return Some("image");
+        }
+        if self.only_writable && !(region.protect.write || region.protect.copy_on_write) {
+            return Some("not writable");
+        }
+        if self.skip_executable && region.protect.execute {
+            return Some("executable");
+        }
+        if self.min_region_size.is_some_and(|min| region.size < min) {
+            return Some("too small");
+        }
+        if self.max_region_size.is_some_and(|max| region.size > max) {
+            return Some("too large");
+        }
+        if let Some(stack_pointers) = &self.stack_pointers
+            && !stack_pointers.iter().any(|&sp| region.contains(sp))
+        {
+            return Some("not a stack");
+        }
+        None
+    }
+}
+
+/// Summary of a completed scan
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    /// Addresses of all matches in the target process
+    pub matches: Vec<usize>,
+    /// Number of regions that were read and scanned
+    pub regions_scanned: usize,
+    /// Number of bytes that were read and scanned
+    pub bytes_scanned: usize,
+    /// Number of regions that could not be read
+    pub regions_failed: usize,
+    /// Number of regions that were scanned but had unreadable pages
+    pub regions_partial: usize,
+    /// Number of bytes in regions or pages that could not be read
+    pub bytes_failed: usize,
+    /// Whether the scan stopped early because `max_matches` was reached
+    pub truncated: bool,
+    /// Whether the scan stopped early because `timeout` ran out
+    pub timed_out: bool,
+    /// Whether the scan stopped early because it was cancelled
+    pub cancelled: bool,
+    /// Time spent mapping and searching all regions
+    pub elapsed: Duration,
+}
+
+impl ScanReport {
+    /// Scan throughput in MiB per second
+    pub fn mib_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_scanned as f64 / (1024.0 * 1024.0) / secs
+        } else {
+            0.0
+        }
+    }
 }
 
 /// Perform static, single-pass scan all readable regions.
@@ -20,166 +245,1070 @@ pub fn scan_process(
     opts: &ScanOptions,
     modules: &[MemoryRegion],
 ) -> Result<()> {
-    let mut memory_mapper = MemoryMapper::new(proc);
-    let mut total_regions = 0usize;
-    let mut total_bytes = 0usize;
-    let mut matches_found = 0usize;
+    let color = io::stdout().is_terminal();
+    scan_process_to(
+        proc,
+        sys,
+        pattern,
+        opts,
+        modules,
+        &mut io::stdout().lock(),
+        color,
+    )
+}
+
+/// Perform the same scan as [`scan_process`], but write the results to
+/// `out` instead of stdout
+///
+/// Matches are written as they are found, so wrap `out` in a
+/// [`std::io::BufWriter`] for match-heavy scans; it is flushed before
+/// returning. Human output is only colored if `color` is set, e.g. when
+/// `out` is a terminal, and colors aren't turned off with
+/// [`crate::color::set_enabled`]; the CSV summary still goes to stderr.
+/// The scan stops at the first failed write and returns its error.
+pub fn scan_process_to(
+    proc: &ProcessHandle,
+    sys: &SystemInfo,
+    pattern: &[u8],
+    opts: &ScanOptions,
+    modules: &[MemoryRegion],
+    out: &mut dyn Write,
+    color: bool,
+) -> Result<()> {
+    let _colors = (!color).then(crate::color::disable_for_thread);
+    opts.check_pattern_mask(pattern)?;
+    let mut report = ScanReport::default();
+    let start = Instant::now();
+    let deadline = opts.timeout.map(|timeout| start + timeout);
 
     // First map all regions
-    for region in MemoryRegionIterator::new(proc, sys) {
-        let current_module = modules.iter().find(|ign| ign.is_superset_of(&region));
-        let current_module_file = current_module.and_then(|ign| ign.image_file.as_deref());
-        let current_module_name = current_module_file
-            .and_then(|f| Some(f.rsplit(['\\', '/'].as_ref()).next().unwrap_or(f)));
+    let memory_mapper = map_scan_regions(proc, sys, opts, modules, deadline, &mut report, out)?;
+    // Matches are printed while searching, so progress only covers the reads
+    opts.finish_progress();
+    let opts = &ScanOptions {
+        progress: None,
+        ..opts.clone()
+    };
+
+    if opts.output != OutputFormat::Human {
+        if opts.output == OutputFormat::Csv {
+            writeln!(out, "address,module,offset")?;
+        }
+        let mut symbols = SymbolResolver::new(proc, modules);
+        let matches_found = try_visit_matches(
+            &memory_mapper,
+            pattern,
+            opts,
+            deadline,
+            &mut report,
+            |mapped, offset| {
+                let address = mapped.remote_region.base_address + offset;
+                let symbol = symbols.describe(address);
+                print_match_record(out, address, modules, symbol.as_deref(), opts.output)
+            },
+        )?;
+        report.elapsed = start.elapsed();
+        print_summary_record(out, &report, matches_found, opts.output)?;
+        out.flush()?;
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "{} mapped {} regions, ~{}",
+        "[info]".bright_cyan(),
+        report.regions_scanned,
+        format_size(report.bytes_scanned)
+    )?;
+
+    // Now scan all mapped regions
+    let mut symbols = SymbolResolver::new(proc, modules);
+    let arch = Arch::of_process(proc);
+    let matches_found = try_visit_matches(
+        &memory_mapper,
+        pattern,
+        opts,
+        deadline,
+        &mut report,
+        |mapped, offset| {
+            let haystack = mapped.data();
+            let match_address = mapped.remote_region.base_address + offset;
+            let symbol = symbols.describe(match_address);
+            print_match_context(
+                out,
+                match_address,
+                symbol.as_deref(),
+                haystack,
+                offset..offset + pattern.len(),
+                arch,
+                opts,
+            )
+        },
+    )?;
+    report.elapsed = start.elapsed();
+
+    writeln!(
+        out,
+        "{} scanned {} regions, ~{}, {} matches in {:.3}s ({:.1} MiB/s)",
+        "[done]".bright_cyan(),
+        report.regions_scanned,
+        format_size(report.bytes_scanned),
+        matches_found,
+        report.elapsed.as_secs_f64(),
+        report.mib_per_sec()
+    )?;
+    if report.regions_failed > 0 || report.regions_partial > 0 {
+        writeln!(
+            out,
+            "{} {} regions unreadable, {} partially readable, ~{} skipped",
+            "[warn]".yellow(),
+            report.regions_failed,
+            report.regions_partial,
+            format_size(report.bytes_failed)
+        )?;
+    }
+    if report.truncated {
+        writeln!(
+            out,
+            "{} stopped at {} matches (use --max to raise)",
+            "[warn]".yellow(),
+            matches_found,
+        )?;
+    }
+    if report.timed_out {
+        writeln!(
+            out,
+            "{} stopped after {:.1}s (use --timeout to raise), results are partial",
+            "[warn]".yellow(),
+            report.elapsed.as_secs_f64(),
+        )?;
+    }
+    if report.cancelled {
+        writeln!(
+            out,
+            "{} scan cancelled, results are partial",
+            "[warn]".yellow()
+        )?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Perform the same scan as [`scan_process`], but collect the matches
+/// into a [`ScanReport`] instead of printing them.
+pub fn scan_process_collect(
+    proc: &ProcessHandle,
+    sys: &SystemInfo,
+    pattern: &[u8],
+    opts: &ScanOptions,
+    modules: &[MemoryRegion],
+) -> Result<ScanReport> {
+    opts.check_pattern_mask(pattern)?;
+    let mut report = ScanReport::default();
+    let start = Instant::now();
+    let deadline = opts.timeout.map(|timeout| start + timeout);
+    let memory_mapper = map_scan_regions(
+        proc,
+        sys,
+        opts,
+        modules,
+        deadline,
+        &mut report,
+        &mut io::stdout(),
+    )?;
+    let mut matches = Vec::new();
+    visit_matches(
+        &memory_mapper,
+        pattern,
+        opts,
+        deadline,
+        &mut report,
+        |mapped, offset| {
+            matches.push(mapped.remote_region.base_address + offset);
+        },
+    );
+    opts.finish_progress();
+    report.matches = matches;
+    report.elapsed = start.elapsed();
+    Ok(report)
+}
+
+/// A scan running on a worker thread, started by [`scan_process_spawn`]
+///
+/// Dropping the handle cancels the scan without waiting for the thread.
+pub struct ScanHandle {
+    matches: mpsc::Receiver<usize>,
+    progress: Arc<LatestProgress>,
+    cancel: Arc<AtomicBool>,
+    thread: Option<JoinHandle<ScanReport>>,
+}
+
+impl ScanHandle {
+    /// Take the matches found since the last call, without waiting for more
+    pub fn try_recv_matches(&self) -> Vec<usize> {
+        self.matches.try_iter().collect()
+    }
+
+    /// How far the scan has got, as of the last region boundary
+    pub fn progress(&self) -> ScanProgress {
+        *self
+            .progress
+            .latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Ask the scan to stop at the next region boundary
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if the worker thread is done, i.e. [`ScanHandle::join`]
+    /// won't block
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+    }
 
-        if !opts.all_modules {
-            if let Some(ign) = current_module {
-                let image_file = ign.image_file.as_deref().unwrap_or("unknown");
-                if opts.verbose > 2 {
+    /// Wait for the scan to finish and return its report
+    ///
+    /// `report.matches` holds the matches that weren't taken with
+    /// [`ScanHandle::try_recv_matches`] yet.
+    pub fn join(mut self) -> Result<ScanReport> {
+        let thread = self.thread.take().expect("scan thread already joined");
+        let mut report = thread.join().map_err(|_| other!("scan thread panicked"))?;
+        report.matches = self.try_recv_matches();
+        Ok(report)
+    }
+}
+
+impl Drop for ScanHandle {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Keeps the last progress update for [`ScanHandle::progress`] and passes
+/// it on to the reporter of the caller's options
+struct LatestProgress {
+    latest: Mutex<ScanProgress>,
+    reporter: Option<Arc<dyn ProgressReporter>>,
+}
+
+impl ProgressReporter for LatestProgress {
+    fn update(&self, progress: &ScanProgress) {
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner) = *progress;
+        if let Some(reporter) = &self.reporter {
+            reporter.update(progress);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(reporter) = &self.reporter {
+            reporter.finish();
+        }
+    }
+}
+
+/// Start the same scan as [`scan_process_collect`] on a worker thread and
+/// return right away, e.g. to keep a UI responsive
+///
+/// Matches are streamed to [`ScanHandle::try_recv_matches`] as they are
+/// found. The thread holds its own reference to `proc` until it finishes,
+/// so the handle outlives the scan (and a
+/// [`SharedProcessHandle`](crate::process::SharedProcessHandle) it came
+/// from can't be closed meanwhile). No region logs are printed; progress
+/// still goes to the reporter of `opts`, and `opts.cancel` is used if set.
+/// A `pattern_mask` that doesn't fit `pattern` finds nothing.
+pub fn scan_process_spawn(
+    proc: Arc<ProcessHandle>,
+    sys: &SystemInfo,
+    pattern: &[u8],
+    opts: &ScanOptions,
+    modules: &[MemoryRegion],
+) -> ScanHandle {
+    let cancel = opts.cancel.clone().unwrap_or_default();
+    let progress = Arc::new(LatestProgress {
+        latest: Mutex::new(ScanProgress::default()),
+        reporter: opts.progress.clone(),
+    });
+    let opts = ScanOptions {
+        progress: Some(progress.clone()),
+        cancel: Some(cancel.clone()),
+        ..opts.clone()
+    };
+    let (sender, matches) = mpsc::channel();
+    let (sys, pattern, modules) = (sys.clone(), pattern.to_vec(), modules.to_vec());
+
+    let thread = std::thread::spawn(move || {
+        let mut report = ScanReport::default();
+        let start = Instant::now();
+        let deadline = opts.timeout.map(|timeout| start + timeout);
+        let memory_mapper = map_scan_regions(
+            &proc,
+            &sys,
+            &opts,
+            &modules,
+            deadline,
+            &mut report,
+            &mut io::sink(),
+        )
+        .expect("writing to io::sink can't fail");
+        // Nobody is left to receive matches once the handle is dropped
+        let visited = try_visit_matches(
+            &memory_mapper,
+            &pattern,
+            &opts,
+            deadline,
+            &mut report,
+            |mapped, offset| sender.send(mapped.remote_region.base_address + offset),
+        );
+        if visited.is_err() {
+            report.cancelled = true;
+        }
+        opts.finish_progress();
+        report.elapsed = start.elapsed();
+        report
+    });
+
+    ScanHandle {
+        matches,
+        progress,
+        cancel,
+        thread: Some(thread),
+    }
+}
+
+/// Scan exactly `regions` for `pattern`, e.g. regions kept from an earlier
+/// enumeration or [`crate::process::get_process_module_regions`], without
+/// enumerating the address space again
+///
+/// The region filters of `opts` are not applied, but `max_matches`,
+/// `timeout`, `use_memmap` and `read_chunk` are. Regions that can't be read
+/// are skipped, and the call only fails if none of them could be. Matches
+/// are sorted by address; inside a region with an image file they carry
+/// its file name and their offset from the start of the region.
+pub fn scan_regions(
+    proc: &ProcessHandle,
+    regions: &[MemoryRegion],
+    pattern: &[u8],
+    opts: &ScanOptions,
+) -> Result<Vec<ScanMatch>> {
+    opts.check_pattern_mask(pattern)?;
+    let mut report = ScanReport::default();
+    let deadline = opts.timeout.map(|timeout| Instant::now() + timeout);
+    let mut memory_mapper = MemoryMapper::new(proc);
+    memory_mapper.set_use_memmap(opts.use_memmap);
+    memory_mapper.set_read_chunk(opts.read_chunk);
+    let mut last_error = None;
+    for region in regions {
+        if scan_stopped(opts, deadline, &mut report) {
+            break;
+        }
+        if let Err(err) = memory_mapper.map_region(region.clone()) {
+            last_error = Some(err);
+        }
+    }
+    if memory_mapper.is_empty()
+        && let Some(err) = last_error
+    {
+        bail!(
+            "none of the {} regions could be read: {}",
+            regions.len(),
+            err
+        );
+    }
+
+    let mut matches = Vec::new();
+    visit_matches(
+        &memory_mapper,
+        pattern,
+        opts,
+        deadline,
+        &mut report,
+        |mapped, offset| {
+            matches.push(mapped.remote_region.base_address + offset);
+        },
+    );
+    // Overlapping regions find the same match twice, not always in a row
+    matches.sort_unstable();
+    matches.dedup();
+    Ok(matches
+        .into_iter()
+        .map(|address| ScanMatch::locate(address, regions))
+        .collect())
+}
+
+/// Scan for `find` like [`scan_process_collect`] and overwrite every
+/// occurrence with `replace`, returning the number of occurrences patched
+///
+/// Both patterns must have the same length. Occurrences that overlap one
+/// that was already patched are skipped. Pages that aren't writable are
+/// written with [`force_write_process_memory`]; occurrences that still
+/// can't be written are reported as warnings in human output, and the
+/// call only fails if none of them could be.
+pub fn replace_pattern(
+    proc: &ProcessHandle,
+    sys: &SystemInfo,
+    find: &[u8],
+    replace: &[u8],
+    modules: &[MemoryRegion],
+    opts: &ScanOptions,
+) -> Result<usize> {
+    if find.len() != replace.len() {
+        bail!(
+            InvalidPattern,
+            "find and replace patterns must have the same length ({} vs {} bytes)",
+            find.len(),
+            replace.len()
+        );
+    }
+    if find.is_empty() {
+        bail!(InvalidPattern, "find pattern must not be empty");
+    }
+
+    let report = scan_process_collect(proc, sys, find, opts, modules)?;
+    let targets = patch_targets(&report.matches, find.len());
+    let mut patched = 0;
+    let mut last_error = None;
+    for &address in &targets {
+        let err = match force_write_process_memory(proc, address, replace) {
+            Ok(written) if written == replace.len() => {
+                patched += 1;
+                continue;
+            }
+            Ok(written) => MemScanError::WriteFailed {
+                addr: address,
+                reason: format!("only wrote {} of {} bytes", written, replace.len()),
+            },
+            Err(err) => err,
+        };
+        if opts.output == OutputFormat::Human {
+            println!("{} {}", "[warn]".yellow(), err);
+        }
+        last_error = Some(err);
+    }
+    if patched == 0
+        && let Some(err) = last_error
+    {
+        bail!("failed to patch any of {} matches: {}", targets.len(), err);
+    }
+    Ok(patched)
+}
+
+/// The sorted match addresses that can be patched with a `len`-byte
+/// pattern without overlapping an earlier one, i.e. those that
+/// [`replace_pattern`] writes to
+pub fn patch_targets(matches: &[usize], len: usize) -> Vec<usize> {
+    let mut sorted = matches.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut targets = Vec::new();
+    let mut next_free = 0;
+    for address in sorted {
+        if address >= next_free {
+            targets.push(address);
+            next_free = address + len;
+        }
+    }
+    targets
+}
+
+/// Match addresses that appeared, disappeared or stayed between two scans
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanDelta {
+    /// Addresses only matched by the later scan
+    pub added: Vec<usize>,
+    /// Addresses only matched by the earlier scan
+    pub removed: Vec<usize>,
+    /// Addresses matched by both scans
+    pub persisted: Vec<usize>,
+}
+
+/// Compare the match addresses of two scans, e.g. two [`ScanReport::matches`]
+///
+/// Each list of the delta is sorted and free of duplicates.
+pub fn diff_scan_results(before: &[usize], after: &[usize]) -> ScanDelta {
+    let before: BTreeSet<usize> = before.iter().copied().collect();
+    let after: BTreeSet<usize> = after.iter().copied().collect();
+    ScanDelta {
+        added: after.difference(&before).copied().collect(),
+        removed: before.difference(&after).copied().collect(),
+        persisted: before.intersection(&after).copied().collect(),
+    }
+}
+
+/// Read the match addresses back from `--output jsonl` scan results
+///
+/// Lines without an `address` field, like the summary record, are skipped.
+pub fn parse_jsonl_matches(text: &str) -> Result<Vec<usize>> {
+    const KEY: &str = "\"address\":\"";
+    let mut addresses = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let Some(start) = line.find(KEY) else {
+            continue;
+        };
+        let value = &line[start + KEY.len()..];
+        let value = value.split('"').next().unwrap_or(value);
+        let address = value
+            .strip_prefix("0x")
+            .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| other!("invalid address {:?} on line {}", value, i + 1))?;
+        addresses.push(address);
+    }
+    Ok(addresses)
+}
+
+/// Print the matches that appeared or disappeared since an earlier scan,
+/// as colored lines with addresses padded to `pointer_size` or one record
+/// per change
+pub fn print_scan_delta(delta: &ScanDelta, output: OutputFormat, pointer_size: usize) {
+    let changes = delta
+        .added
+        .iter()
+        .map(|&address| (address, "added"))
+        .chain(delta.removed.iter().map(|&address| (address, "removed")));
+    match output {
+        OutputFormat::Human => {
+            for (address, change) in changes {
+                if change == "added" {
                     println!(
-                        "{}   {:016x} - {:016x} ({} KiB) \t{}{}{}",
-                        "[skip]".bright_yellow(),
-                        region.base_address,
-                        region.base_address + region.size,
-                        region.size / 1024,
-                        "[".magenta(),
-                        image_file.magenta(),
-                        "]".magenta()
+                        "{} {}",
+                        "+".bright_green(),
+                        format_address(address, pointer_size)
                     );
-                } else if opts.verbose > 1 {
-                    let image_name = image_file
-                        .rsplit(['\\', '/'].as_ref())
-                        .next()
-                        .unwrap_or(image_file);
+                } else {
                     println!(
-                        "{}   {:016x} - {:016x} ({} KiB) \t{}{}{}",
-                        "[skip]".bright_yellow(),
-                        region.base_address,
-                        region.base_address + region.size,
-                        region.size / 1024,
-                        "[".magenta(),
-                        image_name.magenta(),
-                        "]".magenta()
+                        "{} {}",
+                        "-".bright_red(),
+                        format_address(address, pointer_size)
                     );
                 }
-                continue;
             }
+            println!(
+                "{} {} added, {} removed, {} persisted",
+                "[done]".bright_cyan(),
+                delta.added.len().to_string().bright_green(),
+                delta.removed.len().to_string().bright_red(),
+                delta.persisted.len()
+            );
         }
-
-        if opts.verbose > 1 {
+        OutputFormat::Jsonl => {
+            for (address, change) in changes {
+                println!(
+                    "{{\"address\":\"0x{:016x}\",\"change\":\"{}\"}}",
+                    address, change
+                );
+            }
             println!(
-                "{} {:016x} - {:016x} ({} KiB) \t[{}, {}, {}, {}]",
+                "{{\"added\":{},\"removed\":{},\"persisted\":{}}}",
+                delta.added.len(),
+                delta.removed.len(),
+                delta.persisted.len()
+            );
+        }
+        OutputFormat::Csv => {
+            println!("address,change");
+            for (address, change) in changes {
+                println!("0x{:016x},{}", address, change);
+            }
+        }
+    }
+}
+
+/// Call `f` with the region and offset of every match across all mapped
+/// regions, stopping once `opts.max_matches` is reached.
+///
+/// Sets `report.truncated` if further matches were left unvisited, or
+/// `report.timed_out` or `report.cancelled` if the scan was stopped first
+/// (see [`scan_stopped`]), and returns the number of matches visited.
+fn visit_matches<F>(
+    memory_mapper: &MemoryMapper,
+    pattern: &[u8],
+    opts: &ScanOptions,
+    deadline: Option<Instant>,
+    report: &mut ScanReport,
+    mut f: F,
+) -> usize
+where
+    F: FnMut(&MappedMemory, usize),
+{
+    let Ok(matches_found) = try_visit_matches(
+        memory_mapper,
+        pattern,
+        opts,
+        deadline,
+        report,
+        |mapped, offset| {
+            f(mapped, offset);
+            Ok::<_, Infallible>(())
+        },
+    );
+    matches_found
+}
+
+/// Like [`visit_matches`], but stop at the first error returned by `f`
+fn try_visit_matches<F, E>(
+    memory_mapper: &MemoryMapper,
+    pattern: &[u8],
+    opts: &ScanOptions,
+    deadline: Option<Instant>,
+    report: &mut ScanReport,
+    mut f: F,
+) -> std::result::Result<usize, E>
+where
+    F: FnMut(&MappedMemory, usize) -> std::result::Result<(), E>,
+{
+    let limit = opts.max_matches.unwrap_or(usize::MAX);
+    let mut matches_found = 0usize;
+    for mapped in memory_mapper {
+        if scan_stopped(opts, deadline, report) {
+            break;
+        }
+        for offset in mapped_match_offsets(mapped, pattern, opts.pattern_mask.as_deref()) {
+            if matches_found == limit {
+                report.truncated = true;
+                return Ok(matches_found);
+            }
+            f(mapped, offset)?;
+            matches_found += 1;
+        }
+        opts.report_progress(ScanProgress {
+            current_address: mapped.remote_region.base_address,
+            regions_scanned: report.regions_scanned,
+            matches_found,
+        });
+    }
+    Ok(matches_found)
+}
+
+/// Whether the scan budget ending at `deadline` is spent or `opts.cancel`
+/// is set, which is checked at region boundaries and recorded in
+/// `report.timed_out` or `report.cancelled`
+fn scan_stopped(opts: &ScanOptions, deadline: Option<Instant>, report: &mut ScanReport) -> bool {
+    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        report.timed_out = true;
+    }
+    if opts
+        .cancel
+        .as_ref()
+        .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    {
+        report.cancelled = true;
+    }
+    report.timed_out || report.cancelled
+}
+
+/// Map all regions that should be scanned, recording mapped and failed
+/// regions in `report` and logging them to `out`.
+fn map_scan_regions<'a>(
+    proc: &'a ProcessHandle,
+    sys: &SystemInfo,
+    opts: &ScanOptions,
+    modules: &[MemoryRegion],
+    deadline: Option<Instant>,
+    report: &mut ScanReport,
+    out: &mut dyn Write,
+) -> io::Result<MemoryMapper<'a>> {
+    // Region logs would corrupt structured output
+    let verbose = match opts.output {
+        OutputFormat::Human => opts.verbose,
+        _ => 0,
+    };
+    let pointer_size = proc.pointer_size();
+    let mut memory_mapper = MemoryMapper::new(proc);
+    memory_mapper.set_use_memmap(opts.use_memmap);
+    memory_mapper.set_read_chunk(opts.read_chunk);
+    let mut selected = Vec::new();
+    let regions = MemoryRegionIterator::new_with_filter(proc, sys, opts.region_filter.clone())
+        .annotated(modules);
+    for (region, current_module) in regions {
+        if scan_stopped(opts, deadline, report) {
+            break;
+        }
+        let current_module_file = current_module.and_then(|ign| ign.image_file.as_deref());
+        let current_module_name =
+            current_module_file.map(|f| f.rsplit(['\\', '/'].as_ref()).next().unwrap_or(f));
+
+        if !opts.all_modules
+            && let Some(ign) = current_module
+        {
+            let image_file = ign.image_file.as_deref().unwrap_or("unknown");
+            if verbose > 2 {
+                writeln!(
+                    out,
+                    "{}   {} - {} ({}) \t{}{}{}",
+                    "[skip]".bright_yellow(),
+                    format_address(region.base_address, pointer_size),
+                    format_address(region.base_address + region.size, pointer_size),
+                    format_size(region.size),
+                    "[".magenta(),
+                    image_file.magenta(),
+                    "]".magenta()
+                )?;
+            } else if verbose > 1 {
+                let image_name = image_file
+                    .rsplit(['\\', '/'].as_ref())
+                    .next()
+                    .unwrap_or(image_file);
+                writeln!(
+                    out,
+                    "{}   {} - {} ({}) \t{}{}{}",
+                    "[skip]".bright_yellow(),
+                    format_address(region.base_address, pointer_size),
+                    format_address(region.base_address + region.size, pointer_size),
+                    format_size(region.size),
+                    "[".magenta(),
+                    image_name.magenta(),
+                    "]".magenta()
+                )?;
+            }
+            continue;
+        }
+
+        if let Some(reason) = opts.skip_reason(&region) {
+            if verbose > 1 {
+                writeln!(
+                    out,
+                    "{}   {} - {} ({}) \t({})",
+                    "[skip]".bright_yellow(),
+                    format_address(region.base_address, pointer_size),
+                    format_address(region.base_address + region.size, pointer_size),
+                    format_size(region.size),
+                    reason
+                )?;
+            }
+            continue;
+        }
+
+        if verbose > 1 {
+            writeln!(
+                out,
+                "{} {} - {} ({}) \t[{}, {}, {}, {}]",
                 "[region]".bright_blue(),
-                region.base_address,
-                region.base_address + region.size,
-                region.size / 1024,
+                format_address(region.base_address, pointer_size),
+                format_address(region.base_address + region.size, pointer_size),
+                format_size(region.size),
                 region.type_.green(),
                 region.state.green(),
                 region.protect.green(),
                 current_module_name.unwrap_or("unknown").magenta()
-            );
-        } else if opts.verbose > 0 {
-            println!(
-                "{} {:016x} - {:016x} ({} KiB)",
+            )?;
+        } else if verbose > 0 {
+            writeln!(
+                out,
+                "{} {} - {} ({})",
                 "[region]".bright_blue(),
-                region.base_address,
-                region.base_address + region.size,
-                region.size / 1024
-            );
+                format_address(region.base_address, pointer_size),
+                format_address(region.base_address + region.size, pointer_size),
+                format_size(region.size)
+            )?;
         }
 
-        total_regions += 1;
-        total_bytes += region.size;
+        selected.push(region);
+    }
+
+    // Adjacent regions with the same attributes are mapped with one call
+    let regions = if opts.use_memmap {
+        let merged = merge_adjacent(&selected);
+        if verbose > 0 && merged.len() < selected.len() {
+            writeln!(
+                out,
+                "{} merged {} adjacent regions into {}",
+                "[info]".bright_cyan(),
+                selected.len(),
+                merged.len()
+            )?;
+        }
+        merged
+    } else {
+        selected
+    };
+    for region in regions {
+        if scan_stopped(opts, deadline, report) {
+            break;
+        }
         let region_base_addr = region.base_address;
-        if let Err(err) = memory_mapper.map_region(region) {
-            if opts.verbose > 0 {
-                println!(
-                    "{} memory mapping failed for region {:016x}: {}",
-                    "[warn]".yellow(),
-                    region_base_addr,
-                    err
-                );
+        let region_size = region.size;
+        match memory_mapper.map_region(region) {
+            Ok(mapped) => {
+                let unreadable = mapped.unreadable_bytes();
+                report.regions_scanned += 1;
+                report.bytes_scanned += region_size - unreadable;
+                if unreadable > 0 {
+                    report.regions_partial += 1;
+                    report.bytes_failed += unreadable;
+                    if verbose > 0 {
+                        writeln!(
+                            out,
+                            "{} region {} is partially unreadable, ~{} skipped",
+                            "[warn]".yellow(),
+                            format_address(region_base_addr, pointer_size),
+                            format_size(unreadable)
+                        )?;
+                    }
+                }
+            }
+            Err(err) => {
+                report.regions_failed += 1;
+                report.bytes_failed += region_size;
+                if verbose > 0 {
+                    writeln!(
+                        out,
+                        "{} memory mapping failed for region {}: {}",
+                        "[warn]".yellow(),
+                        format_address(region_base_addr, pointer_size),
+                        err
+                    )?;
+                }
             }
         }
+        opts.report_progress(ScanProgress {
+            current_address: region_base_addr,
+            regions_scanned: report.regions_scanned,
+            matches_found: 0,
+        });
     }
+    Ok(memory_mapper)
+}
 
-    println!(
-        "{} mapped {} regions, ~{} KiB",
-        "[info]".bright_cyan(),
-        total_regions,
-        total_bytes / 1024,
-    );
+/// Find the addresses of all occurrences of `pattern` in a mapped region.
+pub fn find_matches(mapped: &MappedMemory, pattern: &[u8]) -> Vec<usize> {
+    let base_address = mapped.remote_region.base_address;
+    mapped_match_offsets(mapped, pattern, None)
+        .map(|offset| base_address + offset)
+        .collect()
+}
 
-    // Now scan all mapped regions
-    for mapped in memory_mapper.into_iter() {
-        total_bytes += mapped.remote_region.size;
-        let matches = scan_region(&mapped, pattern, opts)?;
-        matches_found += matches;
+pub fn scan_region(mapped: &MappedMemory, pattern: &[u8], opts: &ScanOptions) -> Result<usize> {
+    opts.check_pattern_mask(pattern)?;
+    let mut matches_found = 0usize;
+    let haystack = mapped.data();
+    // Without a process handle assume the target has memscan's word size
+    let arch = if cfg!(target_pointer_width = "64") {
+        Arch::X64
+    } else {
+        Arch::X86
+    };
+    let mut out = io::stdout().lock();
+    for match_offset in mapped_match_offsets(mapped, pattern, opts.pattern_mask.as_deref()) {
+        let match_address = mapped.remote_region.base_address + match_offset;
+        print_match_context(
+            &mut out,
+            match_address,
+            None,
+            haystack,
+            match_offset..match_offset + pattern.len(),
+            arch,
+            opts,
+        )?;
+        matches_found += 1;
     }
+    Ok(matches_found)
+}
 
-    println!(
-        "{} scanned {} regions, ~{} KiB, {} matches",
-        "[done]".bright_cyan(),
-        total_regions,
-        total_bytes / 1024,
-        matches_found,
-    );
+/// Iterate over the offsets of all occurrences of `pattern` in the readable
+/// parts of a mapped region, skipping zero-filled unreadable pages.
+fn mapped_match_offsets<'h>(
+    mapped: &'h MappedMemory,
+    pattern: &'h [u8],
+    mask: Option<&'h [bool]>,
+) -> impl Iterator<Item = usize> + 'h {
+    let haystack = mapped.data();
+    mapped.valid_ranges().into_iter().flat_map(move |range| {
+        let start = range.start;
+        match_offsets(&haystack[range], pattern, mask).map(move |offset| start + offset)
+    })
+}
+
+/// Iterate over the offsets of all (possibly overlapping) occurrences of
+/// `pattern`, comparing only the bytes selected by `mask` if given.
+///
+/// A mask that doesn't fit the pattern never matches.
+fn match_offsets<'h>(
+    haystack: &'h [u8],
+    pattern: &'h [u8],
+    mask: Option<&'h [bool]>,
+) -> impl Iterator<Item = usize> + 'h {
+    let mut prev_off = 0;
+    std::iter::from_fn(move || {
+        if prev_off >= haystack.len() {
+            return None;
+        }
+        let rest = &haystack[prev_off..];
+        let rel_off = match mask {
+            Some(mask) => search_masked(rest, pattern, mask).ok().flatten(),
+            None => optimized_search(rest, pattern),
+        }?;
+        let match_offset = prev_off + rel_off;
+        prev_off = match_offset + 1; // continue searching after this match
+        Some(match_offset)
+    })
+}
+
+/// Write a single match as a JSON or CSV record with its module-relative offset
+fn print_match_record(
+    out: &mut dyn Write,
+    address: usize,
+    modules: &[MemoryRegion],
+    symbol: Option<&str>,
+    output: OutputFormat,
+) -> io::Result<()> {
+    let module = modules
+        .iter()
+        .find(|m| address >= m.base_address && address < m.base_address.saturating_add(m.size));
+    let module_name = module
+        .and_then(|m| m.image_file.as_deref())
+        .map(|f| f.rsplit(['\\', '/'].as_ref()).next().unwrap_or(f));
+    let offset = module.map(|m| address - m.base_address);
 
+    match output {
+        OutputFormat::Jsonl => writeln!(
+            out,
+            "{{\"address\":\"0x{:016x}\",\"module\":{},\"offset\":{},\"symbol\":{}}}",
+            address,
+            module_name.map_or("null".to_string(), json_string),
+            offset.map_or("null".to_string(), |o| o.to_string()),
+            symbol.map_or("null".to_string(), json_string),
+        )?,
+        OutputFormat::Csv => writeln!(
+            out,
+            "0x{:016x},{},{}",
+            address,
+            module_name.map_or(String::new(), csv_field),
+            offset.map_or(String::new(), |o| o.to_string()),
+        )?,
+        OutputFormat::Human => {}
+    }
     Ok(())
 }
 
-pub fn scan_region(mapped: &MappedMemory, pattern: &[u8], opts: &ScanOptions) -> Result<usize> {
-    let mut matches_found = 0usize;
-    let mut prev_off = 0;
-    let haystack = mapped.data();
-    while prev_off < haystack.len() {
-        if let Some(rel_off) = optimized_search(&haystack[prev_off..], pattern) {
-            let match_address = mapped.remote_region.base_address + prev_off + rel_off;
-            print_match_context(match_address, haystack, pattern, prev_off, rel_off, opts);
-            matches_found += 1;
+/// Write the final scan summary as a JSON object.
+///
+/// For CSV output the summary goes to stderr so `out` stays valid CSV.
+fn print_summary_record(
+    out: &mut dyn Write,
+    report: &ScanReport,
+    matches_found: usize,
+    output: OutputFormat,
+) -> io::Result<()> {
+    let summary = format!(
+        "{{\"regions_scanned\":{},\"bytes_scanned\":{},\"regions_failed\":{},\"regions_partial\":{},\"bytes_failed\":{},\"matches\":{},\"truncated\":{},\"timed_out\":{},\"cancelled\":{},\"elapsed_secs\":{:.6},\"mib_per_sec\":{:.3}}}",
+        report.regions_scanned,
+        report.bytes_scanned,
+        report.regions_failed,
+        report.regions_partial,
+        report.bytes_failed,
+        matches_found,
+        report.truncated,
+        report.timed_out,
+        report.cancelled,
+        report.elapsed.as_secs_f64(),
+        report.mib_per_sec(),
+    );
+    match output {
+        OutputFormat::Csv => eprintln!("{}", summary),
+        _ => writeln!(out, "{}", summary)?,
+    }
+    Ok(())
+}
 
-            prev_off += rel_off + 1; // continue searching after this match
-        } else {
-            break;
+/// Quote and escape a string as a JSON string literal
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
     }
-    Ok(matches_found)
+    out.push('"');
+    out
+}
+
+/// Quote a CSV field if it contains separators or quotes
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
 }
 
+/// Write a match at `memory_slice[matched]`, located at `abs_addr` in the
+/// target, with the context `opts` asks for
 fn print_match_context(
+    out: &mut dyn Write,
     abs_addr: usize,
+    symbol: Option<&str>,
     memory_slice: &[u8],
-    pattern: &[u8],
-    prev_off: usize,
-    rel_off: usize,
+    matched: Range<usize>,
+    arch: Arch,
     opts: &ScanOptions,
-) {
-    println!("{}  {:016x}", "[match]".bright_green(), abs_addr);
+) -> io::Result<()> {
+    let address = format_address(abs_addr, arch.pointer_size());
+    match symbol {
+        Some(symbol) => writeln!(
+            out,
+            "{}  {}  {}",
+            "[match]".bright_green(),
+            address,
+            symbol.magenta()
+        )?,
+        None => writeln!(out, "{}  {}", "[match]".bright_green(), address)?,
+    }
     if opts.verbose > 0 {
         // Display surrounding bytes and highlight match
         const CONTEXT_BYTES: usize = 8;
-        let match_offset = prev_off + rel_off;
-        let start = match_offset.saturating_sub(CONTEXT_BYTES);
-        let end = std::cmp::min(
-            match_offset + pattern.len() + CONTEXT_BYTES,
-            memory_slice.len(),
-        );
-        print!("{}", " ... ".bright_black());
-        let mut i = start;
-        while i < end {
-            if i == match_offset {
-                // Highlight match
-                for b in &memory_slice[i..i + pattern.len()] {
-                    print!("{}", format!("{:02x} ", b).bright_green().bold());
-                }
-                i += pattern.len();
-            } else {
-                print!("{}", format!("{:02x} ", memory_slice[i]).bright_black());
-                i += 1;
-            }
+        writeln!(
+            out,
+            "{}",
+            format_hexdump(memory_slice, matched.clone(), CONTEXT_BYTES)
+        )?;
+    }
+    if opts.disasm {
+        // Decode from the match itself, which code patterns usually start
+        const DISASM_BYTES: usize = 32;
+        let end = memory_slice
+            .len()
+            .min(matched.start + matched.len().max(DISASM_BYTES));
+        for line in disassemble(&memory_slice[matched.start..end], abs_addr, arch, abs_addr) {
+            writeln!(out, "{}", line.bright_black())?;
+        }
+    }
+    Ok(())
+}
+
+/// Format `bytes[highlight]` plus up to `context` bytes on either side as a
+/// single line of hex, with the highlighted bytes in bold green.
+///
+/// The context is trimmed at the edges of `bytes`, and `highlight` is
+/// clamped to the buffer.
+pub fn format_hexdump(bytes: &[u8], highlight: Range<usize>, context: usize) -> String {
+    let highlight_end = highlight.end.min(bytes.len());
+    let highlight_start = highlight.start.min(highlight_end);
+    let start = highlight_start.saturating_sub(context);
+    let end = highlight_end.saturating_add(context).min(bytes.len());
+
+    let mut out = format!("{}", " ... ".bright_black());
+    for (i, b) in bytes.iter().enumerate().take(end).skip(start) {
+        let hex = format!("{:02x} ", b);
+        if (highlight_start..highlight_end).contains(&i) {
+            out.push_str(&format!("{}", hex.bright_green().bold()));
+        } else {
+            out.push_str(&format!("{}", hex.bright_black()));
         }
-        println!("{}", " ... ".bright_black());
     }
+    out.push_str(&format!("{}", " ... ".bright_black()));
+    out
 }
 
 /// Very simple O(n*m) pattern matcher sufficient for now.
@@ -205,6 +1334,56 @@ pub fn optimized_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     memmem::find(haystack, needle)
 }
 
+/// Offsets of all (possibly overlapping) occurrences of `needle` in
+/// `haystack`, in ascending order
+pub fn search_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    match_offsets(haystack, needle, None).collect()
+}
+
+/// Offset of the first occurrence of `needle` in `haystack`, where only the
+/// bytes whose `mask` entry is set have to match
+///
+/// Unmasked bytes are wildcards, e.g. for signatures like `48 8B 05 ?? ?? ??
+/// ??` whose operands change between builds. An empty needle never matches
+/// and one without any masked byte matches at the start.
+pub fn search_masked(haystack: &[u8], needle: &[u8], mask: &[bool]) -> Result<Option<usize>> {
+    if needle.len() != mask.len() {
+        bail!(
+            InvalidPattern,
+            "Mask has {} entries but the needle is {} bytes",
+            mask.len(),
+            needle.len()
+        );
+    }
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Ok(None);
+    }
+    let Some(anchor) = mask.iter().position(|&masked| masked) else {
+        return Ok(Some(0));
+    };
+
+    // Jump between occurrences of the first masked byte, then check the rest
+    let last_start = haystack.len() - needle.len();
+    let mut start = 0;
+    while start <= last_start {
+        let window = &haystack[start + anchor..=last_start + anchor];
+        let Some(found) = memchr::memchr(needle[anchor], window) else {
+            break;
+        };
+        let candidate = start + found;
+        let matches = needle
+            .iter()
+            .zip(mask)
+            .zip(&haystack[candidate..])
+            .all(|((expected, &masked), actual)| !masked || expected == actual);
+        if matches {
+            return Ok(Some(candidate));
+        }
+        start = candidate + 1;
+    }
+    Ok(None)
+}
+
 // no extra helpers needed on UNIX; we call ProcessHandleUnix::read_mem directly
 
 #[cfg(test)]
@@ -246,6 +1425,60 @@ mod tests {
         assert_eq!(naive_search(haystack, needle), Some(0));
     }
 
+    #[test]
+    fn test_search_all_overlapping() {
+        assert_eq!(search_all(b"aaaa", b"aa"), vec![0, 1, 2]);
+        assert_eq!(search_all(b"hello world", b"o"), vec![4, 7]);
+        assert!(search_all(b"hello", b"").is_empty());
+        assert!(search_all(b"hi", b"hello").is_empty());
+    }
+
+    #[test]
+    fn test_search_masked() {
+        let haystack = b"\x48\x8b\x05\x10\x20\x48\x8b\x0d\x30\x40";
+        let needle = [0x48, 0x8b, 0x0d, 0x00, 0x00];
+        let mask = [true, true, true, false, false];
+        assert_eq!(search_masked(haystack, &needle, &mask).unwrap(), Some(5));
+
+        // Wildcards in front of the first masked byte
+        let mask = [false, true, true, false, false];
+        assert_eq!(
+            search_masked(haystack, &[0, 0x8b, 0x05, 0, 0], &mask).unwrap(),
+            Some(0)
+        );
+        // A match that would run past the end doesn't count
+        let mask = [true, false];
+        assert_eq!(
+            search_masked(&haystack[1..6], &[0x48, 0], &mask).unwrap(),
+            None
+        );
+
+        assert_eq!(
+            search_masked(haystack, &[1, 2], &[false; 2]).unwrap(),
+            Some(0)
+        );
+        assert_eq!(search_masked(haystack, &[], &[]).unwrap(), None);
+        assert!(search_masked(haystack, &[1, 2], &[true]).is_err());
+    }
+
+    #[test]
+    fn test_match_offsets_masked() {
+        let haystack = b"\xe8\x01\x02\x90\xe8\x03\x04\x90\xe8\x05\x06\xcc";
+        let (pattern, mask) = crate::parse_ida_signature("E8 ? ? 90").unwrap();
+        let offsets: Vec<usize> = match_offsets(haystack, &pattern, Some(&mask)).collect();
+        assert_eq!(offsets, vec![0, 4]);
+        // Without the mask the wildcard bytes have to be zero
+        assert_eq!(match_offsets(haystack, &pattern, None).count(), 0);
+        // A mask of the wrong length never matches
+        assert_eq!(match_offsets(haystack, &pattern, Some(&[true])).count(), 0);
+
+        let opts = ScanOptions {
+            pattern_mask: Some(vec![true]),
+            ..Default::default()
+        };
+        assert!(opts.check_pattern_mask(&pattern).is_err());
+    }
+
     #[test]
     fn test_optimized_search_found() {
         let haystack = b"hello world";
@@ -281,6 +1514,330 @@ mod tests {
         assert_eq!(optimized_search(haystack, needle), Some(0));
     }
 
+    #[test]
+    fn test_match_offsets_overlapping() {
+        let offsets: Vec<usize> = match_offsets(b"aaaa", b"aa", None).collect();
+        assert_eq!(offsets, vec![0, 1, 2]);
+        assert_eq!(match_offsets(b"hello", b"", None).count(), 0);
+    }
+
+    fn buffer_mapper<'a>(buffers: &[(usize, &[u8])]) -> MemoryMapper<'a> {
+        let mut mapper = MemoryMapper::empty();
+        for &(base, data) in buffers {
+            let region = MemoryRegion::private(base, data.len());
+            mapper.insert(MappedMemory::from_buffer(region, data.to_vec()));
+        }
+        mapper
+    }
+
+    #[test]
+    fn test_skip_reason_protection_filters() {
+        let mapper = buffer_mapper(&[(0x1000, &[0u8; 16])]);
+        let mut region = mapper.get(0x1000).unwrap().remote_region.clone();
+        region.protect.write = false;
+        assert_eq!(ScanOptions::default().skip_reason(&region), None);
+
+        let writable_only = ScanOptions {
+            only_writable: true,
+            ..Default::default()
+        };
+        assert_eq!(writable_only.skip_reason(&region), Some("not writable"));
+        region.protect.copy_on_write = true;
+        assert_eq!(writable_only.skip_reason(&region), None);
+
+        let no_exec = ScanOptions {
+            skip_executable: true,
+            ..Default::default()
+        };
+        assert_eq!(no_exec.skip_reason(&region), None);
+        region.protect.execute = true;
+        assert_eq!(no_exec.skip_reason(&region), Some("executable"));
+    }
+
+    #[test]
+    fn test_skip_reason_region_types() {
+        let mapper = buffer_mapper(&[(0x1000, &[0u8; 16])]);
+        let mut region = mapper.get(0x1000).unwrap().remote_region.clone();
+        let private_only = ScanOptions {
+            include_mapped: false,
+            include_image: false,
+            ..Default::default()
+        };
+        assert_eq!(private_only.skip_reason(&region), None);
+
+        region.type_ = MemoryType::Mapped;
+        assert_eq!(private_only.skip_reason(&region), Some("mapped"));
+        assert_eq!(ScanOptions::default().skip_reason(&region), None);
+
+        region.type_ = MemoryType::Image;
+        assert_eq!(private_only.skip_reason(&region), Some("image"));
+        assert_eq!(ScanOptions::default().skip_reason(&region), None);
+
+        region.state.committed = false;
+        assert_eq!(
+            ScanOptions::default().skip_reason(&region),
+            Some("not committed")
+        );
+    }
+
+    #[test]
+    fn test_skip_reason_size_thresholds() {
+        let mapper = buffer_mapper(&[(0x1000, &[0u8; 0x100])]);
+        let region = &mapper.get(0x1000).unwrap().remote_region;
+
+        let bounded = |min_region_size, max_region_size| ScanOptions {
+            min_region_size,
+            max_region_size,
+            ..Default::default()
+        };
+        assert_eq!(bounded(Some(0x100), Some(0x100)).skip_reason(region), None);
+        assert_eq!(
+            bounded(Some(0x101), None).skip_reason(region),
+            Some("too small")
+        );
+        assert_eq!(
+            bounded(None, Some(0xff)).skip_reason(region),
+            Some("too large")
+        );
+    }
+
+    #[test]
+    fn test_skip_reason_stack_pointers() {
+        let mapper = buffer_mapper(&[(0x1000, &[0u8; 0x100])]);
+        let region = &mapper.get(0x1000).unwrap().remote_region;
+
+        let stacks = |stack_pointers: &[usize]| ScanOptions {
+            stack_pointers: Some(stack_pointers.to_vec()),
+            ..Default::default()
+        };
+        assert_eq!(stacks(&[0x5000, 0x10f8]).skip_reason(region), None);
+        assert_eq!(stacks(&[0x1100]).skip_reason(region), Some("not a stack"));
+        assert_eq!(stacks(&[]).skip_reason(region), Some("not a stack"));
+    }
+
+    #[test]
+    fn test_visit_matches_max_matches() {
+        let mapper = buffer_mapper(&[(0x1000, b"\xC3\x90\xC3\xC3"), (0x2000, b"\xC3\xC3")]);
+        let visit = |max_matches| {
+            let opts = ScanOptions {
+                max_matches,
+                ..Default::default()
+            };
+            let mut report = ScanReport::default();
+            let mut addresses = Vec::new();
+            let found = visit_matches(
+                &mapper,
+                &[0xC3],
+                &opts,
+                None,
+                &mut report,
+                |mapped, offset| addresses.push(mapped.remote_region.base_address + offset),
+            );
+            assert_eq!(found, addresses.len());
+            (found, report.truncated)
+        };
+
+        assert_eq!(visit(None), (5, false));
+        assert_eq!(visit(Some(5)), (5, false));
+        assert_eq!(visit(Some(2)), (2, true));
+        assert_eq!(visit(Some(0)), (0, true));
+    }
+
+    #[test]
+    fn test_patch_targets_skip_overlaps() {
+        assert_eq!(
+            patch_targets(&[0x1003, 0x1000, 0x1001, 0x1004, 0x1000], 2),
+            [0x1000, 0x1003]
+        );
+        assert_eq!(patch_targets(&[0x10, 0x11, 0x12], 1), [0x10, 0x11, 0x12]);
+        assert!(patch_targets(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn test_visit_matches_reports_progress() {
+        #[derive(Default)]
+        struct Recorder(std::sync::Mutex<Vec<ScanProgress>>);
+
+        impl ProgressReporter for Recorder {
+            fn update(&self, progress: &ScanProgress) {
+                self.0.lock().unwrap().push(*progress);
+            }
+        }
+
+        let mapper = buffer_mapper(&[(0x1000, b"\xC3\x90\xC3"), (0x2000, b"\xC3")]);
+        let recorder = Arc::new(Recorder::default());
+        let opts = ScanOptions {
+            progress: Some(recorder.clone()),
+            ..Default::default()
+        };
+        let mut report = ScanReport {
+            regions_scanned: 2,
+            ..Default::default()
+        };
+        visit_matches(&mapper, &[0xC3], &opts, None, &mut report, |_, _| {});
+
+        let updates = recorder.0.lock().unwrap();
+        let seen: Vec<(usize, usize)> = updates
+            .iter()
+            .map(|p| (p.current_address, p.matches_found))
+            .collect();
+        assert_eq!(seen, [(0x1000, 2), (0x2000, 3)]);
+        assert!(updates.iter().all(|p| p.regions_scanned == 2));
+    }
+
+    #[test]
+    fn test_visit_matches_deadline() {
+        let mapper = buffer_mapper(&[(0x1000, b"\xC3\xC3"), (0x2000, b"\xC3")]);
+        let opts = ScanOptions::default();
+
+        let mut report = ScanReport::default();
+        let deadline = Instant::now() + Duration::from_secs(3600);
+        let found = visit_matches(
+            &mapper,
+            &[0xC3],
+            &opts,
+            Some(deadline),
+            &mut report,
+            |_, _| {},
+        );
+        assert_eq!((found, report.timed_out), (3, false));
+
+        let mut report = ScanReport::default();
+        let found = visit_matches(
+            &mapper,
+            &[0xC3],
+            &opts,
+            Some(Instant::now()),
+            &mut report,
+            |_, _| {},
+        );
+        assert_eq!((found, report.timed_out), (0, true));
+    }
+
+    #[test]
+    fn test_visit_matches_cancelled() {
+        let mapper = buffer_mapper(&[(0x1000, b"\xC3\xC3"), (0x2000, b"\xC3")]);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let opts = ScanOptions {
+            cancel: Some(cancel.clone()),
+            ..Default::default()
+        };
+
+        let mut report = ScanReport::default();
+        let found = visit_matches(&mapper, &[0xC3], &opts, None, &mut report, |_, _| {
+            // Stops at the next region, not in the middle of this one
+            cancel.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(
+            (found, report.cancelled, report.timed_out),
+            (2, true, false)
+        );
+    }
+
+    /// Strip ANSI color codes so hexdumps can be compared as plain text
+    fn strip_ansi(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_diff_scan_results() {
+        let delta = diff_scan_results(&[0x30, 0x10, 0x20, 0x10], &[0x20, 0x40, 0x30]);
+        assert_eq!(delta.added, vec![0x40]);
+        assert_eq!(delta.removed, vec![0x10]);
+        assert_eq!(delta.persisted, vec![0x20, 0x30]);
+        assert_eq!(diff_scan_results(&[], &[]), ScanDelta::default());
+    }
+
+    #[test]
+    fn test_parse_jsonl_matches() {
+        let text = concat!(
+            "{\"address\":\"0x00000000deadbeef\",\"module\":null,\"offset\":null,\"symbol\":null}\n",
+            "\n",
+            "{\"address\":\"0x0000000000001000\",\"module\":\"a.dll\",\"offset\":16,\"symbol\":null}\n",
+            "{\"regions_scanned\":2,\"matches\":2}\n",
+        );
+        assert_eq!(parse_jsonl_matches(text).unwrap(), vec![0xdeadbeef, 0x1000]);
+
+        let err = parse_jsonl_matches("{}\n{\"address\":\"1000\"}").unwrap_err();
+        assert_eq!(err.to_string(), "invalid address \"1000\" on line 2");
+    }
+
+    #[test]
+    fn test_format_hexdump_highlight() {
+        // The highlight is only visible with colors on, even under NO_COLOR
+        let _colors = crate::color::force_for_test(true);
+        let bytes = [0x00, 0x11, 0x22, 0xde, 0xad, 0x33, 0x44, 0x55];
+        let dump = format_hexdump(&bytes, 3..5, 2);
+        assert_eq!(strip_ansi(&dump), " ... 11 22 de ad 33 44  ... ");
+        let highlighted = format!(
+            "{}{}",
+            "de ".bright_green().bold(),
+            "ad ".bright_green().bold()
+        );
+        assert!(dump.contains(&highlighted));
+        assert!(!dump.contains(&format!("{}", "22 ".bright_green().bold())));
+        assert!(!dump.contains(&format!("{}", "33 ".bright_green().bold())));
+    }
+
+    #[test]
+    fn test_format_hexdump_trims_context_at_edges() {
+        let bytes = [0xaa, 0xbb, 0xcc];
+        assert_eq!(
+            strip_ansi(&format_hexdump(&bytes, 0..1, 8)),
+            " ... aa bb cc  ... "
+        );
+        assert_eq!(
+            strip_ansi(&format_hexdump(&bytes, 2..3, 1)),
+            " ... bb cc  ... "
+        );
+        // Highlight past the end is clamped
+        assert_eq!(
+            strip_ansi(&format_hexdump(&bytes, 2..10, 0)),
+            " ... cc  ... "
+        );
+    }
+
+    #[test]
+    fn test_scan_report_mib_per_sec() {
+        let report = ScanReport {
+            bytes_scanned: 64 * 1024 * 1024,
+            elapsed: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert!((report.mib_per_sec() - 128.0).abs() < 1e-9);
+        assert_eq!(ScanReport::default().mib_per_sec(), 0.0);
+    }
+
+    #[test]
+    fn test_json_string_escaping() {
+        assert_eq!(json_string("foo.dll"), "\"foo.dll\"");
+        assert_eq!(
+            json_string("C:\\Windows\\\"x\"\n"),
+            "\"C:\\\\Windows\\\\\\\"x\\\"\\n\""
+        );
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("libc.so.6"), "libc.so.6");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    }
+
     #[test]
     fn test_both_searches_match() {
         // Ensure both search functions produce the same results
@@ -0,0 +1,298 @@
+//! Export symbol resolution for module-relative results
+//!
+//! Matches inside a loaded module are easier to understand relative to the
+//! nearest exported symbol than as a raw offset. Export tables are parsed
+//! from the PE headers in target memory, so this works for any PE module
+//! mapped into the target (including under Wine). Parsing is only compiled
+//! with the `symbols` feature; without it every lookup returns `None`.
+
+#[cfg(feature = "symbols")]
+use crate::process::read_process_memory;
+use crate::process::{MemoryRegion, ProcessHandle};
+#[cfg(feature = "symbols")]
+use std::collections::HashMap;
+
+/// Upper bound on the number of exports read from a single module, which
+/// guards against garbage headers
+#[cfg(feature = "symbols")]
+const MAX_EXPORTS: usize = 1 << 20;
+
+/// Longest export name that is read from the target
+#[cfg(feature = "symbols")]
+const MAX_NAME_LEN: usize = 512;
+
+/// Named exports of a PE module, sorted by RVA
+#[cfg(feature = "symbols")]
+#[derive(Debug, Clone)]
+pub struct ExportTable {
+    exports: Vec<(usize, String)>,
+}
+
+#[cfg(feature = "symbols")]
+impl ExportTable {
+    /// Read and parse the export table of `module` from target memory
+    pub fn read(proc: &ProcessHandle, module: &MemoryRegion) -> Option<Self> {
+        Self::parse(module.size, |rva, buf| {
+            read_process_memory(proc, module.base_address + rva, buf) == buf.len()
+        })
+    }
+
+    /// Parse an export table through `read(rva, buf)`, which fills `buf` with
+    /// the bytes at `rva` and returns whether that succeeded
+    fn parse(image_size: usize, read: impl Fn(usize, &mut [u8]) -> bool) -> Option<Self> {
+        let read_checked = |rva: usize, buf: &mut [u8]| {
+            rva.checked_add(buf.len())
+                .is_some_and(|end| end <= image_size)
+                && read(rva, buf)
+        };
+        let read_u16 = |rva: usize| {
+            let mut buf = [0u8; 2];
+            read_checked(rva, &mut buf).then(|| u16::from_le_bytes(buf))
+        };
+        let read_u32 = |rva: usize| {
+            let mut buf = [0u8; 4];
+            read_checked(rva, &mut buf).then(|| u32::from_le_bytes(buf) as usize)
+        };
+        let read_u32_array = |rva: usize, count: usize| {
+            let mut buf = vec![0u8; count.checked_mul(4)?];
+            read_checked(rva, &mut buf).then(|| {
+                buf.chunks_exact(4)
+                    .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as usize)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        if read_u16(0)? != 0x5A4D {
+            return None; // "MZ"
+        }
+        let nt_headers = read_u32(0x3C)?;
+        if read_u32(nt_headers)? != 0x0000_4550 {
+            return None; // "PE\0\0"
+        }
+        // The optional header follows the 4-byte signature and 20-byte COFF header
+        let optional_header = nt_headers + 24;
+        let (rva_count_offset, data_dirs_offset) = match read_u16(optional_header)? {
+            0x10B => (92, 96),   // PE32
+            0x20B => (108, 112), // PE32+
+            _ => return None,
+        };
+        if read_u32(optional_header + rva_count_offset)? == 0 {
+            return None;
+        }
+        let export_rva = read_u32(optional_header + data_dirs_offset)?;
+        let export_size = read_u32(optional_header + data_dirs_offset + 4)?;
+        if export_rva == 0 || export_size == 0 {
+            return None;
+        }
+
+        let function_count = read_u32(export_rva + 20)?;
+        let name_count = read_u32(export_rva + 24)?;
+        if function_count > MAX_EXPORTS || name_count > MAX_EXPORTS {
+            return None;
+        }
+        let functions = read_u32_array(read_u32(export_rva + 28)?, function_count)?;
+        let names = read_u32_array(read_u32(export_rva + 32)?, name_count)?;
+        let ordinals_rva = read_u32(export_rva + 36)?;
+
+        let export_range = export_rva..export_rva + export_size;
+        let mut exports = Vec::with_capacity(names.len());
+        for (i, &name_rva) in names.iter().enumerate() {
+            let Some(ordinal) = read_u16(ordinals_rva + i * 2) else {
+                continue;
+            };
+            let Some(&function_rva) = functions.get(ordinal as usize) else {
+                continue;
+            };
+            // Forwarded exports point at a "dll.name" string inside the export directory
+            if function_rva == 0 || export_range.contains(&function_rva) {
+                continue;
+            }
+            if let Some(name) = read_name(name_rva, &read_checked) {
+                exports.push((function_rva, name));
+            }
+        }
+        exports.sort();
+        Some(Self { exports })
+    }
+
+    /// The closest export at or before `rva` and the distance from it
+    pub fn nearest(&self, rva: usize) -> Option<(&str, usize)> {
+        let index = self
+            .exports
+            .partition_point(|&(export_rva, _)| export_rva <= rva);
+        let (export_rva, name) = self.exports.get(index.checked_sub(1)?)?;
+        Some((name.as_str(), rva - export_rva))
+    }
+
+    /// Number of named exports
+    pub fn len(&self) -> usize {
+        self.exports.len()
+    }
+
+    /// Check if the module has no named exports
+    pub fn is_empty(&self) -> bool {
+        self.exports.is_empty()
+    }
+}
+
+/// Read a NUL-terminated export name at `rva`
+#[cfg(feature = "symbols")]
+fn read_name(rva: usize, read: &impl Fn(usize, &mut [u8]) -> bool) -> Option<String> {
+    let mut name = Vec::new();
+    let mut byte = [0u8; 1];
+    while name.len() < MAX_NAME_LEN {
+        if !read(rva + name.len(), &mut byte) {
+            return None;
+        }
+        if byte[0] == 0 {
+            return String::from_utf8(name).ok();
+        }
+        name.push(byte[0]);
+    }
+    None
+}
+
+/// Find the export of `module` closest before `addr`, returning its name
+/// and the distance from it
+#[cfg(feature = "symbols")]
+pub fn resolve_nearest_export(
+    proc: &ProcessHandle,
+    module: &MemoryRegion,
+    addr: usize,
+) -> Option<(String, usize)> {
+    let rva = addr.checked_sub(module.base_address)?;
+    let table = ExportTable::read(proc, module)?;
+    table
+        .nearest(rva)
+        .map(|(name, distance)| (name.to_string(), distance))
+}
+
+/// Resolves addresses to `module!export+0x<offset>`, caching the export
+/// table of every module it has looked at
+pub struct SymbolResolver<'a> {
+    #[cfg_attr(not(feature = "symbols"), allow(dead_code))]
+    proc: &'a ProcessHandle,
+    #[cfg_attr(not(feature = "symbols"), allow(dead_code))]
+    modules: &'a [MemoryRegion],
+    #[cfg(feature = "symbols")]
+    tables: HashMap<usize, Option<ExportTable>>,
+}
+
+impl<'a> SymbolResolver<'a> {
+    /// Create a resolver for addresses inside `modules` of `proc`
+    pub fn new(proc: &'a ProcessHandle, modules: &'a [MemoryRegion]) -> Self {
+        Self {
+            proc,
+            modules,
+            #[cfg(feature = "symbols")]
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Describe `address` relative to the nearest preceding export of the
+    /// module containing it, e.g. `kernel32.dll!CreateFileW+0x1c`
+    #[cfg(feature = "symbols")]
+    pub fn describe(&mut self, address: usize) -> Option<String> {
+        let module = self.modules.iter().find(|m| {
+            address >= m.base_address && address < m.base_address.saturating_add(m.size)
+        })?;
+        let table = self
+            .tables
+            .entry(module.base_address)
+            .or_insert_with(|| ExportTable::read(self.proc, module))
+            .as_ref()?;
+        let (name, distance) = table.nearest(address - module.base_address)?;
+        let module_name = module
+            .image_file
+            .as_deref()
+            .map(|f| f.rsplit(['\\', '/']).next().unwrap_or(f))
+            .unwrap_or("unknown");
+        Some(if distance == 0 {
+            format!("{}!{}", module_name, name)
+        } else {
+            format!("{}!{}+0x{:x}", module_name, name, distance)
+        })
+    }
+
+    /// Describe `address` relative to the nearest export (always `None`
+    /// without the `symbols` feature)
+    #[cfg(not(feature = "symbols"))]
+    pub fn describe(&mut self, _address: usize) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "symbols"))]
+mod tests {
+    use super::*;
+
+    fn put_u16(image: &mut [u8], at: usize, v: u16) {
+        image[at..at + 2].copy_from_slice(&v.to_le_bytes());
+    }
+
+    fn put_u32(image: &mut [u8], at: usize, v: u32) {
+        image[at..at + 4].copy_from_slice(&v.to_le_bytes());
+    }
+
+    /// A minimal PE32+ image exporting Beta at 0x1000, Alpha at 0x1100 and
+    /// a forwarded export Fwd
+    fn synthetic_image() -> Vec<u8> {
+        let mut image = vec![0u8; 0x2000];
+        put_u16(&mut image, 0, 0x5A4D);
+        put_u32(&mut image, 0x3C, 0x80);
+        put_u32(&mut image, 0x80, 0x0000_4550);
+        let optional_header = 0x80 + 24;
+        put_u16(&mut image, optional_header, 0x20B);
+        put_u32(&mut image, optional_header + 108, 16);
+        put_u32(&mut image, optional_header + 112, 0x200);
+        put_u32(&mut image, optional_header + 116, 0x100);
+
+        put_u32(&mut image, 0x200 + 20, 3);
+        put_u32(&mut image, 0x200 + 24, 3);
+        put_u32(&mut image, 0x200 + 28, 0x300);
+        put_u32(&mut image, 0x200 + 32, 0x320);
+        put_u32(&mut image, 0x200 + 36, 0x340);
+        for (i, rva) in [0x1000, 0x1100, 0x250].into_iter().enumerate() {
+            put_u32(&mut image, 0x300 + i * 4, rva);
+        }
+        for (i, (name_rva, ordinal)) in [(0x360, 1), (0x370, 0), (0x380, 2)].into_iter().enumerate()
+        {
+            put_u32(&mut image, 0x320 + i * 4, name_rva);
+            put_u16(&mut image, 0x340 + i * 2, ordinal);
+        }
+        image[0x360..0x366].copy_from_slice(b"Alpha\0");
+        image[0x370..0x375].copy_from_slice(b"Beta\0");
+        image[0x380..0x384].copy_from_slice(b"Fwd\0");
+        image
+    }
+
+    fn parse(image: &[u8]) -> Option<ExportTable> {
+        ExportTable::parse(image.len(), |rva, buf| {
+            buf.copy_from_slice(&image[rva..rva + buf.len()]);
+            true
+        })
+    }
+
+    #[test]
+    fn test_export_table_nearest() {
+        let table = parse(&synthetic_image()).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.nearest(0x1000), Some(("Beta", 0)));
+        assert_eq!(table.nearest(0x1010), Some(("Beta", 0x10)));
+        assert_eq!(table.nearest(0x1100), Some(("Alpha", 0)));
+        assert_eq!(table.nearest(0x1f00), Some(("Alpha", 0xe00)));
+        assert_eq!(table.nearest(0xfff), None);
+    }
+
+    #[test]
+    fn test_export_table_rejects_non_pe() {
+        let mut image = synthetic_image();
+        image[0..4].copy_from_slice(b"\x7fELF");
+        assert!(parse(&image).is_none());
+
+        // Export directory pointing outside the image
+        let mut image = synthetic_image();
+        put_u32(&mut image, 0x80 + 24 + 112, 0x10_0000);
+        assert!(parse(&image).is_none());
+    }
+}
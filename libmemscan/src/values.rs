@@ -3,7 +3,12 @@
 //! This module provides type-safe value handling for different data types
 //! in memory, including conversions, comparisons, and mathematical operations.
 
-use anyhow::Result;
+use crate::error::{MemScanError, Result};
+use std::cmp::Ordering;
+
+/// Significant digits floats are shown with unless asked otherwise, enough
+/// to tell apart neighbouring F32 values without their binary noise
+pub const DEFAULT_FLOAT_PRECISION: usize = 7;
 
 /// Supported value types for filtering
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,6 +21,8 @@ pub enum ValueType {
     U16,
     U32,
     U64,
+    I128,
+    U128,
     F32,
     F64,
 }
@@ -28,12 +35,83 @@ impl ValueType {
             ValueType::I16 | ValueType::U16 => 2,
             ValueType::I32 | ValueType::U32 | ValueType::F32 => 4,
             ValueType::I64 | ValueType::U64 | ValueType::F64 => 8,
+            ValueType::I128 | ValueType::U128 => 16,
+        }
+    }
+
+    /// Lowercase name of this type, e.g. `i32`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueType::I8 => "i8",
+            ValueType::I16 => "i16",
+            ValueType::I32 => "i32",
+            ValueType::I64 => "i64",
+            ValueType::U8 => "u8",
+            ValueType::U16 => "u16",
+            ValueType::U32 => "u32",
+            ValueType::U64 => "u64",
+            ValueType::I128 => "i128",
+            ValueType::U128 => "u128",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+        }
+    }
+
+    /// Parse a type name as returned by [`ValueType::name`], ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "i8" => ValueType::I8,
+            "i16" => ValueType::I16,
+            "i32" => ValueType::I32,
+            "i64" => ValueType::I64,
+            "u8" => ValueType::U8,
+            "u16" => ValueType::U16,
+            "u32" => ValueType::U32,
+            "u64" => ValueType::U64,
+            "i128" => ValueType::I128,
+            "u128" => ValueType::U128,
+            "f32" => ValueType::F32,
+            "f64" => ValueType::F64,
+            _ => return None,
+        })
+    }
+}
+
+/// Byte order multi-byte values are read in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    /// Least significant byte first, as on x86
+    #[default]
+    Little,
+    /// Most significant byte first, e.g. network data or emulated consoles
+    Big,
+}
+
+impl Endianness {
+    /// Short name of this byte order, `le` or `be`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Endianness::Little => "le",
+            Endianness::Big => "be",
+        }
+    }
+
+    /// Parse `le`/`little` or `be`/`big`, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "le" | "little" => Some(Endianness::Little),
+            "be" | "big" => Some(Endianness::Big),
+            _ => None,
         }
     }
 }
 
 /// A value read from memory that can be one of several types
-#[derive(Debug, Clone)]
+///
+/// Values only compare equal or ordered to values of the same type, so
+/// `Value::I32(1) == Value::U32(1)` is false and their `partial_cmp` is
+/// None. Use [`compare_loose`] to compare across types.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     I8(i8),
     I16(i16),
@@ -43,6 +121,8 @@ pub enum Value {
     U16(u16),
     U32(u32),
     U64(u64),
+    I128(i128),
+    U128(u128),
     F32(f32),
     F64(f64),
 }
@@ -53,7 +133,7 @@ impl Value {
         if offset + value_type.size() > bytes.len() {
             return None;
         }
-        
+
         let slice = &bytes[offset..offset + value_type.size()];
         Some(match value_type {
             ValueType::I8 => Value::I8(i8::from_le_bytes([slice[0]])),
@@ -64,11 +144,104 @@ impl Value {
             ValueType::U16 => Value::U16(u16::from_le_bytes(slice.try_into().ok()?)),
             ValueType::U32 => Value::U32(u32::from_le_bytes(slice.try_into().ok()?)),
             ValueType::U64 => Value::U64(u64::from_le_bytes(slice.try_into().ok()?)),
+            ValueType::I128 => Value::I128(i128::from_le_bytes(slice.try_into().ok()?)),
+            ValueType::U128 => Value::U128(u128::from_le_bytes(slice.try_into().ok()?)),
             ValueType::F32 => Value::F32(f32::from_le_bytes(slice.try_into().ok()?)),
             ValueType::F64 => Value::F64(f64::from_le_bytes(slice.try_into().ok()?)),
         })
     }
-    
+
+    /// Read a big-endian value from bytes at the given offset
+    pub fn from_bytes_be(bytes: &[u8], offset: usize, value_type: ValueType) -> Option<Self> {
+        let mut slice = bytes
+            .get(offset..offset.checked_add(value_type.size())?)?
+            .to_vec();
+        slice.reverse();
+        Self::from_bytes(&slice, 0, value_type)
+    }
+
+    /// Read a value in the given byte order from bytes at the given offset
+    pub fn from_bytes_endian(
+        bytes: &[u8],
+        offset: usize,
+        value_type: ValueType,
+        endianness: Endianness,
+    ) -> Option<Self> {
+        match endianness {
+            Endianness::Little => Self::from_bytes(bytes, offset, value_type),
+            Endianness::Big => Self::from_bytes_be(bytes, offset, value_type),
+        }
+    }
+
+    /// Parse a literal as a value of the given type
+    ///
+    /// Integer types also accept `0x`-prefixed hex literals and `true` or
+    /// `false` (1 or 0), and 1-byte types a single-quoted ASCII character
+    /// such as `'A'` (65).
+    pub fn parse_as(s: &str, value_type: ValueType) -> Result<Self> {
+        let s = s.trim();
+        let value = match value_type {
+            ValueType::I8 => parse_integer(s).map(Value::I8),
+            ValueType::I16 => parse_integer(s).map(Value::I16),
+            ValueType::I32 => parse_integer(s).map(Value::I32),
+            ValueType::I64 => parse_integer(s).map(Value::I64),
+            ValueType::U8 => parse_integer(s).map(Value::U8),
+            ValueType::U16 => parse_integer(s).map(Value::U16),
+            ValueType::U32 => parse_integer(s).map(Value::U32),
+            ValueType::U64 => parse_integer(s).map(Value::U64),
+            ValueType::I128 => parse_integer(s).map(Value::I128),
+            ValueType::U128 => parse_integer(s).map(Value::U128),
+            ValueType::F32 => s.parse().ok().map(Value::F32),
+            ValueType::F64 => s.parse().ok().map(Value::F64),
+        };
+        value
+            .or_else(|| parse_char_or_bool(s, value_type))
+            .ok_or_else(|| MemScanError::InvalidValue {
+                value_type,
+                text: s.to_string(),
+            })
+    }
+
+    /// Infer the narrowest type for a literal typed without a value type.
+    ///
+    /// Integers (decimal or `0x` hex) get the smallest unsigned type that can
+    /// hold them (up to `U128`), or the smallest signed type if negative.
+    /// Literals with a decimal point or exponent become `F64`.
+    pub fn infer(s: &str) -> Option<(Value, ValueType)> {
+        let s = s.trim();
+        let value = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            narrowest_unsigned(u128::from_str_radix(hex, 16).ok()?)
+        } else if let Ok(v) = s.parse::<u128>() {
+            narrowest_unsigned(v)
+        } else if let Ok(v) = s.parse::<i128>() {
+            narrowest_signed(v)
+        } else if s.contains(['.', 'e', 'E']) {
+            Value::F64(s.parse().ok()?)
+        } else {
+            return None;
+        };
+        let value_type = value.value_type();
+        Some((value, value_type))
+    }
+
+    /// Get the type of this value
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::I8(_) => ValueType::I8,
+            Value::I16(_) => ValueType::I16,
+            Value::I32(_) => ValueType::I32,
+            Value::I64(_) => ValueType::I64,
+            Value::U8(_) => ValueType::U8,
+            Value::U16(_) => ValueType::U16,
+            Value::U32(_) => ValueType::U32,
+            Value::U64(_) => ValueType::U64,
+            Value::I128(_) => ValueType::I128,
+            Value::U128(_) => ValueType::U128,
+            Value::F32(_) => ValueType::F32,
+            Value::F64(_) => ValueType::F64,
+        }
+    }
+
     /// Convert value to bytes for writing to memory
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
@@ -80,10 +253,131 @@ impl Value {
             Value::U16(v) => v.to_le_bytes().to_vec(),
             Value::U32(v) => v.to_le_bytes().to_vec(),
             Value::U64(v) => v.to_le_bytes().to_vec(),
+            Value::I128(v) => v.to_le_bytes().to_vec(),
+            Value::U128(v) => v.to_le_bytes().to_vec(),
             Value::F32(v) => v.to_le_bytes().to_vec(),
             Value::F64(v) => v.to_le_bytes().to_vec(),
         }
     }
+
+    /// Format the value for display, rounding floats to `precision`
+    /// significant digits, or printing them exactly with `None`
+    ///
+    /// Integers are always printed in full.
+    pub fn format_with(&self, precision: Option<usize>) -> String {
+        match (self, precision) {
+            (Value::F32(v), Some(digits)) => format_significant(*v as f64, digits),
+            (Value::F64(v), Some(digits)) => format_significant(*v, digits),
+            (Value::I8(v), _) => v.to_string(),
+            (Value::I16(v), _) => v.to_string(),
+            (Value::I32(v), _) => v.to_string(),
+            (Value::I64(v), _) => v.to_string(),
+            (Value::U8(v), _) => v.to_string(),
+            (Value::U16(v), _) => v.to_string(),
+            (Value::U32(v), _) => v.to_string(),
+            (Value::U64(v), _) => v.to_string(),
+            (Value::I128(v), _) => v.to_string(),
+            (Value::U128(v), _) => v.to_string(),
+            (Value::F32(v), None) => v.to_string(),
+            (Value::F64(v), None) => v.to_string(),
+        }
+    }
+}
+
+/// Format `v` with `digits` significant digits like C's `%g`, switching to
+/// scientific notation for very large or small magnitudes
+fn format_significant(v: f64, digits: usize) -> String {
+    if !v.is_finite() || v == 0.0 {
+        return v.to_string();
+    }
+    let digits = digits.max(1);
+    let scientific = format!("{:.*e}", digits - 1, v);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    if exponent < -4 || exponent >= digits as i32 {
+        format!("{}e{}", trim_fraction(mantissa), exponent)
+    } else {
+        let decimals = (digits as i32 - 1 - exponent) as usize;
+        trim_fraction(&format!("{:.*}", decimals, v)).to_string()
+    }
+}
+
+/// Strip trailing zeros (and a trailing dot) from a decimal fraction
+fn trim_fraction(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+/// Parse a decimal or `0x` hex integer literal
+fn parse_integer<T>(s: &str) -> Option<T>
+where
+    T: std::str::FromStr + TryFrom<u128>,
+{
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => T::try_from(u128::from_str_radix(hex, 16).ok()?).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// Parse `true`/`false` for integer types and a quoted ASCII character
+/// like `'A'` for 1-byte types
+fn parse_char_or_bool(s: &str, value_type: ValueType) -> Option<Value> {
+    let byte = match s {
+        "true" => 1,
+        "false" => 0,
+        _ if value_type.size() == 1 => {
+            let mut chars = s.strip_prefix('\'')?.strip_suffix('\'')?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii() => c as u8,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(match value_type {
+        ValueType::I8 => Value::I8(byte as i8),
+        ValueType::I16 => Value::I16(byte.into()),
+        ValueType::I32 => Value::I32(byte.into()),
+        ValueType::I64 => Value::I64(byte.into()),
+        ValueType::U8 => Value::U8(byte),
+        ValueType::U16 => Value::U16(byte.into()),
+        ValueType::U32 => Value::U32(byte.into()),
+        ValueType::U64 => Value::U64(byte.into()),
+        ValueType::I128 => Value::I128(byte.into()),
+        ValueType::U128 => Value::U128(byte.into()),
+        ValueType::F32 | ValueType::F64 => return None,
+    })
+}
+
+fn narrowest_unsigned(v: u128) -> Value {
+    if let Ok(v) = u8::try_from(v) {
+        Value::U8(v)
+    } else if let Ok(v) = u16::try_from(v) {
+        Value::U16(v)
+    } else if let Ok(v) = u32::try_from(v) {
+        Value::U32(v)
+    } else if let Ok(v) = u64::try_from(v) {
+        Value::U64(v)
+    } else {
+        Value::U128(v)
+    }
+}
+
+fn narrowest_signed(v: i128) -> Value {
+    if let Ok(v) = i8::try_from(v) {
+        Value::I8(v)
+    } else if let Ok(v) = i16::try_from(v) {
+        Value::I16(v)
+    } else if let Ok(v) = i32::try_from(v) {
+        Value::I32(v)
+    } else if let Ok(v) = i64::try_from(v) {
+        Value::I64(v)
+    } else {
+        Value::I128(v)
+    }
 }
 
 /// Math operations for modifying values
@@ -95,59 +389,55 @@ pub enum MathOp {
     Divide,
 }
 
-/// Compare two values for equality
-pub fn values_equal(a: &Value, b: &Value) -> bool {
-    match (a, b) {
-        (Value::I8(a), Value::I8(b)) => a == b,
-        (Value::I16(a), Value::I16(b)) => a == b,
-        (Value::I32(a), Value::I32(b)) => a == b,
-        (Value::I64(a), Value::I64(b)) => a == b,
-        (Value::U8(a), Value::U8(b)) => a == b,
-        (Value::U16(a), Value::U16(b)) => a == b,
-        (Value::U32(a), Value::U32(b)) => a == b,
-        (Value::U64(a), Value::U64(b)) => a == b,
-        (Value::F32(a), Value::F32(b)) => a == b,
-        (Value::F64(a), Value::F64(b)) => a == b,
-        _ => false,
+impl PartialOrd for Value {
+    /// Order two values of the same type, returning None for values of
+    /// different types or if either side is NaN
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Value::I8(a), Value::I8(b)) => a.partial_cmp(b),
+            (Value::I16(a), Value::I16(b)) => a.partial_cmp(b),
+            (Value::I32(a), Value::I32(b)) => a.partial_cmp(b),
+            (Value::I64(a), Value::I64(b)) => a.partial_cmp(b),
+            (Value::U8(a), Value::U8(b)) => a.partial_cmp(b),
+            (Value::U16(a), Value::U16(b)) => a.partial_cmp(b),
+            (Value::U32(a), Value::U32(b)) => a.partial_cmp(b),
+            (Value::U64(a), Value::U64(b)) => a.partial_cmp(b),
+            (Value::I128(a), Value::I128(b)) => a.partial_cmp(b),
+            (Value::U128(a), Value::U128(b)) => a.partial_cmp(b),
+            (Value::F32(a), Value::F32(b)) => a.partial_cmp(b),
+            (Value::F64(a), Value::F64(b)) => a.partial_cmp(b),
+            _ => None,
+        }
     }
 }
 
-/// Compare if value a is less than value b
+/// Compare two values for equality (false for values of different types)
+pub fn values_equal(a: &Value, b: &Value) -> bool {
+    a == b
+}
+
+/// Compare if value a is less than value b (false for values of different types)
 pub fn value_less_than(a: &Value, b: &Value) -> bool {
-    match (a, b) {
-        (Value::I8(a), Value::I8(b)) => a < b,
-        (Value::I16(a), Value::I16(b)) => a < b,
-        (Value::I32(a), Value::I32(b)) => a < b,
-        (Value::I64(a), Value::I64(b)) => a < b,
-        (Value::U8(a), Value::U8(b)) => a < b,
-        (Value::U16(a), Value::U16(b)) => a < b,
-        (Value::U32(a), Value::U32(b)) => a < b,
-        (Value::U64(a), Value::U64(b)) => a < b,
-        (Value::F32(a), Value::F32(b)) => a < b,
-        (Value::F64(a), Value::F64(b)) => a < b,
-        _ => false,
-    }
+    a < b
 }
 
-/// Compare if value a is greater than value b
+/// Compare if value a is greater than value b (false for values of different types)
 pub fn value_greater_than(a: &Value, b: &Value) -> bool {
-    match (a, b) {
-        (Value::I8(a), Value::I8(b)) => a > b,
-        (Value::I16(a), Value::I16(b)) => a > b,
-        (Value::I32(a), Value::I32(b)) => a > b,
-        (Value::I64(a), Value::I64(b)) => a > b,
-        (Value::U8(a), Value::U8(b)) => a > b,
-        (Value::U16(a), Value::U16(b)) => a > b,
-        (Value::U32(a), Value::U32(b)) => a > b,
-        (Value::U64(a), Value::U64(b)) => a > b,
-        (Value::F32(a), Value::F32(b)) => a > b,
-        (Value::F64(a), Value::F64(b)) => a > b,
-        _ => false,
-    }
+    a > b
 }
 
 /// Apply a math operation to two values
+///
+/// Integer division by zero fails with [`MemScanError::InvalidValue`];
+/// float division follows IEEE 754 and yields an infinity or NaN.
 pub fn apply_math_op(a: &Value, b: &Value, op: MathOp) -> Result<Value> {
+    let float = matches!(b, Value::F32(_) | Value::F64(_));
+    if op == MathOp::Divide && !float && value_to_f64(b) == 0.0 {
+        return Err(MemScanError::InvalidValue {
+            value_type: b.value_type(),
+            text: "division by zero".to_string(),
+        });
+    }
     Ok(match (a, b) {
         (Value::I8(a), Value::I8(b)) => match op {
             MathOp::Add => Value::I8(a.wrapping_add(*b)),
@@ -197,6 +487,18 @@ pub fn apply_math_op(a: &Value, b: &Value, op: MathOp) -> Result<Value> {
             MathOp::Multiply => Value::U64(a.wrapping_mul(*b)),
             MathOp::Divide => Value::U64(a.wrapping_div(*b)),
         },
+        (Value::I128(a), Value::I128(b)) => match op {
+            MathOp::Add => Value::I128(a.wrapping_add(*b)),
+            MathOp::Subtract => Value::I128(a.wrapping_sub(*b)),
+            MathOp::Multiply => Value::I128(a.wrapping_mul(*b)),
+            MathOp::Divide => Value::I128(a.wrapping_div(*b)),
+        },
+        (Value::U128(a), Value::U128(b)) => match op {
+            MathOp::Add => Value::U128(a.wrapping_add(*b)),
+            MathOp::Subtract => Value::U128(a.wrapping_sub(*b)),
+            MathOp::Multiply => Value::U128(a.wrapping_mul(*b)),
+            MathOp::Divide => Value::U128(a.wrapping_div(*b)),
+        },
         (Value::F32(a), Value::F32(b)) => match op {
             MathOp::Add => Value::F32(a + b),
             MathOp::Subtract => Value::F32(a - b),
@@ -209,7 +511,13 @@ pub fn apply_math_op(a: &Value, b: &Value, op: MathOp) -> Result<Value> {
             MathOp::Multiply => Value::F64(a * b),
             MathOp::Divide => Value::F64(a / b),
         },
-        _ => anyhow::bail!("Type mismatch in math operation"),
+        _ => {
+            return Err(MemScanError::TypeMismatch {
+                role: "operand".to_string(),
+                found: b.value_type(),
+                expected: a.value_type(),
+            });
+        }
     })
 }
 
@@ -224,12 +532,36 @@ pub fn value_subtract(a: &Value, b: &Value) -> Option<Value> {
         (Value::U16(a), Value::U16(b)) => Value::U16(a.wrapping_sub(*b)),
         (Value::U32(a), Value::U32(b)) => Value::U32(a.wrapping_sub(*b)),
         (Value::U64(a), Value::U64(b)) => Value::U64(a.wrapping_sub(*b)),
+        (Value::I128(a), Value::I128(b)) => Value::I128(a.wrapping_sub(*b)),
+        (Value::U128(a), Value::U128(b)) => Value::U128(a.wrapping_sub(*b)),
         (Value::F32(a), Value::F32(b)) => Value::F32(a - b),
         (Value::F64(a), Value::F64(b)) => Value::F64(a - b),
         _ => return None,
     })
 }
 
+/// Signed difference `a - b` as f64, returning None if types don't match
+///
+/// Unlike [`value_subtract`] this never wraps, so a decrease of an unsigned
+/// value yields a negative delta. 128-bit deltas are rounded to f64.
+pub fn delta_f64(a: &Value, b: &Value) -> Option<f64> {
+    Some(match (a, b) {
+        (Value::I8(a), Value::I8(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::I16(a), Value::I16(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::I32(a), Value::I32(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::I64(a), Value::I64(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::U8(a), Value::U8(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::U16(a), Value::U16(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::U32(a), Value::U32(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::U64(a), Value::U64(b)) => (*a as i128 - *b as i128) as f64,
+        (Value::I128(a), Value::I128(b)) => *a as f64 - *b as f64,
+        (Value::U128(a), Value::U128(b)) => *a as f64 - *b as f64,
+        (Value::F32(a), Value::F32(b)) => *a as f64 - *b as f64,
+        (Value::F64(a), Value::F64(b)) => a - b,
+        _ => return None,
+    })
+}
+
 /// Convert a value to f64 for comparison purposes
 pub fn value_to_f64(value: &Value) -> f64 {
     match value {
@@ -241,16 +573,119 @@ pub fn value_to_f64(value: &Value) -> f64 {
         Value::U16(v) => *v as f64,
         Value::U32(v) => *v as f64,
         Value::U64(v) => *v as f64,
+        Value::I128(v) => *v as f64,
+        Value::U128(v) => *v as f64,
         Value::F32(v) => *v as f64,
         Value::F64(v) => *v,
     }
 }
 
+/// Widen an integer value to i128, returning None for floats and for
+/// `U128` values above `i128::MAX`
+fn value_to_i128(value: &Value) -> Option<i128> {
+    Some(match value {
+        Value::I8(v) => *v as i128,
+        Value::I16(v) => *v as i128,
+        Value::I32(v) => *v as i128,
+        Value::I64(v) => *v as i128,
+        Value::U8(v) => *v as i128,
+        Value::U16(v) => *v as i128,
+        Value::U32(v) => *v as i128,
+        Value::U64(v) => *v as i128,
+        Value::I128(v) => *v,
+        Value::U128(v) => i128::try_from(*v).ok()?,
+        Value::F32(_) | Value::F64(_) => return None,
+    })
+}
+
+/// Compare two values of possibly different types by promoting both
+///
+/// Two integers are compared exactly as i128. If either side is a float both
+/// are compared as f64, so integers above 2^53 (e.g. large u64 values) may
+/// round to a neighbouring value and compare equal to it. Returns None if
+/// either side is NaN.
+pub fn compare_loose(a: &Value, b: &Value) -> Option<Ordering> {
+    let is_float = |v: &Value| matches!(v, Value::F32(_) | Value::F64(_));
+    match (a, b) {
+        (Value::U128(a), Value::U128(b)) => Some(a.cmp(b)),
+        _ if is_float(a) || is_float(b) => value_to_f64(a).partial_cmp(&value_to_f64(b)),
+        // Only a U128 above i128::MAX doesn't widen, and it is the larger side
+        _ => match (value_to_i128(a), value_to_i128(b)) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            (None, _) => Some(Ordering::Greater),
+            (_, None) => Some(Ordering::Less),
+        },
+    }
+}
+
+/// Convert an f64 to a value of `value_type`, returning None if it is not
+/// exactly representable (fractional or out of range for integer types)
+pub fn value_from_f64(v: f64, value_type: ValueType) -> Option<Value> {
+    if value_type != ValueType::F32 && value_type != ValueType::F64 && v.fract() != 0.0 {
+        return None;
+    }
+    Some(match value_type {
+        ValueType::I8 => Value::I8(i8::try_from(v as i128).ok()?),
+        ValueType::I16 => Value::I16(i16::try_from(v as i128).ok()?),
+        ValueType::I32 => Value::I32(i32::try_from(v as i128).ok()?),
+        ValueType::I64 => Value::I64(i64::try_from(v as i128).ok()?),
+        ValueType::U8 => Value::U8(u8::try_from(v as i128).ok()?),
+        ValueType::U16 => Value::U16(u16::try_from(v as i128).ok()?),
+        ValueType::U32 => Value::U32(u32::try_from(v as i128).ok()?),
+        ValueType::U64 => Value::U64(u64::try_from(v as i128).ok()?),
+        // `as` saturates, so check the range before converting
+        ValueType::I128 => {
+            if v < i128::MIN as f64 || v >= -(i128::MIN as f64) {
+                return None;
+            }
+            Value::I128(v as i128)
+        }
+        ValueType::U128 => {
+            if v < 0.0 || v >= u128::MAX as f64 {
+                return None;
+            }
+            Value::U128(v as u128)
+        }
+        ValueType::F32 => Value::F32(v as f32),
+        ValueType::F64 => Value::F64(v),
+    })
+}
+
+/// Interpret an unsigned 32 or 64-bit value as an address in the target process
+pub fn value_to_address(value: &Value) -> Option<usize> {
+    match value {
+        Value::U32(v) => usize::try_from(*v).ok(),
+        Value::U64(v) => usize::try_from(*v).ok(),
+        _ => None,
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_value_type_name_round_trip() {
+        for ty in [
+            ValueType::I8,
+            ValueType::I16,
+            ValueType::I32,
+            ValueType::I64,
+            ValueType::U8,
+            ValueType::U16,
+            ValueType::U32,
+            ValueType::U64,
+            ValueType::I128,
+            ValueType::U128,
+            ValueType::F32,
+            ValueType::F64,
+        ] {
+            assert_eq!(ValueType::from_name(ty.name()), Some(ty));
+        }
+        assert_eq!(ValueType::from_name("F32"), Some(ValueType::F32));
+        assert_eq!(ValueType::from_name("i256"), None);
+    }
+
     #[test]
     fn test_value_type_size() {
         assert_eq!(ValueType::I8.size(), 1);
@@ -263,6 +698,8 @@ mod tests {
         assert_eq!(ValueType::I64.size(), 8);
         assert_eq!(ValueType::U64.size(), 8);
         assert_eq!(ValueType::F64.size(), 8);
+        assert_eq!(ValueType::I128.size(), 16);
+        assert_eq!(ValueType::U128.size(), 16);
     }
 
     #[test]
@@ -275,6 +712,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_value_from_bytes_be() {
+        let bytes = [0x00, 0x00, 0x01, 0x02, 0xff];
+        assert_eq!(
+            Value::from_bytes_be(&bytes, 0, ValueType::I32),
+            Some(Value::I32(0x0102))
+        );
+        assert_eq!(
+            Value::from_bytes_be(&bytes, 3, ValueType::I16),
+            Some(Value::I16(0x02ff))
+        );
+        assert_eq!(
+            Value::from_bytes_endian(&bytes, 3, ValueType::I16, Endianness::Little),
+            Some(Value::I16(-254))
+        );
+        assert_eq!(Value::from_bytes_be(&bytes, 2, ValueType::I32), None);
+        assert_eq!(
+            Value::from_bytes_be(&bytes, usize::MAX, ValueType::U8),
+            None
+        );
+        assert_eq!(
+            Value::from_bytes_be(&1.5f32.to_be_bytes(), 0, ValueType::F32),
+            Some(Value::F32(1.5))
+        );
+
+        assert_eq!(Endianness::from_name("BE"), Some(Endianness::Big));
+        assert_eq!(Endianness::from_name("little"), Some(Endianness::Little));
+        assert_eq!(Endianness::from_name("middle"), None);
+    }
+
     #[test]
     fn test_value_to_bytes() {
         let val = Value::I32(0x42);
@@ -282,6 +749,40 @@ mod tests {
         assert_eq!(bytes, vec![0x42, 0x00, 0x00, 0x00]);
     }
 
+    #[test]
+    fn test_value_128_bit_round_trip() {
+        let guid = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210u128;
+        let bytes = Value::U128(guid).to_bytes();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(bytes[0], 0x10);
+        assert_eq!(
+            Value::from_bytes(&bytes, 0, ValueType::U128),
+            Some(Value::U128(guid))
+        );
+        assert_eq!(
+            Value::from_bytes(&bytes, 0, ValueType::I128),
+            Some(Value::I128(guid as i128))
+        );
+        assert_eq!(Value::from_bytes(&bytes, 1, ValueType::U128), None);
+
+        assert_eq!(
+            Value::parse_as("0xffffffffffffffffffffffffffffffff", ValueType::U128).unwrap(),
+            Value::U128(u128::MAX)
+        );
+        assert_eq!(
+            Value::parse_as("-170141183460469231731687303715884105728", ValueType::I128).unwrap(),
+            Value::I128(i128::MIN)
+        );
+        assert_eq!(
+            Value::infer("18446744073709551616").unwrap().1,
+            ValueType::U128
+        );
+        assert_eq!(
+            apply_math_op(&Value::U128(u128::MAX), &Value::U128(2), MathOp::Add).unwrap(),
+            Value::U128(1)
+        );
+    }
+
     #[test]
     fn test_values_equal() {
         assert!(values_equal(&Value::I32(42), &Value::I32(42)));
@@ -297,6 +798,176 @@ mod tests {
         assert!(!value_greater_than(&Value::I32(10), &Value::I32(20)));
     }
 
+    #[test]
+    fn test_value_partial_ord() {
+        assert!(Value::U8(1) < Value::U8(2));
+        assert!(Value::F64(-0.5) > Value::F64(-1.0));
+        assert_eq!(
+            Value::I64(3).partial_cmp(&Value::I64(3)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(Value::F32(f32::NAN).partial_cmp(&Value::F32(0.0)), None);
+
+        // Values of different types are neither equal nor ordered
+        assert_ne!(Value::I32(1), Value::U32(1));
+        assert_eq!(Value::I32(1).partial_cmp(&Value::I64(2)), None);
+        assert!(!value_less_than(&Value::I32(1), &Value::I64(2)));
+        assert!(!value_greater_than(&Value::I64(2), &Value::I32(1)));
+
+        let mut values = vec![Value::U16(30), Value::U16(10), Value::U16(20)];
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(values, [Value::U16(10), Value::U16(20), Value::U16(30)]);
+    }
+
+    #[test]
+    fn test_value_to_address() {
+        assert_eq!(
+            value_to_address(&Value::U64(0x7ff6_0000_1000)),
+            Some(0x7ff6_0000_1000)
+        );
+        assert_eq!(value_to_address(&Value::U32(0x1000)), Some(0x1000));
+        assert_eq!(value_to_address(&Value::I64(0x1000)), None);
+        assert_eq!(value_to_address(&Value::F64(4096.0)), None);
+    }
+
+    #[test]
+    fn test_value_parse_as() {
+        assert!(matches!(
+            Value::parse_as("300", ValueType::I16),
+            Ok(Value::I16(300))
+        ));
+        assert!(matches!(
+            Value::parse_as("300", ValueType::U16),
+            Ok(Value::U16(300))
+        ));
+        assert!(matches!(
+            Value::parse_as("0x1FF", ValueType::U32),
+            Ok(Value::U32(0x1FF))
+        ));
+        assert!(matches!(Value::parse_as("-2.5", ValueType::F32), Ok(Value::F32(v)) if v == -2.5));
+        assert!(Value::parse_as("300", ValueType::U8).is_err());
+        assert!(Value::parse_as("-1", ValueType::U32).is_err());
+        assert!(Value::parse_as("1.5", ValueType::I32).is_err());
+    }
+
+    #[test]
+    fn test_value_parse_as_char_and_bool() {
+        assert_eq!(
+            Value::parse_as("'A'", ValueType::U8).unwrap(),
+            Value::U8(65)
+        );
+        assert_eq!(
+            Value::parse_as("'z'", ValueType::I8).unwrap(),
+            Value::I8(122)
+        );
+        assert_eq!(
+            Value::parse_as("true", ValueType::I32).unwrap(),
+            Value::I32(1)
+        );
+        assert_eq!(
+            Value::parse_as("false", ValueType::U64).unwrap(),
+            Value::U64(0)
+        );
+
+        // Characters are only bytes, booleans only integers
+        assert!(Value::parse_as("'A'", ValueType::I32).is_err());
+        assert!(Value::parse_as("'AB'", ValueType::U8).is_err());
+        assert!(Value::parse_as("'é'", ValueType::U8).is_err());
+        assert!(Value::parse_as("''", ValueType::U8).is_err());
+        assert!(Value::parse_as("true", ValueType::F32).is_err());
+    }
+
+    #[test]
+    fn test_value_infer() {
+        // 300 needs 16 bits and fits both U16 and I16
+        let (value, value_type) = Value::infer("300").unwrap();
+        assert_eq!(value_type, ValueType::U16);
+        assert!(values_equal(&value, &Value::U16(300)));
+        assert!(Value::parse_as("300", ValueType::I16).is_ok());
+
+        assert_eq!(Value::infer("3.5").unwrap().1, ValueType::F64);
+        assert_eq!(Value::infer("0x1FF").unwrap().1, ValueType::U16);
+        assert_eq!(Value::infer("7").unwrap().1, ValueType::U8);
+        assert_eq!(Value::infer("-129").unwrap().1, ValueType::I16);
+        assert_eq!(
+            Value::infer("18446744073709551615").unwrap().1,
+            ValueType::U64
+        );
+        assert!(Value::infer("abc").is_none());
+        assert!(Value::infer("inf").is_none());
+    }
+
+    #[test]
+    fn test_value_from_f64() {
+        assert!(matches!(
+            value_from_f64(300.0, ValueType::I16),
+            Some(Value::I16(300))
+        ));
+        assert!(value_from_f64(300.0, ValueType::U8).is_none());
+        assert!(value_from_f64(-1.0, ValueType::U32).is_none());
+        assert!(value_from_f64(1.5, ValueType::I32).is_none());
+        assert!(matches!(value_from_f64(1.5, ValueType::F32), Some(Value::F32(v)) if v == 1.5));
+        assert_eq!(value_from_f64(-2.0, ValueType::I128), Some(Value::I128(-2)));
+        assert!(value_from_f64(2f64.powi(127), ValueType::I128).is_none());
+        assert!(value_from_f64(2f64.powi(128), ValueType::U128).is_none());
+        assert!(value_from_f64(-1.0, ValueType::U128).is_none());
+        assert_eq!(Value::I16(1).value_type(), ValueType::I16);
+    }
+
+    #[test]
+    fn test_compare_loose() {
+        assert_eq!(
+            compare_loose(&Value::U8(200), &Value::I32(200)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare_loose(&Value::I8(-1), &Value::U64(u64::MAX)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_loose(&Value::U16(3), &Value::F64(2.5)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare_loose(&Value::F32(f32::NAN), &Value::I32(0)), None);
+        // Exact for integers, even beyond f64 precision
+        assert_eq!(
+            compare_loose(&Value::U64((1 << 53) + 1), &Value::I64(1 << 53)),
+            Some(Ordering::Greater)
+        );
+        // Including U128 values that don't fit an i128
+        assert_eq!(
+            compare_loose(&Value::I128(i128::MAX), &Value::U128(u128::MAX)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_loose(&Value::U128(u128::MAX), &Value::U128(u128::MAX - 1)),
+            Some(Ordering::Greater)
+        );
+    }
+
+    #[test]
+    fn test_format_with_precision() {
+        // One ULP above the F32 closest to 3.14
+        let noisy = Value::F32(f32::from_bits(0x4048_F5C4));
+        assert_eq!(noisy.format_with(None), "3.1400003");
+        assert_eq!(noisy.format_with(Some(DEFAULT_FLOAT_PRECISION)), "3.14");
+        assert_eq!(noisy.format_with(Some(2)), "3.1");
+        assert_eq!(Value::F64(1234.5).format_with(Some(3)), "1.23e3");
+        assert_eq!(Value::F64(0.000012345).format_with(Some(3)), "1.23e-5");
+        assert_eq!(Value::F64(-0.5).format_with(Some(7)), "-0.5");
+        assert_eq!(Value::F32(100.0).format_with(Some(7)), "100");
+        assert_eq!(Value::F64(f64::NAN).format_with(Some(7)), "NaN");
+        assert_eq!(Value::I32(123456789).format_with(Some(2)), "123456789");
+    }
+
+    #[test]
+    fn test_delta_f64() {
+        assert_eq!(delta_f64(&Value::I32(150), &Value::I32(100)), Some(50.0));
+        assert_eq!(delta_f64(&Value::U8(0), &Value::U8(255)), Some(-255.0));
+        assert_eq!(delta_f64(&Value::F32(1.5), &Value::F32(1.0)), Some(0.5));
+        assert_eq!(delta_f64(&Value::I32(1), &Value::U32(1)), None);
+    }
+
     #[test]
     fn test_math_operations() {
         let result = apply_math_op(&Value::I32(10), &Value::I32(5), MathOp::Add).unwrap();
@@ -311,4 +982,20 @@ mod tests {
         let result = apply_math_op(&Value::I32(10), &Value::I32(5), MathOp::Divide).unwrap();
         assert!(values_equal(&result, &Value::I32(2)));
     }
+
+    #[test]
+    fn test_divide_by_zero() {
+        let err = apply_math_op(&Value::U8(10), &Value::U8(0), MathOp::Divide).unwrap_err();
+        assert!(matches!(
+            err,
+            MemScanError::InvalidValue {
+                value_type: ValueType::U8,
+                ..
+            }
+        ));
+        assert!(apply_math_op(&Value::I64(-3), &Value::I64(0), MathOp::Divide).is_err());
+
+        let result = apply_math_op(&Value::F64(1.0), &Value::F64(0.0), MathOp::Divide).unwrap();
+        assert!(values_equal(&result, &Value::F64(f64::INFINITY)));
+    }
 }
@@ -1,8 +1,8 @@
 //! Windows-specific memory mapping implementation using file mapping objects
 
+use crate::error::{MemScanError, Result, bail};
 use crate::process::{MemoryRegion, ProcessHandle};
 use crate::windows::memoryapi::MapViewOfFile2;
-use anyhow::Result;
 use std::ptr::{null, null_mut};
 use winapi::{
     shared::minwindef::LPVOID,
@@ -43,7 +43,7 @@ impl MappedMemoryWin {
             );
 
             if mapping_handle.is_null() {
-                anyhow::bail!(
+                bail!(
                     "CreateFileMappingW failed: {}",
                     std::io::Error::last_os_error()
                 );
@@ -63,11 +63,10 @@ impl MappedMemoryWin {
 
             if local_ptr.is_null() {
                 CloseHandle(mapping_handle);
-                anyhow::bail!(
-                    "MapViewOfFile2 failed for address {:016x}: {}",
-                    region.base_address,
-                    std::io::Error::last_os_error()
-                );
+                return Err(MemScanError::ReadFailed {
+                    addr: region.base_address,
+                    reason: format!("MapViewOfFile2 failed: {}", std::io::Error::last_os_error()),
+                });
             }
 
             Ok(Self {
@@ -82,6 +81,13 @@ impl MappedMemoryWin {
     pub fn as_slice(&self) -> &[u8] {
         unsafe { std::slice::from_raw_parts(self.local_ptr as *const u8, self.size) }
     }
+
+    /// Refresh mapped memory
+    ///
+    /// The view is backed by the remote process memory, so it is always current.
+    pub fn refresh(&mut self, _proc: &ProcessHandle) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl Drop for MappedMemoryWin {
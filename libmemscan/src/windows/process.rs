@@ -1,46 +1,83 @@
+use crate::error::{MemScanError, Result, bail};
 use crate::process::{
-    MemoryProtection, MemoryRegion, MemoryState, MemoryType, ProcessHandle, SystemInfo,
-    is_region_interesting,
+    MemoryProtection, MemoryRegion, MemoryState, MemoryType, ProcessHandle, SystemInfo, ThreadInfo,
+    check_lookup_deadline,
 };
-use anyhow::Result;
 use std::mem::{MaybeUninit, size_of, transmute};
+use std::time::Instant;
 use winapi::{
     shared::{
         basetsd::SIZE_T,
-        minwindef::{DWORD, FALSE, HMODULE, LPCVOID, LPVOID, MAX_PATH},
+        minwindef::{BOOL, DWORD, FALSE, HMODULE, LPARAM, LPCVOID, LPVOID, MAX_PATH, TRUE},
+        windef::HWND,
+        winerror::{ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER, ERROR_NOT_ALL_ASSIGNED},
     },
     um::{
         handleapi::CloseHandle,
-        memoryapi::{ReadProcessMemory, VirtualQueryEx},
-        processthreadsapi::OpenProcess,
+        memoryapi::{ReadProcessMemory, VirtualProtectEx, VirtualQueryEx},
+        processthreadsapi::{
+            GetCurrentProcess, GetCurrentThreadId, GetProcessId, GetThreadContext, OpenProcess,
+            OpenProcessToken, OpenThread, ResumeThread, SuspendThread,
+        },
         psapi::{EnumProcessModules, GetModuleFileNameExA, GetModuleInformation, MODULEINFO},
+        securitybaseapi::AdjustTokenPrivileges,
         sysinfoapi::{GetNativeSystemInfo, SYSTEM_INFO},
         tlhelp32::{
             CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW,
-            TH32CS_SNAPPROCESS,
+            TH32CS_SNAPPROCESS, TH32CS_SNAPTHREAD, THREADENTRY32, Thread32First, Thread32Next,
         },
+        winbase::LookupPrivilegeValueW,
         winnt::{
-            CHAR, HANDLE, MEM_COMMIT, MEM_FREE, MEM_IMAGE, MEM_MAPPED, MEM_PRIVATE, MEM_RESERVE,
-            MEMORY_BASIC_INFORMATION, PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE,
-            PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_NOACCESS, PAGE_NOCACHE, PAGE_READONLY,
-            PAGE_READWRITE, PAGE_WRITECOPY, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
-            PROCESS_VM_WRITE, PROCESS_VM_OPERATION,
+            CHAR, CONTEXT, CONTEXT_CONTROL, HANDLE, MEM_COMMIT, MEM_FREE, MEM_IMAGE, MEM_MAPPED,
+            MEM_PRIVATE, MEM_RESERVE, MEMORY_BASIC_INFORMATION, PAGE_EXECUTE, PAGE_EXECUTE_READ,
+            PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_NOACCESS,
+            PAGE_NOCACHE, PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY, PROCESS_QUERY_INFORMATION,
+            PROCESS_VM_OPERATION, PROCESS_VM_READ, PROCESS_VM_WRITE, SE_PRIVILEGE_ENABLED,
+            THREAD_GET_CONTEXT, THREAD_SUSPEND_RESUME, TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES,
+            TOKEN_QUERY,
+        },
+        winuser::{
+            EnumWindows, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+            IsWindowVisible,
         },
+        wow64apiset::IsWow64Process,
     },
 };
 
 // ================== Windows-specific process types ==================
 
 #[derive(Debug)]
-pub struct ProcessHandleWin(pub HANDLE);
+pub struct ProcessHandleWin(HANDLE);
 
 unsafe impl Send for ProcessHandleWin {}
 unsafe impl Sync for ProcessHandleWin {}
 
 impl ProcessHandleWin {
+    /// The underlying handle, which stays owned by (and is closed with) `self`
     pub fn raw(&self) -> HANDLE {
         self.0
     }
+
+    /// Id of the target process
+    pub fn pid(&self) -> u32 {
+        unsafe { GetProcessId(self.0) }
+    }
+
+    /// Close the handle, reporting an error that dropping it would ignore
+    pub fn close(mut self) -> Result<()> {
+        // Leave a null handle behind so `Drop` doesn't close it again
+        let handle = std::mem::replace(&mut self.0, std::ptr::null_mut());
+        if unsafe { CloseHandle(handle) } == FALSE {
+            bail!("CloseHandle failed: {}", std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Size of a pointer in the target, 4 for WOW64 and 32-bit processes and
+    /// 8 for 64-bit ones
+    pub fn pointer_size(&self) -> usize {
+        if is_64bit_process(self) { 8 } else { 4 }
+    }
 }
 
 impl Drop for ProcessHandleWin {
@@ -113,29 +150,126 @@ impl From<u32> for MemoryType {
 // ================== Windows-specific process functions ==================
 
 pub(crate) fn open_process(pid: u32) -> Result<ProcessHandle> {
+    const ACCESS: DWORD =
+        PROCESS_QUERY_INFORMATION | PROCESS_VM_READ | PROCESS_VM_WRITE | PROCESS_VM_OPERATION;
     unsafe {
-        let handle = OpenProcess(
-            PROCESS_QUERY_INFORMATION | PROCESS_VM_READ | PROCESS_VM_WRITE | PROCESS_VM_OPERATION,
-            FALSE,
-            pid,
-        );
+        let mut handle = OpenProcess(ACCESS, FALSE, pid);
         if handle.is_null() {
-            anyhow::bail!("OpenProcess failed for pid {}", pid);
+            let err = std::io::Error::last_os_error();
+            // OpenProcess rejects the pid of a process that doesn't exist
+            if err.raw_os_error() == Some(ERROR_INVALID_PARAMETER as i32) {
+                bail!(ProcessNotFound, "with pid {}", pid);
+            }
+            if err.raw_os_error() != Some(ERROR_ACCESS_DENIED as i32) {
+                bail!("OpenProcess failed for pid {}: {}", pid, err);
+            }
+            // Elevated processes and services of other users need SeDebugPrivilege
+            if enable_debug_privilege() {
+                handle = OpenProcess(ACCESS, FALSE, pid);
+            }
+            if handle.is_null() {
+                bail!(
+                    AccessDenied,
+                    "access denied opening pid {}: run memscan as administrator so it can \
+                     enable SeDebugPrivilege (protected processes such as anti-cheat or \
+                     antivirus services can't be opened even then)",
+                    pid
+                );
+            }
         }
         Ok(ProcessHandleWin(handle))
     }
 }
 
+/// Enable SeDebugPrivilege on the token of this process, returning whether
+/// it is held now
+///
+/// Only succeeds when running as administrator, since other users don't
+/// have the privilege to enable.
+fn enable_debug_privilege() -> bool {
+    unsafe {
+        let mut token: HANDLE = std::ptr::null_mut();
+        if OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY,
+            &mut token,
+        ) == FALSE
+        {
+            return false;
+        }
+        let name: Vec<u16> = "SeDebugPrivilege\0".encode_utf16().collect();
+        let mut privileges: TOKEN_PRIVILEGES = std::mem::zeroed();
+        privileges.PrivilegeCount = 1;
+        privileges.Privileges[0].Attributes = SE_PRIVILEGE_ENABLED;
+        // AdjustTokenPrivileges also succeeds when the privilege isn't held,
+        // which only shows in the last error
+        let enabled = LookupPrivilegeValueW(
+            std::ptr::null(),
+            name.as_ptr(),
+            &mut privileges.Privileges[0].Luid,
+        ) != FALSE
+            && AdjustTokenPrivileges(
+                token,
+                FALSE,
+                &mut privileges,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            ) != FALSE
+            && std::io::Error::last_os_error().raw_os_error()
+                != Some(ERROR_NOT_ALL_ASSIGNED as i32);
+        CloseHandle(token);
+        enabled
+    }
+}
+
+/// List the PID and executable name of every running process.
+pub(crate) fn list_processes(deadline: Option<Instant>) -> Result<Vec<(u32, String)>> {
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == winapi::um::handleapi::INVALID_HANDLE_VALUE {
+            bail!("CreateToolhelp32Snapshot failed");
+        }
+
+        let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+        entry.dwSize = size_of::<PROCESSENTRY32W>() as u32;
+
+        let mut processes = Vec::new();
+        if Process32FirstW(snapshot, &mut entry) != FALSE {
+            loop {
+                if let Err(err) = check_lookup_deadline(deadline) {
+                    CloseHandle(snapshot);
+                    return Err(err);
+                }
+                let len = entry
+                    .szExeFile
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(entry.szExeFile.len());
+                let exe_name = String::from_utf16_lossy(&entry.szExeFile[..len]);
+                processes.push((entry.th32ProcessID, exe_name));
+
+                if Process32NextW(snapshot, &mut entry) == FALSE {
+                    break;
+                }
+            }
+        }
+
+        CloseHandle(snapshot);
+        Ok(processes)
+    }
+}
+
 /// Find the PID of the first process whose executable name matches `name` (case-insensitive).
 ///
 /// Example names: `"notepad"` or `"notepad.exe"`.
-pub(crate) fn find_process_by_name(name: &str) -> Result<Option<u32>> {
+pub(crate) fn find_process_by_name(name: &str, deadline: Option<Instant>) -> Result<Option<u32>> {
     let name = name.to_ascii_lowercase();
 
     unsafe {
         let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
         if snapshot == winapi::um::handleapi::INVALID_HANDLE_VALUE {
-            anyhow::bail!("CreateToolhelp32Snapshot failed");
+            bail!("CreateToolhelp32Snapshot failed");
         }
 
         let mut entry: PROCESSENTRY32W = std::mem::zeroed();
@@ -149,6 +283,10 @@ pub(crate) fn find_process_by_name(name: &str) -> Result<Option<u32>> {
         }
 
         loop {
+            if let Err(err) = check_lookup_deadline(deadline) {
+                CloseHandle(snapshot);
+                return Err(err);
+            }
             let exe_name = {
                 let len = entry
                     .szExeFile
@@ -173,73 +311,216 @@ pub(crate) fn find_process_by_name(name: &str) -> Result<Option<u32>> {
     }
 }
 
+/// Find the PID of the process owning the first visible top-level window
+/// whose title contains `title` (case-insensitive).
+pub(crate) fn find_process_by_window_title(title: &str) -> Result<Option<u32>> {
+    struct Search {
+        title: String,
+        found: Option<u32>,
+    }
+
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        unsafe {
+            let search = &mut *(lparam as *mut Search);
+            if IsWindowVisible(hwnd) == FALSE {
+                return TRUE;
+            }
+            let len = GetWindowTextLengthW(hwnd);
+            if len <= 0 {
+                return TRUE;
+            }
+            let mut buf = vec![0u16; len as usize + 1];
+            let copied = GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32);
+            let text = String::from_utf16_lossy(&buf[..copied.max(0) as usize]).to_lowercase();
+            if !text.contains(&search.title) {
+                return TRUE;
+            }
+            let mut pid: DWORD = 0;
+            GetWindowThreadProcessId(hwnd, &mut pid);
+            if pid == 0 {
+                return TRUE;
+            }
+            search.found = Some(pid);
+            FALSE // stop enumerating
+        }
+    }
+
+    let mut search = Search {
+        title: title.to_lowercase(),
+        found: None,
+    };
+    // Also FALSE when `visit` stopped the enumeration early
+    let ok = unsafe { EnumWindows(Some(visit), &mut search as *mut Search as LPARAM) };
+    if ok == FALSE && search.found.is_none() {
+        bail!("EnumWindows failed");
+    }
+    Ok(search.found)
+}
+
+/// A target is 64-bit unless it runs under WOW64 (or memscan itself is 32-bit)
+pub(crate) fn is_64bit_process(proc: &ProcessHandleWin) -> bool {
+    if !cfg!(target_pointer_width = "64") {
+        return false;
+    }
+    let mut wow64: BOOL = FALSE;
+    let ok = unsafe { IsWow64Process(proc.raw(), &mut wow64) };
+    ok == FALSE || wow64 == FALSE
+}
+
 /// Get a list of module base addresses for the given process using EnumProcessModules.
 pub(crate) fn get_process_module_regions(proc: &ProcessHandleWin) -> Result<Vec<MemoryRegion>> {
-    let mut modules = Vec::new();
-    let mut h_mods: [HMODULE; 1024];
-    let mut cb_needed: DWORD = 0;
+    //* Skip first module (the main executable)
+    //* We only want to get the unrelated DLL modules here.
+    enum_process_modules(proc)?
+        .into_iter()
+        .skip(1)
+        .map(|h_mod| module_region(proc, h_mod))
+        .collect()
+}
+
+pub(crate) fn get_main_module(proc: &ProcessHandleWin) -> Result<MemoryRegion> {
+    // The first module is always the main executable
+    let Some(&h_mod) = enum_process_modules(proc)?.first() else {
+        bail!("EnumProcessModules returned no modules");
+    };
+    module_region(proc, h_mod)
+}
+
+pub(crate) fn enumerate_threads(proc: &ProcessHandleWin) -> Result<Vec<ThreadInfo>> {
+    unsafe {
+        let pid = GetProcessId(proc.raw());
+        if pid == 0 {
+            bail!("GetProcessId failed: {}", std::io::Error::last_os_error());
+        }
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == winapi::um::handleapi::INVALID_HANDLE_VALUE {
+            bail!("CreateToolhelp32Snapshot failed");
+        }
 
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = size_of::<THREADENTRY32>() as u32;
+
+        // The snapshot lists the threads of every process
+        let mut threads = Vec::new();
+        if Thread32First(snapshot, &mut entry) != FALSE {
+            loop {
+                if entry.th32OwnerProcessID == pid {
+                    threads.push(ThreadInfo {
+                        tid: entry.th32ThreadID,
+                        stack_pointer: thread_stack_pointer(entry.th32ThreadID),
+                    });
+                }
+                if Thread32Next(snapshot, &mut entry) == FALSE {
+                    break;
+                }
+            }
+        }
+
+        CloseHandle(snapshot);
+        Ok(threads)
+    }
+}
+
+/// `CONTEXT` must be 16-byte aligned on x64, which winapi doesn't declare
+#[repr(C, align(16))]
+struct AlignedContext(CONTEXT);
+
+/// The stack pointer of thread `tid`, read while it is suspended
+fn thread_stack_pointer(tid: DWORD) -> Option<usize> {
     unsafe {
-        h_mods = [std::ptr::null_mut(); 1024];
-        let res = EnumProcessModules(
+        // Suspending the calling thread would never return, and its context
+        // can't be read while it runs anyway
+        if tid == GetCurrentThreadId() {
+            let marker = 0u8;
+            return Some(std::hint::black_box(&marker) as *const u8 as usize);
+        }
+        let thread = OpenThread(THREAD_GET_CONTEXT | THREAD_SUSPEND_RESUME, FALSE, tid);
+        if thread.is_null() {
+            return None;
+        }
+        let suspended = SuspendThread(thread) != DWORD::MAX;
+        let mut context: AlignedContext = std::mem::zeroed();
+        context.0.ContextFlags = CONTEXT_CONTROL;
+        let ok = GetThreadContext(thread, &mut context.0) != FALSE;
+        if suspended {
+            ResumeThread(thread);
+        }
+        CloseHandle(thread);
+        if !ok {
+            return None;
+        }
+        #[cfg(target_arch = "x86_64")]
+        let stack_pointer = context.0.Rsp as usize;
+        #[cfg(target_arch = "x86")]
+        let stack_pointer = context.0.Esp as usize;
+        Some(stack_pointer)
+    }
+}
+
+/// Handles of the modules loaded in the target, the main executable first
+fn enum_process_modules(proc: &ProcessHandleWin) -> Result<Vec<HMODULE>> {
+    let mut h_mods: [HMODULE; 1024] = [std::ptr::null_mut(); 1024];
+    let mut cb_needed: DWORD = 0;
+    let res = unsafe {
+        EnumProcessModules(
             proc.raw(),
             h_mods.as_mut_ptr(),
             (size_of::<HMODULE>() * h_mods.len()) as DWORD,
             &mut cb_needed as *mut DWORD,
+        )
+    };
+    if res == FALSE {
+        bail!(
+            "EnumProcessModules failed: {}",
+            std::io::Error::last_os_error()
         );
-        if res == FALSE {
-            anyhow::bail!(
-                "EnumProcessModules failed: {}",
+    }
+    let count = ((cb_needed as usize) / size_of::<HMODULE>()).min(h_mods.len());
+    Ok(h_mods[..count].to_vec())
+}
+
+/// The image region of module `h_mod`, named after its file
+fn module_region(proc: &ProcessHandleWin, h_mod: HMODULE) -> Result<MemoryRegion> {
+    unsafe {
+        let mut modimage: [TCHAR; MAX_PATH] = [0; MAX_PATH];
+        let res = GetModuleFileNameExA(proc.raw(), h_mod, modimage.as_mut_ptr(), MAX_PATH as DWORD);
+        if res == 0 {
+            bail!(
+                "GetModuleFileNameExA failed: {}",
                 std::io::Error::last_os_error()
             );
         }
-
-        let count = (cb_needed as usize) / size_of::<HMODULE>();
-        for &h_mod in &h_mods[1..count] {
-            //* Skip first module (the main executable)
-            //* We only want to get the unrelated DLL modules here.
-            let mut modimage: [TCHAR; MAX_PATH] = [0; MAX_PATH];
-            let res =
-                GetModuleFileNameExA(proc.raw(), h_mod, modimage.as_mut_ptr(), MAX_PATH as DWORD);
-            if res == 0 {
-                anyhow::bail!(
-                    "GetModuleFileNameExA failed: {}",
-                    std::io::Error::last_os_error()
-                );
-            }
-            let image_file = {
-                let len = modimage
-                    .iter()
-                    .position(|&c| c == 0)
-                    .unwrap_or(modimage.len());
-                let modimage_u8: [u8; MAX_PATH] = transmute(modimage);
-                String::from_utf8_lossy(&modimage_u8[..len]).to_string()
-            };
-            let mut modinfo = MaybeUninit::<MODULEINFO>::uninit();
-            let res = GetModuleInformation(
-                proc.raw(),
-                h_mod,
-                modinfo.as_mut_ptr(),
-                size_of::<MODULEINFO>() as DWORD,
+        let image_file = {
+            let len = modimage
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(modimage.len());
+            let modimage_u8: [u8; MAX_PATH] = transmute(modimage);
+            String::from_utf8_lossy(&modimage_u8[..len]).to_string()
+        };
+        let mut modinfo = MaybeUninit::<MODULEINFO>::uninit();
+        let res = GetModuleInformation(
+            proc.raw(),
+            h_mod,
+            modinfo.as_mut_ptr(),
+            size_of::<MODULEINFO>() as DWORD,
+        );
+        if res == FALSE {
+            bail!(
+                "GetModuleInformation failed: {}",
+                std::io::Error::last_os_error()
             );
-            if res == FALSE {
-                anyhow::bail!(
-                    "GetModuleInformation failed: {}",
-                    std::io::Error::last_os_error()
-                );
-            }
-            let modinfo = modinfo.assume_init();
-            modules.push(MemoryRegion {
-                base_address: modinfo.lpBaseOfDll as usize,
-                size: modinfo.SizeOfImage as usize,
-                protect: PAGE_EXECUTE_READ.into(),
-                state: MEM_COMMIT.into(),
-                type_: MEM_IMAGE.into(),
-                image_file: Some(image_file),
-            });
         }
+        let modinfo = modinfo.assume_init();
+        Ok(MemoryRegion {
+            base_address: modinfo.lpBaseOfDll as usize,
+            size: modinfo.SizeOfImage as usize,
+            protect: PAGE_EXECUTE_READ.into(),
+            state: MEM_COMMIT.into(),
+            type_: MEM_IMAGE.into(),
+            image_file: Some(image_file),
+        })
     }
-    Ok(modules)
 }
 
 pub(crate) fn query_system_info() -> SystemInfo {
@@ -274,7 +555,8 @@ pub(crate) fn memory_region_iterator_next(
     // To get extended error information, call GetLastError.
     // Possible error values include ERROR_INVALID_PARAMETER.
     if res == 0 {
-        // Failed (or reached the end of the address space)
+        // Failed (or reached the end of the address space), stop iterating
+        *cur_addr = usize::MAX;
         return None;
     }
 
@@ -285,25 +567,25 @@ pub(crate) fn memory_region_iterator_next(
     let prot: MemoryProtection = mbi.Protect.into();
     let state: MemoryState = mbi.State.into();
 
-    // Advance iterator *before* possible continue
     *cur_addr = region_base.saturating_add(region_size);
 
-    if is_region_interesting(&prot, &state) {
-        return Some(MemoryRegion {
-            base_address: region_base,
-            size: region_size,
-            protect: prot,
-            state: state,
-            type_: mbi.Type.into(),
-            image_file: None,
-        });
-    } else {
-        return None;
-    }
+    // The iterator applies its region filter
+    Some(MemoryRegion {
+        base_address: region_base,
+        size: region_size,
+        protect: prot,
+        state,
+        type_: mbi.Type.into(),
+        image_file: None,
+    })
 }
 
-/// Read process memory into the provided buffer. Returns the number of bytes read (0 on failure).
-pub(crate) fn read_process_memory(proc: &ProcessHandleWin, addr: usize, buf: &mut [u8]) -> usize {
+/// Read process memory into the provided buffer. Returns the number of bytes read.
+pub(crate) fn try_read_process_memory(
+    proc: &ProcessHandleWin,
+    addr: usize,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
     unsafe {
         let mut bytes_read: SIZE_T = 0;
         let res = ReadProcessMemory(
@@ -313,7 +595,11 @@ pub(crate) fn read_process_memory(proc: &ProcessHandleWin, addr: usize, buf: &mu
             buf.len() as SIZE_T,
             &mut bytes_read as *mut SIZE_T,
         );
-        if res == 0 { 0 } else { bytes_read as usize }
+        if res == 0 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(bytes_read as usize)
+        }
     }
 }
 
@@ -330,3 +616,65 @@ pub(crate) fn write_process_memory(proc: &ProcessHandleWin, addr: usize, buf: &[
         if res == 0 { 0 } else { bytes_written as usize }
     }
 }
+
+pub(crate) fn write_process_memory_many(
+    proc: &ProcessHandleWin,
+    writes: &[(usize, &[u8])],
+) -> Vec<usize> {
+    writes
+        .iter()
+        .map(|(addr, buf)| write_process_memory(proc, *addr, buf))
+        .collect()
+}
+
+/// Write process memory, making the pages writable for the duration of
+/// the write when `WriteProcessMemory` alone fails
+///
+/// The original protection is restored afterwards. Needs
+/// `PROCESS_VM_OPERATION` on the handle; guard pages and pages that
+/// `VirtualProtectEx` refuses to change can't be written.
+pub(crate) fn force_write_process_memory(
+    proc: &ProcessHandleWin,
+    addr: usize,
+    buf: &[u8],
+) -> Result<usize> {
+    let written = write_process_memory(proc, addr, buf);
+    if written > 0 || buf.is_empty() {
+        return Ok(written);
+    }
+
+    unsafe {
+        let mut old_protect: DWORD = 0;
+        if VirtualProtectEx(
+            proc.raw(),
+            addr as LPVOID,
+            buf.len() as SIZE_T,
+            PAGE_EXECUTE_READWRITE,
+            &mut old_protect,
+        ) == 0
+        {
+            bail!(
+                "Failed to make memory at {:016x} writable: {}",
+                addr,
+                std::io::Error::last_os_error()
+            );
+        }
+        let written = write_process_memory(proc, addr, buf);
+        let write_err = std::io::Error::last_os_error();
+        let mut restored: DWORD = 0;
+        VirtualProtectEx(
+            proc.raw(),
+            addr as LPVOID,
+            buf.len() as SIZE_T,
+            old_protect,
+            &mut restored,
+        );
+        if written == 0 {
+            return Err(MemScanError::WriteFailed {
+                addr,
+                reason: write_err.to_string(),
+            });
+        }
+        Ok(written)
+    }
+}
@@ -4,17 +4,17 @@
 
 #[cfg(test)]
 mod integration_tests {
-    use libmemscan::diff::{diff_snapshots, MemoryRegionSnapshot};
+    use libmemscan::diff::{MemoryRegionSnapshot, diff_snapshots};
 
     #[test]
     fn test_snapshot_diff_workflow() {
         // Create a snapshot and test that it can detect changes when refreshed
         let data = vec![0xAA, 0xBB, 0xCC, 0xDD];
         let snapshot1 = MemoryRegionSnapshot::from_slice(&data);
-        
+
         // Create another snapshot from the same data (no changes)
         let snapshot2 = MemoryRegionSnapshot::from_slice(&data);
-        
+
         // These will be at different addresses, so diff returns empty
         // This tests the address mismatch detection
         let changes = diff_snapshots(&snapshot1, &snapshot2);
@@ -27,10 +27,10 @@ mod integration_tests {
         let data: Vec<u8> = (0..100).map(|i| (i % 256) as u8).collect();
         let snapshot1 = MemoryRegionSnapshot::from_slice(&data);
         let snapshot2 = MemoryRegionSnapshot::from_slice(&data);
-        
+
         // Different addresses mean no comparison
         let changes = diff_snapshots(&snapshot1, &snapshot2);
-        
+
         // Since snapshots are from different memory addresses, no changes are detected
         // (this is by design to prevent comparing unrelated memory)
         assert_eq!(changes.len(), 0);
@@ -42,11 +42,175 @@ mod integration_tests {
         let data = vec![0x42; 1000];
         let snapshot1 = MemoryRegionSnapshot::from_slice(&data);
         let snapshot2 = MemoryRegionSnapshot::from_slice(&data);
-        
+
         // Detect changes
         let changes = diff_snapshots(&snapshot1, &snapshot2);
-        
+
         // Verify no changes detected (different addresses means no comparison)
         assert_eq!(changes.len(), 0);
     }
+
+    #[test]
+    fn test_memscan_scan_pattern_own_process() {
+        use libmemscan::MemScan;
+
+        // Built at runtime so the pattern only sits in the heap buffer and
+        // in `pattern` itself
+        let pattern: Vec<u8> = (0..16u8).map(|i| i.wrapping_mul(37) ^ 0xA5).collect();
+        let buffer = pattern.clone();
+        let memscan = MemScan::attach(std::process::id()).unwrap();
+        let matches = memscan.scan_pattern(&pattern).unwrap();
+        let addresses: Vec<usize> = matches.iter().map(|m| m.address).collect();
+        assert!(addresses.contains(&(buffer.as_ptr() as usize)));
+        assert!(addresses.windows(2).all(|w| w[0] < w[1]));
+        std::hint::black_box(&buffer);
+
+        assert!(MemScan::attach_by_name("no-such-process-memscan").is_err());
+    }
+
+    #[test]
+    fn test_replace_pattern_own_process() {
+        use libmemscan::process::{get_process_module_regions, open_process, query_system_info};
+        use libmemscan::scanner::{ScanOptions, replace_pattern};
+
+        let find: Vec<u8> = (0..16u8).map(|i| i.wrapping_mul(53) ^ 0x5C).collect();
+        let replace: Vec<u8> = find.iter().map(|b| !b).collect();
+        // Large enough to get a mapping of its own rather than a heap block
+        // next to `find` or to buffers the scan frees
+        let mut buffer = vec![0u8; 4 << 20];
+        let offset = 1 << 20;
+        buffer[offset..offset + find.len()].copy_from_slice(&find);
+        let buffer = std::hint::black_box(buffer);
+        let proc = open_process(std::process::id()).unwrap();
+        let sys = query_system_info();
+        let modules = get_process_module_regions(&proc).unwrap();
+        // Only the region containing the buffer is scanned and patched
+        let opts = ScanOptions {
+            only_writable: true,
+            stack_pointers: Some(vec![buffer.as_ptr() as usize + offset]),
+            ..Default::default()
+        };
+
+        let short = find[..15].to_vec();
+        let err = replace_pattern(&proc, &sys, &short, &replace, &modules, &opts).unwrap_err();
+        assert!(err.to_string().contains("same length"), "{}", err);
+
+        let patched = replace_pattern(&proc, &sys, &find, &replace, &modules, &opts).unwrap();
+        assert_eq!(patched, 1);
+        let buffer = std::hint::black_box(buffer);
+        assert_eq!(&buffer[offset..offset + replace.len()], &replace[..]);
+    }
+
+    #[test]
+    fn test_enumerate_threads_own_process() {
+        use libmemscan::process::{enumerate_threads, open_process};
+        use std::sync::mpsc;
+
+        // A thread blocked in a syscall, so its stack pointer can be read
+        let (address_tx, address_rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            let marker = 0u64;
+            address_tx
+                .send(std::hint::black_box(&marker) as *const u64 as usize)
+                .unwrap();
+            done_rx.recv().unwrap();
+        });
+        let marker = address_rx.recv().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        let proc = open_process(std::process::id()).unwrap();
+        let threads = enumerate_threads(&proc).unwrap();
+        done_tx.send(()).unwrap();
+        thread.join().unwrap();
+
+        assert!(threads.len() >= 2, "{:?}", threads);
+        // The stack grows down from the marker while the thread blocks
+        assert!(
+            threads
+                .iter()
+                .filter_map(|t| t.stack_pointer)
+                .any(|sp| sp < marker && marker - sp < 1 << 20),
+            "no stack pointer just below {:x}: {:x?}",
+            marker,
+            threads
+        );
+    }
+
+    #[test]
+    fn test_scan_process_to_own_process() {
+        use libmemscan::process::{get_process_module_regions, open_process, query_system_info};
+        use libmemscan::scanner::{OutputFormat, ScanOptions, scan_process_to};
+
+        let pattern: Vec<u8> = (0..16u8).map(|i| i.wrapping_mul(71) ^ 0x3E).collect();
+        let buffer = pattern.clone();
+        let proc = open_process(std::process::id()).unwrap();
+        let sys = query_system_info();
+        let modules = get_process_module_regions(&proc).unwrap();
+        let opts = ScanOptions {
+            output: OutputFormat::Jsonl,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        scan_process_to(&proc, &sys, &pattern, &opts, &modules, &mut out, false).unwrap();
+        std::hint::black_box(&buffer);
+        let text = String::from_utf8(out).unwrap();
+        let expected = format!("\"address\":\"0x{:016x}\"", buffer.as_ptr() as usize);
+        assert!(
+            text.lines().any(|line| line.contains(&expected)),
+            "{}",
+            text
+        );
+        let summary = text.lines().last().unwrap();
+        assert!(summary.starts_with("{\"regions_scanned\":"), "{}", summary);
+        assert!(summary.contains("\"cancelled\":false"), "{}", summary);
+    }
+
+    #[test]
+    fn test_scan_process_spawn_own_process() {
+        use libmemscan::process::{get_process_module_regions, open_process, query_system_info};
+        use libmemscan::scanner::{ScanOptions, scan_process_spawn};
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicBool;
+
+        let pattern: Vec<u8> = (0..16u8).map(|i| i.wrapping_mul(89) ^ 0x7B).collect();
+        let buffer = pattern.clone();
+        let proc = Arc::new(open_process(std::process::id()).unwrap());
+        let sys = query_system_info();
+        let modules = get_process_module_regions(&proc).unwrap();
+
+        let handle = scan_process_spawn(
+            proc.clone(),
+            &sys,
+            &pattern,
+            &ScanOptions::default(),
+            &modules,
+        );
+        let mut addresses = Vec::new();
+        while !handle.is_finished() {
+            addresses.extend(handle.try_recv_matches());
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        let progress = handle.progress();
+        let report = handle.join().unwrap();
+        addresses.extend(&report.matches);
+        std::hint::black_box(&buffer);
+        assert!(addresses.contains(&(buffer.as_ptr() as usize)));
+        assert!(!report.cancelled);
+        assert!(report.regions_scanned > 0);
+        assert_eq!(progress.matches_found, addresses.len());
+
+        // Cancelled before it starts, nothing is mapped
+        let opts = ScanOptions {
+            cancel: Some(Arc::new(AtomicBool::new(true))),
+            ..Default::default()
+        };
+        let report = scan_process_spawn(proc, &sys, &pattern, &opts, &modules)
+            .join()
+            .unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.regions_scanned, 0);
+        assert!(report.matches.is_empty());
+    }
 }
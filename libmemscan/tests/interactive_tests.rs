@@ -1,32 +1,15 @@
 //! Tests for the interactive module
 
-use libmemscan::interactive::FilterOp;
-use libmemscan::process::{MemoryRegion, MemoryProtection, MemoryState, MemoryType};
-use libmemscan::values::{MathOp, Value, ValueType};
-
-/// Helper function to create a mock memory region for testing
-fn create_test_region(base: usize, size: usize) -> MemoryRegion {
-    MemoryRegion {
-        base_address: base,
-        size,
-        type_: MemoryType::Private,
-        state: MemoryState {
-            committed: true,
-            free: false,
-            reserved: false,
-        },
-        protect: MemoryProtection {
-            no_access: false,
-            read: true,
-            write: true,
-            execute: false,
-            copy_on_write: false,
-            guarded: false,
-            no_cache: false,
-        },
-        image_file: None,
-    }
-}
+#![allow(clippy::approx_constant)]
+
+use libmemscan::MemScanError;
+use libmemscan::diff::MemoryDiff;
+use libmemscan::interactive::{FieldPredicate, FieldSpec, FilterOp, InteractiveScanner};
+use libmemscan::process::{
+    MemoryRegion, MemoryType, SharedProcessHandle, open_process, query_system_info,
+};
+use libmemscan::values::{Endianness, MathOp, Value, ValueType};
+use std::path::PathBuf;
 
 #[test]
 fn test_value_type_sizes() {
@@ -82,7 +65,7 @@ fn test_value_conversions() {
 #[test]
 fn test_value_from_bytes_offset() {
     let bytes = vec![0x00, 0x00, 0x42, 0x00, 0x00, 0x00];
-    
+
     // Read I32 at offset 0
     let val = Value::from_bytes(&bytes, 0, ValueType::I32).unwrap();
     match val {
@@ -107,7 +90,7 @@ fn test_filter_operations() {
     // FilterOp enum values
     assert_eq!(FilterOp::Equals, FilterOp::Equals);
     assert_ne!(FilterOp::Equals, FilterOp::LessThan);
-    
+
     // MathOp enum values
     assert_eq!(MathOp::Add, MathOp::Add);
     assert_ne!(MathOp::Add, MathOp::Subtract);
@@ -138,7 +121,7 @@ fn test_value_display() {
 #[test]
 fn test_value_subtract() {
     use libmemscan::values::value_subtract;
-    
+
     // Test integer subtraction
     let result = value_subtract(&Value::I32(100), &Value::I32(50));
     assert!(result.is_some());
@@ -146,7 +129,7 @@ fn test_value_subtract() {
         Value::I32(v) => assert_eq!(v, 50),
         _ => panic!("Wrong type"),
     }
-    
+
     // Test float subtraction
     let result = value_subtract(&Value::F64(100.0), &Value::F64(50.0));
     assert!(result.is_some());
@@ -154,7 +137,7 @@ fn test_value_subtract() {
         Value::F64(v) => assert!((v - 50.0).abs() < 0.001),
         _ => panic!("Wrong type"),
     }
-    
+
     // Test type mismatch
     let result = value_subtract(&Value::I32(100), &Value::U32(50));
     assert!(result.is_none());
@@ -163,9 +146,1644 @@ fn test_value_subtract() {
 #[test]
 fn test_value_to_f64() {
     use libmemscan::values::value_to_f64;
-    
+
     assert_eq!(value_to_f64(&Value::I32(100)), 100.0);
     assert_eq!(value_to_f64(&Value::F64(3.14)), 3.14);
     assert_eq!(value_to_f64(&Value::U64(1000)), 1000.0);
 }
 
+#[test]
+fn test_filter_own_process_buffer() {
+    let buffer: Vec<i32> = vec![5, 7, 5, 9, 5, 1];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+
+    assert_eq!(scanner.initial_scan().unwrap(), buffer.len());
+    assert_eq!(
+        scanner
+            .filter(FilterOp::Equals, Some(Value::I32(5)))
+            .unwrap(),
+        3
+    );
+    let addresses: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    let base = buffer.as_ptr() as usize;
+    assert_eq!(addresses, vec![base, base + 8, base + 16]);
+    for m in scanner.matches() {
+        assert!(matches!(m.current_value, Value::I32(5)));
+        assert!(matches!(m.previous_value, Some(Value::I32(5))));
+    }
+
+    // Nothing changed, so every remaining match is kept
+    assert_eq!(scanner.filter(FilterOp::Unchanged, None).unwrap(), 3);
+    assert_eq!(scanner.filter(FilterOp::Changed, None).unwrap(), 0);
+}
+
+#[test]
+fn test_loose_typing_own_process_buffer() {
+    let buffer: Vec<u8> = vec![200, 255, 3, 7];
+    let proc = open_process(std::process::id()).unwrap();
+    let scanner = || {
+        let region = MemoryRegion::private(buffer.as_ptr() as usize, buffer.len());
+        InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U8, false)
+    };
+
+    // Strict: literals that don't fit the match type never match
+    for (op, literal) in [
+        (FilterOp::LessThan, Value::F64(7.5)),
+        (FilterOp::GreaterThan, Value::I32(-1)),
+    ] {
+        let mut strict = scanner();
+        assert!(!strict.loose_typing());
+        assert_eq!(strict.initial_scan().unwrap(), 4);
+        assert_eq!(strict.filter(op, Some(literal)).unwrap(), 0);
+    }
+
+    // Loose: both sides are promoted, so cross-width literals compare as numbers
+    let mut loose = scanner();
+    loose.set_loose_typing(true);
+    assert_eq!(loose.initial_scan().unwrap(), 4);
+    assert_eq!(
+        loose
+            .filter(FilterOp::GreaterThan, Some(Value::I32(-1)))
+            .unwrap(),
+        4
+    );
+    assert_eq!(
+        loose
+            .filter(FilterOp::LessThan, Some(Value::F64(7.5)))
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        loose.filter(FilterOp::Equals, Some(Value::I64(7))).unwrap(),
+        1
+    );
+    assert_eq!(loose.matches()[0].address, buffer.as_ptr() as usize + 3);
+}
+
+#[test]
+fn test_scan_regions_own_process_buffer() {
+    use libmemscan::scanner::{ScanOptions, scan_regions};
+
+    let pattern: Vec<u8> = (0..16u8).map(|i| i.wrapping_mul(71) ^ 0x3C).collect();
+    let mut buffer = vec![0u8; 96];
+    for offset in [8, 32, 64] {
+        buffer[offset..offset + 16].copy_from_slice(&pattern);
+    }
+    let buffer = std::hint::black_box(buffer);
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+
+    // The inner region repeats the middle match after the outer one's last
+    let outer = MemoryRegion::private(base, buffer.len());
+    let inner = MemoryRegion::private(base + 24, 32);
+    let opts = ScanOptions::default();
+    let matches = scan_regions(&proc, &[inner, outer.clone()], &pattern, &opts).unwrap();
+    let addresses: Vec<usize> = matches.iter().map(|m| m.address).collect();
+    assert_eq!(addresses, vec![base + 8, base + 32, base + 64]);
+    assert_eq!(matches[0].module, None);
+    assert_eq!(matches[0].offset, None);
+
+    let mut region = outer;
+    region.image_file = Some("/tmp/x.bin".to_string());
+    let matches = scan_regions(&proc, &[region], &pattern, &opts).unwrap();
+    assert_eq!(matches[1].module.as_deref(), Some("x.bin"));
+    assert_eq!(matches[1].offset, Some(32));
+}
+
+#[test]
+fn test_scan_struct_own_process_buffer() {
+    // Three 16-byte entities: { health: i32 @ +0, pad: 4 bytes, speed: f32 @ +8, pad: 4 bytes }
+    let entities: [(i32, f32); 3] = [(100, 0.5), (100, 2.0), (50, 0.5)];
+    let mut buffer = vec![0u8; entities.len() * 16];
+    for (i, (health, speed)) in entities.iter().enumerate() {
+        buffer[i * 16..i * 16 + 4].copy_from_slice(&health.to_le_bytes());
+        buffer[i * 16 + 8..i * 16 + 12].copy_from_slice(&speed.to_le_bytes());
+    }
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(buffer.as_ptr() as usize, buffer.len());
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+
+    let fields = [
+        FieldSpec {
+            offset: 0,
+            value_type: ValueType::I32,
+            predicate: FieldPredicate::Equals(Value::I32(100)),
+        },
+        FieldSpec {
+            offset: 8,
+            value_type: ValueType::F32,
+            predicate: FieldPredicate::Between(Value::F64(0.0), Value::F64(1.0)),
+        },
+    ];
+    assert_eq!(scanner.scan_struct(&fields).unwrap(), 1);
+    let m = &scanner.matches()[0];
+    assert_eq!(m.address, buffer.as_ptr() as usize);
+    assert!(matches!(m.current_value, Value::I32(100)));
+
+    // The first field keeps being tracked by the regular filters
+    assert_eq!(scanner.filter(FilterOp::Unchanged, None).unwrap(), 1);
+    assert!(scanner.scan_struct(&[]).is_err());
+}
+
+#[test]
+fn test_scan_struct_offset_first_field_own_process_buffer() {
+    // Four 8-byte entities: { id: u32 @ +0, health: u32 @ +4 }
+    let buffer: Vec<u32> = vec![1, 100, 2, 50, 3, 100, 4, 75];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let base = buffer.as_ptr() as usize;
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U8, false);
+    scanner.set_alignment(1).unwrap();
+
+    let fields = [
+        FieldSpec {
+            offset: 4,
+            value_type: ValueType::U32,
+            predicate: FieldPredicate::Equals(Value::U32(100)),
+        },
+        FieldSpec {
+            offset: 0,
+            value_type: ValueType::U8,
+            predicate: FieldPredicate::GreaterThan(Value::U8(0)),
+        },
+    ];
+    // Bases step by the 4-byte struct alignment rather than the scanner's 1,
+    // and matches sit at the struct base
+    assert_eq!(scanner.scan_struct(&fields).unwrap(), 2);
+    assert_eq!(scanner.alignment(), 4);
+    assert_eq!(scanner.matches()[0].address, base);
+    assert!(matches!(scanner.matches()[0].current_value, Value::U32(1)));
+    assert_eq!(scanner.matches()[1].address, base + 16);
+    assert!(matches!(scanner.matches()[1].current_value, Value::U32(3)));
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_scan_sequence_own_process_buffer() {
+    let buffer: Vec<i32> = vec![9, 10, 20, 30, 40, 41, 5, 0, 0, 1];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let base = buffer.as_ptr() as usize;
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+
+    // Runs of three with a step of 10: 10,20,30 and 20,30,40
+    assert_eq!(scanner.scan_sequence(3, Some(Value::I32(10))).unwrap(), 2);
+    assert_eq!(scanner.matches()[0].address, base + 4);
+    assert!(matches!(scanner.matches()[1].current_value, Value::I32(20)));
+
+    // Strictly increasing runs of four: 9..30, 10..40 and 20..41
+    assert_eq!(scanner.scan_sequence(4, None).unwrap(), 3);
+    assert_eq!(scanner.matches()[2].address, base + 8);
+
+    // Literal steps are cast to the scan type, and negative steps run downwards
+    assert_eq!(
+        scanner.scan_sequence(2, Some(Value::F64(-36.0))).unwrap(),
+        1
+    );
+    assert_eq!(scanner.matches()[0].address, base + 20);
+    assert!(scanner.scan_sequence(1, None).is_err());
+}
+
+#[test]
+fn test_lazy_scanner_maps_regions_on_demand() {
+    let mut buffer: Vec<i32> = vec![0x5eed_1234, 7, 0x5eed_1234];
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+    let contains_buffer =
+        move |r: &MemoryRegion| r.base_address <= base && base < r.base_address + r.size;
+    let mut scanner =
+        InteractiveScanner::new_lazy(&proc, &query_system_info(), contains_buffer, ValueType::I32);
+    scanner.set_use_memmap(false);
+
+    // Nothing is enumerated before the first scan, and nothing stays mapped after it
+    assert_eq!(scanner.region_count(), 0);
+    assert!(scanner.initial_scan().unwrap() >= buffer.len());
+    assert_eq!(scanner.region_count(), 1);
+
+    // The first filter maps the region again
+    scanner
+        .filter(FilterOp::Equals, Some(Value::I32(0x5eed_1234)))
+        .unwrap();
+    let addresses: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    assert!(addresses.contains(&base) && addresses.contains(&(base + 8)));
+    assert_eq!(scanner.region_count(), 1);
+
+    buffer[0] = 1;
+    std::hint::black_box(&buffer);
+    scanner.filter(FilterOp::Changed, None).unwrap();
+    assert!(scanner.matches().iter().any(|m| m.address == base));
+    assert!(!scanner.matches().iter().any(|m| m.address == base + 8));
+}
+
+#[test]
+fn test_lazy_scanner_modifies_unmapped_matches() {
+    let buffer: Vec<i32> = vec![10, 20, 30];
+    let base = buffer.as_ptr() as usize;
+    let end = base + std::mem::size_of_val(&buffer[..]);
+    let proc = open_process(std::process::id()).unwrap();
+    let contains_buffer =
+        move |r: &MemoryRegion| r.base_address <= base && base < r.base_address + r.size;
+    let mut scanner =
+        InteractiveScanner::new_lazy(&proc, &query_system_info(), contains_buffer, ValueType::I32);
+    scanner.set_use_memmap(false);
+
+    // Narrowing by address reads no memory, so the region stays unmapped
+    scanner.initial_scan().unwrap();
+    scanner.filter_not_in_range(0, base).unwrap();
+    scanner.filter_not_in_range(end, usize::MAX).unwrap();
+    assert_eq!(scanner.matches().len(), buffer.len());
+
+    scanner
+        .modify_value(base, MathOp::Multiply, Value::I32(3))
+        .unwrap();
+    assert_eq!(std::hint::black_box(&buffer)[..], [30, 20, 30]);
+
+    let report = scanner.modify_all(MathOp::Add, Value::I32(1)).unwrap();
+    assert_eq!(report.succeeded, 3);
+    assert!(report.failures.is_empty());
+    assert_eq!(std::hint::black_box(&buffer)[..], [31, 21, 31]);
+}
+
+#[test]
+fn test_lazy_scanner_scans_structs_and_sequences() {
+    let buffer: Vec<u32> = vec![
+        0x5eed_1234,
+        0xc0ff_ee42,
+        0x5eed_0000,
+        0x5eed_0007,
+        0x5eed_000e,
+    ];
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+    let contains_buffer =
+        move |r: &MemoryRegion| r.base_address <= base && base < r.base_address + r.size;
+    let mut scanner =
+        InteractiveScanner::new_lazy(&proc, &query_system_info(), contains_buffer, ValueType::U32);
+    scanner.set_use_memmap(false);
+
+    // Neither needs an initial scan to have enumerated the regions first
+    let fields = [
+        FieldSpec {
+            offset: 0,
+            value_type: ValueType::U32,
+            predicate: FieldPredicate::Equals(Value::U32(0x5eed_1234)),
+        },
+        FieldSpec {
+            offset: 4,
+            value_type: ValueType::U32,
+            predicate: FieldPredicate::Equals(Value::U32(0xc0ff_ee42)),
+        },
+    ];
+    assert!(scanner.scan_struct(&fields).unwrap() >= 1);
+    assert!(scanner.matches().iter().any(|m| m.address == base));
+
+    assert!(scanner.scan_sequence(3, Some(Value::U32(7))).unwrap() >= 1);
+    assert!(scanner.matches().iter().any(|m| m.address == base + 8));
+
+    // The region holding the matches is mapped by the next filter
+    assert!(scanner.filter(FilterOp::Unchanged, None).unwrap() >= 1);
+    assert!(scanner.matches().iter().any(|m| m.address == base + 8));
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_lazy_scanner_loads_session() {
+    let buffer: Vec<i32> = vec![11, 22, 33];
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    scanner.initial_scan().unwrap();
+    scanner
+        .filter(FilterOp::GreaterThan, Some(Value::I32(15)))
+        .unwrap();
+    let path =
+        std::env::temp_dir().join(format!("memscan_lazy_session_{}.txt", std::process::id()));
+    scanner.save_session(&path, &[]).unwrap();
+
+    let contains_buffer =
+        move |r: &MemoryRegion| r.base_address <= base && base < r.base_address + r.size;
+    let mut lazy =
+        InteractiveScanner::new_lazy(&proc, &query_system_info(), contains_buffer, ValueType::U8);
+    lazy.set_use_memmap(false);
+    let loaded = lazy.load_session(&path, &[]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!((loaded.restored, loaded.unresolved), (2, 0));
+    let restored: Vec<(usize, i32)> = lazy
+        .matches()
+        .iter()
+        .map(|m| match m.current_value {
+            Value::I32(v) => (m.address, v),
+            ref other => panic!("unexpected value {:?}", other),
+        })
+        .collect();
+    assert_eq!(restored, vec![(base + 4, 22), (base + 8, 33)]);
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_change_frequency_own_process_buffer() {
+    let mut buffer: Vec<i32> = vec![0, 0, 0];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 3);
+
+    // The first slot ticks on every pass, the second changes once
+    for pass in 1..=3 {
+        buffer[0] = pass;
+        if pass == 2 {
+            buffer[1] = 7;
+        }
+        std::hint::black_box(&buffer);
+        let kept = scanner
+            .filter(FilterOp::GreaterThan, Some(Value::I32(-1)))
+            .unwrap();
+        assert_eq!(kept, 3);
+    }
+    let counts: Vec<u32> = scanner.matches().iter().map(|m| m.change_count).collect();
+    assert_eq!(counts, vec![3, 1, 0]);
+
+    let base = buffer.as_ptr() as usize;
+    assert_eq!(scanner.filter_change_frequency(1, 1).unwrap(), 1);
+    assert_eq!(scanner.matches()[0].address, base + 4);
+    assert!(scanner.filter_change_frequency(2, 1).is_err());
+}
+
+#[test]
+fn test_coalesce_own_process_buffer() {
+    // Two "objects" 64 bytes apart, each matching at several nearby slots
+    let mut buffer: Vec<i32> = vec![0; 32];
+    for i in [0, 1, 3, 16, 18] {
+        buffer[i] = 42;
+    }
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    scanner.initial_scan().unwrap();
+    assert_eq!(
+        scanner
+            .filter(FilterOp::Equals, Some(Value::I32(42)))
+            .unwrap(),
+        5
+    );
+
+    assert_eq!(scanner.coalesce(16), 2);
+    let base = buffer.as_ptr() as usize;
+    let addresses: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    assert_eq!(addresses, vec![base, base + 64]);
+    assert!(matches!(scanner.matches()[0].current_value, Value::I32(42)));
+    assert!(matches!(
+        scanner.matches()[0].previous_value,
+        Some(Value::I32(42))
+    ));
+
+    // A zero distance never merges anything
+    assert_eq!(scanner.coalesce(0), 2);
+}
+
+fn temp_dump_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("memscan-{}-{}.bin", name, std::process::id()))
+}
+
+#[test]
+fn test_dump_and_diff_own_process_buffer() {
+    let mut buffer: Vec<i32> = vec![10, 20, 30, 40];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+
+    let path = temp_dump_path("diff");
+    let dumped = scanner.dump_region(base + 4, &path).unwrap();
+    assert_eq!(dumped.base_address, base);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 16);
+
+    buffer[1] = 21;
+    buffer[3] = -1;
+    std::hint::black_box(&buffer);
+    let changes = scanner.diff_against_file(base, &path).unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].address, base + 4);
+    assert!(matches!(changes[0].old_value, Value::I32(20)));
+    assert!(matches!(changes[0].new_value, Value::I32(21)));
+    assert_eq!(changes[1].address, base + 12);
+
+    // The same dump read again as big-endian u16 halves
+    let changes = scanner
+        .diff_against_file_as(base, &path, ValueType::U16, 2, Endianness::Big)
+        .unwrap();
+    let addresses: Vec<usize> = changes.iter().map(|c| c.address).collect();
+    assert_eq!(addresses, vec![base + 4, base + 12, base + 14]);
+    assert_eq!(changes[0].old_value, Value::U16(0x1400));
+    assert_eq!(changes[0].new_value, Value::U16(0x1500));
+    assert!(
+        scanner
+            .diff_against_file_as(base, &path, ValueType::U16, 0, Endianness::Big)
+            .is_err()
+    );
+
+    // A dump of a different size is rejected
+    std::fs::write(&path, [0u8; 8]).unwrap();
+    assert!(scanner.diff_against_file(base, &path).is_err());
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(scanner.diff_against_file(base, &path).is_err());
+    assert!(scanner.dump_region(base + 64, &path).is_err());
+}
+
+#[test]
+fn test_filter_rate_own_process_buffer() {
+    let mut buffer: Vec<i32> = vec![0, 0, 0];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 3);
+
+    scanner.save_checkpoint("a".to_string()).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    buffer[0] = 100;
+    buffer[2] = 100_000;
+    std::hint::black_box(&buffer);
+    scanner.save_checkpoint("b".to_string()).unwrap();
+
+    // Checkpoints must be given in the order they were saved
+    assert!(scanner.filter_rate("b", "a", 250.0, 250.0).is_err());
+    assert!(scanner.filter_rate("a", "missing", 250.0, 250.0).is_err());
+
+    // 100 over at least 0.2s is at most 500 per second, far from 100_000's rate
+    assert_eq!(scanner.filter_rate("a", "b", 275.0, 225.0).unwrap(), 1);
+    assert_eq!(scanner.matches()[0].address, base);
+}
+
+#[test]
+fn test_memory_diff_own_process_buffer() {
+    let mut buffer: Vec<u8> = vec![1, 2, 3, 4, 5];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, buffer.len());
+    let mut diff = MemoryDiff::new(&proc);
+
+    // Taking a snapshot of the same region again replaces it
+    diff.take_snapshot(region.clone()).unwrap();
+    diff.take_snapshot(region).unwrap();
+    assert_eq!(diff.snapshot_count(), 1);
+    assert!(diff.diff_all().unwrap().is_empty());
+
+    buffer[1] = 9;
+    buffer[3] = 8;
+    std::hint::black_box(&buffer);
+    let changes = diff.diff_all().unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].address, base + 1);
+    assert_eq!((changes[0].old_value, changes[0].new_value), (2, 9));
+    assert_eq!(changes[1].address, base + 3);
+    assert_eq!((changes[1].old_value, changes[1].new_value), (4, 8));
+
+    diff.update_all_snapshots().unwrap();
+    assert!(diff.diff_all().unwrap().is_empty());
+}
+
+#[test]
+fn test_memory_diff_typed_own_process_buffer() {
+    let mut buffer: Vec<i32> = vec![10, 20, 30, 40];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut diff = MemoryDiff::new(&proc);
+    diff.take_snapshot(region).unwrap();
+
+    buffer[2] = -30;
+    std::hint::black_box(&buffer);
+    let changes = diff.diff_typed_parallel(ValueType::I32, 4).unwrap();
+    assert_eq!(changes.len(), 1);
+    let region_changes = &changes[&base];
+    assert_eq!(region_changes.len(), 1);
+    assert_eq!(region_changes[0].address, base + 8);
+    assert_eq!(region_changes[0].old_value, Value::I32(30));
+    assert_eq!(region_changes[0].new_value, Value::I32(-30));
+
+    // A byte-wise view of the same change
+    let changes = diff.diff_typed_parallel(ValueType::U8, 1).unwrap();
+    assert_eq!(changes[&base].len(), 4);
+    assert!(diff.diff_typed_parallel(ValueType::I32, 0).is_err());
+
+    // The same snapshot compared as big-endian values
+    diff.set_endianness(Endianness::Big);
+    let changes = diff.diff_typed_parallel(ValueType::I32, 4).unwrap();
+    let region_changes = &changes[&base];
+    assert_eq!(region_changes.len(), 1);
+    assert_eq!(
+        region_changes[0].new_value,
+        Value::I32(i32::from_be_bytes((-30i32).to_le_bytes()))
+    );
+}
+
+#[test]
+fn test_write_value_verified_own_process_buffer() {
+    let buffer: Vec<i32> = vec![1, 2, 3];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+
+    scanner
+        .write_value_verified(base + 4, Value::I32(42))
+        .unwrap();
+    assert_eq!(std::hint::black_box(&buffer)[1], 42);
+
+    assert_eq!(scanner.initial_scan().unwrap(), 3);
+    let report = scanner.write_all_verified(Value::I32(7)).unwrap();
+    assert_eq!(report.succeeded, 3);
+    assert!(report.failures.is_empty());
+    assert_eq!(std::hint::black_box(&buffer)[..], [7, 7, 7]);
+
+    // Unmapped memory can't be written, let alone verified
+    assert!(scanner.write_value_verified(0, Value::I32(1)).is_err());
+}
+
+#[test]
+fn test_memory_diff_changed_regions_own_process_buffer() {
+    let mut first: Vec<u8> = vec![0; 64];
+    let second: Vec<u8> = vec![0; 64];
+    let proc = open_process(std::process::id()).unwrap();
+    let mut diff = MemoryDiff::new(&proc);
+    diff.take_snapshot(MemoryRegion::private(first.as_ptr() as usize, first.len()))
+        .unwrap();
+    diff.take_snapshot(MemoryRegion::private(
+        second.as_ptr() as usize,
+        second.len(),
+    ))
+    .unwrap();
+    assert!(diff.changed_regions().unwrap().is_empty());
+
+    first[10] = 1;
+    std::hint::black_box(&first);
+    std::hint::black_box(&second);
+    assert_eq!(
+        diff.changed_regions().unwrap(),
+        vec![first.as_ptr() as usize]
+    );
+}
+
+#[test]
+fn test_set_alignment_validation() {
+    let proc = open_process(std::process::id()).unwrap();
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![], ValueType::I32, false);
+    assert_eq!(scanner.alignment(), 4);
+
+    // Zero and non-powers of two are rejected and leave the alignment alone
+    assert!(scanner.set_alignment(0).is_err());
+    assert!(scanner.set_alignment(3).is_err());
+    assert!(scanner.set_alignment_unclamped(0).is_err());
+    assert!(scanner.set_alignment_unclamped(12).is_err());
+    assert_eq!(scanner.alignment(), 4);
+
+    scanner.set_alignment(1).unwrap();
+    assert_eq!(scanner.alignment(), 1);
+
+    // Wider alignments are clamped to the value size unless asked for
+    scanner.set_alignment(16).unwrap();
+    assert_eq!(scanner.alignment(), 4);
+    scanner.set_alignment_unclamped(16).unwrap();
+    assert_eq!(scanner.alignment(), 16);
+
+    // An unclamped stride only holds for its type
+    scanner.set_value_type(ValueType::I64);
+    assert_eq!(scanner.alignment(), 8);
+
+    // A chosen alignment is clamped again to every new type
+    scanner.set_alignment(2).unwrap();
+    scanner.set_value_type(ValueType::U8);
+    assert_eq!(scanner.alignment(), 1);
+    scanner.set_value_type(ValueType::U32);
+    assert_eq!(scanner.alignment(), 2);
+}
+
+#[test]
+fn test_auto_detect_keeps_chosen_alignment() {
+    // Only the i32 reading of the first slot holds the value
+    let buffer: Vec<i32> = vec![123_456_789, -1];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    scanner.set_auto_detect(true);
+    scanner.set_alignment(2).unwrap();
+    scanner.initial_scan().unwrap();
+
+    let kept = scanner
+        .filter(FilterOp::Equals, Some(Value::I64(123_456_789)))
+        .unwrap();
+    std::hint::black_box(&buffer);
+    assert_eq!(kept, 1);
+    assert!(!scanner.auto_detect());
+    assert_eq!(scanner.value_type(), ValueType::I32);
+    assert_eq!(scanner.alignment(), 2);
+}
+
+#[test]
+fn test_unaligned_scan_own_process_buffer() {
+    let buffer: Vec<u8> = vec![0, 0x2a, 0, 0, 0, 0, 0, 0];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, buffer.len());
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    scanner.set_alignment(1).unwrap();
+
+    scanner.initial_scan().unwrap();
+    assert_eq!(
+        scanner
+            .filter(FilterOp::Equals, Some(Value::I32(42)))
+            .unwrap(),
+        1
+    );
+    assert_eq!(scanner.matches()[0].address, base + 1);
+}
+
+#[test]
+fn test_memory_diff_hashes_only_own_process_buffer() {
+    let page_size = libmemscan::process::query_system_info().page_size;
+    let mut buffer: Vec<u8> = vec![0; page_size * 3];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let mut diff = MemoryDiff::new(&proc);
+    diff.set_hashes_only(true);
+    diff.take_snapshot(MemoryRegion::private(base, buffer.len()))
+        .unwrap();
+    assert!(diff.diff_pages().unwrap().is_empty());
+
+    buffer[page_size * 2 + 7] = 1;
+    std::hint::black_box(&buffer);
+    let pages = diff.diff_pages().unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].page_index, 2);
+    assert_eq!(pages[0].address, base + page_size * 2);
+    assert_eq!(diff.changed_regions().unwrap(), vec![base]);
+
+    // Byte-level diffs need the data that hashes-only snapshots don't keep
+    assert!(diff.diff_all().is_err());
+    assert!(
+        diff.diff(&[MemoryRegion::private(base, buffer.len())])
+            .is_err()
+    );
+
+    diff.update_all_snapshots().unwrap();
+    assert!(diff.diff_pages().unwrap().is_empty());
+}
+
+#[test]
+fn test_filter_eq_address_own_process_buffer() {
+    let buffer: Vec<i32> = vec![100, 7, 100, 3, 100];
+    let reference: i32 = 100;
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 5);
+
+    // The reference doesn't have to be one of the matches
+    let ref_addr = std::hint::black_box(&reference) as *const i32 as usize;
+    assert_eq!(scanner.filter_eq_address(ref_addr).unwrap(), 3);
+    let addresses: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    assert_eq!(addresses, vec![base, base + 8, base + 16]);
+
+    assert!(scanner.filter_eq_address(0).is_err());
+}
+
+#[test]
+fn test_exclusion_filters_own_process_buffer() {
+    let buffer: Vec<i32> = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 8);
+
+    // Drop the second and third slot
+    assert_eq!(scanner.filter_not_in_range(base + 4, base + 12).unwrap(), 6);
+    assert!(scanner.filter_not_in_range(base + 4, base + 4).is_err());
+
+    // The last four slots stand in for a noisy library
+    let library = MemoryRegion {
+        type_: MemoryType::Image,
+        image_file: Some("C:\\Windows\\System32\\Noisy.dll".to_string()),
+        ..MemoryRegion::private(base + 16, 16)
+    };
+    assert_eq!(
+        scanner
+            .filter_not_module("noisy.dll", std::slice::from_ref(&library))
+            .unwrap(),
+        2
+    );
+    let kept: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    assert_eq!(kept, vec![base, base + 12]);
+    assert!(scanner.filter_not_module("other.dll", &[library]).is_err());
+}
+
+#[test]
+fn test_session_rebases_module_matches() {
+    // Two copies of the same "module" stand in for one image loaded at
+    // different bases across runs
+    let first: Vec<i32> = vec![10, 20, 30, 40];
+    let second: Vec<i32> = vec![11, 21, 31, 41];
+    let outside: i32 = 5;
+    let proc = open_process(std::process::id()).unwrap();
+    let size = std::mem::size_of_val(&first[..]);
+    let module_at = |base: usize| MemoryRegion {
+        type_: MemoryType::Image,
+        image_file: Some("/opt/game/libgame.so".to_string()),
+        ..MemoryRegion::private(base, size)
+    };
+    let first_base = first.as_ptr() as usize;
+    let second_base = second.as_ptr() as usize;
+    let outside_addr = std::hint::black_box(&outside) as *const i32 as usize;
+
+    let mut scanner = InteractiveScanner::with_memmap(
+        &proc,
+        vec![
+            MemoryRegion::private(first_base, size),
+            MemoryRegion::private(outside_addr, 4),
+        ],
+        ValueType::I32,
+        false,
+    );
+    scanner.initial_scan().unwrap();
+    scanner
+        .filter(FilterOp::GreaterThan, Some(Value::I32(15)))
+        .unwrap();
+    scanner
+        .filter(FilterOp::LessThan, Some(Value::I32(35)))
+        .unwrap();
+    assert_eq!(scanner.matches().len(), 2);
+
+    let path = std::env::temp_dir().join(format!("memscan_session_{}.txt", std::process::id()));
+    let save = scanner
+        .save_session(&path, &[module_at(first_base)])
+        .unwrap();
+    assert_eq!(save.saved, 2);
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(save.file_size, saved.len() as u64);
+    assert!(saved.starts_with("memscan-session 2\n"));
+    assert!(saved.contains(&format!("process {}", std::process::id())));
+    assert!(saved.contains("type i32\nalignment 4\n"));
+    assert!(saved.contains("module i32 4 libgame.so"));
+    assert!(saved.contains("module i32 8 libgame.so"));
+
+    let mut restarted = InteractiveScanner::with_memmap(
+        &proc,
+        vec![MemoryRegion::private(second_base, size)],
+        ValueType::U8,
+        false,
+    );
+    let loaded = restarted
+        .load_session(&path, &[module_at(second_base)])
+        .unwrap();
+    assert_eq!(loaded.restored, 2);
+    assert_eq!(loaded.unresolved, 0);
+    assert_eq!(loaded.saved_pid, Some(std::process::id()));
+    assert_eq!(restarted.value_type(), ValueType::I32);
+    assert_eq!(restarted.alignment(), 4);
+    let restored: Vec<(usize, i32)> = restarted
+        .matches()
+        .iter()
+        .map(|m| match m.current_value {
+            Value::I32(v) => (m.address, v),
+            ref other => panic!("unexpected value {:?}", other),
+        })
+        .collect();
+    assert_eq!(restored, vec![(second_base + 4, 21), (second_base + 8, 31)]);
+
+    // Without the module nothing can be re-resolved
+    let loaded = restarted.load_session(&path, &[]).unwrap();
+    assert_eq!(loaded.restored, 0);
+    assert_eq!(loaded.unresolved, 2);
+
+    // Version 1 files have no process or alignment lines
+    std::fs::write(
+        &path,
+        format!(
+            "memscan-session 1\ntype i32\nabsolute i32 {:x}\n",
+            second_base + 4
+        ),
+    )
+    .unwrap();
+    let loaded = restarted.load_session(&path, &[]).unwrap();
+    assert_eq!(loaded.restored, 1);
+    assert_eq!(loaded.saved_pid, None);
+    assert_eq!(restarted.alignment(), 4);
+
+    std::fs::write(&path, "memscan-session 99\ntype i32\n").unwrap();
+    let err = restarted.load_session(&path, &[]).unwrap_err().to_string();
+    assert!(err.contains("version 99 session file"), "{}", err);
+
+    std::fs::write(&path, "not a session\n").unwrap();
+    assert!(restarted.load_session(&path, &[]).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(all(feature = "gzip", feature = "binary-session"))]
+fn test_session_binary_and_gzip_formats() {
+    let buffer: Vec<u32> = vec![7, 8, 7, 9];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let size = std::mem::size_of_val(&buffer[..]);
+    let module = MemoryRegion {
+        type_: MemoryType::Image,
+        image_file: Some("game.exe".to_string()),
+        ..MemoryRegion::private(base, 8)
+    };
+    let mut scanner = InteractiveScanner::with_memmap(
+        &proc,
+        vec![MemoryRegion::private(base, size)],
+        ValueType::U32,
+        false,
+    );
+    scanner.initial_scan().unwrap();
+    scanner
+        .filter(FilterOp::Equals, Some(Value::U32(7)))
+        .unwrap();
+    assert_eq!(scanner.matches().len(), 2);
+
+    let dir = std::env::temp_dir();
+    let pid = std::process::id();
+    let text = dir.join(format!("memscan_session_{}.txt", pid));
+    let binary = dir.join(format!("memscan_session_{}.bin", pid));
+    let compressed = dir.join(format!("memscan_session_{}.bin.gz", pid));
+    let modules = [module];
+    let text_size = scanner.save_session(&text, &modules).unwrap().file_size;
+    let save = scanner
+        .save_session_as(
+            &binary,
+            &modules,
+            libmemscan::interactive::SessionFormat::Binary,
+        )
+        .unwrap();
+    assert_eq!(save.saved, 2);
+    assert!(save.file_size < text_size);
+    scanner
+        .save_session_as(
+            &compressed,
+            &modules,
+            libmemscan::interactive::SessionFormat::Binary,
+        )
+        .unwrap();
+    assert_eq!(std::fs::read(&compressed).unwrap()[..2], [0x1f, 0x8b]);
+
+    // The loader sniffs the format, so a binary file renamed to .txt loads too
+    std::fs::rename(&binary, &text).unwrap();
+    for path in [&text, &compressed] {
+        let mut restored = InteractiveScanner::with_memmap(
+            &proc,
+            vec![MemoryRegion::private(base, size)],
+            ValueType::U8,
+            false,
+        );
+        let loaded = restored.load_session(path, &modules).unwrap();
+        assert_eq!(loaded.restored, 2);
+        assert_eq!(loaded.saved_pid, Some(pid));
+        assert_eq!(restored.value_type(), ValueType::U32);
+        let addresses: Vec<usize> = restored.matches().iter().map(|m| m.address).collect();
+        assert_eq!(addresses, vec![base, base + 8]);
+        std::fs::remove_file(path).unwrap();
+    }
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_sample_changes_reads_own_process() {
+    use std::time::Duration;
+
+    // The polling loop itself is tested on a fake clock in the library;
+    // this checks reading the live value, which doesn't change here
+    let value: i32 = 7;
+    let proc = open_process(std::process::id()).unwrap();
+    let addr = std::hint::black_box(&value) as *const i32 as usize;
+    let scanner = InteractiveScanner::with_memmap(&proc, vec![], ValueType::I32, false);
+
+    let samples = scanner.sample_changes(addr, Duration::from_millis(20), Duration::from_millis(5));
+    assert_eq!(samples.len(), 1);
+    assert!(matches!(samples[0].1, Value::I32(7)));
+
+    // Unreadable addresses yield no samples
+    let samples = scanner.sample_changes(0, Duration::from_millis(20), Duration::from_millis(5));
+    assert!(samples.is_empty());
+}
+
+#[test]
+fn test_modify_all_reports_failures_per_address() {
+    let buffer: Vec<i32> = vec![4, 4];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    scanner.initial_scan().unwrap();
+
+    let report = scanner.modify_all(MathOp::Add, Value::I32(1)).unwrap();
+    assert_eq!(report.succeeded, 2);
+    assert!(report.failures.is_empty());
+    assert_eq!(std::hint::black_box(&buffer)[..], [5, 5]);
+
+    // An operand of another type is rejected before any match is touched
+    let err = scanner
+        .modify_all(MathOp::Add, Value::U8(1))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "operand type u8 does not match scan type i32");
+    assert_eq!(std::hint::black_box(&buffer)[..], [5, 5]);
+}
+
+#[test]
+fn test_filter_checkpoint_ratio_own_process_buffer() {
+    let mut buffer: Vec<i32> = vec![100, 80, 0, 40];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 4);
+    scanner.save_checkpoint("full".to_string()).unwrap();
+
+    // Half of 100 and 80 (within 10%), a zero base, and a quarter of 40
+    buffer.copy_from_slice(&[50, 42, 0, 10]);
+    std::hint::black_box(&buffer);
+    assert_eq!(
+        scanner.filter_checkpoint_ratio("full", 0.5, 10.0).unwrap(),
+        2
+    );
+    let addresses: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    assert_eq!(addresses, vec![base, base + 4]);
+
+    assert!(
+        scanner
+            .filter_checkpoint_ratio("missing", 0.5, 10.0)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_shared_process_handle_close() {
+    let mut shared = SharedProcessHandle::new(open_process(std::process::id()).unwrap());
+    let process = shared.share().unwrap();
+    let scanner = InteractiveScanner::with_memmap(&process, vec![], ValueType::I32, false);
+
+    // Closing is refused while the scanner's copy is alive
+    let err = shared.close().unwrap_err();
+    assert!(err.to_string().contains("still used by 1"));
+    assert!(!shared.is_closed());
+    assert!(shared.get().is_ok());
+
+    drop(scanner);
+    drop(process);
+    shared.close().unwrap();
+    assert!(shared.is_closed());
+    assert!(shared.get().unwrap_err().to_string().contains("closed"));
+    assert!(shared.share().is_err());
+    assert!(shared.close().is_err());
+}
+
+#[test]
+fn test_filter_across_regions_own_process_buffers() {
+    let first: Vec<i32> = vec![1, 2, 3, 2];
+    let second: Vec<i32> = vec![2, 9, 2, 9];
+    let third: Vec<i32> = vec![9, 9];
+    let proc = open_process(std::process::id()).unwrap();
+    let size = std::mem::size_of_val(&first[..]);
+    let (a, b, c) = (
+        first.as_ptr() as usize,
+        second.as_ptr() as usize,
+        third.as_ptr() as usize,
+    );
+    // Regions are given out of address order on purpose
+    let regions = vec![
+        MemoryRegion::private(c, 8),
+        MemoryRegion::private(a, size),
+        MemoryRegion::private(b, size),
+    ];
+    let mut scanner = InteractiveScanner::with_memmap(&proc, regions, ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 10);
+    assert_eq!(scanner.region_count(), 3);
+
+    assert_eq!(
+        scanner
+            .filter(FilterOp::Equals, Some(Value::I32(2)))
+            .unwrap(),
+        4
+    );
+    let mut addresses: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    addresses.sort_unstable();
+    let mut expected = vec![a + 4, a + 12, b, b + 8];
+    expected.sort_unstable();
+    assert_eq!(addresses, expected);
+    // The region without a 2 is dropped
+    assert_eq!(scanner.region_count(), 2);
+    std::hint::black_box((&first, &second, &third));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_write_value_forced_read_only_page() {
+    // A page of our own that is made read-only, rather than a `static`,
+    // which the compiler may assume never changes
+    let page_size = query_system_info().page_size;
+    let page = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(page, libc::MAP_FAILED);
+    let target = page.cast::<i32>();
+    unsafe {
+        target.write(0x1234_5678);
+        assert_eq!(libc::mprotect(page, page_size, libc::PROT_READ), 0);
+    }
+
+    let proc = open_process(std::process::id()).unwrap();
+    let scanner = InteractiveScanner::with_memmap(&proc, Vec::new(), ValueType::I32, false);
+    scanner
+        .write_value_forced(target as usize, Value::I32(42))
+        .unwrap();
+    let read_back = unsafe { std::ptr::read_volatile(target) };
+    assert_eq!(read_back, 42);
+    unsafe { libc::munmap(page, page_size) };
+
+    // Nothing is mapped at the null page, so both write paths fail
+    let err = scanner
+        .write_value_forced(0, Value::I32(1))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("address is not mapped"), "{}", err);
+}
+
+#[test]
+fn test_initial_scan_matches_sorted_by_address() {
+    let buffers: Vec<Vec<i32>> = (0..8).map(|i| vec![i; 16]).collect();
+    let proc = open_process(std::process::id()).unwrap();
+    // Regions are given in reverse address order on purpose
+    let mut regions: Vec<MemoryRegion> = buffers
+        .iter()
+        .map(|b| MemoryRegion::private(b.as_ptr() as usize, std::mem::size_of_val(&b[..])))
+        .collect();
+    regions.sort_by_key(|r| std::cmp::Reverse(r.base_address));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, regions, ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 8 * 16);
+
+    let addresses: Vec<usize> = scanner.matches().iter().map(|m| m.address).collect();
+    assert!(addresses.windows(2).all(|w| w[0] < w[1]));
+    std::hint::black_box(&buffers);
+}
+
+#[test]
+fn test_fill_own_process_buffer() {
+    let buffer: Vec<u8> = vec![0xAA; 64];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, buffer.len());
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U8, false);
+    scanner.initial_scan().unwrap();
+
+    assert_eq!(scanner.fill(base + 8, 0x90, 16).unwrap(), 16);
+    let buffer = std::hint::black_box(buffer);
+    assert_eq!(&buffer[..8], &[0xAA; 8]);
+    assert_eq!(&buffer[8..24], &[0x90; 16]);
+    assert_eq!(&buffer[24..], &[0xAA; 40]);
+
+    // Past the end of the region, outside any region, and empty
+    assert!(scanner.fill(base + 60, 0, 8).is_err());
+    assert!(scanner.fill(base + 64, 0, 1).is_err());
+    assert!(scanner.fill(base, 0, 0).is_err());
+}
+
+#[test]
+fn test_reinterpret_as_own_process_buffer() {
+    let buffer: Vec<u32> = vec![u32::MAX, 5, u32::MAX - 1];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U32, false);
+    scanner.initial_scan().unwrap();
+    scanner
+        .filter(FilterOp::GreaterThan, Some(Value::U32(1 << 31)))
+        .unwrap();
+    scanner.save_checkpoint("high".to_string()).unwrap();
+
+    assert_eq!(scanner.reinterpret_as(ValueType::I32).unwrap(), 2);
+    assert_eq!(scanner.value_type(), ValueType::I32);
+    let values: Vec<String> = scanner
+        .matches()
+        .iter()
+        .map(|m| format!("{:?}", m.current_value))
+        .collect();
+    assert_eq!(values, vec!["I32(-1)", "I32(-2)"]);
+    assert!(
+        scanner
+            .matches()
+            .iter()
+            .all(|m| m.value_type == ValueType::I32)
+    );
+
+    // Filters now compare as i32
+    assert_eq!(
+        scanner
+            .filter(FilterOp::LessThan, Some(Value::I32(-1)))
+            .unwrap(),
+        1
+    );
+    assert!(scanner.reinterpret_as(ValueType::I64).is_err());
+    assert!(scanner.reinterpret_as(ValueType::F32).is_ok());
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_write_many_reports_each_address() {
+    let mut ints: Vec<i32> = vec![0; 4];
+    let mut floats: Vec<f64> = vec![0.0; 2];
+    let proc = open_process(std::process::id()).unwrap();
+    let scanner = InteractiveScanner::with_memmap(&proc, Vec::new(), ValueType::I32, false);
+
+    let int_addr = ints.as_mut_ptr() as usize;
+    let float_addr = floats.as_mut_ptr() as usize;
+    // The unmapped null page in the middle must not stop the later writes
+    let report = scanner.write_many(&[
+        (int_addr, Value::I32(7)),
+        (int_addr + 8, Value::I32(-3)),
+        (0, Value::I32(1)),
+        (float_addr + 8, Value::F64(2.5)),
+    ]);
+    assert_eq!(report.succeeded, 3);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].0, 0);
+
+    let ints = std::hint::black_box(ints);
+    let floats = std::hint::black_box(floats);
+    assert_eq!(ints, [7, 0, -3, 0]);
+    assert_eq!(floats, [0.0, 2.5]);
+}
+
+#[test]
+fn test_writes_reject_values_of_another_type() {
+    let buffer: Vec<i32> = vec![5; 4];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    scanner.initial_scan().unwrap();
+
+    let err = scanner
+        .modify_value(base, MathOp::Add, Value::F32(1.0))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "operand type f32 does not match scan type i32");
+    let err = scanner
+        .write_value(base, Value::U8(1))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "value type u8 does not match scan type i32");
+    let err = scanner.write_all(Value::I64(1)).unwrap_err().to_string();
+    assert_eq!(err, "value type i64 does not match scan type i32");
+
+    // Nothing was written
+    let buffer = std::hint::black_box(buffer);
+    assert_eq!(buffer, [5; 4]);
+}
+
+#[test]
+fn test_initial_scan_match_cap() {
+    let buffer = vec![0u8; 64];
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(buffer.as_ptr() as usize, buffer.len());
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U8, false);
+    assert_eq!(
+        scanner.max_initial_matches(),
+        Some(libmemscan::interactive::DEFAULT_MAX_INITIAL_MATCHES)
+    );
+
+    scanner.set_max_initial_matches(Some(64));
+    assert_eq!(scanner.initial_scan().unwrap(), 64);
+
+    // An aborted scan leaves no matches behind
+    scanner.set_max_initial_matches(Some(63));
+    let err = scanner.initial_scan().unwrap_err().to_string();
+    assert!(err.contains("more than 63 addresses"), "{}", err);
+    assert!(scanner.matches().is_empty());
+
+    // A coarser alignment needs fewer slots
+    scanner.set_alignment_unclamped(4).unwrap();
+    assert_eq!(scanner.initial_scan().unwrap(), 16);
+
+    scanner.set_max_initial_matches(None);
+    scanner.set_alignment(1).unwrap();
+    assert_eq!(scanner.initial_scan().unwrap(), 64);
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_lazy_initial_scan_match_cap() {
+    let buffer = vec![0u8; 64];
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+    let contains_buffer =
+        move |r: &MemoryRegion| r.base_address <= base && base < r.base_address + r.size;
+    let mut scanner =
+        InteractiveScanner::new_lazy(&proc, &query_system_info(), contains_buffer, ValueType::U8);
+    scanner.set_use_memmap(false);
+    scanner.set_max_initial_matches(Some(buffer.len() - 1));
+
+    let err = scanner.initial_scan().unwrap_err().to_string();
+    assert!(err.contains("larger value type"), "{}", err);
+    assert!(scanner.matches().is_empty());
+    assert_eq!(scanner.region_count(), 0);
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_scan_generation() {
+    let buffer = vec![7u8; 64];
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(buffer.as_ptr() as usize, buffer.len());
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U8, false);
+    assert_eq!(scanner.generation(), 0);
+
+    scanner.initial_scan().unwrap();
+    assert_eq!(scanner.generation(), 1);
+    scanner
+        .filter(FilterOp::Equals, Some(Value::U8(7)))
+        .unwrap();
+    assert_eq!(scanner.generation(), 2);
+    scanner.filter_not_in_range(0, 1).unwrap();
+    assert_eq!(scanner.generation(), 3);
+
+    // A failed filter doesn't count
+    assert!(scanner.filter_eq_address(0).is_err());
+    assert_eq!(scanner.generation(), 3);
+
+    scanner.rescan().unwrap();
+    assert_eq!(scanner.generation(), 4);
+
+    scanner.set_value_type(ValueType::U16);
+    assert_eq!(scanner.generation(), 0);
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_write_bytes_masked() {
+    let buffer = vec![0xAAu8; 8];
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(buffer.as_ptr() as usize, buffer.len());
+    let scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U8, false);
+
+    let data = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mask = [true, true, false, false, true, false, false, true];
+    let written = scanner
+        .write_bytes_masked(buffer.as_ptr() as usize, &data, &mask)
+        .unwrap();
+    assert_eq!(written, 4);
+    assert_eq!(
+        std::hint::black_box(&buffer).as_slice(),
+        &[1, 2, 0xAA, 0xAA, 5, 0xAA, 0xAA, 8]
+    );
+
+    // Nothing masked writes nothing
+    let written = scanner
+        .write_bytes_masked(buffer.as_ptr() as usize, &data, &[false; 8])
+        .unwrap();
+    assert_eq!(written, 0);
+}
+
+#[test]
+fn test_unchanged_float_tolerance() {
+    let mut buffer = vec![1.0f32, 2.0, 3.0, 4.0];
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(buffer.as_ptr() as usize, buffer.len() * 4);
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::F32, false);
+    assert!(scanner.set_float_tolerance(-1.0).is_err());
+    assert!(scanner.set_float_tolerance(f64::NAN).is_err());
+    scanner.initial_scan().unwrap();
+
+    // One ulp of jitter on the first two, a real change on the third
+    buffer[0] = f32::from_bits(buffer[0].to_bits() + 1);
+    buffer[1] = f32::from_bits(buffer[1].to_bits() - 1);
+    buffer[2] = 3.5;
+    std::hint::black_box(&mut buffer);
+    scanner.set_float_tolerance(1e-5).unwrap();
+    assert_eq!(scanner.filter(FilterOp::Unchanged, None).unwrap(), 3);
+    assert_eq!(scanner.filter(FilterOp::Changed, None).unwrap(), 0);
+
+    // Exact by default
+    let region = MemoryRegion::private(buffer.as_ptr() as usize, buffer.len() * 4);
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::F32, false);
+    assert_eq!(scanner.float_tolerance(), 0.0);
+    scanner.initial_scan().unwrap();
+    buffer[0] = f32::from_bits(buffer[0].to_bits() + 1);
+    std::hint::black_box(&mut buffer);
+    assert_eq!(scanner.filter(FilterOp::Changed, None).unwrap(), 1);
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_lazy_regions_are_cached() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let buffer = vec![3u8; 64];
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+    let walks = AtomicUsize::new(0);
+    let contains_buffer = |r: &MemoryRegion| {
+        // Called for every region of a walk, so count walks by one region
+        let hit = r.base_address <= base && base < r.base_address + r.size;
+        if hit {
+            walks.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
+    };
+    let mut scanner =
+        InteractiveScanner::new_lazy(&proc, &query_system_info(), contains_buffer, ValueType::U8);
+    scanner.set_use_memmap(false);
+
+    let regions = scanner.regions();
+    assert_eq!(regions.len(), 1);
+    assert!(regions[0].contains(base));
+    assert_eq!(walks.load(Ordering::Relaxed), 1);
+
+    // Scans reuse the cached regions
+    assert!(scanner.initial_scan().unwrap() >= buffer.len());
+    assert!(scanner.rescan().unwrap() >= buffer.len());
+    assert_eq!(walks.load(Ordering::Relaxed), 1);
+
+    assert_eq!(scanner.refresh_regions(), 1);
+    assert_eq!(walks.load(Ordering::Relaxed), 2);
+    assert!(scanner.initial_scan().unwrap() >= buffer.len());
+    assert_eq!(walks.load(Ordering::Relaxed), 2);
+    std::hint::black_box(&buffer);
+}
+
+#[test]
+fn test_filter_stable_own_process_buffer() {
+    let mut buffer: Vec<i32> = vec![5, 5, 5];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 3);
+
+    // The first slot ticks, the second changes on the first pass only
+    for pass in 1..=3 {
+        buffer[0] = pass;
+        if pass == 1 {
+            buffer[1] = 9;
+        }
+        std::hint::black_box(&buffer);
+        scanner
+            .filter(FilterOp::GreaterThan, Some(Value::I32(-1)))
+            .unwrap();
+    }
+    let counts: Vec<u32> = scanner.matches().iter().map(|m| m.stable_count).collect();
+    assert_eq!(counts, vec![0, 2, 3]);
+
+    let base = buffer.as_ptr() as usize;
+    assert_eq!(scanner.filter_stable(2), 2);
+    assert_eq!(scanner.filter_stable(3), 1);
+    assert_eq!(scanner.matches()[0].address, base + 8);
+}
+
+#[test]
+fn test_u128_scan_uses_16_byte_alignment() {
+    let guid = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210u128;
+    let buffer: Vec<u128> = vec![1, guid, 3, 4];
+    std::hint::black_box(&buffer);
+    let base = buffer.as_ptr() as usize;
+    let proc = open_process(std::process::id()).unwrap();
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+
+    let mut scanner =
+        InteractiveScanner::with_memmap(&proc, vec![region.clone()], ValueType::U128, false);
+    assert_eq!(scanner.alignment(), 16);
+    assert_eq!(scanner.initial_scan().unwrap(), 4);
+    assert!(scanner.matches().iter().all(|m| m.address % 16 == 0));
+    scanner
+        .filter(FilterOp::Equals, Some(Value::U128(guid)))
+        .unwrap();
+    assert_eq!(scanner.matches().len(), 1);
+    assert_eq!(scanner.matches()[0].address, base + 16);
+    assert_eq!(scanner.matches()[0].current_value, Value::U128(guid));
+
+    // Loosening the alignment also visits the slots straddling two values
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U128, false);
+    scanner.set_alignment(8).unwrap();
+    assert_eq!(scanner.initial_scan().unwrap(), 7);
+}
+
+#[test]
+fn test_bulk_write_limit_refuses_until_confirmed() {
+    let buffer: Vec<i32> = vec![7; 4];
+    let proc = open_process(std::process::id()).unwrap();
+    let region =
+        MemoryRegion::private(buffer.as_ptr() as usize, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    scanner.initial_scan().unwrap();
+    assert_eq!(scanner.bulk_write_limit(), Some(100));
+    assert!(!scanner.bulk_write_needs_confirmation());
+
+    scanner.set_bulk_write_limit(Some(3));
+    assert!(scanner.bulk_write_needs_confirmation());
+    let err = scanner.write_all(Value::I32(1)).unwrap_err().to_string();
+    assert!(err.contains("4 addresses"), "{}", err);
+    assert!(scanner.modify_all(MathOp::Add, Value::I32(1)).is_err());
+    assert!(scanner.write_all_forced(Value::I32(1)).is_err());
+    assert_eq!(std::hint::black_box(&buffer)[..], [7; 4]);
+
+    let report = scanner
+        .without_bulk_write_limit(|scanner| scanner.write_all(Value::I32(1)))
+        .unwrap();
+    assert_eq!(report.succeeded, 4);
+    assert_eq!(std::hint::black_box(&buffer)[..], [1; 4]);
+    // The limit applies again afterwards
+    assert_eq!(scanner.bulk_write_limit(), Some(3));
+    assert!(scanner.write_all(Value::I32(2)).is_err());
+
+    scanner.set_bulk_write_limit(None);
+    assert_eq!(scanner.write_all(Value::I32(2)).unwrap().succeeded, 4);
+    assert_eq!(std::hint::black_box(&buffer)[..], [2; 4]);
+}
+
+#[test]
+fn test_checkpoint_deltas_own_process_buffer() {
+    let mut buffer: Vec<i32> = vec![10, 20, 30];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 3);
+
+    scanner.save_checkpoint("a".to_string()).unwrap();
+    buffer.copy_from_slice(&[15, 20, 0]);
+    std::hint::black_box(&buffer);
+    scanner.save_checkpoint("b".to_string()).unwrap();
+
+    assert_eq!(
+        scanner.checkpoint_deltas("a", "b").unwrap(),
+        vec![(base, 5.0), (base + 4, 0.0), (base + 8, -30.0)]
+    );
+    assert_eq!(
+        scanner.checkpoint_deltas("b", "a").unwrap()[2],
+        (base + 8, 30.0)
+    );
+    assert!(scanner.checkpoint_deltas("a", "missing").is_err());
+}
+
+#[test]
+fn test_error_kinds_own_process_buffer() {
+    let buffer: Vec<i32> = vec![10, 20];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::I32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 2);
+    std::hint::black_box(&buffer);
+
+    let err = scanner.write_value(base, Value::U8(1)).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            MemScanError::TypeMismatch {
+                found: ValueType::U8,
+                expected: ValueType::I32,
+                ..
+            }
+        ),
+        "{}",
+        err
+    );
+    let err = scanner.checkpoint_deltas("a", "b").unwrap_err();
+    assert!(matches!(err, MemScanError::CheckpointNotFound(ref name) if name == "a"));
+    let err = scanner
+        .modify_value(base + 0x1000_0000, MathOp::Add, Value::I32(1))
+        .unwrap_err();
+    assert!(matches!(err, MemScanError::NotMapped(_)), "{}", err);
+}
+
+#[test]
+fn test_filter_predicate_own_process_buffer() {
+    let mut buffer: Vec<u32> = vec![64, 0x1ff, 12, 0xff];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 4);
+
+    let low_byte_ff =
+        |current: &Value, _: Option<&Value>| matches!(current, Value::U32(v) if v & 0xff == 0xff);
+    assert_eq!(scanner.filter_predicate(low_byte_ff).unwrap(), 2);
+
+    // The closure sees the live value and the one recorded by the last pass
+    buffer[1] = 0x2ff;
+    std::hint::black_box(&buffer);
+    let grew = |current: &Value, previous: Option<&Value>| match (current, previous) {
+        (Value::U32(cur), Some(Value::U32(prev))) => cur > prev,
+        _ => false,
+    };
+    assert_eq!(scanner.filter_predicate(grew).unwrap(), 1);
+    let m = &scanner.matches()[0];
+    assert_eq!(m.address, base + 4);
+    assert_eq!(m.current_value, Value::U32(0x2ff));
+    assert_eq!(m.previous_value, Some(Value::U32(0x1ff)));
+}
+
+#[test]
+fn test_try_filter_predicate_error_keeps_matches() {
+    let mut buffer: Vec<u32> = vec![1, 2, 3, 4];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 4);
+    let generation = scanner.generation();
+
+    // Rejects the first match, then fails on the third
+    buffer[0] = 10;
+    std::hint::black_box(&buffer);
+    let mut calls = 0;
+    let result = scanner
+        .try_filter_predicate(|_, _| {
+            calls += 1;
+            match calls {
+                1 => Ok(false),
+                3 => Err("callback failed"),
+                _ => Ok(true),
+            }
+        })
+        .unwrap();
+    assert_eq!(result, Err("callback failed"));
+    assert_eq!(calls, 3);
+    assert_eq!(scanner.matches().len(), 4);
+    assert_eq!(scanner.matches()[0].current_value, Value::U32(1));
+    assert_eq!(scanner.generation(), generation);
+
+    let result = scanner.try_filter_predicate(|current, _| Ok::<_, ()>(current != &Value::U32(10)));
+    assert_eq!(result.unwrap(), Ok(3));
+}
+
+#[test]
+fn test_filter_changed_by_negative_delta_unsigned_own_process_buffer() {
+    let mut buffer: Vec<u32> = vec![100, 200, 300];
+    let proc = open_process(std::process::id()).unwrap();
+    let base = buffer.as_ptr() as usize;
+    let region = MemoryRegion::private(base, std::mem::size_of_val(&buffer[..]));
+    let mut scanner = InteractiveScanner::with_memmap(&proc, vec![region], ValueType::U32, false);
+    assert_eq!(scanner.initial_scan().unwrap(), 3);
+
+    buffer.copy_from_slice(&[95, 195, 303]);
+    std::hint::black_box(&buffer);
+    assert_eq!(scanner.filter_changed_by(-5.0, 0.0).unwrap(), 2);
+    assert_eq!(scanner.matches()[0].address, base);
+    assert_eq!(scanner.matches()[1].address, base + 4);
+
+    // Fractional deltas work on integer types within the tolerance
+    buffer.copy_from_slice(&[98, 195, 303]);
+    std::hint::black_box(&buffer);
+    assert_eq!(scanner.filter_changed_by(2.5, 0.5).unwrap(), 1);
+    assert_eq!(scanner.matches()[0].address, base);
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn test_filter_pointer_like_counts_unmapped_regions() {
+    let target: Vec<u64> = vec![0; 4];
+    let pointers: Vec<u64> = vec![target.as_ptr() as u64 + 8, 0x1234];
+    let proc = open_process(std::process::id()).unwrap();
+    let mut scanner = InteractiveScanner::with_memmap(
+        &proc,
+        vec![
+            MemoryRegion::private(pointers.as_ptr() as usize, 16),
+            MemoryRegion::private(target.as_ptr() as usize, 32),
+        ],
+        ValueType::U64,
+        false,
+    );
+    scanner.initial_scan().unwrap();
+
+    // Dropping the zeros leaves no match in `target`, which gets unmapped,
+    // but a pointer into it still points into a scanned region
+    assert_eq!(
+        scanner
+            .filter(FilterOp::GreaterThan, Some(Value::U64(0)))
+            .unwrap(),
+        2
+    );
+    assert_eq!(scanner.filter_pointer_like(&[]).unwrap(), 1);
+    assert_eq!(scanner.matches()[0].address, pointers.as_ptr() as usize);
+    std::hint::black_box((&target, &pointers));
+}
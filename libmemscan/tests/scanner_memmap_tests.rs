@@ -10,6 +10,7 @@ mod scanner_memmap_tests {
         let opts = ScanOptions {
             verbose: 0,
             all_modules: true,
+            ..Default::default()
         };
 
         assert!(opts.all_modules);
@@ -22,9 +23,23 @@ mod scanner_memmap_tests {
         let opts = ScanOptions {
             verbose: 1,
             all_modules: false,
+            ..Default::default()
         };
 
         assert!(!opts.all_modules);
         assert_eq!(opts.verbose, 1);
     }
+
+    #[test]
+    fn test_scan_options_use_memmap() {
+        // Memory mapping is enabled by default and can be turned off
+        let opts = ScanOptions::default();
+        assert!(opts.use_memmap);
+
+        let opts = ScanOptions {
+            use_memmap: false,
+            ..Default::default()
+        };
+        assert!(!opts.use_memmap);
+    }
 }
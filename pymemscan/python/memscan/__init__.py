@@ -29,14 +29,18 @@ __all__ = [
     "find_process_by_name",
     "query_system_info",
     "get_process_module_regions",
+    "get_main_module",
     "parse_hex_pattern",
     "read_process_memory",
     "write_process_memory",
     "create_interactive_scanner",
+    "create_memory_diff",
+    "snapshot_region",
     # Classes
     "PyProcessHandle",
     "PyMemoryRegion",
     "PySystemInfo",
     "PyInteractiveScanner",
     "PyMatchedAddress",
+    "PyMemoryDiff",
 ]
@@ -4,21 +4,93 @@
 //! functionality to Python scripts. The API is explicit and requires specialized
 //! function calls for fine-grained control.
 
-use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::exceptions::{
+    PyLookupError, PyOSError, PyPermissionError, PyRuntimeError, PyTypeError, PyValueError,
+};
 use pyo3::prelude::*;
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use libmemscan::interactive::{FilterOp, InteractiveScanner, MatchedAddress};
-use libmemscan::process::{
-    self, MemoryProtection, MemoryRegion, MemoryState, MemoryType, ProcessHandle, SystemInfo,
-};
+use libmemscan::MemScanError;
+use libmemscan::diff::{MemoryDiff, MemoryRegionSnapshot};
+use libmemscan::interactive::{FilterOp, InteractiveScanner, MatchedAddress, WriteReport};
+use libmemscan::process::{self, MemoryRegion, ProcessHandle, SharedProcessHandle, SystemInfo};
 use libmemscan::scanner::{self, ScanOptions};
 use libmemscan::values::{MathOp, Value, ValueType};
 
+/// Convert a libmemscan error into the Python exception for its kind, e.g.
+/// `PermissionError` when the OS refuses access to the target
+fn py_err(err: MemScanError) -> PyErr {
+    let message = err.to_string();
+    py_exception(&err, message)
+}
+
+/// Like [`py_err`], with the message prefixed by what failed
+fn py_err_in(context: &str, err: MemScanError) -> PyErr {
+    let message = format!("{}: {}", context, err);
+    py_exception(&err, message)
+}
+
+fn py_exception(err: &MemScanError, message: String) -> PyErr {
+    match err {
+        MemScanError::ProcessNotFound(_) | MemScanError::CheckpointNotFound(_) => {
+            PyLookupError::new_err(message)
+        }
+        MemScanError::AccessDenied(_) => PyPermissionError::new_err(message),
+        MemScanError::ReadFailed { .. }
+        | MemScanError::WriteFailed { .. }
+        | MemScanError::NotMapped(_)
+        | MemScanError::Io(_) => PyOSError::new_err(message),
+        MemScanError::TypeMismatch { .. } => PyTypeError::new_err(message),
+        MemScanError::InvalidPattern(_) | MemScanError::InvalidValue { .. } => {
+            PyValueError::new_err(message)
+        }
+        _ => PyRuntimeError::new_err(message),
+    }
+}
+
 /// Python wrapper for ProcessHandle
+///
+/// Scanners and diffs keep their own reference to the handle, so it can
+/// only be closed once all of them are gone.
 #[pyclass]
 struct PyProcessHandle {
-    handle: ProcessHandle,
+    handle: SharedProcessHandle,
+}
+
+impl PyProcessHandle {
+    /// The handle, or an error once it was closed
+    fn get(&self) -> PyResult<&ProcessHandle> {
+        self.handle.get().map_err(py_err)
+    }
+
+    /// A reference for a scanner or diff that keeps the handle open
+    fn share(&self) -> PyResult<Arc<ProcessHandle>> {
+        self.handle.share().map_err(py_err)
+    }
+}
+
+#[pymethods]
+impl PyProcessHandle {
+    /// Close the handle, failing while a scanner or diff still uses it
+    fn close(&mut self) -> PyResult<()> {
+        self.handle
+            .close()
+            .map_err(|e| py_err_in("Close failed", e))
+    }
+
+    /// Whether the handle has been closed
+    #[getter]
+    fn closed(&self) -> bool {
+        self.handle.is_closed()
+    }
+
+    /// Size of a pointer in the target, 4 for 32-bit (e.g. WOW64) processes
+    /// and 8 for 64-bit ones
+    #[getter]
+    fn pointer_size(&self) -> PyResult<usize> {
+        Ok(self.get()?.pointer_size())
+    }
 }
 
 /// Python wrapper for MemoryRegion
@@ -35,10 +107,44 @@ struct PyMemoryRegion {
     state: String,
     #[pyo3(get)]
     protect: String,
+    /// Path of the backing image file, for module regions
+    #[pyo3(get)]
+    image_file: Option<String>,
 }
 
 #[pymethods]
 impl PyMemoryRegion {
+    /// Create a region, e.g. to scan a known address range; the attributes
+    /// use the names `__repr__` shows, like `protect="READ_WRITE"`
+    #[new]
+    #[pyo3(signature = (
+        base_address,
+        size,
+        region_type = String::from("PRIVATE"),
+        state = String::from("COMMITTED"),
+        protect = String::from("READ_WRITE"),
+        image_file = None,
+    ))]
+    fn new(
+        base_address: usize,
+        size: usize,
+        region_type: String,
+        state: String,
+        protect: String,
+        image_file: Option<String>,
+    ) -> PyResult<Self> {
+        let region = PyMemoryRegion {
+            base_address,
+            size,
+            region_type,
+            state,
+            protect,
+            image_file,
+        };
+        to_memory_region(&region)?;
+        Ok(region)
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "MemoryRegion(base=0x{:016x}, size={}, type={}, state={}, protect={})",
@@ -71,14 +177,13 @@ impl PySystemInfo {
 }
 
 /// Python wrapper for InteractiveScanner
-/// Uses unsafe code to manage lifetime, but keeps the process handle alive
+/// The scanner borrows from `_process` with its lifetime extended to
+/// `'static`, which is sound because fields drop in declaration order
 #[pyclass(unsendable)]
 struct PyInteractiveScanner {
     scanner: Option<InteractiveScanner<'static>>,
-    process_handle: *const ProcessHandle,
     value_type: ValueType,
-    // Keep a reference to the PyProcessHandle to ensure it stays alive
-    _phantom: std::marker::PhantomData<&'static ProcessHandle>,
+    _process: Arc<ProcessHandle>,
 }
 
 /// Python wrapper for matched address
@@ -91,6 +196,10 @@ struct PyMatchedAddress {
     current_value: f64,
     #[pyo3(get)]
     previous_value: Option<f64>,
+    /// Type the value is read as, e.g. "i32", which can differ per match
+    /// after an auto-detect scan
+    #[pyo3(get)]
+    type_name: String,
 }
 
 #[pymethods]
@@ -98,17 +207,25 @@ impl PyMatchedAddress {
     fn __repr__(&self) -> String {
         match self.previous_value {
             Some(prev) => format!(
-                "MatchedAddress(addr=0x{:016x}, current={}, previous={})",
-                self.address, self.current_value, prev
+                "MatchedAddress(addr=0x{:016x}, type={}, current={}, previous={})",
+                self.address, self.type_name, self.current_value, prev
             ),
             None => format!(
-                "MatchedAddress(addr=0x{:016x}, current={})",
-                self.address, self.current_value
+                "MatchedAddress(addr=0x{:016x}, type={}, current={})",
+                self.address, self.type_name, self.current_value
             ),
         }
     }
 }
 
+/// Python wrapper for MemoryDiff
+/// Borrows from `_process` like PyInteractiveScanner
+#[pyclass(unsendable)]
+struct PyMemoryDiff {
+    diff: MemoryDiff<'static>,
+    _process: Arc<ProcessHandle>,
+}
+
 /// Convert Rust Value to f64 for Python
 fn value_to_f64(value: &Value) -> f64 {
     match value {
@@ -120,6 +237,8 @@ fn value_to_f64(value: &Value) -> f64 {
         Value::U16(v) => *v as f64,
         Value::U32(v) => *v as f64,
         Value::U64(v) => *v as f64,
+        Value::I128(v) => *v as f64,
+        Value::U128(v) => *v as f64,
         Value::F32(v) => *v as f64,
         Value::F64(v) => *v,
     }
@@ -128,16 +247,16 @@ fn value_to_f64(value: &Value) -> f64 {
 /// Open a process by its PID
 #[pyfunction]
 fn open_process(pid: u32) -> PyResult<PyProcessHandle> {
-    let handle = process::open_process(pid)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to open process: {}", e)))?;
-    Ok(PyProcessHandle { handle })
+    let handle = process::open_process(pid).map_err(|e| py_err_in("Failed to open process", e))?;
+    Ok(PyProcessHandle {
+        handle: SharedProcessHandle::new(handle),
+    })
 }
 
 /// Find a process by its name
 #[pyfunction]
 fn find_process_by_name(name: &str) -> PyResult<Option<u32>> {
-    process::find_process_by_name(name)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to find process: {}", e)))
+    process::find_process_by_name(name).map_err(|e| py_err_in("Failed to find process", e))
 }
 
 /// Get system information
@@ -155,33 +274,61 @@ fn query_system_info() -> PySystemInfo {
 /// Get process module regions
 #[pyfunction]
 fn get_process_module_regions(handle: &PyProcessHandle) -> PyResult<Vec<PyMemoryRegion>> {
-    let regions = process::get_process_module_regions(&handle.handle)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to get module regions: {}", e)))?;
-
-    Ok(regions
-        .into_iter()
-        .map(|r| PyMemoryRegion {
-            base_address: r.base_address,
-            size: r.size,
-            region_type: r.type_.to_string(),
-            state: r.state.to_string(),
-            protect: r.protect.to_string(),
-        })
-        .collect())
+    let regions = process::get_process_module_regions(handle.get()?)
+        .map_err(|e| py_err_in("Failed to get module regions", e))?;
+
+    Ok(regions.into_iter().map(to_py_region).collect())
+}
+
+/// Get the image region of the main executable, whose `image_file` is its path
+#[pyfunction]
+fn get_main_module(handle: &PyProcessHandle) -> PyResult<PyMemoryRegion> {
+    let region = process::get_main_module(handle.get()?)
+        .map_err(|e| py_err_in("Failed to get main module", e))?;
+    Ok(to_py_region(region))
+}
+
+fn to_py_region(r: MemoryRegion) -> PyMemoryRegion {
+    PyMemoryRegion {
+        base_address: r.base_address,
+        size: r.size,
+        region_type: r.type_.to_string(),
+        state: r.state.to_string(),
+        protect: r.protect.to_string(),
+        image_file: r.image_file,
+    }
 }
 
 /// Parse a hex pattern string into bytes
 #[pyfunction]
 fn parse_hex_pattern(pattern: &str) -> PyResult<Vec<u8>> {
-    libmemscan::parse_hex_pattern(pattern)
-        .map_err(|e| PyValueError::new_err(format!("Invalid hex pattern: {}", e)))
+    libmemscan::parse_hex_pattern(pattern).map_err(|e| py_err_in("Invalid hex pattern", e))
+}
+
+/// Find the first occurrence of `needle` in `haystack`
+#[pyfunction]
+fn search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    scanner::optimized_search(haystack, needle)
+}
+
+/// Find all (possibly overlapping) occurrences of `needle` in `haystack`
+#[pyfunction]
+fn search_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    scanner::search_all(haystack, needle)
+}
+
+/// Find the first occurrence of `needle` in `haystack`, only comparing the
+/// bytes whose `mask` entry is true
+#[pyfunction]
+fn search_masked(haystack: &[u8], needle: &[u8], mask: Vec<bool>) -> PyResult<Option<usize>> {
+    scanner::search_masked(haystack, needle, &mask).map_err(py_err)
 }
 
 /// Read memory from a process at a specific address
 #[pyfunction]
 fn read_process_memory(handle: &PyProcessHandle, address: usize, size: usize) -> PyResult<Vec<u8>> {
     let mut buffer = vec![0u8; size];
-    let bytes_read = process::read_process_memory(&handle.handle, address, &mut buffer);
+    let bytes_read = process::read_process_memory(handle.get()?, address, &mut buffer);
 
     if bytes_read == 0 {
         return Err(PyRuntimeError::new_err("Failed to read process memory"));
@@ -198,7 +345,7 @@ fn write_process_memory(
     address: usize,
     data: Vec<u8>,
 ) -> PyResult<usize> {
-    let bytes_written = process::write_process_memory(&handle.handle, address, &data);
+    let bytes_written = process::write_process_memory(handle.get()?, address, &data);
 
     if bytes_written == 0 {
         return Err(PyRuntimeError::new_err("Failed to write process memory"));
@@ -207,6 +354,29 @@ fn write_process_memory(
     Ok(bytes_written)
 }
 
+/// Convert a Python region back into a MemoryRegion, parsing its type,
+/// state and protection
+fn to_memory_region(r: &PyMemoryRegion) -> PyResult<MemoryRegion> {
+    let invalid = |e| py_err_in("Invalid memory region", e);
+    Ok(MemoryRegion {
+        base_address: r.base_address,
+        size: r.size,
+        protect: r.protect.parse().map_err(invalid)?,
+        state: r.state.parse().map_err(invalid)?,
+        type_: r.region_type.parse().map_err(invalid)?,
+        image_file: r.image_file.clone(),
+    })
+}
+
+/// Read exactly `size` bytes at `address`, failing on a partial read
+#[pyfunction]
+fn snapshot_region(handle: &PyProcessHandle, address: usize, size: usize) -> PyResult<Vec<u8>> {
+    let snapshot =
+        MemoryRegionSnapshot::from_process(handle.get()?, MemoryRegion::private(address, size))
+            .map_err(|e| py_err_in("Snapshot failed", e))?;
+    Ok(snapshot.data)
+}
+
 /// Create an interactive scanner for a process
 #[pyfunction]
 fn create_interactive_scanner(
@@ -214,70 +384,64 @@ fn create_interactive_scanner(
     regions: Vec<PyMemoryRegion>,
     value_type: &str,
 ) -> PyResult<PyInteractiveScanner> {
-    let vtype = match value_type.to_lowercase().as_str() {
-        "i8" => ValueType::I8,
-        "i16" => ValueType::I16,
-        "i32" => ValueType::I32,
-        "i64" => ValueType::I64,
-        "u8" => ValueType::U8,
-        "u16" => ValueType::U16,
-        "u32" => ValueType::U32,
-        "u64" => ValueType::U64,
-        "f32" => ValueType::F32,
-        "f64" => ValueType::F64,
-        _ => {
-            return Err(PyValueError::new_err(format!(
-                "Invalid value type: {}",
-                value_type
-            )));
-        }
-    };
+    let vtype = ValueType::from_name(value_type)
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid value type: {}", value_type)))?;
 
-    let rust_regions: Vec<MemoryRegion> = regions
-        .into_iter()
-        .map(|r| {
-            MemoryRegion {
-                base_address: r.base_address,
-                size: r.size,
-                type_: MemoryType::Unknown, // Simplified since we just need the address range
-                state: MemoryState {
-                    committed: true,
-                    free: false,
-                    reserved: false,
-                },
-                protect: MemoryProtection {
-                    no_access: false,
-                    read: true,
-                    write: false,
-                    execute: false,
-                    copy_on_write: false,
-                    guarded: false,
-                    no_cache: false,
-                },
-                image_file: None,
-            }
-        })
-        .collect();
-
-    // SAFETY: We're storing a raw pointer to the ProcessHandle
-    // The Python wrapper must ensure the handle outlives the scanner
-    let process_ptr = &handle.handle as *const ProcessHandle;
+    let rust_regions = regions
+        .iter()
+        .map(to_memory_region)
+        .collect::<PyResult<Vec<MemoryRegion>>>()?;
 
-    // SAFETY: We extend the lifetime of the reference here
-    // This is safe because we control both lifetimes through Python's ownership
+    let process = handle.share()?;
+    // SAFETY: The ProcessHandle lives in the Arc's allocation, which never
+    // moves and is kept alive by `_process` until after the scanner drops
     let scanner = unsafe {
-        let process_ref = &*process_ptr;
+        let process_ref = &*Arc::as_ptr(&process);
         InteractiveScanner::new(process_ref, rust_regions, vtype)
     };
 
     Ok(PyInteractiveScanner {
         scanner: Some(scanner),
-        process_handle: process_ptr,
         value_type: vtype,
-        _phantom: std::marker::PhantomData,
+        _process: process,
     })
 }
 
+/// Create a change detector for a process
+#[pyfunction]
+fn create_memory_diff(handle: &PyProcessHandle) -> PyResult<PyMemoryDiff> {
+    let process = handle.share()?;
+    // SAFETY: As in create_interactive_scanner, `_process` outlives the diff
+    let diff = unsafe { MemoryDiff::new(&*Arc::as_ptr(&process)) };
+
+    Ok(PyMemoryDiff {
+        diff,
+        _process: process,
+    })
+}
+
+/// Number of successful writes and `(address, reason)` of each failed one
+type WriteOutcome = (usize, Vec<(usize, String)>);
+
+/// Helper to convert a bulk write report into a Python tuple
+fn write_outcome(report: WriteReport) -> WriteOutcome {
+    (report.succeeded, report.failures)
+}
+
+/// Helper to run a bulk write, lifting the scanner's bulk write limit if
+/// `force` is set
+fn bulk_write(
+    scanner: &mut InteractiveScanner<'static>,
+    force: bool,
+    write: impl FnOnce(&InteractiveScanner<'static>) -> libmemscan::error::Result<WriteReport>,
+) -> libmemscan::error::Result<WriteReport> {
+    if force {
+        scanner.without_bulk_write_limit(write)
+    } else {
+        write(scanner)
+    }
+}
+
 /// Helper to convert f64 to Value based on ValueType
 fn f64_to_value(f: f64, vtype: ValueType) -> Value {
     match vtype {
@@ -289,6 +453,8 @@ fn f64_to_value(f: f64, vtype: ValueType) -> Value {
         ValueType::U16 => Value::U16(f as u16),
         ValueType::U32 => Value::U32(f as u32),
         ValueType::U64 => Value::U64(f as u64),
+        ValueType::I128 => Value::I128(f as i128),
+        ValueType::U128 => Value::U128(f as u128),
         ValueType::F32 => Value::F32(f as f32),
         ValueType::F64 => Value::F64(f),
     }
@@ -305,7 +471,43 @@ impl PyInteractiveScanner {
 
         scanner
             .initial_scan()
-            .map_err(|e| PyRuntimeError::new_err(format!("Initial scan failed: {}", e)))
+            .map_err(|e| py_err_in("Initial scan failed", e))
+    }
+
+    /// Abort initial scans that would record more than `max` addresses, or
+    /// never with None (defaults to 50 million)
+    #[pyo3(signature = (max))]
+    fn set_max_initial_matches(&mut self, max: Option<usize>) -> PyResult<()> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        scanner.set_max_initial_matches(max);
+        Ok(())
+    }
+
+    /// Refuse writes to all matches without `force` while there are more
+    /// than `limit` of them, or never with None (defaults to 100)
+    #[pyo3(signature = (limit))]
+    fn set_bulk_write_limit(&mut self, limit: Option<usize>) -> PyResult<()> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        scanner.set_bulk_write_limit(limit);
+        Ok(())
+    }
+
+    /// Get the most matches a write to all matches may touch, if limited
+    fn bulk_write_limit(&self) -> PyResult<Option<usize>> {
+        let scanner = self
+            .scanner
+            .as_ref()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        Ok(scanner.bulk_write_limit())
     }
 
     /// Filter addresses by value equality
@@ -318,7 +520,7 @@ impl PyInteractiveScanner {
         let val = f64_to_value(value, self.value_type);
         scanner
             .filter(FilterOp::Equals, Some(val))
-            .map_err(|e| PyRuntimeError::new_err(format!("Filter failed: {}", e)))
+            .map_err(|e| py_err_in("Filter failed", e))
     }
 
     /// Filter addresses by value less than
@@ -331,7 +533,7 @@ impl PyInteractiveScanner {
         let val = f64_to_value(value, self.value_type);
         scanner
             .filter(FilterOp::LessThan, Some(val))
-            .map_err(|e| PyRuntimeError::new_err(format!("Filter failed: {}", e)))
+            .map_err(|e| py_err_in("Filter failed", e))
     }
 
     /// Filter addresses by value greater than
@@ -344,7 +546,7 @@ impl PyInteractiveScanner {
         let val = f64_to_value(value, self.value_type);
         scanner
             .filter(FilterOp::GreaterThan, Some(val))
-            .map_err(|e| PyRuntimeError::new_err(format!("Filter failed: {}", e)))
+            .map_err(|e| py_err_in("Filter failed", e))
     }
 
     /// Filter addresses where value increased
@@ -356,7 +558,7 @@ impl PyInteractiveScanner {
 
         scanner
             .filter(FilterOp::Increased, None)
-            .map_err(|e| PyRuntimeError::new_err(format!("Filter failed: {}", e)))
+            .map_err(|e| py_err_in("Filter failed", e))
     }
 
     /// Filter addresses where value decreased
@@ -368,7 +570,7 @@ impl PyInteractiveScanner {
 
         scanner
             .filter(FilterOp::Decreased, None)
-            .map_err(|e| PyRuntimeError::new_err(format!("Filter failed: {}", e)))
+            .map_err(|e| py_err_in("Filter failed", e))
     }
 
     /// Filter addresses where value changed
@@ -380,7 +582,7 @@ impl PyInteractiveScanner {
 
         scanner
             .filter(FilterOp::Changed, None)
-            .map_err(|e| PyRuntimeError::new_err(format!("Filter failed: {}", e)))
+            .map_err(|e| py_err_in("Filter failed", e))
     }
 
     /// Filter addresses where value unchanged
@@ -392,7 +594,39 @@ impl PyInteractiveScanner {
 
         scanner
             .filter(FilterOp::Unchanged, None)
-            .map_err(|e| PyRuntimeError::new_err(format!("Filter failed: {}", e)))
+            .map_err(|e| py_err_in("Filter failed", e))
+    }
+
+    /// Drop matches at addresses in `lo..hi`
+    fn filter_not_in_range(&mut self, lo: usize, hi: usize) -> PyResult<usize> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        scanner
+            .filter_not_in_range(lo, hi)
+            .map_err(|e| py_err_in("Filter failed", e))
+    }
+
+    /// Keep matches for which `callback(current, previous)` is truthy
+    ///
+    /// `current` is the value read from the process for this pass and
+    /// `previous` the one recorded by the last scan or filter. If the
+    /// callback raises, the matches are left as they were and the exception
+    /// is re-raised.
+    fn filter_predicate(&mut self, callback: &Bound<'_, PyAny>) -> PyResult<usize> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        scanner
+            .try_filter_predicate(|current, previous| {
+                let args = (value_to_f64(current), previous.map(value_to_f64));
+                callback.call1(args)?.is_truthy()
+            })
+            .map_err(|e| py_err_in("Filter failed", e))?
     }
 
     /// Get list of matched addresses
@@ -409,6 +643,7 @@ impl PyInteractiveScanner {
                 address: m.address,
                 current_value: value_to_f64(&m.current_value),
                 previous_value: m.previous_value.as_ref().map(value_to_f64),
+                type_name: m.value_type.name().to_string(),
             })
             .collect())
     }
@@ -423,17 +658,32 @@ impl PyInteractiveScanner {
         Ok(scanner.matches().len())
     }
 
-    /// Set value at all matched addresses
-    fn set_value(&mut self, value: f64) -> PyResult<usize> {
+    /// Get the number of scans and filter passes so far
+    fn generation(&self) -> PyResult<u32> {
+        let scanner = self
+            .scanner
+            .as_ref()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        Ok(scanner.generation())
+    }
+
+    /// Set value at all matched addresses, returning the number of
+    /// successful writes and the address and reason of every failed one
+    ///
+    /// Raises without writing anything if there are more matches than the
+    /// bulk write limit, unless `force` is set.
+    #[pyo3(signature = (value, force = false))]
+    fn set_value(&mut self, value: f64, force: bool) -> PyResult<WriteOutcome> {
         let scanner = self
             .scanner
             .as_mut()
             .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
 
         let val = f64_to_value(value, self.value_type);
-        scanner
-            .write_all(val)
-            .map_err(|e| PyRuntimeError::new_err(format!("Set value failed: {}", e)))
+        bulk_write(scanner, force, |scanner| scanner.write_all(val))
+            .map(write_outcome)
+            .map_err(|e| py_err_in("Set value failed", e))
     }
 
     /// Set value at a specific address
@@ -446,11 +696,27 @@ impl PyInteractiveScanner {
         let val = f64_to_value(value, self.value_type);
         scanner
             .write_value(address, val)
-            .map_err(|e| PyRuntimeError::new_err(format!("Set value failed: {}", e)))
+            .map_err(|e| py_err_in("Set value failed", e))
     }
 
-    /// Add value to all matched addresses
-    fn add_value(&mut self, value: f64) -> PyResult<usize> {
+    /// Set each `(address, value)` pair in one batch, returning the same
+    /// outcome as `set_value`
+    fn write_many(&mut self, writes: Vec<(usize, f64)>) -> PyResult<WriteOutcome> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        let writes: Vec<(usize, Value)> = writes
+            .into_iter()
+            .map(|(address, value)| (address, f64_to_value(value, self.value_type)))
+            .collect();
+        Ok(write_outcome(scanner.write_many(&writes)))
+    }
+
+    /// Set value at a specific address, writing through page protections
+    /// that would refuse a plain write
+    fn set_value_forced(&mut self, address: usize, value: f64) -> PyResult<()> {
         let scanner = self
             .scanner
             .as_mut()
@@ -458,12 +724,13 @@ impl PyInteractiveScanner {
 
         let val = f64_to_value(value, self.value_type);
         scanner
-            .modify_all(MathOp::Add, val)
-            .map_err(|e| PyRuntimeError::new_err(format!("Math operation failed: {}", e)))
+            .write_value_forced(address, val)
+            .map_err(|e| py_err_in("Forced set value failed", e))
     }
 
-    /// Subtract value from all matched addresses
-    fn sub_value(&mut self, value: f64) -> PyResult<usize> {
+    /// Set value at a specific address and read it back, failing if the
+    /// target doesn't hold the new value afterwards
+    fn set_value_verified(&mut self, address: usize, value: f64) -> PyResult<()> {
         let scanner = self
             .scanner
             .as_mut()
@@ -471,34 +738,72 @@ impl PyInteractiveScanner {
 
         let val = f64_to_value(value, self.value_type);
         scanner
-            .modify_all(MathOp::Subtract, val)
-            .map_err(|e| PyRuntimeError::new_err(format!("Math operation failed: {}", e)))
+            .write_value_verified(address, val)
+            .map_err(|e| py_err_in("Verified set value failed", e))
     }
 
-    /// Multiply value at all matched addresses
-    fn mul_value(&mut self, value: f64) -> PyResult<usize> {
+    /// Add value to all matched addresses, returning the same outcome as `set_value`
+    #[pyo3(signature = (value, force = false))]
+    fn add_value(&mut self, value: f64, force: bool) -> PyResult<WriteOutcome> {
         let scanner = self
             .scanner
             .as_mut()
             .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
 
         let val = f64_to_value(value, self.value_type);
-        scanner
-            .modify_all(MathOp::Multiply, val)
-            .map_err(|e| PyRuntimeError::new_err(format!("Math operation failed: {}", e)))
+        bulk_write(scanner, force, |scanner| {
+            scanner.modify_all(MathOp::Add, val)
+        })
+        .map(write_outcome)
+        .map_err(|e| py_err_in("Math operation failed", e))
     }
 
-    /// Divide value at all matched addresses
-    fn div_value(&mut self, value: f64) -> PyResult<usize> {
+    /// Subtract value from all matched addresses, returning the same outcome as `set_value`
+    #[pyo3(signature = (value, force = false))]
+    fn sub_value(&mut self, value: f64, force: bool) -> PyResult<WriteOutcome> {
         let scanner = self
             .scanner
             .as_mut()
             .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
 
         let val = f64_to_value(value, self.value_type);
-        scanner
-            .modify_all(MathOp::Divide, val)
-            .map_err(|e| PyRuntimeError::new_err(format!("Math operation failed: {}", e)))
+        bulk_write(scanner, force, |scanner| {
+            scanner.modify_all(MathOp::Subtract, val)
+        })
+        .map(write_outcome)
+        .map_err(|e| py_err_in("Math operation failed", e))
+    }
+
+    /// Multiply value at all matched addresses, returning the same outcome as `set_value`
+    #[pyo3(signature = (value, force = false))]
+    fn mul_value(&mut self, value: f64, force: bool) -> PyResult<WriteOutcome> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        let val = f64_to_value(value, self.value_type);
+        bulk_write(scanner, force, |scanner| {
+            scanner.modify_all(MathOp::Multiply, val)
+        })
+        .map(write_outcome)
+        .map_err(|e| py_err_in("Math operation failed", e))
+    }
+
+    /// Divide value at all matched addresses, returning the same outcome as `set_value`
+    #[pyo3(signature = (value, force = false))]
+    fn div_value(&mut self, value: f64, force: bool) -> PyResult<WriteOutcome> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        let val = f64_to_value(value, self.value_type);
+        bulk_write(scanner, force, |scanner| {
+            scanner.modify_all(MathOp::Divide, val)
+        })
+        .map(write_outcome)
+        .map_err(|e| py_err_in("Math operation failed", e))
     }
 
     /// Save a checkpoint with a given name
@@ -510,7 +815,7 @@ impl PyInteractiveScanner {
 
         scanner
             .save_checkpoint(name.to_string())
-            .map_err(|e| PyRuntimeError::new_err(format!("Save checkpoint failed: {}", e)))
+            .map_err(|e| py_err_in("Save checkpoint failed", e))
     }
 
     /// List all checkpoint names
@@ -537,10 +842,7 @@ impl PyInteractiveScanner {
         if scanner.delete_checkpoint(name) {
             Ok(())
         } else {
-            Err(PyRuntimeError::new_err(format!(
-                "Checkpoint '{}' not found",
-                name
-            )))
+            Err(py_err(MemScanError::CheckpointNotFound(name.to_string())))
         }
     }
 
@@ -559,7 +861,75 @@ impl PyInteractiveScanner {
 
         scanner
             .filter_checkpoint_relative(cp1, cp2, cp3, margin)
-            .map_err(|e| PyRuntimeError::new_err(format!("Checkpoint filter failed: {}", e)))
+            .map_err(|e| py_err_in("Checkpoint filter failed", e))
+    }
+
+    /// Filter addresses whose current value is `ratio` times their value
+    /// in checkpoint `base_cp`, within `margin_percent` of the ratio
+    fn filter_checkpoint_ratio(
+        &mut self,
+        base_cp: &str,
+        ratio: f64,
+        margin_percent: f64,
+    ) -> PyResult<usize> {
+        let scanner = self
+            .scanner
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("Scanner not initialized"))?;
+
+        scanner
+            .filter_checkpoint_ratio(base_cp, ratio, margin_percent)
+            .map_err(|e| py_err_in("Checkpoint filter failed", e))
+    }
+}
+
+#[pymethods]
+impl PyMemoryDiff {
+    /// Snapshot a region, replacing any earlier snapshot of it
+    fn take_snapshot(&mut self, region: PyMemoryRegion) -> PyResult<()> {
+        self.diff
+            .take_snapshot(to_memory_region(&region)?)
+            .map_err(|e| py_err_in("Snapshot failed", e))
+    }
+
+    /// Get the bytes that changed since the snapshots were taken, as
+    /// dicts with `address`, `old` and `new` keys sorted by address
+    fn diff(&self) -> PyResult<Vec<HashMap<&'static str, usize>>> {
+        let changes = self
+            .diff
+            .diff_all()
+            .map_err(|e| py_err_in("Diff failed", e))?;
+
+        Ok(changes
+            .into_iter()
+            .map(|c| {
+                HashMap::from([
+                    ("address", c.address),
+                    ("old", c.old_value as usize),
+                    ("new", c.new_value as usize),
+                ])
+            })
+            .collect())
+    }
+
+    /// Get the base addresses of regions that changed since their snapshot,
+    /// compared by hash without collecting the changed bytes
+    fn changed_regions(&self) -> PyResult<Vec<usize>> {
+        self.diff
+            .changed_regions()
+            .map_err(|e| py_err_in("Diff failed", e))
+    }
+
+    /// Refresh all snapshots to the current memory state
+    fn update_snapshot(&mut self) -> PyResult<()> {
+        self.diff
+            .update_all_snapshots()
+            .map_err(|e| py_err_in("Update snapshot failed", e))
+    }
+
+    /// Get number of snapshotted regions
+    fn snapshot_count(&self) -> usize {
+        self.diff.snapshot_count()
     }
 }
 
@@ -570,16 +940,23 @@ fn memscan(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(find_process_by_name, m)?)?;
     m.add_function(wrap_pyfunction!(query_system_info, m)?)?;
     m.add_function(wrap_pyfunction!(get_process_module_regions, m)?)?;
+    m.add_function(wrap_pyfunction!(get_main_module, m)?)?;
     m.add_function(wrap_pyfunction!(parse_hex_pattern, m)?)?;
+    m.add_function(wrap_pyfunction!(search, m)?)?;
+    m.add_function(wrap_pyfunction!(search_all, m)?)?;
+    m.add_function(wrap_pyfunction!(search_masked, m)?)?;
     m.add_function(wrap_pyfunction!(read_process_memory, m)?)?;
     m.add_function(wrap_pyfunction!(write_process_memory, m)?)?;
     m.add_function(wrap_pyfunction!(create_interactive_scanner, m)?)?;
+    m.add_function(wrap_pyfunction!(create_memory_diff, m)?)?;
+    m.add_function(wrap_pyfunction!(snapshot_region, m)?)?;
 
     m.add_class::<PyProcessHandle>()?;
     m.add_class::<PyMemoryRegion>()?;
     m.add_class::<PySystemInfo>()?;
     m.add_class::<PyInteractiveScanner>()?;
     m.add_class::<PyMatchedAddress>()?;
+    m.add_class::<PyMemoryDiff>()?;
 
     Ok(())
 }
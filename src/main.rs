@@ -1,12 +1,23 @@
 use clap::{Parser, Subcommand, ValueHint, builder::styling::AnsiColor};
 use libmemscan::{
-    parse_hex_pattern,
-    process::{find_process_by_name, get_process_module_regions, open_process, query_system_info},
-    scanner::{ScanOptions, scan_process},
-    values::ValueType,
+    color::{self, OwoColorize},
+    fmt::format_address,
+    parse_code_mask, parse_hex_pattern, parse_ida_signature, parse_size,
+    process::{
+        RegionFilter, enumerate_threads, find_process_by_name, find_process_by_name_timeout,
+        find_process_by_window_title, find_processes_matching, find_processes_matching_timeout,
+        get_main_module, get_process_module_regions, open_process, query_system_info,
+    },
+    scanner::{
+        OutputFormat, ScanOptions, diff_scan_results, parse_jsonl_matches, patch_targets,
+        print_scan_delta, replace_pattern, scan_process_collect, scan_process_to,
+    },
 };
-use owo_colors::OwoColorize;
+use std::io::{self, BufWriter, IsTerminal};
+use std::path::PathBuf;
+use std::time::Duration;
 
+mod progress;
 mod repl;
 
 /// MemScan – inspect another process's virtual memory.
@@ -29,6 +40,10 @@ pub struct Cli {
     #[arg(short, long, global = true, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
+    /// Disable colored output (also disabled by a non-empty NO_COLOR)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -39,87 +54,468 @@ pub enum Command {
     #[command(alias = "s")]
     Scan {
         /// Target process executable name or id (e.g. "notepad", "notepad.exe", or 1234)
-        target: String,
+        #[arg(required_unless_present_any = ["match_pattern", "window"])]
+        target: Option<String>,
+
+        /// Select the target by a glob on its process name instead (e.g. "*host*")
+        #[arg(long = "match", value_name = "GLOB", conflicts_with = "target")]
+        match_pattern: Option<String>,
+
+        /// Select the target by part of its window title instead (e.g. "Game Title",
+        /// case-insensitive; Windows only)
+        #[arg(long, value_name = "TITLE", conflicts_with_all = ["target", "match_pattern"])]
+        window: Option<String>,
 
         /// Optional hex pattern to search for (e.g. "DEADBEEF")
         #[arg(short, long, value_hint = ValueHint::Other)]
         pattern: Option<String>,
 
+        /// Search for a signature with wildcards instead, as pasted from a
+        /// disassembler (e.g. "48 8B 05 ? ? ? ?", or the code "\x48\x8B\x05\x00"
+        /// of an x64dbg code and mask pair)
+        #[arg(long, value_name = "SIG", conflicts_with = "pattern")]
+        sig: Option<String>,
+
+        /// Read --sig as x64dbg-style code whose bytes are matched where this
+        /// mask has an x and skipped where it has a ? (e.g. "xxx?")
+        #[arg(long, value_name = "MASK", requires = "sig")]
+        sig_mask: Option<String>,
+
         /// Scan all modules, including those not originating from the target process
         /// (by default, only the process's own modules are scanned)
         #[arg(long)]
         all_modules: bool,
+
+        /// Read memory page by page instead of mapping whole regions
+        /// (slower, but useful when mapping regions fails repeatedly)
+        #[arg(long)]
+        no_memmap: bool,
+
+        /// Bytes to read per call with --no-memmap, rounded up to whole pages
+        /// (e.g. 1M; defaults to the page size)
+        #[arg(long = "chunk", value_name = "SIZE", value_parser = parse_size, requires = "no_memmap")]
+        read_chunk: Option<usize>,
+
+        /// Only scan writable regions (where most mutable state lives)
+        #[arg(long)]
+        writable_only: bool,
+
+        /// Skip executable regions such as code sections
+        #[arg(long)]
+        no_exec: bool,
+
+        /// Skip regions smaller than this size (e.g. 4K, 64M)
+        #[arg(long = "min-region", value_name = "SIZE", value_parser = parse_size)]
+        min_region_size: Option<usize>,
+
+        /// Skip regions larger than this size (e.g. 64M, 1G)
+        #[arg(long = "max-region", value_name = "SIZE", value_parser = parse_size)]
+        max_region_size: Option<usize>,
+
+        /// Only scan the stacks of the target's threads (e.g. for local variables)
+        #[arg(long)]
+        stacks_only: bool,
+
+        /// Also enumerate reserved regions that aren't committed (e.g. for forensic dumps)
+        #[arg(long)]
+        include_reserved: bool,
+
+        /// Also enumerate regions with guard pages (touching them raises a
+        /// guard page exception in the target on Windows)
+        #[arg(long)]
+        include_guarded: bool,
+
+        /// Also enumerate no-access regions
+        #[arg(long)]
+        include_no_access: bool,
+
+        /// Output format for matches (human, jsonl, csv)
+        #[arg(short, long, default_value = "human")]
+        output: String,
+
+        /// Stop scanning after this many matches
+        #[arg(long = "max", value_name = "N")]
+        max_matches: Option<usize>,
+
+        /// Print the instructions at each match
+        /// (a hexdump unless built with the `disasm` feature)
+        #[arg(long)]
+        disasm: bool,
+
+        /// Give up on the process lookup and stop scanning after this many
+        /// seconds, keeping the matches found so far
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+
+        /// Compare against the matches of an earlier `--output jsonl` scan
+        /// and print only the addresses that appeared or disappeared
+        #[arg(long, value_name = "PREV.jsonl", value_hint = ValueHint::FilePath, conflicts_with = "disasm")]
+        diff: Option<PathBuf>,
+    },
+    /// Overwrite every occurrence of a byte pattern with another one
+    #[command(alias = "p")]
+    Patch {
+        /// Target process executable name or id (e.g. "notepad", "notepad.exe", or 1234)
+        #[arg(required_unless_present = "match_pattern")]
+        target: Option<String>,
+
+        /// Select the target by a glob on its process name instead (e.g. "*host*")
+        #[arg(long = "match", value_name = "GLOB", conflicts_with = "target")]
+        match_pattern: Option<String>,
+
+        /// Hex pattern to search for (e.g. "DEADBEEF")
+        #[arg(long, value_hint = ValueHint::Other)]
+        find: String,
+
+        /// Hex pattern written over each occurrence, of the same length
+        #[arg(long, value_hint = ValueHint::Other)]
+        replace: String,
+
+        /// Only list the addresses that would be patched
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Patch all modules, including those not originating from the target process
+        /// (by default, only the process's own modules are patched)
+        #[arg(long)]
+        all_modules: bool,
+
+        /// Only patch writable regions
+        #[arg(long)]
+        writable_only: bool,
+
+        /// Skip executable regions such as code sections
+        #[arg(long)]
+        no_exec: bool,
+
+        /// Stop after this many occurrences
+        #[arg(long = "max", value_name = "N")]
+        max_matches: Option<usize>,
     },
     /// Interactive mode for iterative memory scanning and modification
     #[command(alias = "i")]
     Interactive {
         /// Target process executable name or id (e.g. "notepad", "notepad.exe", or 1234)
-        target: String,
+        #[arg(required_unless_present_any = ["match_pattern", "window"])]
+        target: Option<String>,
+
+        /// Select the target by a glob on its process name instead (e.g. "*host*")
+        #[arg(long = "match", value_name = "GLOB", conflicts_with = "target")]
+        match_pattern: Option<String>,
 
-        /// Value type to scan for (i8, i16, i32, i64, u8, u16, u32, u64, f32, f64)
+        /// Select the target by part of its window title instead (e.g. "Game Title",
+        /// case-insensitive; Windows only)
+        #[arg(long, value_name = "TITLE", conflicts_with_all = ["target", "match_pattern"])]
+        window: Option<String>,
+
+        /// Value type to scan for (i8, i16, i32, i64, u8, u16, u32, u64, i128, u128, f32, f64)
         #[arg(short = 't', long, default_value = "i32")]
         value_type: String,
 
+        /// Detect the value type automatically by trying i16, i32, i64, f32
+        /// and f64 until filters narrow the matches down to one type
+        #[arg(long, conflicts_with = "value_type")]
+        auto: bool,
+
         /// Scan all modules, including those not originating from the target process
         /// (by default, only the process's own modules are scanned)
         #[arg(long)]
         all_modules: bool,
+
+        /// Read memory page by page instead of mapping whole regions
+        /// (slower, but useful when mapping regions fails repeatedly)
+        #[arg(long)]
+        no_memmap: bool,
+
+        /// Only scan writable regions (where most mutable state lives)
+        #[arg(long)]
+        writable_only: bool,
+
+        /// Skip executable regions such as code sections
+        #[arg(long)]
+        no_exec: bool,
+
+        /// Skip regions smaller than this size (e.g. 4K, 64M)
+        #[arg(long = "min-region", value_name = "SIZE", value_parser = parse_size)]
+        min_region_size: Option<usize>,
+
+        /// Skip regions larger than this size (e.g. 64M, 1G)
+        #[arg(long = "max-region", value_name = "SIZE", value_parser = parse_size)]
+        max_region_size: Option<usize>,
+
+        /// Only scan the stacks of the target's threads (e.g. for local variables)
+        #[arg(long)]
+        stacks_only: bool,
+
+        /// Also enumerate reserved regions that aren't committed (e.g. for forensic dumps)
+        #[arg(long)]
+        include_reserved: bool,
+
+        /// Also enumerate regions with guard pages (touching them raises a
+        /// guard page exception in the target on Windows)
+        #[arg(long)]
+        include_guarded: bool,
+
+        /// Also enumerate no-access regions
+        #[arg(long)]
+        include_no_access: bool,
+
+        /// Also scan shared and file mapping regions
+        /// (by default only private memory such as heaps and stacks is scanned)
+        #[arg(long)]
+        include_mapped: bool,
+
+        /// Also scan image regions of loaded executables and libraries
+        #[arg(long)]
+        include_image: bool,
+
+        /// Most addresses the initial scan may record before it gives up,
+        /// or 0 or "none" for no limit (defaults to 50000000)
+        #[arg(long, value_name = "N")]
+        max_initial_matches: Option<String>,
     },
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    if cli.no_color {
+        color::set_enabled(false);
+    }
     match cli.command {
         Command::Scan {
             target,
+            match_pattern,
+            window,
             pattern,
+            sig,
+            sig_mask,
             all_modules,
+            no_memmap,
+            read_chunk,
+            writable_only,
+            no_exec,
+            min_region_size,
+            max_region_size,
+            stacks_only,
+            include_reserved,
+            include_guarded,
+            include_no_access,
+            output,
+            max_matches,
+            disasm,
+            timeout,
+            diff,
         } => {
-            let pid = resolve_target(&target)?;
+            let timeout = timeout.map(Duration::from_secs);
+            let output = parse_output_format(&output)?;
+            // Keep stdout machine-readable for structured output formats
+            let log = output == OutputFormat::Human;
+            if !log {
+                color::set_enabled(false);
+            }
+            let pid = resolve_target(
+                target.as_deref(),
+                match_pattern.as_deref(),
+                window.as_deref(),
+                log,
+                timeout,
+            )?;
             let proc = open_process(pid)?;
+            let pointer_size = proc.pointer_size();
 
             let sys = query_system_info();
-            println!(
-                "{} system info: min_addr={:016x}, max_addr={:016x}, page_size={}, granularity={}",
-                "[info]".bright_cyan(),
-                sys.min_app_addr,
-                sys.max_app_addr,
-                sys.page_size,
-                sys.granularity
-            );
+            if log {
+                println!(
+                    "{} system info: min_addr={}, max_addr={}, page_size={}, granularity={}",
+                    "[info]".bright_cyan(),
+                    format_address(sys.min_app_addr, pointer_size),
+                    format_address(sys.max_app_addr, pointer_size),
+                    sys.page_size,
+                    sys.granularity
+                );
+            }
 
             let modules = get_process_module_regions(&proc)?;
-            println!(
-                "{} found {} module regions",
-                "[info]".bright_cyan(),
-                modules.len()
-            );
+            if log {
+                println!(
+                    "{} found {} module regions",
+                    "[info]".bright_cyan(),
+                    modules.len()
+                );
+                print_main_module(&proc);
+            }
 
-            let Some(pattern) = pattern.as_ref().map(|s| parse_hex_pattern(s)).transpose()? else {
-                anyhow::bail!("a hex pattern must be specified for scanning");
+            let (pattern, pattern_mask) = match (pattern, sig) {
+                (Some(pattern), _) => (parse_hex_pattern(&pattern)?, None),
+                (None, Some(sig)) => {
+                    let (bytes, mask) = match sig_mask {
+                        Some(mask) => parse_code_mask(&sig, &mask)?,
+                        None => parse_ida_signature(&sig)?,
+                    };
+                    (bytes, Some(mask))
+                }
+                (None, None) => {
+                    anyhow::bail!("a hex pattern or --sig must be specified for scanning")
+                }
             };
 
             let opts = ScanOptions {
                 verbose: cli.verbose,
                 all_modules,
+                use_memmap: !no_memmap,
+                read_chunk: read_chunk.unwrap_or(sys.page_size),
+                output,
+                max_matches,
+                only_writable: writable_only,
+                skip_executable: no_exec,
+                min_region_size,
+                max_region_size,
+                stack_pointers: stacks_only
+                    .then(|| thread_stack_pointers(&proc, log))
+                    .transpose()?,
+                disasm,
+                timeout,
+                pattern_mask,
+                progress: progress::progress_bar(&sys, log && cli.verbose == 0),
+                region_filter: RegionFilter {
+                    include_reserved,
+                    include_guarded,
+                    include_no_access,
+                },
+                ..Default::default()
             };
 
-            scan_process(&proc, &sys, &pattern, &opts, &modules)?;
+            if let Some(path) = diff {
+                let previous = std::fs::read_to_string(&path)
+                    .map_err(|e| anyhow::anyhow!("failed to read {}: {}", path.display(), e))?;
+                let before = parse_jsonl_matches(&previous)
+                    .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+                let report = scan_process_collect(&proc, &sys, &pattern, &opts, &modules)?;
+                if report.truncated {
+                    eprintln!(
+                        "{} stopped at {} matches (use --max to raise), the delta is incomplete",
+                        "[warn]".yellow(),
+                        report.matches.len()
+                    );
+                }
+                if report.timed_out {
+                    eprintln!(
+                        "{} stopped after {:.1}s (use --timeout to raise), the delta is incomplete",
+                        "[warn]".yellow(),
+                        report.elapsed.as_secs_f64()
+                    );
+                }
+                print_scan_delta(
+                    &diff_scan_results(&before, &report.matches),
+                    output,
+                    pointer_size,
+                );
+            } else {
+                let color = io::stdout().is_terminal();
+                let mut out = BufWriter::new(io::stdout().lock());
+                scan_process_to(&proc, &sys, &pattern, &opts, &modules, &mut out, color)?;
+            }
+        }
+        Command::Patch {
+            target,
+            match_pattern,
+            find,
+            replace,
+            dry_run,
+            all_modules,
+            writable_only,
+            no_exec,
+            max_matches,
+        } => {
+            let find = parse_hex_pattern(&find)?;
+            let replace = parse_hex_pattern(&replace)?;
+            if find.len() != replace.len() {
+                anyhow::bail!(
+                    "--find and --replace must have the same length ({} vs {} bytes)",
+                    find.len(),
+                    replace.len()
+                );
+            }
+            let pid = resolve_target(
+                target.as_deref(),
+                match_pattern.as_deref(),
+                None,
+                true,
+                None,
+            )?;
+            let proc = open_process(pid)?;
+            let pointer_size = proc.pointer_size();
+            let sys = query_system_info();
+            let modules = get_process_module_regions(&proc)?;
+
+            let opts = ScanOptions {
+                verbose: cli.verbose,
+                all_modules,
+                max_matches,
+                only_writable: writable_only,
+                skip_executable: no_exec,
+                ..Default::default()
+            };
+            if dry_run {
+                let report = scan_process_collect(&proc, &sys, &find, &opts, &modules)?;
+                let targets = patch_targets(&report.matches, find.len());
+                for address in &targets {
+                    println!(
+                        "{}  {}",
+                        "[match]".bright_green(),
+                        format_address(*address, pointer_size)
+                    );
+                }
+                println!(
+                    "{} {} occurrences would be patched (dry run, nothing written)",
+                    "[done]".bright_cyan(),
+                    targets.len()
+                );
+            } else {
+                let patched = replace_pattern(&proc, &sys, &find, &replace, &modules, &opts)?;
+                println!("{} patched {} occurrences", "[done]".bright_cyan(), patched);
+            }
         }
         Command::Interactive {
             target,
+            match_pattern,
+            window,
             value_type,
+            auto,
             all_modules,
+            no_memmap,
+            writable_only,
+            no_exec,
+            min_region_size,
+            max_region_size,
+            stacks_only,
+            include_reserved,
+            include_guarded,
+            include_no_access,
+            include_mapped,
+            include_image,
+            max_initial_matches,
         } => {
-            let pid = resolve_target(&target)?;
+            let max_initial_matches = max_initial_matches
+                .as_deref()
+                .map(parse_match_limit)
+                .transpose()?;
+            let pid = resolve_target(
+                target.as_deref(),
+                match_pattern.as_deref(),
+                window.as_deref(),
+                true,
+                None,
+            )?;
             let proc = open_process(pid)?;
+            let pointer_size = proc.pointer_size();
 
             let sys = query_system_info();
             println!(
-                "{} system info: min_addr={:016x}, max_addr={:016x}, page_size={}, granularity={}",
+                "{} system info: min_addr={}, max_addr={}, page_size={}, granularity={}",
                 "[info]".bright_cyan(),
-                sys.min_app_addr,
-                sys.max_app_addr,
+                format_address(sys.min_app_addr, pointer_size),
+                format_address(sys.max_app_addr, pointer_size),
                 sys.page_size,
                 sys.granularity
             );
@@ -130,48 +526,191 @@ fn main() -> anyhow::Result<()> {
                 "[info]".bright_cyan(),
                 modules.len()
             );
+            print_main_module(&proc);
 
-            let vtype = parse_value_type(&value_type)?;
-            let mut repl = repl::Repl::new(&proc, &sys, vtype, all_modules, &modules)?;
+            let vtype = repl::parse_value_type(&value_type)?;
+            let opts = ScanOptions {
+                verbose: cli.verbose,
+                all_modules,
+                use_memmap: !no_memmap,
+                only_writable: writable_only,
+                skip_executable: no_exec,
+                min_region_size,
+                max_region_size,
+                stack_pointers: stacks_only
+                    .then(|| thread_stack_pointers(&proc, true))
+                    .transpose()?,
+                include_mapped,
+                include_image,
+                progress: progress::progress_bar(&sys, cli.verbose == 0),
+                region_filter: RegionFilter {
+                    include_reserved,
+                    include_guarded,
+                    include_no_access,
+                },
+                ..Default::default()
+            };
+            let mut repl = repl::Repl::new(&proc, &sys, vtype, auto, &opts, &modules)?;
+            if let Some(max) = max_initial_matches {
+                repl.set_max_initial_matches(max);
+            }
             repl.run()?;
         }
     }
     Ok(())
 }
 
-fn resolve_target(target: &str) -> anyhow::Result<u32> {
+/// Print where the target's main executable is loaded, for `exe+offset` addressing
+fn print_main_module(proc: &libmemscan::process::ProcessHandle) {
+    match get_main_module(proc) {
+        Ok(main) => println!(
+            "{} main module {} at {} ({} bytes)",
+            "[info]".bright_cyan(),
+            main.image_file.as_deref().unwrap_or("unknown"),
+            format_address(main.base_address, proc.pointer_size()),
+            main.size
+        ),
+        Err(err) => println!("{} main module not found: {}", "[warn]".yellow(), err),
+    }
+}
+
+/// Stack pointers of the target's threads, for `--stacks-only`
+fn thread_stack_pointers(
+    proc: &libmemscan::process::ProcessHandle,
+    log: bool,
+) -> anyhow::Result<Vec<usize>> {
+    let threads = enumerate_threads(proc)?;
+    let stack_pointers: Vec<usize> = threads.iter().filter_map(|t| t.stack_pointer).collect();
+    if stack_pointers.is_empty() {
+        anyhow::bail!(
+            "could not read the stack pointer of any of the {} threads",
+            threads.len()
+        );
+    }
+    if log {
+        println!(
+            "{} scanning the stacks of {} of {} threads",
+            "[info]".bright_cyan(),
+            stack_pointers.len(),
+            threads.len()
+        );
+    }
+    Ok(stack_pointers)
+}
+
+fn resolve_target(
+    target: Option<&str>,
+    match_pattern: Option<&str>,
+    window: Option<&str>,
+    log: bool,
+    timeout: Option<Duration>,
+) -> anyhow::Result<u32> {
+    if let Some(pattern) = match_pattern {
+        return resolve_match(pattern, log, timeout);
+    }
+    if let Some(title) = window {
+        return resolve_window(title, log);
+    }
+    let Some(target) = target else {
+        anyhow::bail!("a target process, --match pattern or --window title must be specified");
+    };
     if target.chars().all(|c| c.is_ascii_digit()) {
         let pid: u32 = target.parse()?;
-        println!("{} target pid={}", "[info]".bright_cyan(), pid);
+        if log {
+            println!("{} target pid={}", "[info]".bright_cyan(), pid);
+        }
         Ok(pid)
     } else {
+        if log {
+            println!(
+                "{} looking up process by name: {}",
+                "[info]".bright_cyan(),
+                target
+            );
+        }
+        let pid = match timeout {
+            Some(timeout) => find_process_by_name_timeout(target, timeout)?,
+            None => find_process_by_name(target)?,
+        }
+        .ok_or_else(|| anyhow::anyhow!("process with name '{}' not found", target))?;
+        if log {
+            println!("{} found pid={}", "[info]".bright_cyan(), pid);
+        }
+        Ok(pid)
+    }
+}
+
+fn resolve_window(title: &str, log: bool) -> anyhow::Result<u32> {
+    if log {
         println!(
-            "{} looking up process by name: {}",
+            "{} looking up process by window title: {}",
             "[info]".bright_cyan(),
-            target
+            title
         );
-        let pid = find_process_by_name(&target)?
-            .ok_or_else(|| anyhow::anyhow!("process with name '{}' not found", target))?;
+    }
+    let pid = find_process_by_window_title(title)?
+        .ok_or_else(|| anyhow::anyhow!("no window with a title containing '{}' found", title))?;
+    if log {
         println!("{} found pid={}", "[info]".bright_cyan(), pid);
-        Ok(pid)
     }
+    Ok(pid)
 }
 
-fn parse_value_type(s: &str) -> anyhow::Result<ValueType> {
+fn resolve_match(pattern: &str, log: bool, timeout: Option<Duration>) -> anyhow::Result<u32> {
+    if log {
+        println!(
+            "{} looking up process matching: {}",
+            "[info]".bright_cyan(),
+            pattern
+        );
+    }
+    let processes = match timeout {
+        Some(timeout) => find_processes_matching_timeout(pattern, timeout)?,
+        None => find_processes_matching(pattern)?,
+    };
+    match processes.as_slice() {
+        [] => anyhow::bail!("no process matching '{}' found", pattern),
+        [(pid, name)] => {
+            if log {
+                println!("{} found pid={} ({})", "[info]".bright_cyan(), pid, name);
+            }
+            Ok(*pid)
+        }
+        _ => {
+            let candidates: Vec<String> = processes
+                .iter()
+                .map(|(pid, name)| format!("{} ({})", pid, name))
+                .collect();
+            anyhow::bail!(
+                "'{}' matches {} processes: {}; use a narrower pattern or a pid",
+                pattern,
+                processes.len(),
+                candidates.join(", ")
+            )
+        }
+    }
+}
+
+fn parse_output_format(s: &str) -> anyhow::Result<OutputFormat> {
     Ok(match s.to_lowercase().as_str() {
-        "i8" => ValueType::I8,
-        "i16" => ValueType::I16,
-        "i32" => ValueType::I32,
-        "i64" => ValueType::I64,
-        "u8" => ValueType::U8,
-        "u16" => ValueType::U16,
-        "u32" => ValueType::U32,
-        "u64" => ValueType::U64,
-        "f32" => ValueType::F32,
-        "f64" => ValueType::F64,
+        "human" => OutputFormat::Human,
+        "jsonl" | "json" => OutputFormat::Jsonl,
+        "csv" => OutputFormat::Csv,
         _ => anyhow::bail!(
-            "Unknown value type: {}. Valid types: i8, i16, i32, i64, u8, u16, u32, u64, f32, f64",
+            "Unknown output format: {}. Valid formats: human, jsonl, csv",
             s
         ),
     })
 }
+
+/// Parse a match limit, where 0 or `none` means no limit
+fn parse_match_limit(s: &str) -> anyhow::Result<Option<usize>> {
+    if s.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    match s.parse::<usize>() {
+        Ok(0) => Ok(None),
+        Ok(max) => Ok(Some(max)),
+        Err(_) => anyhow::bail!("Invalid match limit: {}. Use a count, 0 or none", s),
+    }
+}
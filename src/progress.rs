@@ -0,0 +1,79 @@
+//! Progress bar for long scans, drawn on stderr
+//!
+//! The bar follows the address of the region being scanned through the
+//! target's address space and shows the regions read and matches found so
+//! far. It needs the `progress` feature, and is left out when stderr isn't
+//! a terminal or colors are turned off.
+
+use libmemscan::process::SystemInfo;
+use libmemscan::scanner::ProgressReporter;
+use std::sync::Arc;
+
+#[cfg(feature = "progress")]
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+#[cfg(feature = "progress")]
+use libmemscan::scanner::ScanProgress;
+
+/// Redraws per second, so that small regions don't slow the scan down
+#[cfg(feature = "progress")]
+const REFRESH_RATE: u8 = 10;
+
+/// A bar from the lowest to the highest application address
+#[cfg(feature = "progress")]
+struct AddressBar {
+    bar: ProgressBar,
+    min_address: usize,
+}
+
+#[cfg(feature = "progress")]
+impl ProgressReporter for AddressBar {
+    fn update(&self, progress: &ScanProgress) {
+        // Searching revisits the regions that were just read, keep the bar where it is
+        let position = progress.current_address.saturating_sub(self.min_address) as u64;
+        if position > self.bar.position() {
+            self.bar.set_position(position);
+        }
+        self.bar.set_message(format!(
+            "{} regions, {} matches",
+            progress.regions_scanned, progress.matches_found
+        ));
+    }
+
+    fn finish(&self) {
+        self.bar.finish_and_clear();
+        // Start over if the same bar is used for another scan
+        self.bar.set_position(0);
+    }
+}
+
+/// A progress bar for scans of the address space in `sys`, or `None` if
+/// it is not `wanted`, stderr isn't a terminal or colors are off
+#[cfg(feature = "progress")]
+pub fn progress_bar(sys: &SystemInfo, wanted: bool) -> Option<Arc<dyn ProgressReporter>> {
+    use std::io::IsTerminal;
+
+    if !wanted || !std::io::stderr().is_terminal() || !libmemscan::color::enabled() {
+        return None;
+    }
+    let length = sys.max_app_addr.saturating_sub(sys.min_app_addr) as u64;
+    let bar = ProgressBar::with_draw_target(
+        Some(length),
+        ProgressDrawTarget::stderr_with_hz(REFRESH_RATE),
+    );
+    let style = ProgressStyle::with_template(
+        "{spinner:.cyan} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {percent:>3}% {msg}",
+    )
+    .unwrap_or_else(|_| ProgressStyle::default_bar());
+    bar.set_style(style.progress_chars("=> "));
+    Some(Arc::new(AddressBar {
+        bar,
+        min_address: sys.min_app_addr,
+    }))
+}
+
+/// A progress bar for scans (always `None`, since the `progress` feature
+/// is off)
+#[cfg(not(feature = "progress"))]
+pub fn progress_bar(_sys: &SystemInfo, _wanted: bool) -> Option<Arc<dyn ProgressReporter>> {
+    None
+}
@@ -1,17 +1,37 @@
 //! REPL (Read-Eval-Print Loop) for interactive memory scanning
 
 use anyhow::Result;
+use libmemscan::color::OwoColorize;
 use libmemscan::{
-    interactive::{FilterOp, InteractiveScanner},
-    process::{MemoryRegionIterator, ProcessHandle, SystemInfo},
-    values::{MathOp, Value, ValueType},
+    disasm::disasm_context,
+    fmt::{format_address, format_size},
+    interactive::{AUTO_DETECT_TYPES, FilterOp, InteractiveScanner, SessionFormat, WriteReport},
+    parse_hex_pattern, parse_size,
+    process::{
+        MemoryRegion, ProcessHandle, SystemInfo, get_main_module, get_process_module_regions,
+        read_process_memory,
+    },
+    scanner::{ScanOptions, format_hexdump},
+    symbols::SymbolResolver,
+    values::{
+        DEFAULT_FLOAT_PRECISION, Endianness, MathOp, Value, ValueType, delta_f64, value_to_f64,
+    },
 };
-use owo_colors::OwoColorize;
 use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
 
 pub struct Repl<'a> {
+    process: &'a ProcessHandle,
     scanner: InteractiveScanner<'a>,
     value_type: ValueType,
+    modules: Vec<MemoryRegion>,
+    /// Pointer size of the target, which addresses are padded to
+    pointer_size: usize,
+    /// Significant digits floats are shown with, or `None` for exact
+    precision: Option<usize>,
+    /// Address shown by the last `goto`, which `goto +n` and `goto -n` move from
+    goto_address: Option<usize>,
 }
 
 impl<'a> Repl<'a> {
@@ -19,28 +39,43 @@ impl<'a> Repl<'a> {
         process: &'a ProcessHandle,
         sys: &SystemInfo,
         value_type: ValueType,
-        all_modules: bool,
-        modules: &[libmemscan::process::MemoryRegion],
+        auto_detect: bool,
+        opts: &ScanOptions,
+        modules: &[MemoryRegion],
     ) -> Result<Self> {
-        // Collect all scannable regions
-        let mut regions = Vec::new();
-        for region in MemoryRegionIterator::new(process, sys) {
+        // Regions are enumerated and mapped one at a time by the initial scan
+        let filter_opts = opts.clone();
+        let module_regions = modules.to_vec();
+        let is_scannable = move |region: &MemoryRegion| {
             // Skip if not all_modules and this is a module region
-            if !all_modules {
-                if modules.iter().any(|m| m.is_superset_of(&region)) {
-                    continue;
-                }
+            if !filter_opts.all_modules && region.containing_module(&module_regions).is_some() {
+                return false;
             }
-            regions.push(region);
-        }
+            filter_opts.skip_reason(region).is_none()
+        };
 
-        let scanner = InteractiveScanner::new(process, regions, value_type);
+        let mut scanner = InteractiveScanner::new_lazy(process, sys, is_scannable, value_type);
+        scanner.set_use_memmap(opts.use_memmap);
+        scanner.set_region_filter(opts.region_filter.clone());
+        scanner.set_progress(opts.progress.clone());
+        scanner.set_auto_detect(auto_detect);
         Ok(Self {
+            process,
             scanner,
             value_type,
+            modules: modules.to_vec(),
+            pointer_size: process.pointer_size(),
+            precision: Some(DEFAULT_FLOAT_PRECISION),
+            goto_address: None,
         })
     }
 
+    /// Abort initial scans that would record more than `max` addresses, or
+    /// never with `None`
+    pub fn set_max_initial_matches(&mut self, max: Option<usize>) {
+        self.scanner.set_max_initial_matches(max);
+    }
+
     pub fn run(&mut self) -> Result<()> {
         println!(
             "{}",
@@ -56,19 +91,30 @@ impl<'a> Repl<'a> {
         println!(
             "{} Performing initial scan for {} values...",
             "[info]".bright_cyan(),
-            format!("{:?}", self.value_type).green()
-        );
-        let count = self.scanner.initial_scan()?;
-        println!(
-            "{} Found {} possible addresses across {} regions",
-            "[done]".bright_cyan(),
-            count.to_string().bright_green(),
-            self.scanner.region_count().to_string().bright_green()
+            self.type_label().green()
         );
+        // A scan over the match cap leaves no matches, but `type` and
+        // `rescan` can still start over
+        match self.scanner.initial_scan() {
+            Ok(count) => {
+                println!(
+                    "{} Found {} possible addresses across {} regions",
+                    "[done]".bright_cyan(),
+                    count.to_string().bright_green(),
+                    self.scanner.region_count().to_string().bright_green()
+                );
+                self.warn_near_match_cap(count);
+            }
+            Err(e) => println!("{} {}", "[error]".bright_red(), e),
+        }
         println!();
 
         loop {
-            print!("{} ", ">".bright_yellow().bold());
+            print!(
+                "{} {} ",
+                format!("[gen {}]", self.scanner.generation()).bright_black(),
+                ">".bright_yellow().bold()
+            );
             io::stdout().flush()?;
 
             let mut input = String::new();
@@ -110,20 +156,41 @@ impl<'a> Repl<'a> {
             "type" | "t" => {
                 if parts.len() < 2 {
                     println!(
-                        "{} Usage: type <i8|i16|i32|i64|u8|u16|u32|u64|f32|f64>",
+                        "{} Usage: type <i8|i16|i32|i64|u8|u16|u32|u64|i128|u128|f32|f64>",
                         "[error]".bright_red()
                     );
                 } else {
                     self.change_type(parts[1])?;
                 }
             }
+            "as" => {
+                if parts.len() < 2 {
+                    println!(
+                        "{} Usage: as <i8|i16|i32|i64|u8|u16|u32|u64|i128|u128|f32|f64>",
+                        "[error]".bright_red()
+                    );
+                } else {
+                    self.reinterpret_as(parts[1])?;
+                }
+            }
             "list" | "l" => {
                 self.list_matches()?;
             }
+            "regions" => match parts.get(1) {
+                None => self.list_regions(),
+                Some(&"refresh") => self.refresh_regions()?,
+                Some(_) => println!("{} Usage: regions [refresh]", "[error]".bright_red()),
+            },
             "filter" | "f" => {
                 if parts.len() < 2 {
                     println!("{} Usage: filter <op> [value]", "[error]".bright_red());
                     println!("  Ops: eq, lt, gt, inc, dec, changed, unchanged");
+                    println!("  Ops: delta <n> [tolerance]");
+                    println!("  Ops: freq <min> [max], stable <n>");
+                    println!("  Ops: rate <cp_a> <cp_b> <per_sec> <margin>");
+                    println!("  Ops: ratio <cp> <ratio> <margin%>");
+                    println!("  Ops: !range <lo> <hi>, !module <name>");
+                    println!("  Ops: ptr, ptrin <lo> <hi>");
                     println!("  Ops: checkpoint <cp1> <cp2> <cp3> <margin_percent>");
                 } else {
                     self.filter_matches(&parts[1..])?;
@@ -140,13 +207,145 @@ impl<'a> Repl<'a> {
                     self.handle_checkpoint(&parts[1..])?;
                 }
             }
+            "cp-deltas" => {
+                if parts.len() < 3 {
+                    println!("{} Usage: cp-deltas <cp_a> <cp_b>", "[error]".bright_red());
+                } else {
+                    self.checkpoint_deltas(parts[1], parts[2])?;
+                }
+            }
             "set" | "s" => {
                 if parts.len() < 2 {
-                    println!("{} Usage: set <value> [address]", "[error]".bright_red());
+                    println!(
+                        "{} Usage: set <value> [address] [--verify|--force]",
+                        "[error]".bright_red()
+                    );
                 } else {
                     self.set_value(&parts[1..])?;
                 }
             }
+            "dump" => {
+                if parts.len() < 3 {
+                    println!("{} Usage: dump <address> <path>", "[error]".bright_red());
+                } else {
+                    self.dump(parts[1], parts[2])?;
+                }
+            }
+            "session" => {
+                if parts.len() < 3 || !matches!(parts[1], "save" | "load") {
+                    println!(
+                        "{} Usage: session save <path> [text|binary] | session load <path>",
+                        "[error]".bright_red()
+                    );
+                } else if parts[1] == "save" {
+                    self.save_session(parts[2], parts.get(3).copied())?;
+                } else {
+                    self.load_session(parts[2])?;
+                }
+            }
+            "diff" => {
+                if parts.len() < 3 {
+                    println!(
+                        "{} Usage: diff <address> <path> [type] [align] [le|be]",
+                        "[error]".bright_red()
+                    );
+                } else {
+                    self.diff(parts[1], parts[2], &parts[3..])?;
+                }
+            }
+            "sequence" => {
+                if parts.len() < 2 {
+                    println!("{} Usage: sequence <len> [step]", "[error]".bright_red());
+                } else {
+                    self.sequence(parts[1], parts.get(2).copied())?;
+                }
+            }
+            "coalesce" => {
+                if parts.len() < 2 {
+                    println!("{} Usage: coalesce <bytes>", "[error]".bright_red());
+                } else {
+                    self.coalesce(parts[1])?;
+                }
+            }
+            "eq-at" => {
+                if parts.len() < 2 {
+                    println!("{} Usage: eq-at <address>", "[error]".bright_red());
+                } else {
+                    self.eq_at(parts[1])?;
+                }
+            }
+            "trace" => {
+                if parts.len() < 3 {
+                    println!(
+                        "{} Usage: trace <address> <seconds>",
+                        "[error]".bright_red()
+                    );
+                } else {
+                    self.trace(parts[1], parts[2])?;
+                }
+            }
+            "peek" => {
+                if parts.len() < 2 {
+                    println!("{} Usage: peek <address> [type]", "[error]".bright_red());
+                } else {
+                    self.peek(&parts[1..])?;
+                }
+            }
+            "goto" | "g" => {
+                if parts.len() < 2 {
+                    println!(
+                        "{} Usage: goto <address|+n|-n> [len]",
+                        "[error]".bright_red()
+                    );
+                } else {
+                    self.goto(&parts[1..])?;
+                }
+            }
+            "dis" => {
+                if parts.len() < 2 {
+                    println!("{} Usage: dis <address>", "[error]".bright_red());
+                } else {
+                    self.dis(parts[1])?;
+                }
+            }
+            "precision" => {
+                if parts.len() < 2 {
+                    println!("{} Usage: precision <digits|exact>", "[error]".bright_red());
+                } else {
+                    self.set_precision(parts[1])?;
+                }
+            }
+            "tolerance" => {
+                if parts.len() < 2 {
+                    println!(
+                        "{} Float tolerance for changed/unchanged: {}",
+                        "[info]".bright_cyan(),
+                        self.scanner.float_tolerance().to_string().bright_green()
+                    );
+                } else {
+                    self.set_tolerance(parts[1])?;
+                }
+            }
+            "poke" => {
+                if parts.len() < 3 {
+                    println!(
+                        "{} Usage: poke <address> <hexbytes>",
+                        "[error]".bright_red()
+                    );
+                } else {
+                    self.poke(&parts[1..])?;
+                }
+            }
+            "fill" => {
+                if parts.len() < 4 {
+                    println!(
+                        "{} Usage: fill <address> <byte> <len>",
+                        "[error]".bright_red()
+                    );
+                } else {
+                    self.fill(&parts[1..])?;
+                }
+            }
             "add" | "sub" | "mul" | "div" => {
                 if parts.len() < 2 {
                     println!(
@@ -175,17 +374,25 @@ impl<'a> Repl<'a> {
         println!("{}", "Available commands:".bright_yellow().bold());
         println!("  {} - Show this help", "help, h".green());
         println!(
-            "  {} - Clear all state and rescan process",
+            "  {} - Clear all state and rescan the cached regions",
             "rescan, r".green()
         );
         println!(
             "  {} - Change value type to scan for",
             "type <ty>, t <ty>".green()
         );
+        println!(
+            "  {} - View current matches as a type of the same size",
+            "as <ty>".green()
+        );
         println!(
             "  {} - List current matched addresses (max 20)",
             "list, l".green()
         );
+        println!(
+            "  {} - List the cached regions scanned, or enumerate them again",
+            "regions [refresh]".green()
+        );
         println!("  {} - Filter addresses", "filter <op> [value]".green());
         println!(
             "    Ops: {} (equals), {} (less than), {} (greater than)",
@@ -200,6 +407,36 @@ impl<'a> Repl<'a> {
             "changed".cyan(),
             "unchanged".cyan()
         );
+        println!(
+            "    Ops: {} (changed by exactly n since the previous scan)",
+            "delta <n> [tolerance]".cyan()
+        );
+        println!(
+            "    Ops: {} (changed in min..=max filter passes)",
+            "freq <min> [max]".cyan()
+        );
+        println!(
+            "    Ops: {} (unchanged for the last n filter passes)",
+            "stable <n>".cyan()
+        );
+        println!(
+            "    Ops: {} (changed by per_sec each second between checkpoints)",
+            "rate <cp_a> <cp_b> <per_sec> <margin>".cyan()
+        );
+        println!(
+            "    Ops: {} (value is ratio times its value at a checkpoint)",
+            "ratio <cp> <ratio> <margin%>".cyan()
+        );
+        println!(
+            "    Ops: {} (drop addresses in range), {} (drop addresses in a module)",
+            "!range <lo> <hi>".cyan(),
+            "!module <name>".cyan()
+        );
+        println!(
+            "    Ops: {} (points into mapped memory), {} (points into range)",
+            "ptr".cyan(),
+            "ptrin <lo> <hi>".cyan()
+        );
         println!(
             "    Ops: {} (relative checkpoint filter)",
             "checkpoint <cp1> <cp2> <cp3> <margin%>".cyan()
@@ -214,14 +451,74 @@ impl<'a> Repl<'a> {
             "list".cyan(),
             "delete <name>".cyan()
         );
+        println!(
+            "  {} - Summarize how matches changed between two checkpoints",
+            "cp-deltas <cp_a> <cp_b>".green()
+        );
         println!(
             "  {} - Set value at address(es)",
-            "set <value> [address]".green()
+            "set <value> [address] [--verify|--force]".green()
         );
         println!(
             "  {} - Add/sub/mul/div value",
             "add/sub/mul/div <value> [address]".green()
         );
+        println!(
+            "  {} - Save the region containing an address to a file",
+            "dump <address> <path>".green()
+        );
+        println!(
+            "  {} - Show values that changed since a region was dumped",
+            "diff <address> <path> [type] [align] [le|be]".green()
+        );
+        println!(
+            "  {} - Save matches (module-relative where possible) or restore them",
+            "session save|load <path> [text|binary]".green()
+        );
+        println!(
+            "  {} - Scan for runs of len values increasing by step (or at all)",
+            "sequence <len> [step]".green()
+        );
+        println!(
+            "  {} - Merge matches closer than n bytes into the lowest address",
+            "coalesce <bytes>".green()
+        );
+        println!(
+            "  {} - Keep matches equal to the value at an address",
+            "eq-at <address>".green()
+        );
+        println!(
+            "  {} - Record how the value at an address changes over time",
+            "trace <address> <seconds>".green()
+        );
+        println!(
+            "  {} - Read a value at any address",
+            "peek <address> [type]".green()
+        );
+        println!(
+            "  {} - Hexdump len bytes (64 by default) at an address, or relative to the last goto",
+            "goto <address|+n|-n> [len], g".green()
+        );
+        println!(
+            "  {} - Disassemble the code around an address",
+            "dis <address>".green()
+        );
+        println!(
+            "  {} - Show floats with n significant digits, or in full",
+            "precision <n|exact>".green()
+        );
+        println!(
+            "  {} - Treat floats at most eps apart as unchanged",
+            "tolerance [eps]".green()
+        );
+        println!(
+            "  {} - Write raw bytes to an address",
+            "poke <address> <hexbytes>".green()
+        );
+        println!(
+            "  {} - Write len copies of a byte, e.g. 0x90 to NOP out code",
+            "fill <address> <byte> <len>".green()
+        );
         println!("  {} - Exit the REPL", "quit, q, exit".green());
         println!();
         println!(
@@ -231,26 +528,9 @@ impl<'a> Repl<'a> {
     }
 
     fn change_type(&mut self, ty: &str) -> Result<()> {
-        let new_type = match ty.to_lowercase().as_str() {
-            "i8" => ValueType::I8,
-            "i16" => ValueType::I16,
-            "i32" => ValueType::I32,
-            "i64" => ValueType::I64,
-            "u8" => ValueType::U8,
-            "u16" => ValueType::U16,
-            "u32" => ValueType::U32,
-            "u64" => ValueType::U64,
-            "f32" => ValueType::F32,
-            "f64" => ValueType::F64,
-            _ => {
-                anyhow::bail!(
-                    "Unknown value type: {}. Valid types: i8, i16, i32, i64, u8, u16, u32, u64, f32, f64",
-                    ty
-                );
-            }
-        };
+        let new_type = parse_value_type(ty)?;
 
-        if new_type == self.value_type {
+        if new_type == self.value_type && !self.scanner.auto_detect() {
             println!(
                 "{} Value type is already {}",
                 "[info]".bright_cyan(),
@@ -271,11 +551,26 @@ impl<'a> Repl<'a> {
         Ok(())
     }
 
+    fn reinterpret_as(&mut self, ty: &str) -> Result<()> {
+        let new_type = parse_value_type(ty)?;
+        let count = self.scanner.reinterpret_as(new_type)?;
+        self.value_type = new_type;
+
+        println!(
+            "{} Viewing {} matches as {}",
+            "[done]".bright_cyan(),
+            count.to_string().bright_green(),
+            format!("{:?}", self.value_type).green()
+        );
+
+        Ok(())
+    }
+
     fn rescan(&mut self) -> Result<()> {
         println!(
             "{} Rescanning process memory from scratch for {} values...",
             "[info]".bright_cyan(),
-            format!("{:?}", self.value_type).green()
+            self.type_label().green()
         );
         let count = self.scanner.rescan()?;
         println!(
@@ -284,28 +579,108 @@ impl<'a> Repl<'a> {
             count.to_string().bright_green(),
             self.scanner.region_count().to_string().bright_green()
         );
+        self.warn_near_match_cap(count);
         println!();
         Ok(())
     }
 
+    fn list_regions(&mut self) {
+        let regions = self.scanner.regions();
+        let total: usize = regions.iter().map(|region| region.size).sum();
+        println!(
+            "{} regions ({}) cached, use {} after the target allocates or frees memory",
+            regions.len().to_string().bright_green(),
+            format_size(total),
+            "regions refresh".green()
+        );
+        for region in &regions {
+            println!(
+                "  {}-{} {:>10} {} {}{}",
+                format_address(region.base_address, self.pointer_size),
+                format_address(
+                    region.base_address.saturating_add(region.size),
+                    self.pointer_size
+                ),
+                format_size(region.size),
+                region.protect,
+                region.type_,
+                region
+                    .image_file
+                    .as_deref()
+                    .map(|file| format!(" {}", file))
+                    .unwrap_or_default()
+            );
+        }
+    }
+
+    fn refresh_regions(&mut self) -> Result<()> {
+        let count = self.scanner.refresh_regions();
+        self.modules = get_process_module_regions(self.process)?;
+        println!(
+            "{} Enumerated {} regions and {} modules, the next rescan uses them",
+            "[done]".bright_cyan(),
+            count.to_string().bright_green(),
+            self.modules.len().to_string().bright_green()
+        );
+        Ok(())
+    }
+
+    /// Warn once an initial scan found over half the addresses it may hold
+    fn warn_near_match_cap(&self, count: usize) {
+        if let Some(max) = self.scanner.max_initial_matches()
+            && count > max / 2
+        {
+            println!(
+                "{} {} addresses is over half of the {} an initial scan may record, \
+                 use a larger value type or narrower regions",
+                "[warn]".yellow(),
+                count,
+                max
+            );
+        }
+    }
+
     fn list_matches(&self) -> Result<()> {
         let matches = self.scanner.matches();
-        println!("{} matches found", matches.len().to_string().bright_green());
+        println!(
+            "{} matches found (generation {})",
+            matches.len().to_string().bright_green(),
+            self.scanner.generation()
+        );
 
         let display_count = matches.len().min(20);
+        let mut symbols = SymbolResolver::new(self.process, &self.modules);
         for (i, m) in matches.iter().take(display_count).enumerate() {
-            let value_str = format_value(&m.current_value);
+            let value_str = m.current_value.format_with(self.precision);
             let prev_str = m
                 .previous_value
                 .as_ref()
-                .map(|v| format!(" (was: {})", format_value(v)))
+                .map(|v| {
+                    format!(
+                        " (was: {}, changed {}x, stable {}x)",
+                        v.format_with(self.precision),
+                        m.change_count,
+                        m.stable_count
+                    )
+                })
+                .unwrap_or_default();
+            let type_str = if self.scanner.auto_detect() {
+                format!(" [{:?}]", m.value_type)
+            } else {
+                String::new()
+            };
+            let symbol_str = symbols
+                .describe(m.address)
+                .map(|symbol| format!(" {}", symbol))
                 .unwrap_or_default();
             println!(
-                "  {}: {} = {}{}",
+                "  {}: {} = {}{}{}{}",
                 i.to_string().bright_black(),
-                format!("{:016x}", m.address).bright_yellow(),
+                format_address(m.address, self.pointer_size).bright_yellow(),
                 value_str.bright_green(),
-                prev_str.bright_black()
+                prev_str.bright_black(),
+                type_str.cyan(),
+                symbol_str.magenta()
             );
         }
 
@@ -354,86 +729,388 @@ impl<'a> Repl<'a> {
             return Ok(());
         }
 
-        let (op, compare_value) = match args[0] {
-            "eq" => {
-                if args.len() < 2 {
-                    anyhow::bail!("Value required for 'eq' filter");
-                }
-                (
-                    FilterOp::Equals,
-                    Some(parse_value(args[1], self.value_type)?),
-                )
+        // Handle changed-by-delta filtering
+        if args[0] == "delta" {
+            if args.len() < 2 {
+                anyhow::bail!("Delta filter requires: delta <n> [tolerance]");
             }
-            "lt" => {
-                if args.len() < 2 {
-                    anyhow::bail!("Value required for 'lt' filter");
-                }
-                (
-                    FilterOp::LessThan,
-                    Some(parse_value(args[1], self.value_type)?),
-                )
-            }
-            "gt" => {
-                if args.len() < 2 {
-                    anyhow::bail!("Value required for 'gt' filter");
-                }
-                (
-                    FilterOp::GreaterThan,
-                    Some(parse_value(args[1], self.value_type)?),
-                )
-            }
-            "inc" | "increased" => (FilterOp::Increased, None),
-            "dec" | "decreased" => (FilterOp::Decreased, None),
-            "changed" => (FilterOp::Changed, None),
-            "unchanged" => (FilterOp::Unchanged, None),
-            _ => anyhow::bail!("Unknown filter operation: {}", args[0]),
-        };
-
-        let before = self.scanner.matches().len();
-        let after = self.scanner.filter(op, compare_value)?;
+            let delta: f64 = args[1]
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid delta value: {}", args[1]))?;
+            let tolerance: f64 = match args.get(2) {
+                Some(t) => t
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid tolerance value: {}", t))?,
+                None => 0.0,
+            };
 
-        println!(
-            "{} Filtered from {} to {} addresses ({} regions)",
-            "[done]".bright_cyan(),
-            before.to_string().bright_yellow(),
-            after.to_string().bright_green(),
-            self.scanner.region_count().to_string().bright_green()
-        );
+            let before = self.scanner.matches().len();
+            let after = self.scanner.filter_changed_by(delta, tolerance)?;
 
-        Ok(())
-    }
+            println!(
+                "{} Filtered from {} to {} addresses ({} regions)",
+                "[done]".bright_cyan(),
+                before.to_string().bright_yellow(),
+                after.to_string().bright_green(),
+                self.scanner.region_count().to_string().bright_green()
+            );
+            self.report_detected_type();
 
-    fn set_value(&mut self, args: &[&str]) -> Result<()> {
-        if args.is_empty() {
-            anyhow::bail!("Value required");
+            return Ok(());
         }
 
-        let value = parse_value(args[0], self.value_type)?;
+        // Handle rate-of-change filtering between two checkpoints
+        if args[0] == "rate" {
+            if args.len() < 5 {
+                anyhow::bail!("Rate filter requires: rate <cp_a> <cp_b> <per_sec> <margin>");
+            }
+            let rate: f64 = args[3]
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid rate value: {}", args[3]))?;
+            let margin: f64 = args[4]
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid margin value: {}", args[4]))?;
+
+            let before = self.scanner.matches().len();
+            let after = self.scanner.filter_rate(args[1], args[2], rate, margin)?;
 
-        if args.len() > 1 {
-            // Set specific address
-            let addr = parse_address(args[1])?;
-            self.scanner.write_value(addr, value)?;
-            println!("{} Set value at {:016x}", "[done]".bright_cyan(), addr);
-        } else {
-            // Set all addresses
-            let count = self.scanner.write_all(value)?;
             println!(
-                "{} Set value at {} addresses",
+                "{} Filtered from {} to {} addresses ({} regions)",
                 "[done]".bright_cyan(),
-                count.to_string().bright_green()
+                before.to_string().bright_yellow(),
+                after.to_string().bright_green(),
+                self.scanner.region_count().to_string().bright_green()
             );
-        }
 
-        Ok(())
-    }
+            return Ok(());
+        }
+
+        // Handle filtering by the ratio to a checkpoint
+        if args[0] == "ratio" {
+            if args.len() < 4 {
+                anyhow::bail!("Ratio filter requires: ratio <cp> <ratio> <margin%>");
+            }
+            let ratio: f64 = args[2]
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid ratio value: {}", args[2]))?;
+            let margin: f64 = args[3]
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid margin value: {}", args[3]))?;
+
+            let before = self.scanner.matches().len();
+            let after = self
+                .scanner
+                .filter_checkpoint_ratio(args[1], ratio, margin)?;
+
+            println!(
+                "{} Filtered from {} to {} addresses ({} regions)",
+                "[done]".bright_cyan(),
+                before.to_string().bright_yellow(),
+                after.to_string().bright_green(),
+                self.scanner.region_count().to_string().bright_green()
+            );
+
+            return Ok(());
+        }
+
+        // Handle stable value filtering
+        if args[0] == "stable" {
+            let Some(min_scans) = args.get(1) else {
+                anyhow::bail!("Stable filter requires: stable <n>");
+            };
+            let min_scans: u32 = min_scans
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid number of filter passes: {}", min_scans))?;
+
+            let before = self.scanner.matches().len();
+            let after = self.scanner.filter_stable(min_scans);
+
+            println!(
+                "{} Filtered from {} to {} addresses ({} regions)",
+                "[done]".bright_cyan(),
+                before.to_string().bright_yellow(),
+                after.to_string().bright_green(),
+                self.scanner.region_count().to_string().bright_green()
+            );
+
+            return Ok(());
+        }
+
+        // Handle change frequency filtering
+        if args[0] == "freq" {
+            if args.len() < 2 {
+                anyhow::bail!("Frequency filter requires: freq <min> [max]");
+            }
+            let min: u32 = args[1]
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid minimum change count: {}", args[1]))?;
+            let max: u32 = match args.get(2) {
+                Some(m) => m
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid maximum change count: {}", m))?,
+                None => u32::MAX,
+            };
+
+            let before = self.scanner.matches().len();
+            let after = self.scanner.filter_change_frequency(min, max)?;
+
+            println!(
+                "{} Filtered from {} to {} addresses ({} regions)",
+                "[done]".bright_cyan(),
+                before.to_string().bright_yellow(),
+                after.to_string().bright_green(),
+                self.scanner.region_count().to_string().bright_green()
+            );
+            self.report_detected_type();
+
+            return Ok(());
+        }
+
+        // Handle exclusion filters
+        if args[0] == "!range" || args[0] == "!module" {
+            let before = self.scanner.matches().len();
+            let after = if args[0] == "!range" {
+                if args.len() < 3 {
+                    anyhow::bail!("Range exclusion requires: !range <lo> <hi>");
+                }
+                let lo = parse_address(args[1])?;
+                let hi = parse_address(args[2])?;
+                self.scanner.filter_not_in_range(lo, hi)?
+            } else {
+                if args.len() < 2 {
+                    anyhow::bail!("Module exclusion requires: !module <name>");
+                }
+                self.scanner.filter_not_module(args[1], &self.modules)?
+            };
+
+            println!(
+                "{} Filtered from {} to {} addresses ({} regions)",
+                "[done]".bright_cyan(),
+                before.to_string().bright_yellow(),
+                after.to_string().bright_green(),
+                self.scanner.region_count().to_string().bright_green()
+            );
+
+            return Ok(());
+        }
+
+        // Handle pointer heuristics
+        if args[0] == "ptr" || args[0] == "ptrin" {
+            let before = self.scanner.matches().len();
+            let after = if args[0] == "ptr" {
+                self.scanner.filter_pointer_like(&self.modules)?
+            } else {
+                if args.len() < 3 {
+                    anyhow::bail!("Pointer range filter requires: ptrin <lo> <hi>");
+                }
+                let lo = parse_address(args[1])?;
+                let hi = parse_address(args[2])?;
+                self.scanner.filter_points_into(lo, hi)?
+            };
+
+            println!(
+                "{} Filtered from {} to {} addresses ({} regions)",
+                "[done]".bright_cyan(),
+                before.to_string().bright_yellow(),
+                after.to_string().bright_green(),
+                self.scanner.region_count().to_string().bright_green()
+            );
+            self.report_detected_type();
+
+            return Ok(());
+        }
+
+        let (op, compare_value) = match args[0] {
+            "eq" => {
+                if args.len() < 2 {
+                    anyhow::bail!("Value required for 'eq' filter");
+                }
+                (FilterOp::Equals, Some(self.parse_literal(args[1])?))
+            }
+            "lt" => {
+                if args.len() < 2 {
+                    anyhow::bail!("Value required for 'lt' filter");
+                }
+                (FilterOp::LessThan, Some(self.parse_literal(args[1])?))
+            }
+            "gt" => {
+                if args.len() < 2 {
+                    anyhow::bail!("Value required for 'gt' filter");
+                }
+                (FilterOp::GreaterThan, Some(self.parse_literal(args[1])?))
+            }
+            "inc" | "increased" => (FilterOp::Increased, None),
+            "dec" | "decreased" => (FilterOp::Decreased, None),
+            "changed" => (FilterOp::Changed, None),
+            "unchanged" => (FilterOp::Unchanged, None),
+            _ => anyhow::bail!("Unknown filter operation: {}", args[0]),
+        };
+
+        let before = self.scanner.matches().len();
+        let after = self.scanner.filter(op, compare_value)?;
+
+        println!(
+            "{} Filtered from {} to {} addresses ({} regions)",
+            "[done]".bright_cyan(),
+            before.to_string().bright_yellow(),
+            after.to_string().bright_green(),
+            self.scanner.region_count().to_string().bright_green()
+        );
+        self.report_detected_type();
+
+        Ok(())
+    }
+
+    /// Label for the scanned value type(s)
+    fn type_label(&self) -> String {
+        if self.scanner.auto_detect() {
+            let types: Vec<String> = AUTO_DETECT_TYPES
+                .iter()
+                .map(|ty| format!("{:?}", ty))
+                .collect();
+            format!("auto-detected ({})", types.join(", "))
+        } else {
+            format!("{:?}", self.value_type)
+        }
+    }
+
+    /// Parse a filter literal, inferring its type while the type is still being detected
+    fn parse_literal(&self, s: &str) -> Result<Value> {
+        if self.scanner.auto_detect() {
+            Value::infer(s)
+                .map(|(value, _)| value)
+                .ok_or_else(|| anyhow::anyhow!("Invalid value: {}", s))
+        } else {
+            Ok(Value::parse_as(s, self.value_type)?)
+        }
+    }
+
+    /// Print the candidate types while auto-detecting, or the type that was locked in
+    fn report_detected_type(&mut self) {
+        if self.scanner.auto_detect() {
+            let types: Vec<String> = self
+                .scanner
+                .matched_types()
+                .iter()
+                .map(|(ty, count)| format!("{:?} ({})", ty, count))
+                .collect();
+            if !types.is_empty() {
+                println!(
+                    "{} Candidate types: {}",
+                    "[info]".bright_cyan(),
+                    types.join(", ").green()
+                );
+            }
+        } else if self.scanner.value_type() != self.value_type {
+            self.value_type = self.scanner.value_type();
+            println!(
+                "{} Detected value type {}",
+                "[done]".bright_cyan(),
+                format!("{:?}", self.value_type).green()
+            );
+        }
+    }
+
+    fn set_value(&mut self, args: &[&str]) -> Result<()> {
+        // `--verify` reads every written value back to catch writes that didn't stick,
+        // `--force` writes through page protections that would refuse a plain write
+        let verify = args.contains(&"--verify");
+        let force = args.contains(&"--force");
+        let args: Vec<&str> = args
+            .iter()
+            .copied()
+            .filter(|a| *a != "--verify" && *a != "--force")
+            .collect();
+        if args.is_empty() {
+            anyhow::bail!("Value required");
+        }
+        if verify && force {
+            anyhow::bail!("--verify and --force can't be combined");
+        }
+
+        let value = Value::parse_as(args[0], self.value_type)?;
+
+        if args.len() > 1 {
+            // Set specific address
+            let addr = parse_address(args[1])?;
+            if verify {
+                self.scanner.write_value_verified(addr, value)?;
+                println!(
+                    "{} Set and verified value at {}",
+                    "[done]".bright_cyan(),
+                    format_address(addr, self.pointer_size)
+                );
+            } else if force {
+                self.scanner.write_value_forced(addr, value)?;
+                println!(
+                    "{} Force-set value at {}",
+                    "[done]".bright_cyan(),
+                    format_address(addr, self.pointer_size)
+                );
+            } else {
+                self.scanner.write_value(addr, value)?;
+                println!(
+                    "{} Set value at {}",
+                    "[done]".bright_cyan(),
+                    format_address(addr, self.pointer_size)
+                );
+            }
+        } else if !self.confirm_bulk_write()? {
+            println!("{} Cancelled, nothing was written", "[info]".bright_cyan());
+        } else if verify {
+            // Set all addresses, reporting each one that didn't take the value
+            let report = self
+                .scanner
+                .without_bulk_write_limit(|scanner| scanner.write_all_verified(value))?;
+            print_write_failures(&report, self.pointer_size);
+            println!(
+                "{} Set and verified value at {} addresses",
+                "[done]".bright_cyan(),
+                report.succeeded.to_string().bright_green()
+            );
+        } else {
+            // Set all addresses
+            let report = self.scanner.without_bulk_write_limit(|scanner| {
+                if force {
+                    scanner.write_all_forced(value)
+                } else {
+                    scanner.write_all(value)
+                }
+            })?;
+            print_write_failures(&report, self.pointer_size);
+            println!(
+                "{} Set value at {} addresses",
+                "[done]".bright_cyan(),
+                report.succeeded.to_string().bright_green()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Ask before writing to more matches than the scanner's bulk write
+    /// limit, returning whether to go ahead
+    fn confirm_bulk_write(&self) -> Result<bool> {
+        if !self.scanner.bulk_write_needs_confirmation() {
+            return Ok(true);
+        }
+        print!(
+            "{} This will modify {} addresses, continue? [y/N] ",
+            "[warn]".yellow(),
+            self.scanner.matches().len().to_string().bright_green()
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+    }
 
     fn modify_value(&mut self, op_str: &str, args: &[&str]) -> Result<()> {
         if args.is_empty() {
             anyhow::bail!("Value required");
         }
 
-        let value = parse_value(args[0], self.value_type)?;
+        let value = Value::parse_as(args[0], self.value_type)?;
         let op = match op_str {
             "add" => MathOp::Add,
             "sub" => MathOp::Subtract,
@@ -446,20 +1123,500 @@ impl<'a> Repl<'a> {
             // Modify specific address
             let addr = parse_address(args[1])?;
             self.scanner.modify_value(addr, op, value)?;
-            println!("{} Modified value at {:016x}", "[done]".bright_cyan(), addr);
+            println!(
+                "{} Modified value at {}",
+                "[done]".bright_cyan(),
+                format_address(addr, self.pointer_size)
+            );
+        } else if !self.confirm_bulk_write()? {
+            println!("{} Cancelled, nothing was modified", "[info]".bright_cyan());
         } else {
             // Modify all addresses
-            let count = self.scanner.modify_all(op, value)?;
+            let report = self
+                .scanner
+                .without_bulk_write_limit(|scanner| scanner.modify_all(op, value))?;
+            print_write_failures(&report, self.pointer_size);
             println!(
                 "{} Modified {} addresses",
                 "[done]".bright_cyan(),
-                count.to_string().bright_green()
+                report.succeeded.to_string().bright_green()
             );
         }
 
         Ok(())
     }
 
+    fn dump(&mut self, address: &str, path: &str) -> Result<()> {
+        let addr = parse_address(address)?;
+        let region = self.scanner.dump_region(addr, Path::new(path))?;
+
+        println!(
+            "{} Dumped {} - {} ({}) to {}",
+            "[done]".bright_cyan(),
+            format_address(region.base_address, self.pointer_size),
+            format_address(region.base_address + region.size, self.pointer_size),
+            format_size(region.size),
+            path.bright_green()
+        );
+
+        Ok(())
+    }
+
+    /// Modules session matches are stored relative to: the loaded modules
+    /// and the main executable, where most static addresses live
+    fn session_modules(&self) -> Vec<MemoryRegion> {
+        let mut modules = self.modules.clone();
+        modules.extend(get_main_module(self.process).ok());
+        modules
+    }
+
+    fn save_session(&mut self, path: &str, format: Option<&str>) -> Result<()> {
+        let format = match format {
+            None | Some("text") => SessionFormat::Text,
+            Some("binary") => SessionFormat::Binary,
+            Some(other) => anyhow::bail!("Unknown session format: {} (use text or binary)", other),
+        };
+        let saved =
+            self.scanner
+                .save_session_as(Path::new(path), &self.session_modules(), format)?;
+        println!(
+            "{} Saved {} matches to {} ({})",
+            "[done]".bright_cyan(),
+            saved.saved,
+            path.bright_green(),
+            format_size(saved.file_size as usize)
+        );
+        Ok(())
+    }
+
+    fn load_session(&mut self, path: &str) -> Result<()> {
+        let loaded = self
+            .scanner
+            .load_session(Path::new(path), &self.session_modules())?;
+        self.value_type = self.scanner.value_type();
+        println!(
+            "{} Restored {} matches from {}",
+            "[done]".bright_cyan(),
+            loaded.restored,
+            path.bright_green()
+        );
+        if let Some(pid) = loaded.saved_pid.filter(|&pid| pid != self.process.pid()) {
+            println!(
+                "{} The session was saved from {} (pid {})",
+                "[info]".bright_cyan(),
+                loaded.saved_process.as_deref().unwrap_or("another process"),
+                pid
+            );
+        }
+        if loaded.unresolved > 0 {
+            println!(
+                "{} {} matches could not be re-resolved",
+                "[warn]".yellow(),
+                loaded.unresolved
+            );
+        }
+        Ok(())
+    }
+
+    fn diff(&mut self, address: &str, path: &str, options: &[&str]) -> Result<()> {
+        let addr = parse_address(address)?;
+        let changes = if options.is_empty() {
+            self.scanner.diff_against_file(addr, Path::new(path))?
+        } else {
+            // Options may come in any order, e.g. `diff <addr> <path> be u16`
+            let mut value_type = None;
+            let mut alignment = None;
+            let mut endianness = Endianness::Little;
+            for option in options {
+                if let Some(order) = Endianness::from_name(option) {
+                    endianness = order;
+                } else if let Ok(align) = option.parse::<usize>() {
+                    alignment = Some(align);
+                } else {
+                    value_type = Some(parse_value_type(option)?);
+                }
+            }
+            // A new type is compared at its own size unless told otherwise
+            let alignment = alignment.unwrap_or_else(|| match value_type {
+                Some(ty) => ty.size(),
+                None => self.scanner.alignment(),
+            });
+            let value_type = value_type.unwrap_or(self.value_type);
+            self.scanner.diff_against_file_as(
+                addr,
+                Path::new(path),
+                value_type,
+                alignment,
+                endianness,
+            )?
+        };
+        println!(
+            "{} values changed since {}",
+            changes.len().to_string().bright_green(),
+            path
+        );
+
+        let display_count = changes.len().min(20);
+        for change in changes.iter().take(display_count) {
+            println!(
+                "  {}: {} -> {}",
+                format_address(change.address, self.pointer_size).bright_yellow(),
+                change.old_value.format_with(self.precision).bright_black(),
+                change.new_value.format_with(self.precision).bright_green()
+            );
+        }
+
+        if changes.len() > display_count {
+            println!(
+                "  {} ... and {} more",
+                "[...]".bright_black(),
+                (changes.len() - display_count).to_string().bright_black()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn sequence(&mut self, len: &str, step: Option<&str>) -> Result<()> {
+        let len: usize = len
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid length: {}", len))?;
+        let step = step
+            .map(|step| Value::parse_as(step, self.value_type))
+            .transpose()?;
+
+        println!(
+            "{} Scanning for runs of {} {} values...",
+            "[info]".bright_cyan(),
+            len,
+            self.type_label().green()
+        );
+        let count = self.scanner.scan_sequence(len, step)?;
+        println!(
+            "{} Found {} runs across {} regions",
+            "[done]".bright_cyan(),
+            count.to_string().bright_green(),
+            self.scanner.region_count().to_string().bright_green()
+        );
+
+        Ok(())
+    }
+
+    fn coalesce(&mut self, within: &str) -> Result<()> {
+        let within = parse_size(within)?;
+        let before = self.scanner.matches().len();
+        let after = self.scanner.coalesce(within);
+
+        println!(
+            "{} Coalesced {} matches into {} ({} regions)",
+            "[done]".bright_cyan(),
+            before.to_string().bright_yellow(),
+            after.to_string().bright_green(),
+            self.scanner.region_count().to_string().bright_green()
+        );
+
+        Ok(())
+    }
+
+    fn eq_at(&mut self, address: &str) -> Result<()> {
+        let addr = parse_address(address)?;
+        let before = self.scanner.matches().len();
+        let after = self.scanner.filter_eq_address(addr)?;
+
+        println!(
+            "{} Filtered from {} to {} addresses ({} regions)",
+            "[done]".bright_cyan(),
+            before.to_string().bright_yellow(),
+            after.to_string().bright_green(),
+            self.scanner.region_count().to_string().bright_green()
+        );
+        self.report_detected_type();
+
+        Ok(())
+    }
+
+    fn trace(&self, address: &str, seconds: &str) -> Result<()> {
+        const INTERVAL: Duration = Duration::from_millis(10);
+        let addr = parse_address(address)?;
+        let duration = seconds
+            .parse::<f64>()
+            .ok()
+            .and_then(|s| Duration::try_from_secs_f64(s).ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid duration: {}", seconds))?;
+
+        println!(
+            "{} Sampling {} every {}ms for {:.1}s...",
+            "[info]".bright_cyan(),
+            format_address(addr, self.pointer_size),
+            INTERVAL.as_millis(),
+            duration.as_secs_f64()
+        );
+        let samples = self.scanner.sample_changes(addr, duration, INTERVAL);
+        let (Some((start, first)), Some((_, last))) = (samples.first(), samples.last()) else {
+            println!(
+                "{} Could not read {}",
+                "[warn]".yellow(),
+                format_address(addr, self.pointer_size)
+            );
+            return Ok(());
+        };
+
+        for (at, value) in &samples {
+            println!(
+                "  {} {}",
+                format!("+{:.3}s", at.duration_since(*start).as_secs_f64()).bright_black(),
+                value.format_with(self.precision).bright_green()
+            );
+        }
+        let values = samples.iter().map(|(_, value)| value);
+        let min = values
+            .clone()
+            .min_by(|a, b| value_to_f64(a).total_cmp(&value_to_f64(b)))
+            .unwrap();
+        let max = values
+            .max_by(|a, b| value_to_f64(a).total_cmp(&value_to_f64(b)))
+            .unwrap();
+        let delta = delta_f64(last, first).unwrap_or(0.0);
+        println!(
+            "{} {} values, min {}, max {}, delta {}",
+            "[done]".bright_cyan(),
+            samples.len().to_string().bright_green(),
+            min.format_with(self.precision).bright_yellow(),
+            max.format_with(self.precision).bright_yellow(),
+            delta.to_string().bright_yellow()
+        );
+
+        Ok(())
+    }
+
+    fn peek(&self, args: &[&str]) -> Result<()> {
+        let addr = parse_address(args[0])?;
+        let value_type = match args.get(1) {
+            Some(ty) => parse_value_type(ty)?,
+            None => self.value_type,
+        };
+
+        let value = self.scanner.read_typed(addr, value_type)?;
+        println!(
+            "  {} = {} ({})",
+            format_address(addr, self.pointer_size).bright_yellow(),
+            value.format_with(self.precision).bright_green(),
+            format!("{:?}", value_type).bright_black()
+        );
+
+        Ok(())
+    }
+
+    fn goto(&mut self, args: &[&str]) -> Result<()> {
+        const DEFAULT_LEN: usize = 64;
+        const MAX_LEN: usize = 64 << 10;
+        const LINE_WIDTH: usize = 16;
+
+        let target = args[0];
+        let addr = match target.split_at_checked(1) {
+            Some((sign @ ("+" | "-"), delta)) => {
+                let from = self.goto_address.ok_or_else(|| {
+                    anyhow::anyhow!("No previous goto to move from, give an absolute address first")
+                })?;
+                let delta = parse_address(delta)?;
+                if sign == "+" {
+                    from.checked_add(delta)
+                } else {
+                    from.checked_sub(delta)
+                }
+            }
+            _ => Some(parse_address(target)?),
+        }
+        .ok_or_else(|| anyhow::anyhow!("{} moves outside the address space", target))?;
+        let len = match args.get(1) {
+            Some(len) => parse_address(len)?,
+            None => DEFAULT_LEN,
+        };
+        if len == 0 || len > MAX_LEN {
+            anyhow::bail!("Length must be between 1 and {}", MAX_LEN);
+        }
+
+        let mut bytes = vec![0u8; len];
+        let bytes_read = read_process_memory(self.process, addr, &mut bytes);
+        if bytes_read == 0 {
+            anyhow::bail!(
+                "Could not read memory at {} (unmapped or not readable)",
+                format_address(addr, self.pointer_size)
+            );
+        }
+        bytes.truncate(bytes_read);
+        self.goto_address = Some(addr);
+
+        // The value of the session type at the address is highlighted
+        let target = 0..self.value_type.size();
+        for (i, line) in bytes.chunks(LINE_WIDTH).enumerate() {
+            let start = i * LINE_WIDTH;
+            let highlight = target.start.saturating_sub(start)..target.end.saturating_sub(start);
+            println!(
+                "  {} {}",
+                format_address(addr + start, self.pointer_size),
+                format_hexdump(line, highlight, LINE_WIDTH)
+            );
+        }
+        if bytes_read < len {
+            println!(
+                "{} Only the first {} of {} bytes are readable",
+                "[warn]".yellow(),
+                bytes_read,
+                len
+            );
+        }
+        Ok(())
+    }
+
+    fn dis(&self, address: &str) -> Result<()> {
+        // Mostly following the address, where decoding stays aligned
+        const BEFORE: usize = 16;
+        const AFTER: usize = 48;
+        let addr = parse_address(address)?;
+        for line in disasm_context(self.process, addr, BEFORE, AFTER)? {
+            if line.starts_with("=>") {
+                println!("  {}", line.bright_green());
+            } else {
+                println!("  {}", line);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_precision(&mut self, digits: &str) -> Result<()> {
+        self.precision = match digits {
+            "exact" => None,
+            _ => match digits.parse::<usize>() {
+                Ok(n) if n > 0 => Some(n),
+                _ => anyhow::bail!("Invalid precision: {}", digits),
+            },
+        };
+        match self.precision {
+            Some(n) => println!(
+                "{} Showing floats with {} significant digits",
+                "[done]".bright_cyan(),
+                n.to_string().bright_green()
+            ),
+            None => println!("{} Showing floats exactly", "[done]".bright_cyan()),
+        }
+        Ok(())
+    }
+
+    fn set_tolerance(&mut self, epsilon: &str) -> Result<()> {
+        let epsilon: f64 = epsilon
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid tolerance: {}", epsilon))?;
+        self.scanner.set_float_tolerance(epsilon)?;
+        println!(
+            "{} Floats at most {} apart now count as unchanged",
+            "[done]".bright_cyan(),
+            epsilon.to_string().bright_green()
+        );
+        Ok(())
+    }
+
+    fn fill(&mut self, args: &[&str]) -> Result<()> {
+        if args.len() < 3 {
+            anyhow::bail!("Address, byte and length required");
+        }
+
+        let addr = parse_address(args[0])?;
+        let byte = u8::try_from(parse_address(args[1])?)
+            .map_err(|_| anyhow::anyhow!("Fill byte must be between 0 and 0xff"))?;
+        let len = parse_address(args[2])?;
+        let written = self.scanner.fill(addr, byte, len)?;
+
+        if written < len {
+            println!(
+                "{} Only filled {} of {} bytes at {} (memory may be write-protected)",
+                "[warn]".yellow(),
+                written.to_string().bright_yellow(),
+                len,
+                format_address(addr, self.pointer_size)
+            );
+        } else {
+            println!(
+                "{} Filled {} bytes at {} with {:02x}",
+                "[done]".bright_cyan(),
+                written.to_string().bright_green(),
+                format_address(addr, self.pointer_size),
+                byte
+            );
+        }
+        Ok(())
+    }
+
+    fn poke(&mut self, args: &[&str]) -> Result<()> {
+        if args.len() < 2 {
+            anyhow::bail!("Address and bytes required");
+        }
+
+        let addr = parse_address(args[0])?;
+        // Allow the bytes to be split across arguments, e.g. "E9 00 10 00 00"
+        let bytes = parse_hex_pattern(&args[1..].join(" "))?;
+        let written = self.scanner.write_bytes(addr, &bytes)?;
+
+        if written < bytes.len() {
+            println!(
+                "{} Only wrote {} of {} bytes at {} (memory may be write-protected)",
+                "[warn]".yellow(),
+                written.to_string().bright_yellow(),
+                bytes.len(),
+                format_address(addr, self.pointer_size)
+            );
+        } else {
+            println!(
+                "{} Wrote {} bytes at {}",
+                "[done]".bright_cyan(),
+                written.to_string().bright_green(),
+                format_address(addr, self.pointer_size)
+            );
+        }
+
+        Ok(())
+    }
+
+    fn checkpoint_deltas(&self, cp_a: &str, cp_b: &str) -> Result<()> {
+        let deltas = self.scanner.checkpoint_deltas(cp_a, cp_b)?;
+        let missing = self.scanner.matches().len() - deltas.len();
+        if deltas.is_empty() {
+            println!(
+                "{} No match is in both '{}' and '{}'",
+                "[info]".bright_cyan(),
+                cp_a,
+                cp_b
+            );
+            return Ok(());
+        }
+        let min = deltas.iter().map(|&(_, d)| d).fold(f64::INFINITY, f64::min);
+        let max = deltas
+            .iter()
+            .map(|&(_, d)| d)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let mean = deltas.iter().map(|&(_, d)| d).sum::<f64>() / deltas.len() as f64;
+        let unchanged = deltas.iter().filter(|&&(_, d)| d == 0.0).count();
+        println!(
+            "{} {} deltas from '{}' to '{}': min {}, max {}, mean {:.3}, {} unchanged",
+            "[info]".bright_cyan(),
+            deltas.len().to_string().bright_green(),
+            cp_a,
+            cp_b,
+            min,
+            max,
+            mean,
+            unchanged
+        );
+        if missing > 0 {
+            println!(
+                "{} {} matches are missing from a checkpoint and were left out",
+                "[warn]".yellow(),
+                missing
+            );
+        }
+        Ok(())
+    }
+
     fn handle_checkpoint(&mut self, args: &[&str]) -> Result<()> {
         if args.is_empty() {
             anyhow::bail!("Checkpoint subcommand required");
@@ -485,7 +1642,12 @@ impl<'a> Repl<'a> {
                 } else {
                     println!("{} Saved checkpoints:", "[info]".bright_cyan());
                     for cp in checkpoints {
-                        println!("  - {}", cp.bright_green());
+                        let age = self
+                            .scanner
+                            .get_checkpoint(cp)
+                            .map(|c| c.timestamp.elapsed().as_secs_f64())
+                            .unwrap_or_default();
+                        println!("  - {} (saved {:.1}s ago)", cp.bright_green(), age);
                     }
                 }
             }
@@ -513,18 +1675,13 @@ impl<'a> Repl<'a> {
     }
 }
 
-fn parse_value(s: &str, value_type: ValueType) -> Result<Value> {
-    Ok(match value_type {
-        ValueType::I8 => Value::I8(s.parse()?),
-        ValueType::I16 => Value::I16(s.parse()?),
-        ValueType::I32 => Value::I32(s.parse()?),
-        ValueType::I64 => Value::I64(s.parse()?),
-        ValueType::U8 => Value::U8(s.parse()?),
-        ValueType::U16 => Value::U16(s.parse()?),
-        ValueType::U32 => Value::U32(s.parse()?),
-        ValueType::U64 => Value::U64(s.parse()?),
-        ValueType::F32 => Value::F32(s.parse()?),
-        ValueType::F64 => Value::F64(s.parse()?),
+/// Parse a value type name, as accepted by [`ValueType::from_name`]
+pub fn parse_value_type(ty: &str) -> Result<ValueType> {
+    ValueType::from_name(ty).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown value type: {}. Valid types: i8, i16, i32, i64, u8, u16, u32, u64, i128, u128, f32, f64",
+            ty
+        )
     })
 }
 
@@ -537,17 +1694,21 @@ fn parse_address(s: &str) -> Result<usize> {
     }
 }
 
-fn format_value(value: &Value) -> String {
-    match value {
-        Value::I8(v) => format!("{}", v),
-        Value::I16(v) => format!("{}", v),
-        Value::I32(v) => format!("{}", v),
-        Value::I64(v) => format!("{}", v),
-        Value::U8(v) => format!("{}", v),
-        Value::U16(v) => format!("{}", v),
-        Value::U32(v) => format!("{}", v),
-        Value::U64(v) => format!("{}", v),
-        Value::F32(v) => format!("{}", v),
-        Value::F64(v) => format!("{}", v),
+/// List the addresses a bulk write failed at, and why
+fn print_write_failures(report: &WriteReport, pointer_size: usize) {
+    if report.failures.is_empty() {
+        return;
+    }
+    println!(
+        "{} {} writes failed:",
+        "[warn]".yellow(),
+        report.failures.len()
+    );
+    for (address, reason) in &report.failures {
+        println!(
+            "  {} {}",
+            format_address(*address, pointer_size).bright_yellow(),
+            reason
+        );
     }
 }